@@ -0,0 +1,95 @@
+/*
+ * C ABI of the edge prover, implemented by the `edge-ffi` crate.
+ *
+ * Handles are opaque, owned by the caller and released with their `_free` function, except that
+ * `edge_setup_into_ready` always consumes the offline setup it is given. Every fallible function
+ * returns an `EdgeStatus`; on failure `edge_last_error` describes the error and out-pointers are
+ * left untouched. `EdgeBytes` are borrowed for the duration of a call, `EdgeBuffer`s are owned by
+ * the caller and released with `edge_buffer_free`.
+ *
+ * This header is written by hand; `tests/header.rs` checks that it matches `src/lib.rs`.
+ */
+
+#ifndef EDGE_FFI_H
+#define EDGE_FFI_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum EdgeStatus {
+  EDGE_STATUS_OK              = 0,
+  EDGE_STATUS_NULL_POINTER    = 1,
+  EDGE_STATUS_INVALID_UTF8    = 2,
+  EDGE_STATUS_SYNTHESIS       = 3,
+  EDGE_STATUS_IO              = 4,
+  EDGE_STATUS_NOVA            = 5,
+  EDGE_STATUS_SUPERNOVA       = 6,
+  EDGE_STATUS_SERIALIZATION   = 7,
+  EDGE_STATUS_JSON            = 8,
+  EDGE_STATUS_INVALID_INPUT   = 9,
+  EDGE_STATUS_BUNDLE_MISMATCH = 10,
+  EDGE_STATUS_SETUP_MISMATCH  = 11,
+  EDGE_STATUS_PANIC           = 12,
+} EdgeStatus;
+
+/* Bytes borrowed from the caller for the duration of a call. `data` may be null if `len` is 0. */
+typedef struct EdgeBytes {
+  const uint8_t *data;
+  size_t         len;
+} EdgeBytes;
+
+/* Bytes owned by the caller, released with `edge_buffer_free`. */
+typedef struct EdgeBuffer {
+  uint8_t *data;
+  size_t   len;
+} EdgeBuffer;
+
+typedef struct EdgeOfflineSetup   EdgeOfflineSetup;
+typedef struct EdgeReadySetup     EdgeReadySetup;
+typedef struct EdgeRecursiveSnark EdgeRecursiveSnark;
+
+/* Message of the last failed call on the calling thread, or null. Valid until the next failure. */
+const char *edge_last_error(void);
+
+/* Creates the setup for the compiled Noir artifacts of a switchboard and stores it to `path`. */
+EdgeStatus edge_setup_store_file(const EdgeBytes *programs, size_t programs_len, const char *path);
+
+EdgeStatus edge_setup_load_file(const char *path, EdgeOfflineSetup **out);
+void       edge_setup_free(EdgeOfflineSetup *setup);
+
+/*
+ * Consumes `setup`, whatever the result. A null `rom_inputs_json` selects the RAM memory model,
+ * otherwise the ROM memory model with one JSON object of named inputs per step. `public_input_json`
+ * is a JSON array of field elements.
+ */
+EdgeStatus edge_setup_into_ready(EdgeOfflineSetup *setup,
+                                 const EdgeBytes  *programs,
+                                 size_t            programs_len,
+                                 size_t            initial_circuit_index,
+                                 const char       *public_input_json,
+                                 const char       *rom_inputs_json,
+                                 EdgeReadySetup  **out);
+void       edge_ready_setup_free(EdgeReadySetup *setup);
+
+EdgeStatus edge_run(const EdgeReadySetup *setup, EdgeRecursiveSnark **out);
+void       edge_recursive_snark_free(EdgeRecursiveSnark *recursive_snark);
+
+/* Writes the serialized proof bundle to `out`. */
+EdgeStatus edge_compress(const EdgeReadySetup     *setup,
+                         const EdgeRecursiveSnark *recursive_snark,
+                         EdgeBuffer               *out);
+
+/* Returns EDGE_STATUS_OK only if the serialized proof bundle is valid for the setup. */
+EdgeStatus edge_verify(const EdgeReadySetup *setup, EdgeBytes bundle);
+
+void edge_buffer_free(EdgeBuffer buffer);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* EDGE_FFI_H */
@@ -0,0 +1,517 @@
+#![warn(missing_docs, clippy::missing_docs_in_private_items)]
+
+//! # C ABI for Native Clients
+//!
+//! This crate exposes the offline setup, proving, compression and verification flow of
+//! `edge-frontend` through a C ABI, so that iOS and Android apps can call the prover from Swift and
+//! Kotlin. The matching declarations are in `include/edge_ffi.h`.
+//!
+//! ## Handles
+//!
+//! Setups and recursive SNARKs are returned as opaque handles through out-pointers. Every handle is
+//! owned by the caller and released with its `_free` function, except that
+//! [`edge_setup_into_ready`] always consumes the offline setup it is given. Handles are not
+//! thread-safe and must not be used from several threads at the same time.
+//!
+//! ## Errors
+//!
+//! Every fallible function returns an [`EdgeStatus`], which maps the [`FrontendError`] variants one
+//! to one. On failure, [`edge_last_error`] returns a message describing the error, and out-pointers
+//! are left untouched. Panics are caught and reported as [`EdgeStatus::Panic`], unless the library
+//! is built with `panic = "abort"` as in the workspace's release profile.
+//!
+//! ## Buffers
+//!
+//! Byte inputs are passed as [`EdgeBytes`], which are borrowed for the duration of the call and
+//! remain owned by the caller. Byte outputs are returned as [`EdgeBuffer`], which are owned by the
+//! caller and must be released with [`edge_buffer_free`].
+
+use std::{
+  any::Any,
+  cell::RefCell,
+  ffi::{c_char, CStr, CString},
+  fmt,
+  panic::{catch_unwind, AssertUnwindSafe},
+  path::PathBuf,
+  ptr, slice,
+};
+
+use edge_frontend::{
+  error::FrontendError,
+  input::{parse_public_input, parse_rom_inputs},
+  noir::NoirProgram,
+  program::{self, Configuration, Memory, Switchboard, RAM, ROM},
+  proof::ProofBundle,
+  setup::{Offline, Ready, Setup},
+};
+use edge_prover::{fast_serde::FastSerde, provider::Bn256EngineKZG, supernova::RecursiveSNARK};
+
+/// Status code returned by every fallible function
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeStatus {
+  /// The call succeeded
+  Ok = 0,
+  /// A required pointer argument was null
+  NullPointer = 1,
+  /// A string argument is not valid UTF-8
+  InvalidUtf8 = 2,
+  /// See [`FrontendError::Synthesis`]
+  Synthesis = 3,
+  /// See [`FrontendError::Io`]
+  Io = 4,
+  /// See [`FrontendError::Nova`]
+  Nova = 5,
+  /// See [`FrontendError::SuperNova`]
+  SuperNova = 6,
+  /// See [`FrontendError::FastSerde`]
+  Serialization = 7,
+  /// See [`FrontendError::Json`]
+  Json = 8,
+  /// See [`FrontendError::InvalidInput`]
+  InvalidInput = 9,
+  /// See [`FrontendError::BundleMismatch`]
+  BundleMismatch = 10,
+  /// See [`FrontendError::SetupMismatch`]
+  SetupMismatch = 11,
+  /// The call panicked
+  Panic = 12,
+}
+
+/// Bytes borrowed from the caller for the duration of a call
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeBytes {
+  /// Pointer to the first byte, may be null if `len` is zero
+  pub data: *const u8,
+  /// Number of bytes
+  pub len:  usize,
+}
+
+/// Bytes owned by the caller, released with [`edge_buffer_free`]
+#[repr(C)]
+#[derive(Debug)]
+pub struct EdgeBuffer {
+  /// Pointer to the first byte
+  pub data: *mut u8,
+  /// Number of bytes
+  pub len:  usize,
+}
+
+impl EdgeBuffer {
+  /// Hands the bytes over to the caller
+  fn new(bytes: Vec<u8>) -> Self {
+    let len = bytes.len();
+    let data = Box::into_raw(bytes.into_boxed_slice()).cast::<u8>();
+    Self { data, len }
+  }
+}
+
+/// An offline setup, loaded with [`edge_setup_load_file`]
+pub struct EdgeOfflineSetup(Setup<Offline>);
+
+/// A setup readied with a switchboard, created with [`edge_setup_into_ready`]
+pub struct EdgeReadySetup(ReadySetup);
+
+/// A recursive SNARK, created with [`edge_run`]
+pub struct EdgeRecursiveSnark(RecursiveSNARK<Bn256EngineKZG>);
+
+/// A ready setup of either memory model
+enum ReadySetup {
+  /// The setup of a switchboard with predefined inputs
+  Rom(Setup<Ready<ROM>>),
+  /// The setup of a switchboard that computes its inputs during execution
+  Ram(Setup<Ready<RAM>>),
+}
+
+impl ReadySetup {
+  /// Executes the switchboard, see [`program::run`]
+  fn run(&self) -> Result<RecursiveSNARK<Bn256EngineKZG>, FrontendError> {
+    match self {
+      Self::Rom(setup) => program::run(setup),
+      Self::Ram(setup) => program::run(setup),
+    }
+  }
+
+  /// Compresses a recursive SNARK and bundles it, see [`program::compress`]
+  fn compress(
+    &self,
+    recursive_snark: &RecursiveSNARK<Bn256EngineKZG>,
+  ) -> Result<ProofBundle, FrontendError> {
+    /// Compresses and bundles with a setup of any memory model
+    fn bundle<M: Memory>(
+      setup: &Setup<Ready<M>>,
+      recursive_snark: &RecursiveSNARK<Bn256EngineKZG>,
+    ) -> Result<ProofBundle, FrontendError> {
+      let proof = program::compress(setup, recursive_snark)?;
+      Ok(ProofBundle::new(setup, recursive_snark, proof))
+    }
+
+    match self {
+      Self::Rom(setup) => bundle(setup, recursive_snark),
+      Self::Ram(setup) => bundle(setup, recursive_snark),
+    }
+  }
+
+  /// Verifies a proof bundle, see [`program::verify_bundle`]
+  fn verify(&self, bundle: &ProofBundle) -> Result<(), FrontendError> {
+    match self {
+      Self::Rom(setup) => program::verify_bundle(setup, bundle),
+      Self::Ram(setup) => program::verify_bundle(setup, bundle),
+    }
+  }
+}
+
+/// Error raised by a call before it is reported as an [`EdgeStatus`] and the last error message
+#[derive(Debug)]
+enum FfiError {
+  /// The named pointer argument was null
+  NullPointer(&'static str),
+  /// The named string argument is not valid UTF-8
+  InvalidUtf8(&'static str),
+  /// The frontend failed
+  Frontend(FrontendError),
+}
+
+impl FfiError {
+  /// Returns the status code reported for the error
+  fn status(&self) -> EdgeStatus {
+    match self {
+      Self::NullPointer(_) => EdgeStatus::NullPointer,
+      Self::InvalidUtf8(_) => EdgeStatus::InvalidUtf8,
+      Self::Frontend(error) => match error {
+        FrontendError::Synthesis(_) => EdgeStatus::Synthesis,
+        FrontendError::Io(_) => EdgeStatus::Io,
+        FrontendError::Nova(_) => EdgeStatus::Nova,
+        FrontendError::SuperNova(_) => EdgeStatus::SuperNova,
+        FrontendError::FastSerde(_) => EdgeStatus::Serialization,
+        FrontendError::Json(_) => EdgeStatus::Json,
+        FrontendError::InvalidInput(_) => EdgeStatus::InvalidInput,
+        FrontendError::BundleMismatch(_) => EdgeStatus::BundleMismatch,
+        FrontendError::SetupMismatch(_) => EdgeStatus::SetupMismatch,
+      },
+    }
+  }
+}
+
+impl fmt::Display for FfiError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::NullPointer(name) => write!(f, "`{name}` must not be null"),
+      Self::InvalidUtf8(name) => write!(f, "`{name}` is not valid UTF-8"),
+      Self::Frontend(error) => fmt::Display::fmt(error, f),
+    }
+  }
+}
+
+impl From<FrontendError> for FfiError {
+  fn from(error: FrontendError) -> Self { Self::Frontend(error) }
+}
+
+thread_local! {
+  /// Message of the last failed call on this thread
+  static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Runs the body of an exported function, catching panics and recording the error message
+fn ffi_call(body: impl FnOnce() -> Result<(), FfiError>) -> EdgeStatus {
+  let (status, message) = match catch_unwind(AssertUnwindSafe(body)) {
+    Ok(Ok(())) => return EdgeStatus::Ok,
+    Ok(Err(error)) => (error.status(), error.to_string()),
+    Err(panic) => (EdgeStatus::Panic, panic_message(panic.as_ref())),
+  };
+
+  let message = CString::new(message.replace('\0', " ")).expect("interior NULs were replaced");
+  LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+  status
+}
+
+/// Extracts the message of a caught panic
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+  panic
+    .downcast_ref::<&str>()
+    .map(|s| (*s).to_string())
+    .or_else(|| panic.downcast_ref::<String>().cloned())
+    .unwrap_or_else(|| "panicked".to_string())
+}
+
+/// Borrows the bytes of an [`EdgeBytes`]
+///
+/// # Safety
+///
+/// `bytes.data` must be null or point to `bytes.len` readable bytes
+unsafe fn borrow_bytes<'a>(bytes: EdgeBytes, name: &'static str) -> Result<&'a [u8], FfiError> {
+  match (bytes.data.is_null(), bytes.len) {
+    (_, 0) => Ok(&[]),
+    (true, _) => Err(FfiError::NullPointer(name)),
+    (false, len) => Ok(slice::from_raw_parts(bytes.data, len)),
+  }
+}
+
+/// Borrows a NUL-terminated UTF-8 string
+///
+/// # Safety
+///
+/// `s` must be null or point to a NUL-terminated string
+unsafe fn borrow_str<'a>(s: *const c_char, name: &'static str) -> Result<&'a str, FfiError> {
+  if s.is_null() {
+    return Err(FfiError::NullPointer(name));
+  }
+  CStr::from_ptr(s).to_str().map_err(|_| FfiError::InvalidUtf8(name))
+}
+
+/// Parses the compiled Noir artifacts of a switchboard
+///
+/// # Safety
+///
+/// `programs` must be null or point to `programs_len` valid [`EdgeBytes`]
+unsafe fn parse_programs(
+  programs: *const EdgeBytes,
+  programs_len: usize,
+) -> Result<Vec<NoirProgram>, FfiError> {
+  if programs.is_null() {
+    return Err(FfiError::NullPointer("programs"));
+  }
+
+  slice::from_raw_parts(programs, programs_len)
+    .iter()
+    .map(|artifact| {
+      let artifact = borrow_bytes(*artifact, "programs[i].data")?;
+      Ok(serde_json::from_slice(artifact).map_err(FrontendError::from)?)
+    })
+    .collect()
+}
+
+/// Moves a value into a new handle behind an out-pointer
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes
+unsafe fn write_handle<T>(out: *mut *mut T, value: T) -> Result<(), FfiError> {
+  if out.is_null() {
+    return Err(FfiError::NullPointer("out"));
+  }
+  out.write(Box::into_raw(Box::new(value)));
+  Ok(())
+}
+
+/// Returns the message of the last failed call on the calling thread
+///
+/// The string is owned by the library and stays valid until the next failed call on the same
+/// thread. Returns null if no call has failed on this thread.
+#[no_mangle]
+pub extern "C" fn edge_last_error() -> *const c_char {
+  LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Creates the setup for a switchboard and stores it to a file, see [`Setup::store_file`]
+///
+/// # Safety
+///
+/// `programs` must point to `programs_len` [`EdgeBytes`] holding compiled Noir artifacts, and
+/// `path` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn edge_setup_store_file(
+  programs: *const EdgeBytes,
+  programs_len: usize,
+  path: *const c_char,
+) -> EdgeStatus {
+  ffi_call(|| {
+    let switchboard = Switchboard::<Configuration>::new(parse_programs(programs, programs_len)?);
+    let path = PathBuf::from(borrow_str(path, "path")?);
+    Setup::new(switchboard)?.store_file(&path)?;
+    Ok(())
+  })
+}
+
+/// Loads an offline setup from a file, see [`Setup::load_file`]
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn edge_setup_load_file(
+  path: *const c_char,
+  out: *mut *mut EdgeOfflineSetup,
+) -> EdgeStatus {
+  ffi_call(|| {
+    let path = PathBuf::from(borrow_str(path, "path")?);
+    write_handle(out, EdgeOfflineSetup(Setup::load_file(&path)?))
+  })
+}
+
+/// Releases an offline setup
+///
+/// # Safety
+///
+/// `setup` must be null or a handle returned by [`edge_setup_load_file`] that has not been released
+/// or consumed.
+#[no_mangle]
+pub unsafe extern "C" fn edge_setup_free(setup: *mut EdgeOfflineSetup) {
+  if !setup.is_null() {
+    drop(Box::from_raw(setup));
+  }
+}
+
+/// Readies an offline setup with a switchboard, see [`Setup::try_into_ready`]
+///
+/// The offline setup is consumed whatever the result and must not be used or released afterwards.
+/// If `rom_inputs_json` is null the switchboard uses the RAM memory model, otherwise the ROM memory
+/// model with the given inputs. See `edge_frontend::input` for the JSON formats.
+///
+/// # Safety
+///
+/// `setup` must be a handle returned by [`edge_setup_load_file`] that has not been released or
+/// consumed, `programs` must point to `programs_len` [`EdgeBytes`] holding compiled Noir
+/// artifacts, `public_input_json` must be a NUL-terminated string, `rom_inputs_json` must be null
+/// or a NUL-terminated string, and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn edge_setup_into_ready(
+  setup: *mut EdgeOfflineSetup,
+  programs: *const EdgeBytes,
+  programs_len: usize,
+  initial_circuit_index: usize,
+  public_input_json: *const c_char,
+  rom_inputs_json: *const c_char,
+  out: *mut *mut EdgeReadySetup,
+) -> EdgeStatus {
+  ffi_call(|| {
+    if setup.is_null() {
+      return Err(FfiError::NullPointer("setup"));
+    }
+    let EdgeOfflineSetup(setup) = *Box::from_raw(setup);
+
+    let circuits = parse_programs(programs, programs_len)?;
+    if initial_circuit_index >= circuits.len() {
+      return Err(
+        FrontendError::InvalidInput(format!(
+          "initial circuit index {initial_circuit_index} is out of range for {} programs",
+          circuits.len()
+        ))
+        .into(),
+      );
+    }
+    let public_input = parse_public_input(borrow_str(public_input_json, "public_input_json")?)?;
+
+    let ready = if rom_inputs_json.is_null() {
+      let switchboard = Switchboard::<RAM>::new(circuits, public_input, initial_circuit_index);
+      ReadySetup::Ram(setup.try_into_ready(switchboard)?)
+    } else {
+      let inputs = parse_rom_inputs(borrow_str(rom_inputs_json, "rom_inputs_json")?)?;
+      if inputs.is_empty() {
+        return Err(
+          FrontendError::InvalidInput("at least one step of ROM inputs is required".to_string())
+            .into(),
+        );
+      }
+      let switchboard =
+        Switchboard::<ROM>::new(circuits, inputs, public_input, initial_circuit_index);
+      ReadySetup::Rom(setup.try_into_ready(switchboard)?)
+    };
+
+    write_handle(out, EdgeReadySetup(ready))
+  })
+}
+
+/// Releases a ready setup
+///
+/// # Safety
+///
+/// `setup` must be null or a handle returned by [`edge_setup_into_ready`] that has not been
+/// released.
+#[no_mangle]
+pub unsafe extern "C" fn edge_ready_setup_free(setup: *mut EdgeReadySetup) {
+  if !setup.is_null() {
+    drop(Box::from_raw(setup));
+  }
+}
+
+/// Executes the switchboard of a ready setup, see [`program::run`]
+///
+/// # Safety
+///
+/// `setup` must be a live handle returned by [`edge_setup_into_ready`] and `out` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn edge_run(
+  setup: *const EdgeReadySetup,
+  out: *mut *mut EdgeRecursiveSnark,
+) -> EdgeStatus {
+  ffi_call(|| {
+    let EdgeReadySetup(setup) = setup.as_ref().ok_or(FfiError::NullPointer("setup"))?;
+    write_handle(out, EdgeRecursiveSnark(setup.run()?))
+  })
+}
+
+/// Releases a recursive SNARK
+///
+/// # Safety
+///
+/// `recursive_snark` must be null or a handle returned by [`edge_run`] that has not been released.
+#[no_mangle]
+pub unsafe extern "C" fn edge_recursive_snark_free(recursive_snark: *mut EdgeRecursiveSnark) {
+  if !recursive_snark.is_null() {
+    drop(Box::from_raw(recursive_snark));
+  }
+}
+
+/// Compresses a recursive SNARK into a serialized [`ProofBundle`], see [`program::compress`]
+///
+/// # Safety
+///
+/// `setup` and `recursive_snark` must be live handles, the latter created with the former, and
+/// `out` must be valid for writes. The buffer written to `out` must be released with
+/// [`edge_buffer_free`].
+#[no_mangle]
+pub unsafe extern "C" fn edge_compress(
+  setup: *const EdgeReadySetup,
+  recursive_snark: *const EdgeRecursiveSnark,
+  out: *mut EdgeBuffer,
+) -> EdgeStatus {
+  ffi_call(|| {
+    let EdgeReadySetup(setup) = setup.as_ref().ok_or(FfiError::NullPointer("setup"))?;
+    let EdgeRecursiveSnark(recursive_snark) =
+      recursive_snark.as_ref().ok_or(FfiError::NullPointer("recursive_snark"))?;
+    if out.is_null() {
+      return Err(FfiError::NullPointer("out"));
+    }
+
+    let bundle = setup.compress(recursive_snark)?;
+    out.write(EdgeBuffer::new(bundle.to_bytes()));
+    Ok(())
+  })
+}
+
+/// Verifies a serialized [`ProofBundle`] against a ready setup, see [`program::verify_bundle`]
+///
+/// Returns [`EdgeStatus::Ok`] only if the bundle is valid.
+///
+/// # Safety
+///
+/// `setup` must be a live handle returned by [`edge_setup_into_ready`] and `bundle` must describe
+/// readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn edge_verify(
+  setup: *const EdgeReadySetup,
+  bundle: EdgeBytes,
+) -> EdgeStatus {
+  ffi_call(|| {
+    let EdgeReadySetup(setup) = setup.as_ref().ok_or(FfiError::NullPointer("setup"))?;
+    let bundle =
+      ProofBundle::from_bytes(borrow_bytes(bundle, "bundle")?).map_err(FrontendError::from)?;
+    Ok(setup.verify(&bundle)?)
+  })
+}
+
+/// Releases a buffer returned by the library
+///
+/// # Safety
+///
+/// `buffer` must have been returned by the library and not released yet. A buffer with a null
+/// `data` pointer is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn edge_buffer_free(buffer: EdgeBuffer) {
+  if !buffer.data.is_null() {
+    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)));
+  }
+}
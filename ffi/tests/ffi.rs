@@ -0,0 +1,91 @@
+use std::{
+  ffi::{CStr, CString},
+  ptr,
+};
+
+use edge_ffi::*;
+use tempfile::tempdir;
+
+fn artifact(name: &str) -> Vec<u8> {
+  std::fs::read(format!("../target/{name}.json")).expect("Failed to read Noir program file")
+}
+
+fn as_bytes(bytes: &[u8]) -> EdgeBytes { EdgeBytes { data: bytes.as_ptr(), len: bytes.len() } }
+
+fn last_error() -> String {
+  unsafe { CStr::from_ptr(edge_last_error()) }.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_ffi_end_to_end() {
+  let artifacts = [artifact("swap_memory"), artifact("square_zeroth")];
+  let programs = artifacts.iter().map(|a| as_bytes(a)).collect::<Vec<_>>();
+  let temp_dir = tempdir().unwrap();
+  let path = CString::new(temp_dir.path().join("setup.bytes").to_str().unwrap()).unwrap();
+  let public_input = CString::new("[3, 5]").unwrap();
+  let rom_inputs = CString::new(r#"[{"next_pc": 1}, {"next_pc": -1}]"#).unwrap();
+
+  unsafe {
+    assert_eq!(
+      edge_setup_store_file(programs.as_ptr(), programs.len(), path.as_ptr()),
+      EdgeStatus::Ok
+    );
+
+    let mut offline = ptr::null_mut();
+    assert_eq!(edge_setup_load_file(path.as_ptr(), &mut offline), EdgeStatus::Ok);
+
+    let mut setup = ptr::null_mut();
+    assert_eq!(
+      edge_setup_into_ready(
+        offline,
+        programs.as_ptr(),
+        programs.len(),
+        0,
+        public_input.as_ptr(),
+        rom_inputs.as_ptr(),
+        &mut setup
+      ),
+      EdgeStatus::Ok
+    );
+
+    let mut recursive_snark = ptr::null_mut();
+    assert_eq!(edge_run(setup, &mut recursive_snark), EdgeStatus::Ok);
+
+    let mut bundle = EdgeBuffer { data: ptr::null_mut(), len: 0 };
+    assert_eq!(edge_compress(setup, recursive_snark, &mut bundle), EdgeStatus::Ok);
+    let bundle_bytes = std::slice::from_raw_parts(bundle.data, bundle.len).to_vec();
+    assert_eq!(edge_verify(setup, as_bytes(&bundle_bytes)), EdgeStatus::Ok);
+    assert_ne!(
+      edge_verify(setup, as_bytes(&bundle_bytes[..bundle_bytes.len() / 2])),
+      EdgeStatus::Ok
+    );
+
+    edge_buffer_free(bundle);
+    edge_recursive_snark_free(recursive_snark);
+    edge_ready_setup_free(setup);
+  }
+}
+
+#[test]
+fn test_ffi_errors() {
+  let missing = CString::new("../target/does_not_exist.bytes").unwrap();
+
+  unsafe {
+    let mut offline = ptr::null_mut();
+    assert_eq!(edge_setup_load_file(ptr::null(), &mut offline), EdgeStatus::NullPointer);
+    assert_eq!(last_error(), "`path` must not be null");
+
+    assert_eq!(edge_setup_load_file(missing.as_ptr(), &mut offline), EdgeStatus::Io);
+    assert!(offline.is_null());
+
+    let invalid = [as_bytes(b"not a noir artifact")];
+    assert_eq!(edge_setup_store_file(invalid.as_ptr(), 1, missing.as_ptr()), EdgeStatus::Json);
+
+    let mut recursive_snark = ptr::null_mut();
+    assert_eq!(edge_run(ptr::null(), &mut recursive_snark), EdgeStatus::NullPointer);
+    assert_eq!(last_error(), "`setup` must not be null");
+
+    edge_setup_free(ptr::null_mut());
+    edge_buffer_free(EdgeBuffer { data: ptr::null_mut(), len: 0 });
+  }
+}
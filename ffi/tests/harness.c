@@ -0,0 +1,91 @@
+/*
+ * Drives the C ABI through setup, proving, compression and verification.
+ *
+ * From the repository root, after `nargo compile --workspace`:
+ *
+ *   cargo build -p edge-ffi
+ *   cc ffi/tests/harness.c -Iffi/include -Ltarget/debug -ledge_ffi -o target/harness
+ *   LD_LIBRARY_PATH=target/debug target/harness target/swap_memory.json target/square_zeroth.json
+ *
+ * The check workflow runs these steps after the Rust tests.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "edge_ffi.h"
+
+#define CHECK(call)                                                                         \
+  do {                                                                                      \
+    EdgeStatus status = (call);                                                             \
+    if (status != EDGE_STATUS_OK) {                                                         \
+      fprintf(stderr, "%s failed with status %d: %s\n", #call, status, edge_last_error()); \
+      exit(1);                                                                              \
+    }                                                                                       \
+  } while (0)
+
+static EdgeBytes read_file(const char *path) {
+  FILE *file = fopen(path, "rb");
+  if (file == NULL) {
+    fprintf(stderr, "cannot open %s\n", path);
+    exit(1);
+  }
+  fseek(file, 0, SEEK_END);
+  long len = ftell(file);
+  fseek(file, 0, SEEK_SET);
+  uint8_t *data = malloc((size_t)len);
+  if (data == NULL || fread(data, 1, (size_t)len, file) != (size_t)len) {
+    fprintf(stderr, "cannot read %s\n", path);
+    exit(1);
+  }
+  fclose(file);
+  return (EdgeBytes){ .data = data, .len = (size_t)len };
+}
+
+int main(int argc, char **argv) {
+  if (argc != 3) {
+    fprintf(stderr, "usage: %s <swap_memory.json> <square_zeroth.json>\n", argv[0]);
+    return 2;
+  }
+
+  EdgeBytes programs[2] = { read_file(argv[1]), read_file(argv[2]) };
+  const char *setup_path = "target/harness_setup.bytes";
+  CHECK(edge_setup_store_file(programs, 2, setup_path));
+
+  /* Errors are reported through the status and the last error message. */
+  EdgeOfflineSetup *missing = NULL;
+  if (edge_setup_load_file("target/does_not_exist.bytes", &missing) != EDGE_STATUS_IO
+      || edge_last_error() == NULL || missing != NULL) {
+    fprintf(stderr, "loading a missing setup did not fail with an IO error\n");
+    return 1;
+  }
+
+  EdgeOfflineSetup *offline = NULL;
+  CHECK(edge_setup_load_file(setup_path, &offline));
+
+  EdgeReadySetup *setup = NULL;
+  CHECK(edge_setup_into_ready(offline, programs, 2, 0, "[3, 5]",
+                              "[{\"next_pc\": 1}, {\"next_pc\": -1}]", &setup));
+
+  EdgeRecursiveSnark *recursive_snark = NULL;
+  CHECK(edge_run(setup, &recursive_snark));
+
+  EdgeBuffer bundle = { 0 };
+  CHECK(edge_compress(setup, recursive_snark, &bundle));
+  CHECK(edge_verify(setup, (EdgeBytes){ .data = bundle.data, .len = bundle.len }));
+
+  bundle.data[bundle.len - 1] ^= 1;
+  if (edge_verify(setup, (EdgeBytes){ .data = bundle.data, .len = bundle.len }) == EDGE_STATUS_OK) {
+    fprintf(stderr, "a tampered bundle was accepted\n");
+    return 1;
+  }
+
+  edge_buffer_free(bundle);
+  edge_recursive_snark_free(recursive_snark);
+  edge_ready_setup_free(setup);
+  free((void *)programs[0].data);
+  free((void *)programs[1].data);
+
+  printf("ok\n");
+  return 0;
+}
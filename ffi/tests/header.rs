@@ -0,0 +1,153 @@
+//! Checks that `include/edge_ffi.h` declares exactly the C ABI of `src/lib.rs`.
+//!
+//! The header is written by hand, so this test compares both sources: every exported function with
+//! its return and parameter types, the status codes and the fields of the `#[repr(C)]` structs.
+
+const SOURCE: &str = include_str!("../src/lib.rs");
+const HEADER: &str = include_str!("../include/edge_ffi.h");
+
+/// Collapses whitespace and removes it around pointers, so `const char  *path` and `const
+/// char*path` compare equal
+fn normalize(c: &str) -> String {
+  c.split_whitespace().collect::<Vec<_>>().join(" ").replace(" *", "*").replace("* ", "*")
+}
+
+/// The C spelling of a Rust type of the ABI, in [`normalize`]d form
+fn c_type(rust: &str) -> String {
+  let rust = rust.trim();
+  if let Some(pointee) = rust.strip_prefix("*const ") {
+    format!("const {}*", c_type(pointee))
+  } else if let Some(pointee) = rust.strip_prefix("*mut ") {
+    format!("{}*", c_type(pointee))
+  } else {
+    match rust {
+      "" => "void",
+      "usize" => "size_t",
+      "u8" => "uint8_t",
+      "c_char" => "char",
+      ty => ty,
+    }
+    .to_string()
+  }
+}
+
+/// Splits a normalized C declaration such as `const char*path` into its type and name
+fn split_declaration(declaration: &str) -> (String, String) {
+  let declaration = normalize(declaration);
+  let start = declaration.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_').len();
+  (declaration[..start].trim().to_string(), declaration[start..].to_string())
+}
+
+/// Strips the `/* ... */` comments of the header
+fn strip_comments(mut header: &str) -> String {
+  let mut out = String::new();
+  while let Some(start) = header.find("/*") {
+    out.push_str(&header[..start]);
+    header = &header[start + header[start..].find("*/").unwrap() + 2..];
+  }
+  out + header
+}
+
+/// A function as `(name, return type, [(parameter type, parameter name)])`
+type Signature = (String, String, Vec<(String, String)>);
+
+fn rust_functions() -> Vec<Signature> {
+  SOURCE
+    .split("extern \"C\" fn ")
+    .skip(1)
+    .map(|rest| {
+      let (name, rest) = rest.split_once('(').unwrap();
+      let (params, rest) = rest.split_once(')').unwrap();
+      let ret = rest[..rest.find('{').unwrap()].trim().trim_start_matches("->");
+      let params = params
+        .split(',')
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| {
+          let (name, ty) = p.split_once(':').unwrap();
+          (c_type(ty), name.trim().to_string())
+        })
+        .collect();
+      (name.to_string(), c_type(ret), params)
+    })
+    .collect()
+}
+
+fn header_functions() -> Vec<Signature> {
+  let header = strip_comments(HEADER);
+  let mut functions = Vec::new();
+  for line_start in header.lines().map(|line| line.as_ptr() as usize - header.as_ptr() as usize) {
+    let line = &header[line_start..];
+    let Some(open) = line.find('(') else { continue };
+    if line[..open].contains(['\n', ';', '#', '}']) || !line[..open].contains("edge_") {
+      continue;
+    }
+    let (ret, name) = split_declaration(&line[..open]);
+    let params = line[open + 1..line.find(')').unwrap()]
+      .split(',')
+      .map(str::trim)
+      .filter(|p| *p != "void")
+      .map(split_declaration)
+      .collect();
+    functions.push((name, ret, params));
+  }
+  functions
+}
+
+#[test]
+fn test_header_functions() {
+  let mut rust = rust_functions();
+  let mut header = header_functions();
+  rust.sort();
+  header.sort();
+  assert!(!rust.is_empty());
+  assert_eq!(rust, header);
+}
+
+#[test]
+fn test_header_status_codes() {
+  let enum_body = SOURCE.split("pub enum EdgeStatus {").nth(1).unwrap().split('}').next().unwrap();
+  let rust = enum_body
+    .lines()
+    .filter_map(|line| line.trim().trim_end_matches(',').split_once(" = "))
+    .map(|(name, value)| (name.to_lowercase(), value.to_string()))
+    .collect::<Vec<_>>();
+
+  let enum_body =
+    HEADER.split("typedef enum EdgeStatus {").nth(1).unwrap().split('}').next().unwrap();
+  let header = enum_body
+    .lines()
+    .filter_map(|line| line.trim().trim_end_matches(',').split_once('='))
+    .map(|(name, value)| {
+      let name = name.trim().trim_start_matches("EDGE_STATUS_").replace('_', "").to_lowercase();
+      (name, value.trim().to_string())
+    })
+    .collect::<Vec<_>>();
+
+  assert!(!rust.is_empty());
+  assert_eq!(rust, header);
+}
+
+#[test]
+fn test_header_structs() {
+  for name in ["EdgeBytes", "EdgeBuffer"] {
+    let body = SOURCE.split(&format!("pub struct {name} {{")).nth(1).unwrap().split('}').next();
+    let rust = body
+      .unwrap()
+      .lines()
+      .filter_map(|line| line.trim().strip_prefix("pub ")?.trim_end_matches(',').split_once(':'))
+      .map(|(field, ty)| (c_type(ty), field.trim().to_string()))
+      .collect::<Vec<_>>();
+
+    let header = strip_comments(HEADER);
+    let body = header.split(&format!("typedef struct {name} {{")).nth(1).unwrap().split('}').next();
+    let header = body
+      .unwrap()
+      .split(';')
+      .filter(|field| !field.trim().is_empty())
+      .map(split_declaration)
+      .collect::<Vec<_>>();
+
+    assert!(!rust.is_empty());
+    assert_eq!(rust, header, "fields of {name}");
+  }
+}
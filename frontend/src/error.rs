@@ -32,4 +32,53 @@ pub enum FrontendError {
   /// The error is a [`edge_prover::fast_serde::SerdeByteError`]
   #[error(transparent)]
   FastSerde(#[from] edge_prover::fast_serde::SerdeByteError),
+
+  /// The error is a `serde_json::Error`
+  #[error(transparent)]
+  Json(#[from] serde_json::Error),
+
+  /// An input given in a serialized form could not be converted into circuit inputs
+  #[error("invalid input: {0}")]
+  InvalidInput(String),
+
+  /// A [`crate::proof::ProofBundle`] field does not match the setup or the proof it carries
+  #[error("proof bundle mismatch: {0}")]
+  BundleMismatch(&'static str),
+
+  /// A switchboard does not match the offline setup it is readied with
+  #[error(transparent)]
+  SetupMismatch(#[from] SetupMismatch),
+}
+
+/// Describes how a switchboard differs from the circuits an offline setup was created for
+///
+/// Returned by [`crate::setup::Setup::try_into_ready`], so that a reordered or recompiled circuit
+/// is reported when the setup is loaded rather than as a failure to compress or verify later.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SetupMismatch {
+  /// The switchboard has a different number of circuits than the setup
+  #[error("setup was created for {expected} circuits, but the switchboard has {found}")]
+  CircuitCount {
+    /// Number of circuits the setup was created for
+    expected: usize,
+    /// Number of circuits in the switchboard
+    found:    usize,
+  },
+
+  /// The circuit at `index` is not the program the setup was created for
+  #[error("circuit {index} does not match the program the setup was created for")]
+  Circuit {
+    /// Index of the mismatched circuit in the switchboard
+    index: usize,
+  },
+
+  /// The public parameters rebuilt from the setup and the switchboard do not have the digest the
+  /// setup was created with
+  #[error("public parameters digest does not match the setup")]
+  PublicParams,
+
+  /// The verifier key digests recomputed from the public parameters are not the ones stored in
+  /// the setup
+  #[error("verifier key digests do not match the setup")]
+  VerifierKey,
 }
@@ -0,0 +1,147 @@
+//! # EVM Calldata
+//!
+//! This module ABI-encodes a [`ProofBundle`] as calldata for an on-chain verifier exposing
+//! [`VERIFY_SIGNATURE`], and renders the matching Solidity interface with [`verifier_interface`].
+//!
+//! The compressed proof is passed as a `uint256[]` in the layout of [`AbiEncode`]: field elements
+//! are single words, points are their affine coordinates and variable-length lists are prefixed
+//! with their length, so a contract reads it with plain indexing. The bundle's setup and verifier
+//! key digests are passed alongside, so a contract can reject proofs made for other circuits.
+//!
+//! Only the calldata side of on-chain verification is provided. A generated Solidity verifier
+//! contract is not included yet: the primary proof (batched Spartan over HyperKZG with a Keccak
+//! transcript) only needs BN254 precompiles, but the secondary proof is an IPA over Grumpkin, which
+//! the EVM has no precompiles for. Verifying it on-chain needs an EVM-friendly secondary proof
+//! first.
+
+use edge_prover::abi::{self, AbiEncode, Word};
+use sha3::{Digest, Keccak256};
+
+use crate::{error::FrontendError, proof::ProofBundle};
+
+/// Solidity signature of the verifier entry point
+///
+/// The arguments are the setup digest, the primary and secondary verifier key digests, the number
+/// of steps, the final program counter, the initial and final registers of the primary and
+/// secondary circuits, and the [`AbiEncode`] words of the compressed proof.
+pub const VERIFY_SIGNATURE: &str = "verify(uint256,uint256,uint256,uint256,uint256,uint256[],\
+                                    uint256[],uint256[],uint256[],uint256[])";
+
+/// An ABI value, either a static word or a dynamic array
+enum Token {
+  /// A static 32 byte word, such as a `uint256` or a `bool`
+  Word(Word),
+  /// A dynamic `uint256[]`
+  Words(Vec<Word>),
+}
+
+/// Returns the function selector for a Solidity signature
+///
+/// # Arguments
+///
+/// * `signature` - The canonical signature, e.g. `transfer(address,uint256)`
+pub fn selector(signature: &str) -> [u8; 4] {
+  let hash = Keccak256::digest(signature.as_bytes());
+  [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Renders the Solidity interface of a verifier accepting [`ProofBundle::to_calldata`]
+pub fn verifier_interface() -> String {
+  "// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+interface IEdgeVerifier {
+    function verify(
+        uint256 setupDigest,
+        uint256 vkDigestPrimary,
+        uint256 vkDigestSecondary,
+        uint256 numSteps,
+        uint256 programCounter,
+        uint256[] calldata z0Primary,
+        uint256[] calldata znPrimary,
+        uint256[] calldata z0Secondary,
+        uint256[] calldata znSecondary,
+        uint256[] calldata proof
+    ) external view returns (bool);
+}
+"
+  .to_string()
+}
+
+impl ProofBundle {
+  /// ABI-encodes the bundle as a call to [`VERIFY_SIGNATURE`]
+  ///
+  /// Field elements are encoded as big-endian `uint256` words.
+  ///
+  /// # Returns
+  ///
+  /// The calldata, starting with the function selector, or a [`FrontendError`] if a commitment of
+  /// the proof is not a valid point
+  pub fn to_calldata(&self) -> Result<Vec<u8>, FrontendError> {
+    Ok(encode_call(&selector(VERIFY_SIGNATURE), &[
+      Token::Word(abi::field(&self.setup_digest)),
+      Token::Word(abi::field(&self.vk_digest_primary)),
+      Token::Word(abi::field(&self.vk_digest_secondary)),
+      Token::Word(abi::uint(self.num_steps as u64)),
+      Token::Word(abi::field(&self.program_counter)),
+      Token::Words(self.z0_primary.iter().map(abi::field).collect()),
+      Token::Words(self.zn_primary.iter().map(abi::field).collect()),
+      Token::Words(self.z0_secondary.iter().map(abi::field).collect()),
+      Token::Words(self.zn_secondary.iter().map(abi::field).collect()),
+      Token::Words(self.proof.to_abi_words()?),
+    ]))
+  }
+}
+
+/// ABI-encodes a call with the given selector and arguments
+fn encode_call(selector: &[u8; 4], tokens: &[Token]) -> Vec<u8> {
+  let mut head = Vec::with_capacity(32 * tokens.len());
+  let mut tail = Vec::new();
+
+  for token in tokens {
+    match token {
+      Token::Word(w) => head.extend_from_slice(w),
+      Token::Words(words) => {
+        head.extend_from_slice(&abi::uint((32 * tokens.len() + tail.len()) as u64));
+        tail.extend_from_slice(&abi::uint(words.len() as u64));
+        words.iter().for_each(|w| tail.extend_from_slice(w));
+      },
+    }
+  }
+
+  [&selector[..], &head[..], &tail[..]].concat()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_selector() {
+    assert_eq!(selector("transfer(address,uint256)"), [0xa9, 0x05, 0x9c, 0xbb]);
+  }
+
+  #[test]
+  fn test_abi_encoding() {
+    let calldata = encode_call(&[1, 2, 3, 4], &[
+      Token::Word(abi::uint(7)),
+      Token::Words(vec![abi::uint(1), abi::uint(2)]),
+      Token::Words(vec![]),
+    ]);
+
+    // dynamic arguments are offsets in the head, pointing to their length and words in the tail
+    let expected = [
+      "01020304",
+      "0000000000000000000000000000000000000000000000000000000000000007",
+      "0000000000000000000000000000000000000000000000000000000000000060",
+      "00000000000000000000000000000000000000000000000000000000000000c0",
+      "0000000000000000000000000000000000000000000000000000000000000002",
+      "0000000000000000000000000000000000000000000000000000000000000001",
+      "0000000000000000000000000000000000000000000000000000000000000002",
+      "0000000000000000000000000000000000000000000000000000000000000000",
+    ]
+    .concat();
+    let hex = calldata.iter().map(|b| format!("{b:02x}")).collect::<String>();
+    assert_eq!(hex, expected);
+  }
+}
@@ -0,0 +1,153 @@
+//! # JSON Inputs
+//!
+//! This module converts inputs given as JSON into the values the switchboard consumes, for callers
+//! that cannot build an [`InputMap`] themselves, such as the WebAssembly bindings.
+//!
+//! The public input is an array of field elements. The ROM inputs are an array with one object per
+//! step, mapping the parameter names of the step's circuit to their values. Field elements are JSON
+//! numbers or decimal or `0x`-prefixed hexadecimal strings, optionally negated with a leading `-`.
+//! Booleans are mapped to 0 and 1, arrays to Noir arrays and objects to Noir structs.
+
+use acvm::{acir::acir_field::GenericFieldElement, AcirField};
+use ark_bn254::Fr;
+use noirc_abi::{input_parser::InputValue, InputMap};
+use serde_json::Value;
+
+use super::*;
+use crate::noir::convert_to_halo2_field;
+
+/// Parses the public input, a JSON array of field elements
+///
+/// # Arguments
+///
+/// * `json` - The initial registers as JSON
+///
+/// # Returns
+///
+/// The initial registers as scalars
+///
+/// # Errors
+///
+/// Returns a [`FrontendError`] if the JSON is malformed or an element is not a field element
+pub fn parse_public_input(json: &str) -> Result<Vec<Scalar>, FrontendError> {
+  let values: Vec<Value> = serde_json::from_str(json)?;
+  values.iter().map(|value| parse_field(value).map(convert_to_halo2_field)).collect()
+}
+
+/// Parses the ROM inputs, a JSON array with one object of named inputs per step
+///
+/// # Arguments
+///
+/// * `json` - The inputs of each step as JSON
+///
+/// # Returns
+///
+/// The inputs of each step, as consumed by [`crate::program::Switchboard::into_rom`]
+///
+/// # Errors
+///
+/// Returns a [`FrontendError`] if the JSON is malformed or a value cannot be converted
+pub fn parse_rom_inputs(json: &str) -> Result<Vec<InputMap>, FrontendError> {
+  let steps: Vec<serde_json::Map<String, Value>> = serde_json::from_str(json)?;
+  steps
+    .into_iter()
+    .map(|step| {
+      step.into_iter().map(|(name, value)| Ok((name, parse_input_value(value)?))).collect()
+    })
+    .collect()
+}
+
+/// Converts a JSON value into a Noir input value
+///
+/// # Errors
+///
+/// Returns a [`FrontendError`] if a leaf of the value is not a field element
+fn parse_input_value(value: Value) -> Result<InputValue, FrontendError> {
+  match value {
+    Value::Array(values) =>
+      values.into_iter().map(parse_input_value).collect::<Result<_, _>>().map(InputValue::Vec),
+    Value::Object(fields) => fields
+      .into_iter()
+      .map(|(name, value)| Ok((name, parse_input_value(value)?)))
+      .collect::<Result<_, _>>()
+      .map(InputValue::Struct),
+    value => parse_field(&value).map(InputValue::Field),
+  }
+}
+
+/// Converts a JSON number, string or boolean into a field element
+///
+/// # Errors
+///
+/// Returns a [`FrontendError`] if the value does not describe a field element
+fn parse_field(value: &Value) -> Result<GenericFieldElement<Fr>, FrontendError> {
+  let invalid = || FrontendError::InvalidInput(format!("expected a field element, found {value}"));
+
+  match value {
+    Value::Bool(b) => Ok(GenericFieldElement::from(u64::from(*b))),
+    Value::Number(n) => n
+      .as_i64()
+      .map(|n| GenericFieldElement::from(i128::from(n)))
+      .or_else(|| n.as_u64().map(GenericFieldElement::from))
+      .ok_or_else(invalid),
+    Value::String(s) => match s.strip_prefix('-') {
+      Some(s) => GenericFieldElement::try_from_str(s).map(|f| -f),
+      None => GenericFieldElement::try_from_str(s),
+    }
+    .ok_or_else(invalid),
+    _ => Err(invalid()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_public_input() {
+    let public_input = parse_public_input(r#"[3, "5", "0x10", "-1", true]"#).unwrap();
+    assert_eq!(public_input, vec![
+      Scalar::from(3),
+      Scalar::from(5),
+      Scalar::from(16),
+      -Scalar::ONE,
+      Scalar::ONE
+    ]);
+
+    assert!(parse_public_input(r#"[1.5]"#).is_err());
+    assert!(parse_public_input(r#"["five"]"#).is_err());
+    assert!(parse_public_input(r#"{"a": 1}"#).is_err());
+  }
+
+  #[test]
+  fn test_parse_rom_inputs() {
+    let inputs =
+      parse_rom_inputs(r#"[{"next_pc": 1}, {"next_pc": -1, "data": {"xs": [1, "2"]}}]"#).unwrap();
+
+    assert_eq!(inputs, vec![
+      InputMap::from([(
+        "next_pc".to_string(),
+        InputValue::Field(GenericFieldElement::from(1_u64))
+      )]),
+      InputMap::from([
+        ("next_pc".to_string(), InputValue::Field(GenericFieldElement::from(-1_i128))),
+        (
+          "data".to_string(),
+          InputValue::Struct(
+            [(
+              "xs".to_string(),
+              InputValue::Vec(vec![
+                InputValue::Field(GenericFieldElement::from(1_u64)),
+                InputValue::Field(GenericFieldElement::from(2_u64)),
+              ]),
+            )]
+            .into(),
+          ),
+        ),
+      ]),
+    ]);
+
+    assert!(parse_rom_inputs(r#"[{"next_pc": null}]"#).is_err());
+    assert!(parse_rom_inputs(r#"[1]"#).is_err());
+  }
+}
@@ -13,6 +13,7 @@
 //! - **Switchboard**: Manages the flow between different circuit implementations
 //! - **Setup**: Handles parameter generation and initialization for the proof system
 //! - **Proof Generation**: Creation and verification of folding proofs
+//! - **Metrics**: Structured timings and sizes of proving runs, serializable as JSON
 //!
 //! ## Cryptographic Backends
 //!
@@ -40,9 +41,14 @@ use tracing::{debug, info};
 use crate::error::FrontendError;
 
 pub mod error;
+pub mod evm;
+pub mod input;
+pub mod metrics;
 pub mod noir;
 pub mod program;
+pub mod proof;
 pub mod setup;
+#[cfg(feature = "wasm")] pub mod wasm;
 
 /// Represents the scalar field for the primary curve (bn254)
 ///
@@ -0,0 +1,58 @@
+//! # Proving Metrics
+//!
+//! This module defines [`ProvingMetrics`], a structured record of a proving run for benchmarking,
+//! filled by [`program::run_with_metrics`] and [`program::compress_with_metrics`]. It holds:
+//!
+//! - The estimated peak memory, reported before proving starts
+//! - Per step: witness generation, synthesis, folding and commitment times, and the sizes of the
+//!   multi-scalar multiplications
+//! - The peak size of the scratch buffers of the folds
+//! - The compression breakdown into folding, sum-checks and polynomial commitment openings of the
+//!   primary and secondary SNARKs
+//!
+//! Metrics serialize as JSON with durations in fractional milliseconds, see
+//! [`ProvingMetrics::to_json`].
+
+use std::time::Duration;
+
+use edge_prover::metrics::duration_ms;
+pub use edge_prover::supernova::{
+  low_memory::MemoryEstimate,
+  metrics::{CompressionMetrics, MsmSizes, SnarkMetrics, StepMetrics},
+};
+
+use super::*;
+
+/// Metrics of a proving run and its compression
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvingMetrics {
+  /// Estimated peak memory of the run
+  pub memory_estimate:   MemoryEstimate,
+  /// Metrics of each step, in execution order
+  ///
+  /// The first step is proven when the recursive SNARK is created, so its synthesis time also
+  /// covers the commitment to its witness and no fold is recorded for it.
+  pub steps:             Vec<StepMetrics>,
+  /// Wall-clock time of the run
+  #[serde(with = "duration_ms")]
+  pub run:               Duration,
+  /// Largest size of the scratch buffers of the folds after a step, in bytes
+  pub peak_buffer_bytes: usize,
+  /// Metrics of the compression, once the run has been compressed
+  pub compression:       Option<CompressionMetrics>,
+}
+
+impl ProvingMetrics {
+  /// Records the metrics of the next step
+  pub(crate) fn push_step(&mut self, step: StepMetrics) {
+    self.peak_buffer_bytes = self.peak_buffer_bytes.max(step.buffer_bytes);
+    self.steps.push(step);
+  }
+
+  /// Serializes the metrics as JSON
+  ///
+  /// # Returns
+  ///
+  /// The JSON string, or a [`FrontendError`] on failure
+  pub fn to_json(&self) -> Result<String, FrontendError> { Ok(serde_json::to_string(self)?) }
+}
@@ -25,9 +25,13 @@ use acvm::{
 };
 use ark_bn254::Fr;
 use bellpepper_core::{num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError};
-use edge_prover::supernova::StepCircuit;
+use edge_prover::{
+  metrics::{self, Phase},
+  supernova::StepCircuit,
+};
 use halo2curves::ff::PrimeField;
 use noirc_abi::{input_parser::InputValue, Abi, AbiType, InputMap};
+use sha3::{Digest, Sha3_256};
 use tracing::{error, trace};
 
 use super::*;
@@ -96,6 +100,20 @@ impl NoirProgram {
   ///
   /// * `witness` - The input map containing witness values
   pub fn set_inputs(&mut self, witness: InputMap) { self.witness = Some(witness); }
+
+  /// Computes a digest of the program's ABI and bytecode
+  ///
+  /// Two programs with the same digest synthesize to the same circuit, so the digest can be used
+  /// to check that previously synthesized shapes still belong to this program. The witness and
+  /// index are not part of the digest.
+  ///
+  /// # Returns
+  ///
+  /// The SHA3-256 hash of the program's JSON encoding
+  pub fn digest(&self) -> [u8; 32] {
+    let bytes = serde_json::to_vec(self).expect("failed to serialize program");
+    Sha3_256::digest(bytes).into()
+  }
 }
 
 impl StepCircuit<Scalar> for NoirProgram {
@@ -188,46 +206,48 @@ impl StepCircuit<Scalar> for NoirProgram {
     let mut allocated_vars: HashMap<Witness, AllocatedNum<Scalar>> = HashMap::new();
 
     let acvm_witness_map = self.witness.as_ref().map(|inputs| {
-      let mut acvm = ACVM::new(
-        &StubbedBlackBoxSolver(false),
-        &self.circuit().opcodes,
-        WitnessMap::new(),
-        self.unconstrained_functions(),
-        &[],
-      );
+      metrics::timed(Phase::WitnessGeneration, || {
+        let mut acvm = ACVM::new(
+          &StubbedBlackBoxSolver(false),
+          &self.circuit().opcodes,
+          WitnessMap::new(),
+          self.unconstrained_functions(),
+          &[],
+        );
 
-      // TODO: Can we remove this clone since it may be a lot of data?
-      let mut inputs_with_folding_variables = inputs.clone();
-      let folding_variables = InputValue::Struct(BTreeMap::from([
-        (
-          "registers".to_string(),
-          InputValue::Vec(
-            z.iter()
-              .filter_map(|var| {
-                var.get_value().map(|v| InputValue::Field(convert_to_acir_field(v)))
-              })
-              .collect(),
+        // TODO: Can we remove this clone since it may be a lot of data?
+        let mut inputs_with_folding_variables = inputs.clone();
+        let folding_variables = InputValue::Struct(BTreeMap::from([
+          (
+            "registers".to_string(),
+            InputValue::Vec(
+              z.iter()
+                .filter_map(|var| {
+                  var.get_value().map(|v| InputValue::Field(convert_to_acir_field(v)))
+                })
+                .collect(),
+            ),
           ),
-        ),
-        (
-          // TODO: This is a bit hacky with unwraps
-          "program_counter".to_string(),
-          InputValue::Field(convert_to_acir_field(pc.unwrap().get_value().unwrap())),
-        ),
-      ]));
-      inputs_with_folding_variables.insert("folding_variables".to_string(), folding_variables);
-
-      // Encode inputs through ABI
-      if let Ok(encoded_map) = self.abi.encode(&inputs_with_folding_variables, None) {
-        for (witness, value) in encoded_map {
-          acvm.overwrite_witness(witness, value);
+          (
+            // TODO: This is a bit hacky with unwraps
+            "program_counter".to_string(),
+            InputValue::Field(convert_to_acir_field(pc.unwrap().get_value().unwrap())),
+          ),
+        ]));
+        inputs_with_folding_variables.insert("folding_variables".to_string(), folding_variables);
+
+        // Encode inputs through ABI
+        if let Ok(encoded_map) = self.abi.encode(&inputs_with_folding_variables, None) {
+          for (witness, value) in encoded_map {
+            acvm.overwrite_witness(witness, value);
+          }
         }
-      }
 
-      // Solve and get resulting witness map
-      debug!("Executing ACVM solve...");
-      acvm.solve();
-      acvm.finalize()
+        // Solve and get resulting witness map
+        debug!("Executing ACVM solve...");
+        acvm.solve();
+        acvm.finalize()
+      })
     });
 
     // Allocate variables from public inputs (z)
@@ -384,7 +404,7 @@ impl StepCircuit<Scalar> for NoirProgram {
 /// # Returns
 ///
 /// The field element in Halo2 representation
-fn convert_to_halo2_field(f: GenericFieldElement<Fr>) -> Scalar {
+pub(crate) fn convert_to_halo2_field(f: GenericFieldElement<Fr>) -> Scalar {
   let bytes = f.to_be_bytes();
   let mut arr = [0u8; 32];
   arr.copy_from_slice(&bytes[..32]);
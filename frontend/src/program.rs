@@ -21,16 +21,29 @@
 //! ## Execution Functions
 //!
 //! - [`run`]: Executes a program with the appropriate memory model
+//! - [`run_low_memory`]: Executes a program in the low-memory proving mode of the prover
+//! - [`run_with_metrics`] and [`compress_with_metrics`]: Execute and compress while recording
+//!   [`ProvingMetrics`]
 //! - [`compress`]: Compresses a recursive SNARK into a more compact form for verification
-
-use edge_prover::supernova::{NonUniformCircuit, RecursiveSNARK, TrivialCircuit};
+//! - [`verify_bundle`]: Verifies a [`ProofBundle`] against a setup
+
+use edge_prover::{
+  supernova::{
+    low_memory::LowMemoryConfig,
+    metrics::{CompressionMetrics, StepMetrics},
+    NonUniformCircuit, RecursiveSNARK, TrivialCircuit,
+  },
+  traits::snark::DigestHelperTrait,
+};
 use halo2curves::{ff::PrimeField, grumpkin};
 use noirc_abi::InputMap;
 use tracing::trace;
 
 use super::*;
 use crate::{
+  metrics::ProvingMetrics,
   noir::NoirProgram,
+  proof::{ProofBundle, PROOF_BUNDLE_VERSION},
   setup::{Ready, Setup},
 };
 
@@ -214,6 +227,15 @@ impl Switchboard<RAM> {
   }
 }
 
+impl<M: Memory> Switchboard<M> {
+  /// Returns the digests of the switchboard's circuits, in circuit index order
+  ///
+  /// See [`NoirProgram::digest`].
+  pub fn program_digests(&self) -> Vec<[u8; 32]> {
+    self.circuits.iter().map(NoirProgram::digest).collect()
+  }
+}
+
 impl<M: Memory> NonUniformCircuit<E1> for Switchboard<M> {
   type C1 = NoirProgram;
   type C2 = TrivialCircuit<grumpkin::Fr>;
@@ -249,20 +271,83 @@ impl<M: Memory> NonUniformCircuit<E1> for Switchboard<M> {
 ///
 /// Returns a [`FrontendError`] if execution fails
 pub fn run<M: Memory>(setup: &Setup<Ready<M>>) -> Result<RecursiveSNARK<E1>, FrontendError> {
+  run_with(setup, None, None)
+}
+
+/// Executes a program with the appropriate memory model in the low-memory proving mode
+///
+/// The running witnesses of inactive circuits are spilled to `config.spill_dir`, cross-terms are
+/// committed to in chunks and multi-scalar multiplications run over windows of the commitment key,
+/// see [`edge_prover::supernova::low_memory`]. The estimated peak memory is logged before proving
+/// starts, and the proof is the same as with [`run`].
+///
+/// # Arguments
+///
+/// * `setup` - The setup parameters for the program
+/// * `config` - The configuration of the low-memory mode
+///
+/// # Returns
+///
+/// A [`RecursiveSNARK`] representing the execution trace, still in the low-memory mode
+///
+/// # Errors
+///
+/// Returns a [`FrontendError`] if execution fails or witnesses cannot be spilled
+pub fn run_low_memory<M: Memory>(
+  setup: &Setup<Ready<M>>,
+  config: &LowMemoryConfig,
+) -> Result<RecursiveSNARK<E1>, FrontendError> {
+  run_with(setup, Some(config), None)
+}
+
+/// Executes a program with the appropriate memory model and records its metrics
+///
+/// This is [`run`], or [`run_low_memory`] if a configuration is given, which also fills `metrics`
+/// with the estimated peak memory, the metrics of each step and the wall-clock time of the run.
+/// Any previous content of `metrics` is replaced.
+///
+/// # Arguments
+///
+/// * `setup` - The setup parameters for the program
+/// * `low_memory` - The configuration of the low-memory mode, or `None` for regular proving
+/// * `metrics` - The metrics to fill
+///
+/// # Returns
+///
+/// A [`RecursiveSNARK`] representing the execution trace
+///
+/// # Errors
+///
+/// Returns a [`FrontendError`] if execution fails, in which case `metrics` covers the steps
+/// proven so far
+pub fn run_with_metrics<M: Memory>(
+  setup: &Setup<Ready<M>>,
+  low_memory: Option<&LowMemoryConfig>,
+  metrics: &mut ProvingMetrics,
+) -> Result<RecursiveSNARK<E1>, FrontendError> {
+  run_with(setup, low_memory, Some(metrics))
+}
+
+/// Dispatches to [`run_rom_with`] or [`run_ram_with`] based on the memory model
+fn run_with<M: Memory>(
+  setup: &Setup<Ready<M>>,
+  low_memory: Option<&LowMemoryConfig>,
+  metrics: Option<&mut ProvingMetrics>,
+) -> Result<RecursiveSNARK<E1>, FrontendError> {
   if std::any::type_name::<M>() == std::any::type_name::<ROM>() {
     // Safety: We've verified the type matches ROM
     let setup = unsafe {
       &*std::ptr::from_ref::<setup::Setup<setup::Ready<M>>>(setup)
         .cast::<setup::Setup<setup::Ready<program::ROM>>>()
     };
-    run_rom(setup)
+    run_rom_with(setup, low_memory, metrics)
   } else if std::any::type_name::<M>() == std::any::type_name::<RAM>() {
     // Safety: We've verified the type matches RAM
     let setup = unsafe {
       &*std::ptr::from_ref::<setup::Setup<setup::Ready<M>>>(setup)
         .cast::<setup::Setup<setup::Ready<program::RAM>>>()
     };
-    run_ram(setup)
+    run_ram_with(setup, low_memory, metrics)
   } else {
     unreachable!("The trait `Memory` is sealed, so you cannot reach this point")
   }
@@ -285,11 +370,22 @@ pub fn run<M: Memory>(setup: &Setup<Ready<M>>) -> Result<RecursiveSNARK<E1>, Fro
 ///
 /// Returns a [`FrontendError`] if execution fails
 pub fn run_rom(setup: &Setup<Ready<ROM>>) -> Result<RecursiveSNARK<E1>, FrontendError> {
+  run_rom_with(setup, None, None)
+}
+
+/// Executes a program using the ROM memory model, optionally in the low-memory proving mode and
+/// recording its metrics
+fn run_rom_with(
+  setup: &Setup<Ready<ROM>>,
+  low_memory: Option<&LowMemoryConfig>,
+  mut metrics: Option<&mut ProvingMetrics>,
+) -> Result<RecursiveSNARK<E1>, FrontendError> {
   info!("Starting SuperNova program with ROM memory model...");
+  begin_run(setup, low_memory, metrics.as_deref_mut());
 
   let z0_primary = &setup.switchboard.public_input;
   let z0_secondary = &[grumpkin::Fr::ZERO];
-  let time = std::time::Instant::now();
+  let time = web_time::Instant::now();
 
   let mut recursive_snark: Option<RecursiveSNARK<E1>> = None;
 
@@ -298,11 +394,22 @@ pub fn run_rom(setup: &Setup<Ready<ROM>>) -> Result<RecursiveSNARK<E1>, Frontend
     info!("Step {} of {} witnesses", idx + 1, setup.switchboard.switchboard_inputs.len());
 
     // TODO: We should not clone the witness here
-    recursive_snark =
-      prove_single_step(setup, recursive_snark, Some(witness.clone()), z0_primary, z0_secondary)?;
+    recursive_snark = prove_single_step(
+      setup,
+      recursive_snark,
+      Some(witness.clone()),
+      z0_primary,
+      z0_secondary,
+      low_memory,
+      metrics.as_deref_mut(),
+    )?;
   }
 
-  trace!("Recursive loop of `program::run()` elapsed: {:?}", time.elapsed());
+  let elapsed = time.elapsed();
+  trace!("Recursive loop of `program::run()` elapsed: {:?}", elapsed);
+  if let Some(metrics) = metrics {
+    metrics.run = elapsed;
+  }
   Ok(recursive_snark.unwrap())
 }
 
@@ -323,11 +430,22 @@ pub fn run_rom(setup: &Setup<Ready<ROM>>) -> Result<RecursiveSNARK<E1>, Frontend
 ///
 /// Returns a [`FrontendError`] if execution fails
 pub fn run_ram(setup: &Setup<Ready<RAM>>) -> Result<RecursiveSNARK<E1>, FrontendError> {
+  run_ram_with(setup, None, None)
+}
+
+/// Executes a program using the RAM memory model, optionally in the low-memory proving mode and
+/// recording its metrics
+fn run_ram_with(
+  setup: &Setup<Ready<RAM>>,
+  low_memory: Option<&LowMemoryConfig>,
+  mut metrics: Option<&mut ProvingMetrics>,
+) -> Result<RecursiveSNARK<E1>, FrontendError> {
   info!("Starting SuperNova program with RAM memory model...");
+  begin_run(setup, low_memory, metrics.as_deref_mut());
 
   let z0_primary = &setup.switchboard.public_input;
   let z0_secondary = &[grumpkin::Fr::ZERO];
-  let time = std::time::Instant::now();
+  let time = web_time::Instant::now();
 
   let mut recursive_snark: Option<RecursiveSNARK<E1>> = None;
   let termination_pc = Scalar::ZERO - Scalar::ONE;
@@ -348,22 +466,45 @@ pub fn run_ram(setup: &Setup<Ready<RAM>>) -> Result<RecursiveSNARK<E1>, Frontend
       None, // RAM doesn't use predefined witness values
       z0_primary,
       z0_secondary,
+      low_memory,
+      metrics.as_deref_mut(),
     )?;
   }
 
-  trace!("Recursive loop of `program::run()` elapsed: {:?}", time.elapsed());
+  let elapsed = time.elapsed();
+  trace!("Recursive loop of `program::run()` elapsed: {:?}", elapsed);
+  if let Some(metrics) = metrics {
+    metrics.run = elapsed;
+  }
   Ok(recursive_snark.unwrap())
 }
 
+/// Logs the estimated peak memory of a run and resets `metrics` for it
+fn begin_run<M: Memory>(
+  setup: &Setup<Ready<M>>,
+  low_memory: Option<&LowMemoryConfig>,
+  metrics: Option<&mut ProvingMetrics>,
+) {
+  let memory_estimate = setup.params.memory_estimate(low_memory);
+  info!("Estimated peak memory: {memory_estimate}");
+  if let Some(metrics) = metrics {
+    *metrics = ProvingMetrics { memory_estimate, ..Default::default() };
+  }
+}
+
 /// Helper function to prove a single step of execution
 ///
-/// This handles the common logic between ROM and RAM execution modes.
+/// This handles the common logic between ROM and RAM execution modes. A new [`RecursiveSNARK`] is
+/// switched to the low-memory proving mode if a configuration is given. The metrics of the step are
+/// recorded into `metrics`, if given.
 fn prove_single_step<M: Memory>(
   setup: &Setup<Ready<M>>,
   recursive_snark: Option<RecursiveSNARK<E1>>,
   witness: Option<InputMap>,
   z0_primary: &[Scalar],
   z0_secondary: &[grumpkin::Fr],
+  low_memory: Option<&LowMemoryConfig>,
+  metrics: Option<&mut ProvingMetrics>,
 ) -> Result<Option<RecursiveSNARK<E1>>, FrontendError> {
   let program_counter = recursive_snark.as_ref().map_or_else(
     || setup.switchboard.initial_circuit_index(),
@@ -386,23 +527,43 @@ fn prove_single_step<M: Memory>(
   let circuit_secondary = setup.switchboard.secondary_circuit();
 
   let mut result = recursive_snark;
+  let mut first_step = None;
   if result.is_none() {
-    result = Some(RecursiveSNARK::new(
-      &setup.params,
-      &setup.switchboard,
-      &circuit_primary,
-      &circuit_secondary,
-      z0_primary,
-      z0_secondary,
-    )?);
+    // the first step is proven by the constructor
+    let time = web_time::Instant::now();
+    let (snark, timings) = edge_prover::metrics::record(|| {
+      RecursiveSNARK::new(
+        &setup.params,
+        &setup.switchboard,
+        &circuit_primary,
+        &circuit_secondary,
+        z0_primary,
+        z0_secondary,
+      )
+    });
+    let mut snark = snark?;
+    first_step = Some((time.elapsed(), timings.witness_generation));
+    if let Some(config) = low_memory {
+      snark.enable_low_memory(&setup.params, config.clone())?;
+    }
+    result = Some(snark);
   }
 
   // Prove the next step
   info!("Proving single step...");
   let snark = result.as_mut().unwrap();
-  snark.prove_step(&setup.params, &circuit_primary, &circuit_secondary)?;
+  let mut step = StepMetrics::default();
+  snark.prove_step_with_metrics(&setup.params, &circuit_primary, &circuit_secondary, &mut step)?;
   info!("Done proving single step...");
 
+  if let Some(metrics) = metrics {
+    if let Some((elapsed, witness_generation)) = first_step {
+      step.witness_generation = witness_generation;
+      step.synthesis = elapsed.saturating_sub(witness_generation);
+    }
+    metrics.push_step(step);
+  }
+
   Ok(result)
 }
 
@@ -424,6 +585,55 @@ pub fn compress<M: Memory>(
   setup: &Setup<Ready<M>>,
   recursive_snark: &RecursiveSNARK<E1>,
 ) -> Result<CompressedSNARK, FrontendError> {
+  compress_with(setup, recursive_snark, false).map(|(proof, _)| proof)
+}
+
+/// Compresses a recursive SNARK like [`compress`], blinding the compressed witnesses
+///
+/// A random satisfying instance is folded into each final instance before compression, see
+/// [`CompressedSNARK::prove_blinded`]. The commitments are not hiding and the sumcheck is not
+/// zero-knowledge, so this is not a zero-knowledge proof on its own.
+///
+/// # Errors
+///
+/// Returns a `FrontendError` if compression fails
+pub fn compress_blinded<M: Memory>(
+  setup: &Setup<Ready<M>>,
+  recursive_snark: &RecursiveSNARK<E1>,
+) -> Result<CompressedSNARK, FrontendError> {
+  compress_with(setup, recursive_snark, true).map(|(proof, _)| proof)
+}
+
+/// Compresses a recursive SNARK like [`compress`] and records the compression breakdown
+///
+/// The time spent folding the running instances, and in the sum-checks and polynomial commitment
+/// openings of the primary and secondary SNARKs, is stored in `metrics.compression`.
+///
+/// # Arguments
+///
+/// * `setup` - The setup parameters for the program
+/// * `recursive_snark` - The recursive SNARK to compress
+/// * `metrics` - The metrics of the run, see [`run_with_metrics`]
+///
+/// # Errors
+///
+/// Returns a `FrontendError` if compression fails
+pub fn compress_with_metrics<M: Memory>(
+  setup: &Setup<Ready<M>>,
+  recursive_snark: &RecursiveSNARK<E1>,
+  metrics: &mut ProvingMetrics,
+) -> Result<CompressedSNARK, FrontendError> {
+  let (proof, compression) = compress_with(setup, recursive_snark, false)?;
+  metrics.compression = Some(compression);
+  Ok(proof)
+}
+
+/// Compresses a recursive SNARK, blinded if `blind` is set, and measures the compression
+fn compress_with<M: Memory>(
+  setup: &Setup<Ready<M>>,
+  recursive_snark: &RecursiveSNARK<E1>,
+  blind: bool,
+) -> Result<(CompressedSNARK, CompressionMetrics), FrontendError> {
   let pk = CompressedSNARK::initialize_pk(
     &setup.params,
     setup.vk_digest_primary,
@@ -435,9 +645,68 @@ pub fn compress<M: Memory>(
     pk.pk_secondary.vk_digest
   );
 
-  debug!("`CompressedSNARK::prove STARTING PROVING!");
-  let proof = CompressedSNARK::prove(&setup.params, &pk, recursive_snark)?;
-  debug!("`CompressedSNARK::prove completed!");
+  let prove = if blind { "prove_blinded" } else { "prove" };
+  let mut metrics = CompressionMetrics::default();
+  debug!("`CompressedSNARK::{prove} STARTING PROVING!");
+  let proof =
+    CompressedSNARK::prove_with_metrics(&setup.params, &pk, recursive_snark, blind, &mut metrics)?;
+  debug!("`CompressedSNARK::{prove} completed!");
 
-  Ok(proof)
+  Ok((proof, metrics))
+}
+
+/// Verifies a [`ProofBundle`] against a setup
+///
+/// The bundle's version, setup digest and verifier key digests are checked against the given setup,
+/// and its metadata is checked against the proof it carries before the proof itself is verified
+/// with the bundle's initial registers.
+///
+/// # Arguments
+///
+/// * `setup` - The setup for the circuits the proof was created with
+/// * `bundle` - The proof bundle to verify
+///
+/// # Errors
+///
+/// Returns a [`FrontendError::BundleMismatch`] if the bundle does not belong to the setup or is
+/// inconsistent with its proof, and another [`FrontendError`] if verification fails
+pub fn verify_bundle<M: Memory>(
+  setup: &Setup<Ready<M>>,
+  bundle: &ProofBundle,
+) -> Result<(), FrontendError> {
+  if bundle.version != PROOF_BUNDLE_VERSION {
+    return Err(edge_prover::fast_serde::SerdeByteError::UnsupportedVersion(bundle.version).into());
+  }
+  if bundle.setup_digest != setup.params.digest() {
+    return Err(FrontendError::BundleMismatch("setup digest"));
+  }
+  if bundle.vk_digest_primary != setup.vk_digest_primary
+    || bundle.vk_digest_secondary != setup.vk_digest_secondary
+  {
+    return Err(FrontendError::BundleMismatch("verifier key digest"));
+  }
+  if bundle.num_steps != bundle.proof.num_steps() {
+    return Err(FrontendError::BundleMismatch("number of steps"));
+  }
+  if bundle.program_counter != bundle.proof.program_counter() {
+    return Err(FrontendError::BundleMismatch("program counter"));
+  }
+
+  let vk = setup.verifier_key()?;
+  if vk.vk_primary.digest() != bundle.vk_digest_primary
+    || vk.vk_secondary.digest() != bundle.vk_digest_secondary
+  {
+    return Err(FrontendError::BundleMismatch("verifier key digest"));
+  }
+
+  debug!("`CompressedSNARK::verify` STARTING VERIFICATION!");
+  let (zn_primary, zn_secondary) =
+    bundle.proof.verify(&setup.params, &vk, &bundle.z0_primary, &bundle.z0_secondary)?;
+  debug!("`CompressedSNARK::verify` completed!");
+
+  if zn_primary != bundle.zn_primary || zn_secondary != bundle.zn_secondary {
+    return Err(FrontendError::BundleMismatch("final registers"));
+  }
+
+  Ok(())
 }
@@ -0,0 +1,206 @@
+//! # Proof Bundles
+//!
+//! This module defines the [`ProofBundle`], a self-describing container for a [`CompressedSNARK`]
+//! that carries everything a separate verifier process needs besides the setup itself:
+//!
+//! - The compressed proof
+//! - The initial (`z0`) and final (`zn`) registers for the primary and secondary circuits
+//! - The final program counter and the number of folded steps
+//! - The digests of the setup and of the verifier keys it was proven against
+//! - A format version
+//!
+//! A bundle can be serialized with [`FastSerde`] for compact storage or as JSON for transport and
+//! inspection. Bundles are checked and verified with [`program::verify_bundle`].
+
+use edge_prover::{
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
+  supernova::RecursiveSNARK,
+};
+use halo2curves::{ff::PrimeField, grumpkin};
+
+use super::*;
+use crate::{
+  program::Memory,
+  setup::{Ready, Setup},
+};
+
+/// The current version of the [`ProofBundle`] format
+pub const PROOF_BUNDLE_VERSION: u32 = 1;
+
+/// A compressed proof together with its public inputs and the metadata needed to verify it
+///
+/// The bundle binds the proof to a specific setup through the public parameters digest and the
+/// verifier key digests, so a verifier can reject a proof made for different circuits before
+/// running the (comparatively expensive) SNARK verification.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProofBundle {
+  /// Format version of the bundle, see [`PROOF_BUNDLE_VERSION`]
+  pub version: u32,
+
+  /// The compressed proof
+  pub proof: CompressedSNARK,
+
+  /// Initial registers of the primary circuits
+  pub z0_primary: Vec<Scalar>,
+
+  /// Final registers of the primary circuits
+  pub zn_primary: Vec<Scalar>,
+
+  /// Initial registers of the secondary circuit
+  pub z0_secondary: Vec<grumpkin::Fr>,
+
+  /// Final registers of the secondary circuit
+  pub zn_secondary: Vec<grumpkin::Fr>,
+
+  /// Program counter after the last step
+  pub program_counter: Scalar,
+
+  /// Number of folded steps
+  pub num_steps: usize,
+
+  /// Digest of the `PublicParams` the proof was created with
+  pub setup_digest: Scalar,
+
+  /// Primary verification key digest
+  pub vk_digest_primary: Scalar,
+
+  /// Secondary verification key digest
+  pub vk_digest_secondary: grumpkin::Fr,
+}
+
+impl ProofBundle {
+  /// Creates a new bundle for a compressed proof of the given recursive SNARK
+  ///
+  /// # Arguments
+  ///
+  /// * `setup` - The setup the proof was created with
+  /// * `recursive_snark` - The recursive SNARK that was compressed
+  /// * `proof` - The compressed proof
+  ///
+  /// # Returns
+  ///
+  /// A [`ProofBundle`] at the current [`PROOF_BUNDLE_VERSION`]
+  pub fn new<M: Memory>(
+    setup: &Setup<Ready<M>>,
+    recursive_snark: &RecursiveSNARK<E1>,
+    proof: CompressedSNARK,
+  ) -> Self {
+    Self {
+      version: PROOF_BUNDLE_VERSION,
+      z0_primary: recursive_snark.z0_primary().clone(),
+      zn_primary: proof.zn_primary().clone(),
+      z0_secondary: recursive_snark.z0_secondary().clone(),
+      zn_secondary: proof.zn_secondary().clone(),
+      program_counter: proof.program_counter(),
+      num_steps: proof.num_steps(),
+      setup_digest: setup.params.digest(),
+      vk_digest_primary: setup.vk_digest_primary,
+      vk_digest_secondary: setup.vk_digest_secondary,
+      proof,
+    }
+  }
+
+  /// Serializes the bundle as JSON
+  ///
+  /// # Returns
+  ///
+  /// The JSON string, or a [`FrontendError`] on failure
+  pub fn to_json(&self) -> Result<String, FrontendError> { Ok(serde_json::to_string(self)?) }
+
+  /// Deserializes a bundle from JSON
+  ///
+  /// # Arguments
+  ///
+  /// * `json` - The JSON encoded bundle
+  ///
+  /// # Returns
+  ///
+  /// The deserialized bundle, or a [`FrontendError`] on failure
+  pub fn from_json(json: &str) -> Result<Self, FrontendError> {
+    let bundle: Self = serde_json::from_str(json)?;
+    if bundle.version != PROOF_BUNDLE_VERSION {
+      return Err(SerdeByteError::UnsupportedVersion(bundle.version).into());
+    }
+    Ok(bundle)
+  }
+}
+
+impl FastSerde for ProofBundle {
+  /// Byte format:
+  ///
+  /// [0..4]   - Magic number (4 bytes)
+  /// [4]      - Serde type: ProofBundle (u8)
+  /// [5]      - Number of sections (u8 = 11)
+  /// Sections (repeated 11 times):
+  ///   [N]      - Section type (u8)
+  ///   [N+1..5] - Section size (u32)
+  ///   [N+5..]  - Section data (variable length)
+  /// Sections:
+  ///   1: version (u32)
+  ///   2: proof (FastSerde)
+  ///   3: z0_primary (scalars)
+  ///   4: zn_primary (scalars)
+  ///   5: z0_secondary (scalars)
+  ///   6: zn_secondary (scalars)
+  ///   7: program_counter (scalar)
+  ///   8: num_steps (u64)
+  ///   9: setup_digest (scalar)
+  ///   10: vk_digest_primary (scalar)
+  ///   11: vk_digest_secondary (scalar)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::ProofBundle);
+
+    out.section(1, self.version.to_le_bytes());
+    out.section(2, self.proof.to_bytes());
+    out.section(3, fast_serde::scalars_to_bytes(&self.z0_primary));
+    out.section(4, fast_serde::scalars_to_bytes(&self.zn_primary));
+    out.section(5, fast_serde::scalars_to_bytes(&self.z0_secondary));
+    out.section(6, fast_serde::scalars_to_bytes(&self.zn_secondary));
+    out.section(7, self.program_counter.to_repr().as_ref().to_vec());
+    out.section(8, (self.num_steps as u64).to_le_bytes());
+    out.section(9, self.setup_digest.to_repr().as_ref().to_vec());
+    out.section(10, self.vk_digest_primary.to_repr().as_ref().to_vec());
+    out.section(11, self.vk_digest_secondary.to_repr().as_ref().to_vec());
+
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::ProofBundle)?;
+
+    let version = sections
+      .section(1)?
+      .try_into()
+      .map(u32::from_le_bytes)
+      .map_err(|_| SerdeByteError::InvalidSectionSize)?;
+    if version != PROOF_BUNDLE_VERSION {
+      return Err(SerdeByteError::UnsupportedVersion(version));
+    }
+
+    let proof = CompressedSNARK::from_bytes(sections.section(2)?)?;
+    let z0_primary = fast_serde::scalars_from_bytes(sections.section(3)?)?;
+    let zn_primary = fast_serde::scalars_from_bytes(sections.section(4)?)?;
+    let z0_secondary = fast_serde::scalars_from_bytes(sections.section(5)?)?;
+    let zn_secondary = fast_serde::scalars_from_bytes(sections.section(6)?)?;
+    let program_counter = fast_serde::scalar_from_bytes(sections.section(7)?)?;
+    let num_steps = fast_serde::usize_from_bytes(sections.section(8)?)?;
+    let setup_digest = fast_serde::scalar_from_bytes(sections.section(9)?)?;
+    let vk_digest_primary = fast_serde::scalar_from_bytes(sections.section(10)?)?;
+    let vk_digest_secondary = fast_serde::scalar_from_bytes(sections.section(11)?)?;
+    sections.finish()?;
+
+    Ok(Self {
+      version,
+      proof,
+      z0_primary,
+      zn_primary,
+      z0_secondary,
+      zn_secondary,
+      program_counter,
+      num_steps,
+      setup_digest,
+      vk_digest_primary,
+      vk_digest_secondary,
+    })
+  }
+}
@@ -11,25 +11,40 @@
 //!
 //! The setup can be in one of two states:
 //! - **Offline**: Contains only auxiliary parameters without a switchboard (can be serialized for
-//!   storage)
+//!   storage), and optionally the circuit shapes synthesized from the switchboard it was created
+//!   with
 //! - **Ready**: Complete setup with a switchboard that's ready for program execution
 //!
 //! ## Storage
 //!
 //! Setup parameters can be serialized and stored to disk, then later deserialized and
-//! combined with a switchboard to create a ready setup.
+//! combined with a switchboard to create a ready setup. Stored setups carry the R1CS shapes of
+//! their circuits, keyed by the digests of the Noir programs they were synthesized from, so that
+//! loading a setup for the same programs does not need to synthesize every circuit again.
+//!
+//! Setups stored with [`Setup::store_mapped_file`] keep the commitment keys in a layout that
+//! [`Setup::load_mapped_file`] can memory-map, so the keys are read in place instead of being
+//! copied into memory.
 
-use std::io::Cursor;
+use std::sync::Arc;
 
 use edge_prover::{
-  fast_serde::{self, FastSerde, SerdeByteError, SerdeByteTypes},
+  fast_serde::{
+    self,
+    mapped::{self, Mapping},
+    FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes,
+  },
   supernova::{get_circuit_shapes, snark::CompressedSNARK, PublicParams},
   traits::{snark::default_ck_hint, Dual, Engine},
+  R1CSWithArity,
 };
 use tracing::debug;
 
 use super::*;
-use crate::program::{Memory, Switchboard};
+use crate::{
+  error::SetupMismatch,
+  program::{Memory, Switchboard},
+};
 
 /// Trait that defines the status of a setup
 ///
@@ -42,6 +57,9 @@ pub trait Status: private::Sealed {
 
   /// The public parameters type associated with this status
   type PublicParams;
+
+  /// The stored circuit shapes type associated with this status
+  type CircuitShapes;
 }
 
 /// Private module for sealing the Status trait
@@ -65,6 +83,8 @@ pub struct Ready<M: Memory> {
 }
 
 impl<M: Memory> Status for Ready<M> {
+  /// A ready setup keeps its circuit shapes inside the public parameters
+  type CircuitShapes = ();
   /// A ready setup uses a switchboard with the specified memory model
   type PublicParams = PublicParams<E1>;
   /// A ready setup has a specific switchboard associated with it
@@ -79,12 +99,29 @@ impl<M: Memory> Status for Ready<M> {
 pub struct Offline;
 
 impl Status for Offline {
+  /// An offline setup may carry the circuit shapes it was created with
+  type CircuitShapes = Option<StoredShapes>;
   /// An offline setup only contains auxiliary parameters
   type PublicParams = AuxParams;
   /// An offline setup doesn't have a switchboard
   type Switchboard = ();
 }
 
+/// Circuit shapes stored alongside an offline setup
+///
+/// The shapes are reused by [`Setup::try_into_ready`] once the digests of the switchboard's
+/// programs have been checked against [`StoredShapes::program_digests`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredShapes {
+  /// Digests of the Noir programs the shapes were synthesized from, see [`NoirProgram::digest`]
+  ///
+  /// [`NoirProgram::digest`]: crate::noir::NoirProgram::digest
+  pub program_digests: Vec<[u8; 32]>,
+
+  /// The R1CS shapes of the augmented circuits, in circuit index order
+  pub shapes: Vec<R1CSWithArity<E1>>,
+}
+
 /// Setup parameters for NIVC computation
 ///
 /// This structure holds the cryptographic parameters, verification key digests,
@@ -102,6 +139,9 @@ pub struct Setup<S: Status> {
 
   /// Switchboard (if the setup is [`Ready`]) or unit (if [`Offline`])
   pub switchboard: S::Switchboard,
+
+  /// Stored circuit shapes (if the setup is [`Offline`]) or unit (if [`Ready`])
+  pub circuit_shapes: S::CircuitShapes,
 }
 
 #[cfg(test)]
@@ -136,26 +176,41 @@ impl<M: Memory> Setup<Ready<M>> {
       vk_digest_primary: pk.pk_primary.vk_digest,
       vk_digest_secondary: pk.pk_secondary.vk_digest,
       switchboard,
+      circuit_shapes: (),
     })
   }
 
   /// Converts a ready setup to an offline setup
   ///
-  /// This extracts the auxiliary parameters from the public parameters and
-  /// creates an offline setup without the switchboard, which can be serialized.
+  /// This extracts the auxiliary parameters and circuit shapes from the public parameters and
+  /// creates an offline setup without the switchboard, which can be serialized. The shapes are
+  /// stored together with the digests of the switchboard's programs.
   ///
   /// # Returns
   ///
-  /// An offline setup containing only the auxiliary parameters
+  /// An offline setup containing the auxiliary parameters and circuit shapes
   fn into_offline(self) -> Setup<Offline> {
+    let program_digests = self.switchboard.program_digests();
+    let (shapes, params) = self.params.into_parts();
     Setup {
-      params:              self.params.into_parts().1,
-      vk_digest_primary:   self.vk_digest_primary,
+      params,
+      vk_digest_primary: self.vk_digest_primary,
       vk_digest_secondary: self.vk_digest_secondary,
-      switchboard:         (),
+      switchboard: (),
+      circuit_shapes: Some(StoredShapes { program_digests, shapes }),
     }
   }
 
+  /// Serializes the setup as an offline setup
+  ///
+  /// These are the bytes [`Setup::store_file`] writes, for callers without a filesystem such as the
+  /// WebAssembly bindings. They are read back with [`FastSerde::from_bytes`].
+  ///
+  /// # Returns
+  ///
+  /// The serialized offline setup
+  pub fn into_bytes(self) -> Vec<u8> { self.into_offline().to_bytes() }
+
   /// Serializes the setup and stores it to a file
   ///
   /// This converts the setup to an offline setup, serializes it, and writes
@@ -169,7 +224,7 @@ impl<M: Memory> Setup<Ready<M>> {
   ///
   /// The serialized bytes on success, or a `FrontendError` on failure
   pub fn store_file(self, path: &std::path::PathBuf) -> Result<Vec<u8>, FrontendError> {
-    let bytes = self.into_offline().to_bytes();
+    let bytes = self.into_bytes();
     if let Some(parent) = path.parent() {
       std::fs::create_dir_all(parent)?;
     }
@@ -180,6 +235,41 @@ impl<M: Memory> Setup<Ready<M>> {
     Ok(bytes)
   }
 
+  /// Serializes the setup into a file that can be memory-mapped
+  ///
+  /// The commitment keys are written in their raw in-memory layout after the rest of the offline
+  /// setup, so that [`Setup::load_mapped_file`] can borrow them from the mapped file instead of
+  /// copying them.
+  ///
+  /// # Arguments
+  ///
+  /// * `path` - The file path where the setup should be stored
+  ///
+  /// # Returns
+  ///
+  /// Unit on success, or a `FrontendError` on failure
+  pub fn store_mapped_file(self, path: &std::path::PathBuf) -> Result<(), FrontendError> {
+    let mut offline = self.into_offline();
+    let powers_of_g =
+      std::mem::take(&mut Arc::make_mut(&mut offline.params.ck_primary).powers_of_g);
+    let ck_secondary = std::mem::take(&mut Arc::make_mut(&mut offline.params.ck_secondary).ck);
+
+    let mut out = SectionWriter::new(SerdeByteTypes::MappedProverParams);
+    out.section(1, offline.to_bytes());
+    mapped::write_points_section(&mut out, 2, &powers_of_g);
+    mapped::write_points_section(&mut out, 3, &ck_secondary);
+    let out = out.finish();
+
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+
+    debug!("using path={:?}", path);
+    std::io::Write::write_all(&mut std::fs::File::create(path)?, &out)?;
+
+    Ok(())
+  }
+
   /// Returns the verifier key for the setup
   ///
   /// This method generates the verifier key for the setup using the public parameters.
@@ -196,8 +286,7 @@ impl<M: Memory> Setup<Ready<M>> {
 impl Setup<Offline> {
   /// Converts an offline setup to a ready setup
   ///
-  /// This combines the auxiliary parameters with a switchboard to create
-  /// a ready setup that can be used to execute programs.
+  /// This is [`Setup::try_into_ready`] for callers that know the switchboard matches the setup.
   ///
   /// # Arguments
   ///
@@ -206,13 +295,82 @@ impl Setup<Offline> {
   /// # Returns
   ///
   /// A ready setup containing the parameters and switchboard
+  ///
+  /// # Panics
+  ///
+  /// Panics if the switchboard does not match the setup, see [`Setup::try_into_ready`].
+  #[deprecated(note = "use `Setup::try_into_ready`, which returns an error on a mismatched \
+                       switchboard instead of panicking")]
   pub fn into_ready<M: Memory>(self, switchboard: Switchboard<M>) -> Setup<Ready<M>> {
-    Setup {
-      params: PublicParams::from_parts(get_circuit_shapes(&switchboard), self.params),
+    self.try_into_ready(switchboard).expect("switchboard does not match the setup")
+  }
+
+  /// Converts an offline setup to a ready setup, checking that the switchboard matches it
+  ///
+  /// This combines the auxiliary parameters with a switchboard to create a ready setup that can be
+  /// used to execute programs. If the setup carries circuit shapes, the digests of the
+  /// switchboard's programs are compared against the stored ones and the stored shapes are used,
+  /// otherwise the switchboard's circuits are synthesized to obtain them. In both cases the digest
+  /// of the resulting public parameters is checked against the one the setup was created with, and
+  /// the verifier key digests are recomputed from them and checked against the stored ones.
+  ///
+  /// # Arguments
+  ///
+  /// * `switchboard` - The switchboard to be used for execution
+  ///
+  /// # Returns
+  ///
+  /// A ready setup containing the parameters and switchboard, or a
+  /// [`FrontendError::SetupMismatch`] describing how the switchboard differs from the setup
+  pub fn try_into_ready<M: Memory>(
+    self,
+    switchboard: Switchboard<M>,
+  ) -> Result<Setup<Ready<M>>, FrontendError> {
+    let circuit_shapes = match self.circuit_shapes {
+      Some(stored) => {
+        let program_digests = switchboard.program_digests();
+        if program_digests.len() != stored.program_digests.len() {
+          return Err(
+            SetupMismatch::CircuitCount {
+              expected: stored.program_digests.len(),
+              found:    program_digests.len(),
+            }
+            .into(),
+          );
+        }
+        if let Some(index) = program_digests
+          .iter()
+          .zip(&stored.program_digests)
+          .position(|(found, stored)| found != stored)
+        {
+          return Err(SetupMismatch::Circuit { index }.into());
+        }
+        debug!("using stored circuit shapes");
+        stored.shapes
+      },
+      None => {
+        debug!("synthesizing circuit shapes");
+        get_circuit_shapes(&switchboard)
+      },
+    };
+
+    let params = PublicParams::try_from_parts(circuit_shapes, self.params)
+      .ok_or(SetupMismatch::PublicParams)?;
+
+    let (pk, _vk) = CompressedSNARK::<E1, S1, S2>::setup(&params)?;
+    if pk.pk_primary.vk_digest != self.vk_digest_primary
+      || pk.pk_secondary.vk_digest != self.vk_digest_secondary
+    {
+      return Err(SetupMismatch::VerifierKey.into());
+    }
+
+    Ok(Setup {
+      params,
       vk_digest_primary: self.vk_digest_primary,
       vk_digest_secondary: self.vk_digest_secondary,
       switchboard,
-    }
+      circuit_shapes: (),
+    })
   }
 
   /// Deserializes a setup from a file
@@ -228,9 +386,36 @@ impl Setup<Offline> {
     let bytes = std::fs::read(path)?;
     Ok(Self::from_bytes(&bytes)?)
   }
+
+  /// Loads a setup stored with [`Setup::store_mapped_file`]
+  ///
+  /// The file is memory-mapped and the commitment keys borrow their points from the mapping where
+  /// the platform allows it, so they are not copied into memory. Borrowed points are not checked
+  /// to lie on their curves, so only load files from a trusted source. The file must not be
+  /// modified while the setup is in use.
+  ///
+  /// # Arguments
+  ///
+  /// * `path` - The file path where the setup is stored
+  ///
+  /// # Returns
+  ///
+  /// The deserialized setup, or a [`FrontendError`] on failure
+  pub fn load_mapped_file(path: &std::path::PathBuf) -> Result<Self, FrontendError> {
+    let mapping = Mapping::open(path)?;
+    let mut sections = SectionReader::new(mapping.bytes(), SerdeByteTypes::MappedProverParams)?;
+
+    let mut setup = Self::from_bytes(sections.section(1)?)?;
+    Arc::make_mut(&mut setup.params.ck_primary).powers_of_g =
+      mapped::read_points_section(&mut sections, &mapping, 2)?;
+    Arc::make_mut(&mut setup.params.ck_secondary).ck =
+      mapped::read_points_section(&mut sections, &mapping, 3)?;
+    sections.finish()?;
+
+    Ok(setup)
+  }
 }
 
-// TODO: We should consider using `rkyv` for serialization and deserialization
 impl FastSerde for Setup<Offline> {
   /// Deserializes a setup from bytes
   ///
@@ -242,25 +427,48 @@ impl FastSerde for Setup<Offline> {
   ///
   /// The deserialized offline setup, or a `SerdeByteError` on failure
   fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
-    let mut cursor = Cursor::new(bytes);
-    Self::validate_header(&mut cursor, SerdeByteTypes::ProverParams, 3)?;
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::ProverParams)?;
 
-    let params =
-      Self::read_section_bytes(&mut cursor, 1).map(|bytes| AuxParams::from_bytes(&bytes))??;
+    let params = AuxParams::from_bytes(sections.section(1)?)?;
 
-    let vk_digest_primary = Self::read_section_bytes(&mut cursor, 2)
+    let vk_digest_primary = sections
+      .section(2)
       .and_then(|bytes| bytes.try_into().map_err(|_| SerdeByteError::G1DecodeError))
       .map(|bytes| <E1 as Engine>::Scalar::from_bytes(&bytes))?
       .into_option()
       .ok_or(SerdeByteError::G1DecodeError)?;
 
-    let vk_digest_secondary = Self::read_section_bytes(&mut cursor, 3)
+    let vk_digest_secondary = sections
+      .section(3)
       .and_then(|bytes| bytes.try_into().map_err(|_| SerdeByteError::G2DecodeError))
       .map(|bytes| <Dual<E1> as Engine>::Scalar::from_bytes(&bytes))?
       .into_option()
       .ok_or(SerdeByteError::G1DecodeError)?;
 
-    Ok(Self { params, vk_digest_primary, vk_digest_secondary, switchboard: () })
+    // the stored shapes are only a cache, so they are optional sections
+    let circuit_shapes = match (sections.optional_section(4), sections.optional_section(5)) {
+      (Some(program_digests), Some(shapes)) => {
+        if program_digests.len() % 32 != 0 {
+          return Err(SerdeByteError::InvalidSectionSize);
+        }
+        let program_digests = program_digests
+          .chunks_exact(32)
+          .map(|digest| digest.try_into().unwrap())
+          .collect::<Vec<[u8; 32]>>();
+        let shapes = fast_serde::list_from_bytes(shapes)?
+          .into_iter()
+          .map(R1CSWithArity::from_bytes)
+          .collect::<Result<Vec<_>, _>>()?;
+        if shapes.len() != program_digests.len() {
+          return Err(SerdeByteError::InvalidSectionSize);
+        }
+        Some(StoredShapes { program_digests, shapes })
+      },
+      _ => None,
+    };
+    sections.finish()?;
+
+    Ok(Self { params, vk_digest_primary, vk_digest_secondary, switchboard: (), circuit_shapes })
   }
 
   /// Serializes a setup to bytes
@@ -269,23 +477,29 @@ impl FastSerde for Setup<Offline> {
   ///
   /// The serialized setup data
   fn to_bytes(&self) -> Vec<u8> {
-    let mut out = Vec::new();
-    out.extend_from_slice(&fast_serde::MAGIC_NUMBER);
-    out.push(SerdeByteTypes::ProverParams as u8);
-    out.push(3); // num_sections
+    let mut out = SectionWriter::new(SerdeByteTypes::ProverParams);
 
-    Self::write_section_bytes(&mut out, 1, &self.params.to_bytes());
-    Self::write_section_bytes(&mut out, 2, &self.vk_digest_primary.to_bytes());
-    Self::write_section_bytes(&mut out, 3, &self.vk_digest_secondary.to_bytes());
+    out.section(1, self.params.to_bytes());
+    out.section(2, self.vk_digest_primary.to_bytes());
+    out.section(3, self.vk_digest_secondary.to_bytes());
 
-    out
+    if let Some(stored) = &self.circuit_shapes {
+      let shapes = stored.shapes.iter().map(R1CSWithArity::to_bytes).collect::<Vec<_>>();
+      out.optional_section(4, stored.program_digests.concat());
+      out.optional_section(5, fast_serde::list_to_bytes(&shapes));
+    }
+
+    out.finish()
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::{demo::square_zeroth, program::Configuration};
+  use crate::{
+    demo::{square_zeroth, swap_memory},
+    program::Configuration,
+  };
 
   #[test]
   fn test_setup_and_params() {
@@ -312,8 +526,90 @@ mod tests {
     let _bytes = setup.store_file(&path.join("setup.bytes")).unwrap();
     let stored_bytes = std::fs::read(path.join("setup.bytes")).unwrap();
     let deserialized = Setup::<Offline>::from_bytes(&stored_bytes).unwrap();
-    let ready_setup = deserialized.into_ready(switchboard);
+    let ready_setup = deserialized.try_into_ready(switchboard).unwrap();
     assert_eq!(vk_digest_primary, ready_setup.vk_digest_primary);
     assert_eq!(vk_digest_secondary, ready_setup.vk_digest_secondary);
   }
+
+  #[test]
+  fn test_setup_store_mapped_file() {
+    let switchboard = Switchboard::<Configuration>::new(vec![square_zeroth()]);
+    let setup = Setup::new(switchboard.clone()).unwrap();
+    let vk_digest_primary = setup.vk_digest_primary;
+    let digest = setup.params.digest();
+    let path = tempfile::tempdir().unwrap().into_path().join("setup.mapped");
+    setup.store_mapped_file(&path).unwrap();
+
+    let mapped_setup = Setup::<Offline>::load_mapped_file(&path).unwrap();
+    assert!(mapped_setup.params.ck_primary.powers_of_g.is_mapped());
+    assert!(mapped_setup.params.ck_secondary.ck.is_mapped());
+
+    let ready_setup = mapped_setup.try_into_ready(switchboard).unwrap();
+    assert_eq!(vk_digest_primary, ready_setup.vk_digest_primary);
+    assert_eq!(digest, ready_setup.params.digest());
+  }
+
+  #[test]
+  fn test_setup_stored_shapes() {
+    let switchboard = Switchboard::<Configuration>::new(vec![square_zeroth()]);
+    let setup = Setup::new(switchboard.clone()).unwrap();
+    let offline_setup = Setup::<Offline>::from_bytes(&setup.into_offline().to_bytes()).unwrap();
+
+    let stored = offline_setup.circuit_shapes.clone().unwrap();
+    assert_eq!(stored.program_digests, switchboard.program_digests());
+    assert_eq!(stored.shapes, get_circuit_shapes(&switchboard));
+
+    // a setup stored without shapes still loads by synthesizing them
+    let mut without_shapes = offline_setup.clone();
+    without_shapes.circuit_shapes = None;
+    let without_shapes = Setup::<Offline>::from_bytes(&without_shapes.to_bytes()).unwrap();
+    assert!(without_shapes.circuit_shapes.is_none());
+
+    let synthesized = without_shapes.try_into_ready(switchboard.clone()).unwrap();
+    let stored = offline_setup.try_into_ready(switchboard).unwrap();
+    assert_eq!(synthesized.params.digest(), stored.params.digest());
+  }
+
+  #[test]
+  fn test_setup_switchboard_mismatch() {
+    let switchboard = Switchboard::<Configuration>::new(vec![square_zeroth(), swap_memory()]);
+    let setup = Setup::new(switchboard).unwrap();
+    let offline_setup = Setup::<Offline>::from_bytes(&setup.into_offline().to_bytes()).unwrap();
+
+    let swapped = Switchboard::<Configuration>::new(vec![swap_memory(), square_zeroth()]);
+    let error = offline_setup.clone().try_into_ready(swapped.clone()).err().unwrap();
+    assert!(matches!(error, FrontendError::SetupMismatch(SetupMismatch::Circuit { index: 0 })));
+
+    let fewer = Switchboard::<Configuration>::new(vec![square_zeroth()]);
+    let error = offline_setup.clone().try_into_ready(fewer).err().unwrap();
+    assert!(matches!(
+      error,
+      FrontendError::SetupMismatch(SetupMismatch::CircuitCount { expected: 2, found: 1 })
+    ));
+
+    // without stored shapes the mismatch is caught by the public parameters digest
+    let mut without_shapes = offline_setup;
+    without_shapes.circuit_shapes = None;
+    let error = without_shapes.try_into_ready(swapped).err().unwrap();
+    assert!(matches!(error, FrontendError::SetupMismatch(SetupMismatch::PublicParams)));
+  }
+
+  #[test]
+  fn test_setup_verifier_key_mismatch() {
+    let switchboard = Switchboard::<Configuration>::new(vec![square_zeroth()]);
+    let setup = Setup::new(switchboard.clone()).unwrap();
+    let offline_setup = Setup::<Offline>::from_bytes(&setup.into_offline().to_bytes()).unwrap();
+
+    let mut tampered = offline_setup.clone();
+    tampered.vk_digest_primary += Scalar::from(1);
+    let error = tampered.try_into_ready(switchboard.clone()).err().unwrap();
+    assert!(matches!(error, FrontendError::SetupMismatch(SetupMismatch::VerifierKey)));
+
+    let mut tampered = offline_setup.clone();
+    tampered.vk_digest_secondary += <Dual<E1> as Engine>::Scalar::from(1);
+    let error = tampered.try_into_ready(switchboard.clone()).err().unwrap();
+    assert!(matches!(error, FrontendError::SetupMismatch(SetupMismatch::VerifierKey)));
+
+    assert!(offline_setup.try_into_ready(switchboard).is_ok());
+  }
 }
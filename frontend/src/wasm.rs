@@ -0,0 +1,196 @@
+//! # WebAssembly Bindings
+//!
+//! This module exposes the offline setup, ROM proving and compression flow to JavaScript through
+//! `wasm-bindgen`. It is compiled with the `wasm` feature and targets `wasm32-unknown-unknown`,
+//! e.g. with `wasm-pack build frontend --features wasm`.
+//!
+//! A [`WasmProver`] is driven in three steps:
+//! 1. Load the bytes of an offline setup (as produced by [`Setup::into_bytes`] or
+//!    [`Setup::store_file`]) with [`WasmProver::load_setup`], and register the compiled Noir
+//!    artifacts of the switchboard in order with [`WasmProver::register_program`].
+//! 2. Run a ROM execution with [`WasmProver::prove_rom`], passing the public input and the inputs
+//!    of each step as JSON, see [`crate::input`] for the format.
+//! 3. Compress the execution with [`WasmProver::compress`], which returns a serialized
+//!    [`ProofBundle`].
+
+use edge_prover::{fast_serde::FastSerde, supernova::RecursiveSNARK};
+use wasm_bindgen::prelude::*;
+
+use super::*;
+use crate::{
+  input::{parse_public_input, parse_rom_inputs},
+  noir::NoirProgram,
+  program::{self, Switchboard, ROM},
+  proof::ProofBundle,
+  setup::{Offline, Ready, Setup},
+};
+
+/// Proves ROM executions of a switchboard from JavaScript
+///
+/// The setup is readied with the registered programs by the first call to
+/// [`WasmProver::prove_rom`] and reused by later calls, so programs can no longer be registered
+/// after the first proof.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct WasmProver {
+  /// Offline setup loaded with [`WasmProver::load_setup`], until the first proof readies it
+  setup:           Option<Setup<Offline>>,
+  /// Programs of the switchboard, in registration order
+  programs:        Vec<NoirProgram>,
+  /// Setup readied with the registered programs
+  ready:           Option<Setup<Ready<ROM>>>,
+  /// Recursive SNARK of the last call to [`WasmProver::prove_rom`]
+  recursive_snark: Option<RecursiveSNARK<E1>>,
+}
+
+#[wasm_bindgen]
+impl WasmProver {
+  /// Creates a prover without a setup or programs
+  #[wasm_bindgen(constructor)]
+  pub fn new() -> Self { Self::default() }
+
+  /// Loads a serialized offline setup
+  ///
+  /// Any setup readied by a previous proof is discarded together with that proof, while the
+  /// registered programs are kept.
+  ///
+  /// # Arguments
+  ///
+  /// * `bytes` - The offline setup, as produced by [`Setup::into_bytes`]
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the bytes are not a valid offline setup
+  #[wasm_bindgen(js_name = loadSetup)]
+  pub fn load_setup(&mut self, bytes: &[u8]) -> Result<(), JsError> {
+    self.setup = Some(Setup::<Offline>::from_bytes(bytes)?);
+    self.ready = None;
+    self.recursive_snark = None;
+    Ok(())
+  }
+
+  /// Registers the next circuit of the switchboard
+  ///
+  /// Circuits must be registered in the order the setup was created with.
+  ///
+  /// # Arguments
+  ///
+  /// * `artifact` - The JSON artifact emitted by `nargo compile`
+  ///
+  /// # Returns
+  ///
+  /// The index of the circuit in the switchboard
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the artifact cannot be parsed or a proof has already been made
+  #[wasm_bindgen(js_name = registerProgram)]
+  pub fn register_program(&mut self, artifact: &[u8]) -> Result<usize, JsError> {
+    if self.ready.is_some() {
+      return Err(JsError::new("programs must be registered before the first proof"));
+    }
+
+    let program: NoirProgram = serde_json::from_slice(artifact)?;
+    self.programs.push(program);
+    Ok(self.programs.len() - 1)
+  }
+
+  /// Runs a ROM execution of the registered programs
+  ///
+  /// The resulting recursive SNARK is kept until it is compressed with [`WasmProver::compress`] or
+  /// replaced by the next call.
+  ///
+  /// # Arguments
+  ///
+  /// * `initial_circuit_index` - The index of the circuit to start execution from
+  /// * `public_input` - JSON array of the initial registers
+  /// * `inputs` - JSON array with the named inputs of each step
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the inputs are invalid, no setup has been loaded, the registered programs
+  /// do not match the setup, or proving fails. A setup that does not match the programs has to be
+  /// loaded again.
+  #[wasm_bindgen(js_name = proveRom)]
+  pub fn prove_rom(
+    &mut self,
+    initial_circuit_index: usize,
+    public_input: &str,
+    inputs: &str,
+  ) -> Result<(), JsError> {
+    let public_input = parse_public_input(public_input)?;
+    let inputs = parse_rom_inputs(inputs)?;
+    if inputs.is_empty() {
+      return Err(JsError::new("at least one step of inputs is required"));
+    }
+    if initial_circuit_index >= self.programs.len() {
+      return Err(JsError::new(&format!(
+        "initial circuit index {initial_circuit_index} is out of range for {} registered programs",
+        self.programs.len()
+      )));
+    }
+    self.recursive_snark = None;
+
+    let setup = match self.ready.take() {
+      Some(mut setup) => {
+        setup.switchboard.public_input = public_input;
+        setup.switchboard.initial_circuit_index = initial_circuit_index;
+        setup.switchboard.switchboard_inputs = inputs;
+        setup
+      },
+      None => {
+        let offline = self.setup.take().ok_or_else(|| JsError::new("no setup has been loaded"))?;
+        let switchboard = Switchboard::<ROM>::new(
+          self.programs.clone(),
+          inputs,
+          public_input,
+          initial_circuit_index,
+        );
+        offline.try_into_ready(switchboard)?
+      },
+    };
+
+    let setup = self.ready.insert(setup);
+    self.recursive_snark = Some(program::run_rom(setup)?);
+    Ok(())
+  }
+
+  /// Compresses the last ROM execution into a serialized proof bundle
+  ///
+  /// # Returns
+  ///
+  /// The [`ProofBundle`] in its binary form, see [`FastSerde::to_bytes`]
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if nothing has been proven yet or compression fails
+  pub fn compress(&self) -> Result<Vec<u8>, JsError> { Ok(self.compress_bundle()?.to_bytes()) }
+
+  /// Compresses the last ROM execution into a proof bundle serialized as JSON
+  ///
+  /// # Returns
+  ///
+  /// The [`ProofBundle`] as JSON, see [`ProofBundle::to_json`]
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if nothing has been proven yet or compression fails
+  #[wasm_bindgen(js_name = compressJson)]
+  pub fn compress_json(&self) -> Result<String, JsError> { Ok(self.compress_bundle()?.to_json()?) }
+}
+
+impl WasmProver {
+  /// Compresses the last ROM execution and bundles it with the data needed to verify it
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if nothing has been proven yet or compression fails
+  fn compress_bundle(&self) -> Result<ProofBundle, JsError> {
+    let (Some(setup), Some(recursive_snark)) = (&self.ready, &self.recursive_snark) else {
+      return Err(JsError::new("nothing to compress, run `proveRom` first"));
+    };
+
+    let proof = program::compress(setup, recursive_snark)?;
+    Ok(ProofBundle::new(setup, recursive_snark, proof))
+  }
+}
@@ -2,11 +2,13 @@ use std::fs;
 
 use acvm::acir::acir_field::GenericFieldElement;
 use edge_frontend::{
-  demo,
+  demo, evm,
   program::{self, Configuration, Switchboard},
+  proof::ProofBundle,
   setup::Setup,
-  CompressedSNARK, Scalar,
+  Scalar,
 };
+use edge_prover::{abi, fast_serde::FastSerde};
 use noirc_abi::{input_parser::InputValue, InputMap};
 use tempfile::tempdir;
 
@@ -59,7 +61,7 @@ fn test_end_to_end_workflow() {
   // Briefly test the switchboard into_rom method
   let pswitchboard =
     switchboard.into_rom(0, vec![input1, input2], vec![Scalar::from(3), Scalar::from(5)]);
-  let psetup = psetup.into_ready(pswitchboard);
+  let psetup = psetup.try_into_ready(pswitchboard).unwrap();
   println!("6. Ready the setup for proving with the switchboard");
 
   // Step 7: Run a proof
@@ -70,29 +72,38 @@ fn test_end_to_end_workflow() {
   let compressed_proof = program::compress(&psetup, &recursive_snark).unwrap();
   println!("8. Compressed the proof");
 
-  // Step 9: Serialize and store the proof in a file
-  let serialized_proof = bincode::serialize(&compressed_proof).unwrap();
+  // Step 9: Bundle, serialize and store the proof in a file
+  let bundle = ProofBundle::new(&psetup, &recursive_snark, compressed_proof);
   let proof_file_path = temp_dir.path().join("test_proof.bytes");
-  fs::write(&proof_file_path, &serialized_proof).unwrap();
-  println!("9. Saved the serialized proof to a file");
+  fs::write(&proof_file_path, bundle.to_bytes()).unwrap();
+  println!("9. Saved the serialized proof bundle to a file");
   // ----------------------------------------------------------------------------------------------------------------- //
 
   // ----------------------------------------------------------------------------------------------------------------- //
   // Separate Verification Phase
   // ----------------------------------------------------------------------------------------------------------------- //
-  // Step 10: Read and deserialize the proof
+  // Step 10: Read and deserialize the proof bundle
   let proof_bytes_from_file = fs::read(&proof_file_path).unwrap();
-  let deserialized_proof: CompressedSNARK = bincode::deserialize(&proof_bytes_from_file).unwrap();
-  println!("10. Read and deserialized the proof");
+  let deserialized_bundle = ProofBundle::from_bytes(&proof_bytes_from_file).unwrap();
+  let json_bundle = ProofBundle::from_json(&deserialized_bundle.to_json().unwrap()).unwrap();
+  println!("10. Read and deserialized the proof bundle");
 
-  // Step 11: Verify the proof digests match by loading the setup from file as if we were a verifier
+  // Step 11: Verify the bundle by loading the setup from file as if we were a verifier, which only
+  // needs the circuits and no state from the prover
   let vsetup = Setup::load_file(&file_path).unwrap();
   let vswitchboard = Switchboard::<Configuration>::new(vec![swap_memory_program, square_program]);
-  let vsetup = vsetup.into_ready(vswitchboard);
-  let vk = vsetup.verifier_key().unwrap();
-  deserialized_proof
-    .verify(&vsetup.params, &vk, recursive_snark.z0_primary(), recursive_snark.z0_secondary())
-    .unwrap();
+  let vsetup = vsetup.try_into_ready(vswitchboard).unwrap();
+  program::verify_bundle(&vsetup, &deserialized_bundle).unwrap();
+  program::verify_bundle(&vsetup, &json_bundle).unwrap();
   println!("11. Verified the proof");
+
+  // Step 12: Encode the bundle as calldata for an on-chain verifier, which takes the verifier key
+  // digests after the setup digest and the proof as whole words
+  let calldata = deserialized_bundle.to_calldata().unwrap();
+  assert_eq!(calldata[..4], evm::selector(evm::VERIFY_SIGNATURE));
+  assert_eq!(calldata[36..68], abi::field(&deserialized_bundle.vk_digest_primary));
+  assert_eq!(calldata[68..100], abi::field(&deserialized_bundle.vk_digest_secondary));
+  assert_eq!((calldata.len() - 4) % 32, 0);
+  println!("12. Encoded the proof as calldata");
   // ----------------------------------------------------------------------------------------------------------------- //
 }
@@ -1,10 +1,13 @@
 use acvm::acir::acir_field::GenericFieldElement;
 use edge_frontend::{
-  program::{compress, run, Switchboard, RAM, ROM},
-  setup::Setup,
+  metrics::ProvingMetrics,
+  program::{
+    compress, compress_with_metrics, run, run_low_memory, run_with_metrics, Switchboard, RAM, ROM,
+  },
+  setup::{Ready, Setup},
   Scalar,
 };
-use edge_prover::supernova::snark::CompressedSNARK;
+use edge_prover::supernova::{low_memory::LowMemoryConfig, snark::CompressedSNARK};
 use halo2curves::{ff::Field, grumpkin};
 use noirc_abi::{input_parser::InputValue, InputMap};
 
@@ -73,9 +76,9 @@ fn test_ivc_private_inputs() {
   assert_eq!(zi[1], Scalar::from(74));
 }
 
-#[test]
-#[traced_test]
-fn test_nivc() {
+/// Sets up the programs `add_external`, `square_zeroth` and `swap_memory`, executed once each in
+/// this order from the registers `[1, 2]`
+fn nivc_setup() -> Setup<Ready<ROM>> {
   let programs = vec![add_external(), square_zeroth(), swap_memory()];
   let switchboard_inputs = vec![
     InputMap::from([
@@ -100,7 +103,13 @@ fn test_nivc() {
     vec![Scalar::from(1), Scalar::from(2)],
     0,
   );
-  let setup = Setup::new(switchboard).unwrap();
+  Setup::new(switchboard).unwrap()
+}
+
+#[test]
+#[traced_test]
+fn test_nivc() {
+  let setup = nivc_setup();
   let snark = run(&setup).unwrap();
   let zi = snark.zi_primary();
   dbg!(zi);
@@ -117,6 +126,52 @@ fn test_nivc() {
   assert_eq!(zi[1], Scalar::from(36));
 }
 
+#[test]
+#[traced_test]
+fn test_nivc_low_memory() {
+  let setup = nivc_setup();
+  let spill_dir = std::env::temp_dir().join(format!("nivc-low-memory-{}", std::process::id()));
+  let config =
+    LowMemoryConfig { chunk_len: 1 << 10, msm_window: 1 << 10, ..LowMemoryConfig::new(&spill_dir) };
+  let snark = run_low_memory(&setup, &config).unwrap();
+  assert!(snark.is_low_memory());
+  assert_eq!(snark.zi_primary(), &vec![Scalar::from(9), Scalar::from(36)]);
+  snark.verify(&setup.params, snark.z0_primary(), snark.z0_secondary()).unwrap();
+
+  let compressed_proof = compress(&setup, &snark).unwrap();
+  let (_, vk) = CompressedSNARK::setup(&setup.params).unwrap();
+  compressed_proof.verify(&setup.params, &vk, snark.z0_primary(), snark.z0_secondary()).unwrap();
+
+  drop(snark);
+  std::fs::remove_dir(&spill_dir).unwrap();
+}
+
+#[test]
+#[traced_test]
+fn test_nivc_metrics() {
+  let setup = nivc_setup();
+  let mut metrics = ProvingMetrics::default();
+  let snark = run_with_metrics(&setup, None, &mut metrics).unwrap();
+  assert_eq!(metrics.steps.iter().map(|step| step.circuit_index).collect::<Vec<_>>(), vec![
+    0, 1, 2
+  ]);
+  assert!(metrics.steps.iter().all(|step| step.witness_generation > std::time::Duration::ZERO));
+  assert!(metrics.steps.iter().skip(1).all(|step| step.msm_primary.witness > 0));
+  assert!(metrics.peak_buffer_bytes > 0);
+  assert!(metrics.memory_estimate.total() > 0);
+
+  let compressed_proof = compress_with_metrics(&setup, &snark, &mut metrics).unwrap();
+  let (_, vk) = CompressedSNARK::setup(&setup.params).unwrap();
+  compressed_proof.verify(&setup.params, &vk, snark.z0_primary(), snark.z0_secondary()).unwrap();
+  let compression = metrics.compression.unwrap();
+  assert!(compression.primary.sumcheck > std::time::Duration::ZERO);
+  assert!(compression.secondary.pcs > std::time::Duration::ZERO);
+
+  let json: serde_json::Value = serde_json::from_str(&metrics.to_json().unwrap()).unwrap();
+  assert_eq!(json["steps"].as_array().unwrap().len(), 3);
+  assert!(json["compression"]["primary"]["total"].as_f64().unwrap() > 0.0);
+}
+
 #[test]
 #[traced_test]
 fn test_ivc_verify() {
@@ -0,0 +1,43 @@
+//! Runs the setup, prove and compress flow through the WebAssembly bindings.
+//!
+//! Run with `cargo test -p edge-frontend --features wasm --target wasm32-unknown-unknown --test
+//! wasm` and `wasm-bindgen-test-runner` as the target runner, after compiling the examples with
+//! `nargo compile --workspace`. The WebAssembly job of the check workflow runs it under Node.
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use edge_frontend::{
+  noir::NoirProgram,
+  program::{self, Configuration, Switchboard},
+  proof::ProofBundle,
+  setup::{Offline, Setup},
+  wasm::WasmProver,
+};
+use edge_prover::fast_serde::FastSerde;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const SWAP_MEMORY: &[u8] = include_bytes!("../../target/swap_memory.json");
+const SQUARE_ZEROTH: &[u8] = include_bytes!("../../target/square_zeroth.json");
+
+#[wasm_bindgen_test]
+fn test_wasm_prove_and_compress() {
+  let programs = vec![NoirProgram::new(SWAP_MEMORY), NoirProgram::new(SQUARE_ZEROTH)];
+  let switchboard = Switchboard::<Configuration>::new(programs.clone());
+  let setup_bytes = Setup::new(switchboard).unwrap().into_bytes();
+
+  let mut prover = WasmProver::new();
+  assert!(prover.compress().is_err());
+  assert!(prover.load_setup(&setup_bytes).is_ok());
+  assert_eq!(prover.register_program(SWAP_MEMORY).ok(), Some(0));
+  assert_eq!(prover.register_program(SQUARE_ZEROTH).ok(), Some(1));
+  assert!(prover.prove_rom(2, "[3, 5]", r#"[{"next_pc": 1}, {"next_pc": -1}]"#).is_err());
+  assert!(prover.prove_rom(0, "[3, 5]", r#"[{"next_pc": 1}, {"next_pc": -1}]"#).is_ok());
+  assert!(prover.register_program(SQUARE_ZEROTH).is_err());
+
+  let bundle = ProofBundle::from_bytes(&prover.compress().ok().unwrap()).unwrap();
+  let json_bundle = ProofBundle::from_json(&prover.compress_json().ok().unwrap()).unwrap();
+
+  let vsetup = Setup::<Offline>::from_bytes(&setup_bytes).unwrap();
+  let vsetup = vsetup.try_into_ready(Switchboard::<Configuration>::new(programs)).unwrap();
+  program::verify_bundle(&vsetup, &bundle).unwrap();
+  program::verify_bundle(&vsetup, &json_bundle).unwrap();
+}
@@ -0,0 +1,132 @@
+//! This module encodes proofs as 32 byte words for verifiers on the EVM.
+//!
+//! The [`FastSerde`](crate::fast_serde::FastSerde) encoding is compact, but a
+//! contract would have to parse its section table and decompress every point.
+//! [`AbiEncode`] instead flattens a proof into the layout of a Solidity
+//! `uint256[]`, which a contract reads with plain indexing:
+//!
+//! - Field elements are one big-endian word.
+//! - Commitments and curve points are two words, their affine `x` and `y` coordinates. The identity
+//!   is encoded as `(0, 0)`, as expected by the `ecAdd` and `ecMul` precompiles.
+//! - Variable-length lists are prefixed with their number of elements. Lists of fixed-size groups,
+//!   such as the three claims per instance of a batched SNARK, are flattened and prefixed with
+//!   their total number of elements.
+//! - Fixed-size fields are written without a prefix, in declaration order.
+
+use ff::PrimeField;
+use group::prime::PrimeCurveAffine;
+
+use crate::{
+  errors::NovaError,
+  provider::traits::DlogGroup,
+  traits::{commitment::CommitmentTrait, Engine},
+  Commitment, CompressedCommitment,
+};
+
+/// A big-endian 32 byte word, the ABI encoding of a `uint256`
+pub type Word = [u8; 32];
+
+/// A type that can be encoded as a sequence of [`Word`]s
+pub trait AbiEncode {
+  /// Appends the encoding of `self` to `out`
+  ///
+  /// Fails if a compressed commitment of `self` does not decompress to a point.
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError>;
+
+  /// Returns the encoding of `self`
+  fn to_abi_words(&self) -> Result<Vec<Word>, NovaError> {
+    let mut out = Vec::new();
+    self.abi_encode(&mut out)?;
+    Ok(out)
+  }
+}
+
+/// Encodes an integer as a word
+pub fn uint(n: u64) -> Word {
+  let mut out = [0u8; 32];
+  out[24..].copy_from_slice(&n.to_be_bytes());
+  out
+}
+
+/// Encodes a field element as a word
+///
+/// The canonical representation of the supported fields is little-endian and
+/// at most 32 bytes long.
+pub fn field<F: PrimeField>(f: &F) -> Word {
+  let mut out = [0u8; 32];
+  let repr = f.to_repr();
+  for (o, b) in out.iter_mut().rev().zip(repr.as_ref()) {
+    *o = *b;
+  }
+  out
+}
+
+/// Appends field elements, prefixed with their number
+pub fn encode_fields<F: PrimeField>(out: &mut Vec<Word>, fs: &[F]) {
+  out.push(uint(fs.len() as u64));
+  out.extend(fs.iter().map(field));
+}
+
+/// Appends the coordinates of a group element
+pub fn encode_point<G: DlogGroup>(out: &mut Vec<Word>, p: &G) {
+  let (x, y, _) = p.to_coordinates();
+  out.extend([field(&x), field(&y)]);
+}
+
+/// Appends the coordinates of affine points, prefixed with their number
+pub fn encode_affine_points<A>(out: &mut Vec<Word>, ps: &[A])
+where
+  A: PrimeCurveAffine,
+  A::Curve: DlogGroup, {
+  out.push(uint(ps.len() as u64));
+  ps.iter().for_each(|p| encode_point(out, &p.to_curve()));
+}
+
+/// Appends the coordinates of a commitment
+pub fn encode_commitment<E: Engine>(out: &mut Vec<Word>, c: &Commitment<E>) {
+  let (x, y, _) = c.to_coordinates();
+  out.extend([field(&x), field(&y)]);
+}
+
+/// Appends the coordinates of compressed commitments, prefixed with their
+/// number
+pub fn encode_compressed_commitments<E: Engine>(
+  out: &mut Vec<Word>,
+  cs: &[CompressedCommitment<E>],
+) -> Result<(), NovaError> {
+  out.push(uint(cs.len() as u64));
+  for c in cs {
+    encode_commitment::<E>(out, &Commitment::<E>::decompress(c)?);
+  }
+  Ok(())
+}
+
+/// Appends the encodings of `items`, prefixed with their number
+pub fn encode_list<T: AbiEncode>(out: &mut Vec<Word>, items: &[T]) -> Result<(), NovaError> {
+  out.push(uint(items.len() as u64));
+  items.iter().try_for_each(|item| item.abi_encode(out))
+}
+
+#[cfg(test)]
+mod tests {
+  use group::Group;
+  use halo2curves::bn256::{Fr, G1};
+
+  use super::*;
+
+  #[test]
+  fn test_words() {
+    let w = field(&Fr::from(0x0102u64));
+    assert_eq!(w[30..], [1, 2]);
+    assert!(w[..30].iter().all(|b| *b == 0));
+
+    // the identity is (0, 0), as for the precompiles
+    let mut out = Vec::new();
+    encode_point(&mut out, &G1::identity());
+    assert_eq!(out, [[0u8; 32]; 2]);
+
+    let mut out = Vec::new();
+    encode_fields(&mut out, &[Fr::from(7u64)]);
+    assert_eq!(out, [uint(1), uint(7)]);
+  }
+}
@@ -0,0 +1,272 @@
+//! This module defines customizable constraint systems (CCS), along with the
+//! committed (CCCS) and linearized (LCCCS) instances folded by the HyperNova
+//! multi-folding scheme in [`nimfs`].
+//!
+//! A CCS over matrices `M_0, ..., M_{t-1}` is satisfied by `z = (W, u, X)` if
+//! `∑_i c_i · ∘_{j ∈ S_i} M_j·z = 0`, where `∘` is the Hadamard product. Each
+//! multiset `S_i` is a product term, so a gate of degree `d` is a single row
+//! instead of `d - 1` R1CS rows. R1CS is the special case `M = (A, B, C)`,
+//! `S = ({0, 1}, {2})` and `c = (1, -1)`, see [`CCSShape::from_r1cs`].
+//!
+//! Bellpepper circuits, and thus every `StepCircuit`, synthesize to R1CS, so
+//! their shapes are lifted with [`CCSShape::from_r1cs`]. Higher-degree gates,
+//! such as ACIR gates with several product terms or Plonkish custom gates, are
+//! described by building the shape with [`CCSShape::new`]. Only the native
+//! multi-folding scheme is provided: folding a CCS step circuit recursively
+//! also needs the verifier of [`nimfs::NIMFS`] as an augmented circuit.
+
+use ff::Field;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  errors::NovaError,
+  r1cs::{R1CSInstance, R1CSShape, R1CSWitness, SparseMatrix},
+  spartan::{math::Math, polys::multilinear::MultilinearPolynomial},
+  traits::{commitment::CommitmentEngineTrait, Engine, TranscriptReprTrait},
+  Commitment, CommitmentKey, CE,
+};
+
+pub mod nimfs;
+
+/// A type that holds the shape of a CCS
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CCSShape<E: Engine> {
+  pub(crate) num_cons: usize,
+  pub(crate) num_vars: usize,
+  pub(crate) num_io:   usize,
+  pub(crate) M:        Vec<SparseMatrix<E::Scalar>>,
+  pub(crate) S:        Vec<Vec<usize>>,
+  pub(crate) c:        Vec<E::Scalar>,
+}
+
+/// A type that holds a witness for a CCS instance
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CCSWitness<E: Engine> {
+  pub(crate) W: Vec<E::Scalar>,
+}
+
+/// A type that holds a committed CCS instance, whose witness is satisfying
+/// with `u = 1`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CCCSInstance<E: Engine> {
+  pub(crate) comm_W: Commitment<E>,
+  pub(crate) X:      Vec<E::Scalar>,
+}
+
+/// A type that holds a linearized committed CCS instance, which claims that
+/// the multilinear extension of `M_j·z` evaluates to `v_j` at `r_x`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct LCCCSInstance<E: Engine> {
+  pub(crate) comm_W: Commitment<E>,
+  pub(crate) u:      E::Scalar,
+  pub(crate) X:      Vec<E::Scalar>,
+  pub(crate) r_x:    Vec<E::Scalar>,
+  pub(crate) v:      Vec<E::Scalar>,
+}
+
+impl<E: Engine> CCSShape<E> {
+  /// Create an object of type `CCSShape` from the explicitly specified CCS
+  /// matrices, multisets and constants
+  pub fn new(
+    num_cons: usize,
+    num_vars: usize,
+    num_io: usize,
+    M: Vec<SparseMatrix<E::Scalar>>,
+    S: Vec<Vec<usize>>,
+    c: Vec<E::Scalar>,
+  ) -> Result<Self, NovaError> {
+    let is_matrix_invalid = |M: &SparseMatrix<E::Scalar>| {
+      M.num_rows() != num_cons || M.num_cols() != num_vars + 1 + num_io
+    };
+    if M.iter().any(is_matrix_invalid) {
+      return Err(NovaError::InvalidIndex);
+    }
+
+    if S.len() != c.len() || S.iter().flatten().any(|j| *j >= M.len()) {
+      return Err(NovaError::InvalidIndex);
+    }
+
+    Ok(Self { num_cons, num_vars, num_io, M, S, c })
+  }
+
+  /// Lifts an R1CS shape to the equivalent CCS shape
+  pub fn from_r1cs(S: &R1CSShape<E>) -> Self {
+    Self {
+      num_cons: S.num_cons,
+      num_vars: S.num_vars,
+      num_io:   S.num_io,
+      M:        vec![S.A.clone(), S.B.clone(), S.C.clone()],
+      S:        vec![vec![0, 1], vec![2]],
+      c:        vec![E::Scalar::ONE, -E::Scalar::ONE],
+    }
+  }
+
+  /// The maximum degree of a product term
+  pub fn degree(&self) -> usize { self.S.iter().map(Vec::len).max().unwrap_or(0) }
+
+  /// The number of variables of the multilinear extensions over the rows
+  pub fn num_rounds(&self) -> usize { self.num_cons.next_power_of_two().log_2() }
+
+  /// Computes `M_j·z` for `z = (W, u, X)`, padded to a power of two
+  pub(crate) fn multiply_witness(
+    &self,
+    W: &[E::Scalar],
+    u: &E::Scalar,
+    X: &[E::Scalar],
+  ) -> Result<Vec<Vec<E::Scalar>>, NovaError> {
+    if W.len() != self.num_vars || X.len() != self.num_io {
+      return Err(NovaError::InvalidWitnessLength);
+    }
+
+    Ok(
+      self
+        .M
+        .par_iter()
+        .map(|M| {
+          let mut Mz = M.multiply_witness(W, u, X);
+          Mz.resize(self.num_cons.next_power_of_two(), E::Scalar::ZERO);
+          Mz
+        })
+        .collect(),
+    )
+  }
+
+  /// Evaluates `∑_i c_i · ∏_{j ∈ S_i} v_j`
+  pub(crate) fn compute_sum_of_products(&self, v: &[E::Scalar]) -> E::Scalar {
+    self
+      .S
+      .iter()
+      .zip(&self.c)
+      .map(|(S_i, c_i)| S_i.iter().map(|j| v[*j]).product::<E::Scalar>() * c_i)
+      .sum()
+  }
+
+  /// Checks if the committed CCS instance is satisfiable given a witness
+  pub fn is_sat(
+    &self,
+    ck: &CommitmentKey<E>,
+    U: &CCCSInstance<E>,
+    W: &CCSWitness<E>,
+  ) -> Result<(), NovaError> {
+    let Mz = self.multiply_witness(&W.W, &E::Scalar::ONE, &U.X)?;
+
+    (0..self.num_cons).into_par_iter().try_for_each(|i| {
+      let row = Mz.iter().map(|Mz| Mz[i]).collect::<Vec<_>>();
+      if self.compute_sum_of_products(&row) != E::Scalar::ZERO {
+        Err(NovaError::UnSatIndex(i))
+      } else {
+        Ok(())
+      }
+    })?;
+
+    if U.comm_W != W.commit(ck) {
+      return Err(NovaError::UnSat);
+    }
+    Ok(())
+  }
+
+  /// Checks if the linearized committed CCS instance is satisfiable given a
+  /// witness
+  pub fn is_sat_linearized(
+    &self,
+    ck: &CommitmentKey<E>,
+    U: &LCCCSInstance<E>,
+    W: &CCSWitness<E>,
+  ) -> Result<(), NovaError> {
+    if U.r_x.len() != self.num_rounds() || U.v.len() != self.M.len() {
+      return Err(NovaError::UnSat);
+    }
+
+    let Mz = self.multiply_witness(&W.W, &U.u, &U.X)?;
+    let is_v_invalid = Mz
+      .into_par_iter()
+      .zip(&U.v)
+      .any(|(Mz, v)| MultilinearPolynomial::evaluate_with(&Mz, &U.r_x) != *v);
+    if is_v_invalid {
+      return Err(NovaError::UnSat);
+    }
+
+    if U.comm_W != W.commit(ck) {
+      return Err(NovaError::UnSat);
+    }
+    Ok(())
+  }
+}
+
+impl<E: Engine> CCSWitness<E> {
+  /// A method to create a witness object using a vector of scalars
+  pub fn new(S: &CCSShape<E>, W: Vec<E::Scalar>) -> Result<Self, NovaError> {
+    if S.num_vars != W.len() {
+      return Err(NovaError::InvalidWitnessLength);
+    }
+    Ok(Self { W })
+  }
+
+  /// Produces a default `CCSWitness` given a `CCSShape`
+  pub fn default(S: &CCSShape<E>) -> Self { Self { W: vec![E::Scalar::ZERO; S.num_vars] } }
+
+  /// Commits to the witness using the supplied generators
+  pub fn commit(&self, ck: &CommitmentKey<E>) -> Commitment<E> { CE::<E>::commit(ck, &self.W) }
+
+  /// Folds an incoming `CCSWitness` into the current one
+  pub(crate) fn fold(&self, W2: &Self, rho: &E::Scalar) -> Self {
+    let W = self.W.par_iter().zip(&W2.W).map(|(a, b)| *a + *rho * *b).collect();
+    Self { W }
+  }
+}
+
+impl<E: Engine> From<R1CSWitness<E>> for CCSWitness<E> {
+  fn from(W: R1CSWitness<E>) -> Self { Self { W: W.W } }
+}
+
+impl<E: Engine> CCCSInstance<E> {
+  /// A method to create an instance object using constituent elements
+  pub fn new(S: &CCSShape<E>, comm_W: Commitment<E>, X: Vec<E::Scalar>) -> Result<Self, NovaError> {
+    if S.num_io != X.len() {
+      return Err(NovaError::InvalidInputLength);
+    }
+    Ok(Self { comm_W, X })
+  }
+}
+
+impl<E: Engine> From<R1CSInstance<E>> for CCCSInstance<E> {
+  fn from(U: R1CSInstance<E>) -> Self { Self { comm_W: U.comm_W, X: U.X } }
+}
+
+impl<E: Engine> LCCCSInstance<E> {
+  /// Produces a default `LCCCSInstance`, satisfied by the default
+  /// `CCSWitness`, to start folding from
+  pub fn default(S: &CCSShape<E>) -> Self {
+    Self {
+      comm_W: Commitment::<E>::default(),
+      u:      E::Scalar::ZERO,
+      X:      vec![E::Scalar::ZERO; S.num_io],
+      r_x:    vec![E::Scalar::ZERO; S.num_rounds()],
+      v:      vec![E::Scalar::ZERO; S.M.len()],
+    }
+  }
+}
+
+impl<E: Engine> TranscriptReprTrait<E::GE> for CCCSInstance<E> {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    [self.comm_W.to_transcript_bytes(), self.X.as_slice().to_transcript_bytes()].concat()
+  }
+}
+
+impl<E: Engine> TranscriptReprTrait<E::GE> for LCCCSInstance<E> {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    [
+      self.comm_W.to_transcript_bytes(),
+      self.u.to_transcript_bytes(),
+      self.X.as_slice().to_transcript_bytes(),
+      self.r_x.as_slice().to_transcript_bytes(),
+      self.v.as_slice().to_transcript_bytes(),
+    ]
+    .concat()
+  }
+}
@@ -0,0 +1,313 @@
+//! This module implements the HyperNova non-interactive multi-folding scheme,
+//! which folds a committed CCS instance into a linearized one with a single
+//! sum-check.
+use ff::Field;
+use serde::{Deserialize, Serialize};
+
+use super::{CCCSInstance, CCSShape, CCSWitness, LCCCSInstance};
+use crate::{
+  errors::NovaError,
+  spartan::{
+    polys::{eq::EqPolynomial, multilinear::MultilinearPolynomial},
+    powers,
+    sumcheck::SumcheckProof,
+  },
+  traits::{Engine, TranscriptEngineTrait},
+};
+
+/// A proof that a `CCCSInstance` was folded into an `LCCCSInstance`
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct NIMFS<E: Engine> {
+  sc_proof: SumcheckProof<E>,
+  sigmas:   Vec<E::Scalar>,
+  thetas:   Vec<E::Scalar>,
+}
+
+/// The challenges of the multi-folding scheme preceding the sum-check
+struct Challenges<E: Engine> {
+  gammas: Vec<E::Scalar>,
+  beta:   Vec<E::Scalar>,
+}
+
+impl<E: Engine> Challenges<E> {
+  fn squeeze(
+    transcript: &mut E::TE,
+    pp_digest: &E::Scalar,
+    S: &CCSShape<E>,
+    U1: &LCCCSInstance<E>,
+    U2: &CCCSInstance<E>,
+  ) -> Result<Self, NovaError> {
+    transcript.absorb(b"pp", pp_digest);
+    transcript.absorb(b"U1", U1);
+    transcript.absorb(b"U2", U2);
+
+    let gamma = transcript.squeeze(b"g")?;
+    let beta = (0..S.num_rounds()).map(|_| transcript.squeeze(b"b")).collect::<Result<_, _>>()?;
+
+    // γ^1, ..., γ^t weigh the linearized claims and γ^{t+1} the CCS claim
+    Ok(Self { gammas: powers(&gamma, S.M.len() + 2), beta })
+  }
+}
+
+impl<E: Engine> NIMFS<E> {
+  /// Takes an `LCCCSInstance`-witness pair (U1, W1) and a `CCCSInstance`-witness
+  /// pair (U2, W2) and folds them into a new `LCCCSInstance`-witness pair.
+  ///
+  /// The sum-check proves `∑_x g(x) = ∑_j γ^j·v_j` for
+  /// `g(x) = ∑_j γ^j·eq(r_x, x)·(M_j·z_1)(x) + γ^{t+1}·eq(β, x)·∑_i c_i·∏_{j ∈ S_i} (M_j·z_2)(x)`.
+  pub fn prove(
+    pp_digest: &E::Scalar,
+    S: &CCSShape<E>,
+    U1: &LCCCSInstance<E>,
+    W1: &CCSWitness<E>,
+    U2: &CCCSInstance<E>,
+    W2: &CCSWitness<E>,
+  ) -> Result<(Self, (LCCCSInstance<E>, CCSWitness<E>)), NovaError> {
+    let t = S.M.len();
+    if U1.r_x.len() != S.num_rounds() || U1.v.len() != t {
+      return Err(NovaError::InvalidInputLength);
+    }
+
+    let mut transcript = E::TE::new(b"NIMFS");
+    let Challenges { gammas, beta } = Challenges::squeeze(&mut transcript, pp_digest, S, U1, U2)?;
+
+    let Mz1 = S.multiply_witness(&W1.W, &U1.u, &U1.X)?;
+    let Mz2 = S.multiply_witness(&W2.W, &E::Scalar::ONE, &U2.X)?;
+
+    let claim = U1.v.iter().zip(&gammas[1..]).map(|(v, gamma)| *v * gamma).sum();
+
+    let mut polys =
+      [EqPolynomial::evals_from_points(&U1.r_x), EqPolynomial::evals_from_points(&beta)]
+        .into_iter()
+        .chain(Mz1)
+        .chain(Mz2)
+        .map(MultilinearPolynomial::new)
+        .collect::<Vec<_>>();
+
+    let comb_func = |evals: &[E::Scalar]| -> E::Scalar {
+      let (eq_r_x, eq_beta) = (evals[0], evals[1]);
+      let (Mz1, Mz2) = evals[2..].split_at(t);
+      let linearized = Mz1.iter().zip(&gammas[1..]).map(|(e, gamma)| *e * gamma).sum::<E::Scalar>();
+      eq_r_x * linearized + gammas[t + 1] * eq_beta * S.compute_sum_of_products(Mz2)
+    };
+
+    let (sc_proof, r_x, evals) = SumcheckProof::prove_poly(
+      &claim,
+      S.num_rounds(),
+      &mut polys,
+      S.degree() + 1,
+      comb_func,
+      &mut transcript,
+    )?;
+
+    // the evaluations of the multilinear extensions of M_j·z at r_x
+    let (sigmas, thetas) = evals[2..].split_at(t);
+    let (sigmas, thetas) = (sigmas.to_vec(), thetas.to_vec());
+
+    let rho = Self::squeeze_rho(&mut transcript, &sigmas, &thetas)?;
+
+    let U = Self::fold(U1, U2, r_x, &sigmas, &thetas, &rho);
+    let W = W1.fold(W2, &rho);
+
+    Ok((Self { sc_proof, sigmas, thetas }, (U, W)))
+  }
+
+  /// Takes an `LCCCSInstance` U1 and a `CCCSInstance` U2 and folds them into
+  /// a new `LCCCSInstance`, checking the sum-check proof along the way.
+  pub fn verify(
+    &self,
+    pp_digest: &E::Scalar,
+    S: &CCSShape<E>,
+    U1: &LCCCSInstance<E>,
+    U2: &CCCSInstance<E>,
+  ) -> Result<LCCCSInstance<E>, NovaError> {
+    let t = S.M.len();
+    if U1.r_x.len() != S.num_rounds()
+      || U1.v.len() != t
+      || U1.X.len() != U2.X.len()
+      || self.sigmas.len() != t
+      || self.thetas.len() != t
+    {
+      return Err(NovaError::InvalidInputLength);
+    }
+
+    let mut transcript = E::TE::new(b"NIMFS");
+    let Challenges { gammas, beta } = Challenges::squeeze(&mut transcript, pp_digest, S, U1, U2)?;
+
+    let claim = U1.v.iter().zip(&gammas[1..]).map(|(v, gamma)| *v * gamma).sum();
+    let (e, r_x) = self.sc_proof.verify(claim, S.num_rounds(), S.degree() + 1, &mut transcript)?;
+
+    // check the final claim of the sum-check against the claimed evaluations
+    let linearized =
+      self.sigmas.iter().zip(&gammas[1..]).map(|(e, gamma)| *e * gamma).sum::<E::Scalar>();
+    let expected = EqPolynomial::new(U1.r_x.clone()).evaluate(&r_x) * linearized
+      + gammas[t + 1]
+        * EqPolynomial::new(beta).evaluate(&r_x)
+        * S.compute_sum_of_products(&self.thetas);
+    if e != expected {
+      return Err(NovaError::InvalidSumcheckProof);
+    }
+
+    let rho = Self::squeeze_rho(&mut transcript, &self.sigmas, &self.thetas)?;
+
+    Ok(Self::fold(U1, U2, r_x, &self.sigmas, &self.thetas, &rho))
+  }
+
+  fn squeeze_rho(
+    transcript: &mut E::TE,
+    sigmas: &[E::Scalar],
+    thetas: &[E::Scalar],
+  ) -> Result<E::Scalar, NovaError> {
+    transcript.absorb(b"sigmas", &sigmas);
+    transcript.absorb(b"thetas", &thetas);
+    transcript.squeeze(b"r")
+  }
+
+  fn fold(
+    U1: &LCCCSInstance<E>,
+    U2: &CCCSInstance<E>,
+    r_x: Vec<E::Scalar>,
+    sigmas: &[E::Scalar],
+    thetas: &[E::Scalar],
+    rho: &E::Scalar,
+  ) -> LCCCSInstance<E> {
+    LCCCSInstance {
+      comm_W: U1.comm_W + U2.comm_W * *rho,
+      u: U1.u + rho,
+      X: U1.X.iter().zip(&U2.X).map(|(x1, x2)| *x1 + *rho * x2).collect(),
+      r_x,
+      v: sigmas.iter().zip(thetas).map(|(sigma, theta)| *sigma + *rho * theta).collect(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ::bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+  use ff::PrimeField;
+
+  use super::*;
+  use crate::{
+    bellpepper::{
+      r1cs::{NovaShape, NovaWitness},
+      solver::SatisfyingAssignment,
+      test_shape_cs::TestShapeCS,
+    },
+    provider::Bn256EngineKZG,
+    r1cs::SparseMatrix,
+    traits::{commitment::CommitmentEngineTrait, snark::default_ck_hint},
+    CommitmentKey,
+  };
+
+  fn synthesize_tiny_r1cs_bellpepper<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    x_val: Option<Scalar>,
+  ) -> Result<(), SynthesisError> {
+    // Consider a cubic equation: `x^3 + x + 5 = y`, where `x` and `y` are
+    // respectively the input and output.
+    let x = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || x_val.unwrap());
+    let _ = x.inputize(cs.namespace(|| "x is input"));
+
+    let x_sq = x.square(cs.namespace(|| "x_sq"))?;
+    let x_cu = x_sq.mul(cs.namespace(|| "x_cu"), &x)?;
+    let y = AllocatedNum::alloc(cs.namespace(|| "y"), || {
+      Ok(x_cu.get_value().unwrap() + x.get_value().unwrap() + Scalar::from(5u64))
+    })?;
+    let _ = y.inputize(cs.namespace(|| "y is output"));
+
+    cs.enforce(
+      || "y = x^3 + x + 5",
+      |lc| lc + x_cu.get_variable() + x.get_variable() + (Scalar::from(5u64), CS::one()),
+      |lc| lc + CS::one(),
+      |lc| lc + y.get_variable(),
+    );
+
+    Ok(())
+  }
+
+  fn execute_sequence<E: Engine>(
+    ck: &CommitmentKey<E>,
+    shape: &CCSShape<E>,
+    instances: &[(CCCSInstance<E>, CCSWitness<E>)],
+  ) {
+    let pp_digest = E::Scalar::ZERO;
+
+    // produce a default running instance
+    let mut r_U = LCCCSInstance::default(shape);
+    let mut r_W = CCSWitness::default(shape);
+    shape.is_sat_linearized(ck, &r_U, &r_W).unwrap();
+
+    for (U, W) in instances {
+      shape.is_sat(ck, U, W).unwrap();
+
+      let (nimfs, (U_folded, W_folded)) =
+        NIMFS::prove(&pp_digest, shape, &r_U, &r_W, U, W).unwrap();
+      let U_verified = nimfs.verify(&pp_digest, shape, &r_U, U).unwrap();
+      assert_eq!(U_folded, U_verified);
+
+      shape.is_sat_linearized(ck, &U_folded, &W_folded).unwrap();
+      r_U = U_folded;
+      r_W = W_folded;
+    }
+
+    // a folding proof does not verify against another instance
+    let (U, W) = &instances[0];
+    let (nimfs, _) = NIMFS::prove(&pp_digest, shape, &r_U, &r_W, U, W).unwrap();
+    assert!(nimfs.verify(&pp_digest, shape, &r_U, &instances[1].0).is_err());
+  }
+
+  fn test_tiny_r1cs_multifolding_with<E: Engine>() {
+    let mut cs: TestShapeCS<E> = TestShapeCS::new();
+    let _ = synthesize_tiny_r1cs_bellpepper(&mut cs, None);
+    let (r1cs_shape, ck) = cs.r1cs_shape_and_key(&*default_ck_hint());
+    let shape = CCSShape::from_r1cs(&r1cs_shape);
+
+    let instances = [5u64, 135]
+      .into_iter()
+      .map(|x| {
+        let mut cs = SatisfyingAssignment::<E>::new();
+        let _ = synthesize_tiny_r1cs_bellpepper(&mut cs, Some(E::Scalar::from(x)));
+        let (U, W) = cs.r1cs_instance_and_witness(&r1cs_shape, &ck).unwrap();
+        (CCCSInstance::from(U), CCSWitness::from(W))
+      })
+      .collect::<Vec<_>>();
+
+    execute_sequence(&ck, &shape, &instances);
+  }
+
+  #[test]
+  fn test_tiny_r1cs_multifolding() { test_tiny_r1cs_multifolding_with::<Bn256EngineKZG>(); }
+
+  fn test_degree_three_gate_with<E: Engine>() {
+    // z = (x, x_cu, 1, y) with the rows `x·x·x - x_cu = 0` and
+    // `x_cu + x + 5 - y = 0`, a single gate of degree three per row
+    let one = E::Scalar::ONE;
+    let five = E::Scalar::from(5u64);
+    let M = vec![
+      SparseMatrix::new(&[(0, 0, one)], 2, 4),
+      SparseMatrix::new(&[(0, 1, one), (1, 0, one), (1, 1, one), (1, 2, five), (1, 3, -one)], 2, 4),
+    ];
+    let shape =
+      CCSShape::<E>::new(2, 2, 1, M, vec![vec![0, 0, 0], vec![1]], vec![one, -one]).unwrap();
+    assert_eq!(shape.degree(), 3);
+
+    let ck = E::CE::setup(b"ck", shape.num_vars);
+    let instances = [2u64, 7]
+      .into_iter()
+      .map(|x| {
+        let x = E::Scalar::from(x);
+        let x_cu = x * x * x;
+        let W = CCSWitness::new(&shape, vec![x, x_cu]).unwrap();
+        let U = CCCSInstance::new(&shape, W.commit(&ck), vec![x_cu + x + five]).unwrap();
+        (U, W)
+      })
+      .collect::<Vec<_>>();
+
+    execute_sequence(&ck, &shape, &instances);
+  }
+
+  #[test]
+  fn test_degree_three_gate() { test_degree_three_gate_with::<Bn256EngineKZG>(); }
+}
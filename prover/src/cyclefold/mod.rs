@@ -2,8 +2,10 @@
 
 mod circuit;
 mod gadgets;
+mod nivc_circuit;
 mod nova_circuit;
 mod util;
 
 pub mod nifs;
+pub mod nivc;
 pub mod snark;
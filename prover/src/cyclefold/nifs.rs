@@ -2,6 +2,8 @@
 
 use std::marker::PhantomData;
 
+use serde::{Deserialize, Serialize};
+
 use super::util::{absorb_cyclefold_r1cs, absorb_primary_commitment, absorb_primary_r1cs};
 use crate::{
   constants::{NIO_CYCLE_FOLD, NUM_CHALLENGE_BITS, NUM_FE_IN_EMULATED_POINT},
@@ -9,14 +11,15 @@ use crate::{
   gadgets::scalar_as_base,
   r1cs::{R1CSInstance, R1CSShape, R1CSWitness, RelaxedR1CSInstance, RelaxedR1CSWitness},
   traits::{commitment::CommitmentTrait, AbsorbInROTrait, Engine, ROConstants, ROTrait},
-  CommitmentKey, CompressedCommitment,
+  Commitment, CommitmentKey, CompressedCommitment,
 };
 
 /// A SNARK that holds the proof of a step of an incremental computation of the
 /// primary circuit in the CycleFold folding scheme.
 /// The difference of this folding scheme from the Nova NIFS in `src/nifs.rs` is
 /// that this
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct PrimaryNIFS<E1, E2>
 where
   E1: Engine<Base = <E2 as Engine>::Scalar>,
@@ -73,6 +76,41 @@ where
 
     Ok((Self { comm_T: comm_T.compress(), _p: PhantomData }, (U, W), r))
   }
+
+  /// Takes a relaxed R1CS instance `U1` and an R1CS instance `U2` and folds
+  /// them into a new relaxed R1CS instance, recomputing the challenge from
+  /// the commitment to the cross term as in [`PrimaryNIFS::prove`].
+  pub fn verify(
+    &self,
+    ro_consts: &ROConstants<E2>,
+    pp_digest: &E1::Scalar,
+    U1: &RelaxedR1CSInstance<E1>,
+    U2: &R1CSInstance<E1>,
+  ) -> Result<RelaxedR1CSInstance<E1>, NovaError> {
+    let arity = U1.X.len();
+
+    if arity != U2.X.len() {
+      return Err(NovaError::InvalidInputLength);
+    }
+
+    let mut ro = E2::RO::new(
+      ro_consts.clone(),
+      1 + NUM_FE_IN_EMULATED_POINT + arity + NUM_FE_IN_EMULATED_POINT, /* pp_digest + u.W
+                                                                        * + u.X + T */
+    );
+
+    ro.absorb(*pp_digest);
+
+    absorb_primary_r1cs::<E1, E2>(U2, &mut ro);
+
+    let comm_T = Commitment::<E1>::decompress(&self.comm_T)?;
+
+    absorb_primary_commitment::<E1, E2>(&comm_T, &mut ro);
+
+    let r = scalar_as_base::<E2>(ro.squeeze(NUM_CHALLENGE_BITS));
+
+    Ok(U1.fold(U2, &comm_T, &r))
+  }
 }
 
 /// A SNARK that holds the proof of a step of an incremental computation of the
@@ -0,0 +1,694 @@
+//! This module defines a CycleFold variant of the SuperNova `RecursiveSNARK`
+//! for non-uniform incremental computation, with its `new`, `prove_step`, and
+//! `verify` methods.
+//!
+//! The secondary circuit of SuperNova is replaced by the CycleFold circuit, so
+//! a program is described by its primary circuits only and the secondary
+//! circuit of the [`NonUniformCircuit`] is never synthesized. One running
+//! instance is kept per primary circuit, and the running instance of a circuit
+//! stays `None` until that circuit is first folded.
+
+use bellpepper_core::{ConstraintSystem, SynthesisError};
+use ff::PrimeFieldBits;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use super::{
+  circuit::CycleFoldCircuit,
+  nifs::{CycleFoldNIFS, PrimaryNIFS},
+  nivc_circuit::{num_fe_primary_ro, NIVCAugmentedCircuit, NIVCAugmentedCircuitInputs},
+  nova_circuit::AugmentedCircuitParams,
+  util::{absorb_primary_relaxed_r1cs, FoldingData},
+};
+use crate::{
+  bellpepper::{
+    r1cs::{NovaShape, NovaWitness},
+    shape_cs::ShapeCS,
+    solver::SatisfyingAssignment,
+  },
+  constants::{BN_LIMB_WIDTH, BN_N_LIMBS, NIO_CYCLE_FOLD, NUM_CHALLENGE_BITS, NUM_HASH_BITS},
+  errors::NovaError,
+  gadgets::scalar_as_base,
+  r1cs::{
+    commitment_key_size, CommitmentKeyHint, R1CSInstance, R1CSWitness, RelaxedR1CSInstance,
+    RelaxedR1CSWitness,
+  },
+  supernova::{NonUniformCircuit, StepCircuit},
+  traits::{
+    commitment::{CommitmentEngineTrait, CommitmentTrait},
+    AbsorbInROTrait, CurveCycleEquipped, Dual, Engine, ROConstantsCircuit, ROTrait,
+  },
+  Commitment, CommitmentKey, DigestComputer, R1CSWithArity, ROConstants, SimpleDigestible,
+};
+
+/// The public parameters used in the CycleFold NIVC recursive SNARK proof and
+/// verification
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PublicParams<E1>
+where E1: CurveCycleEquipped {
+  ro_consts_primary:         ROConstants<Dual<E1>>,
+  ro_consts_circuit_primary: ROConstantsCircuit<Dual<E1>>,
+  ck_primary:                CommitmentKey<E1>,
+  circuit_shapes_primary:    Vec<R1CSWithArity<E1>>,
+  augmented_circuit_params:  AugmentedCircuitParams,
+
+  ro_consts_cyclefold:     ROConstants<Dual<E1>>,
+  ck_cyclefold:            CommitmentKey<Dual<E1>>,
+  circuit_shape_cyclefold: R1CSWithArity<Dual<E1>>,
+  #[serde(skip, default = "OnceCell::new")]
+  digest:                  OnceCell<E1::Scalar>,
+}
+
+impl<E1> PublicParams<E1>
+where E1: CurveCycleEquipped
+{
+  /// Builds the public parameters for the primary circuits of
+  /// `non_uniform_circuit`.
+  ///
+  /// A single primary commitment key, large enough for the largest augmented
+  /// circuit and `ck_hint_primary`, is shared by all the circuits.
+  pub fn setup<NC: NonUniformCircuit<E1>>(
+    non_uniform_circuit: &NC,
+    ck_hint_primary: &CommitmentKeyHint<E1>,
+    ck_hint_cyclefold: &CommitmentKeyHint<Dual<E1>>,
+  ) -> Self {
+    let num_circuits = non_uniform_circuit.num_circuits();
+    let ro_consts_primary = ROConstants::<Dual<E1>>::default();
+    let ro_consts_circuit_primary = ROConstantsCircuit::<Dual<E1>>::default();
+    let augmented_circuit_params = AugmentedCircuitParams::new(BN_LIMB_WIDTH, BN_N_LIMBS);
+
+    let circuit_shapes_primary = (0..num_circuits)
+      .map(|circuit_index| {
+        let c_primary = non_uniform_circuit.primary_circuit(circuit_index);
+        let circuit_primary: NIVCAugmentedCircuit<'_, Dual<E1>, E1, NC::C1> =
+          NIVCAugmentedCircuit::new(
+            &augmented_circuit_params,
+            ro_consts_circuit_primary.clone(),
+            None,
+            &c_primary,
+            num_circuits,
+          );
+        let mut cs: ShapeCS<E1> = ShapeCS::new();
+        circuit_primary.synthesize(&mut cs).expect("circuit synthesis failed");
+        R1CSWithArity::new(cs.r1cs_shape(), c_primary.arity())
+      })
+      .collect::<Vec<_>>();
+
+    let ck_size = circuit_shapes_primary
+      .iter()
+      .map(|circuit| commitment_key_size(&circuit.r1cs_shape, ck_hint_primary))
+      .max()
+      .unwrap();
+    let ck_primary = E1::CE::setup(b"ck", ck_size);
+
+    let ro_consts_cyclefold = ROConstants::<Dual<E1>>::default();
+    let mut cs: ShapeCS<Dual<E1>> = ShapeCS::new();
+    let circuit_cyclefold: CycleFoldCircuit<E1> = CycleFoldCircuit::default();
+    let _ = circuit_cyclefold.synthesize(&mut cs);
+    let (r1cs_shape_cyclefold, ck_cyclefold) = cs.r1cs_shape_and_key(ck_hint_cyclefold);
+    let circuit_shape_cyclefold = R1CSWithArity::new(r1cs_shape_cyclefold, 0);
+
+    Self {
+      ro_consts_primary,
+      ro_consts_circuit_primary,
+      ck_primary,
+      circuit_shapes_primary,
+      augmented_circuit_params,
+      ro_consts_cyclefold,
+      ck_cyclefold,
+      circuit_shape_cyclefold,
+      digest: OnceCell::new(),
+    }
+  }
+
+  /// Calculate the digest of the public parameters.
+  pub fn digest(&self) -> E1::Scalar {
+    self
+      .digest
+      .get_or_try_init(|| DigestComputer::new(self).digest())
+      .cloned()
+      .expect("Failure in retrieving digest")
+  }
+
+  /// Returns the number of primary circuits
+  pub fn num_circuits(&self) -> usize { self.circuit_shapes_primary.len() }
+
+  /// Returns the number of constraints in each primary circuit and in the
+  /// cyclefold circuit
+  pub fn num_constraints(&self) -> (Vec<usize>, usize) {
+    (
+      self.circuit_shapes_primary.iter().map(|circuit| circuit.r1cs_shape.num_cons).collect(),
+      self.circuit_shape_cyclefold.r1cs_shape.num_cons,
+    )
+  }
+
+  /// Returns the number of variables in each primary circuit and in the
+  /// cyclefold circuit
+  pub fn num_variables(&self) -> (Vec<usize>, usize) {
+    (
+      self.circuit_shapes_primary.iter().map(|circuit| circuit.r1cs_shape.num_vars).collect(),
+      self.circuit_shape_cyclefold.r1cs_shape.num_vars,
+    )
+  }
+}
+
+impl<E1> SimpleDigestible for PublicParams<E1> where E1: CurveCycleEquipped {}
+
+/// A SNARK that proves the correct execution of a non-uniform incremental
+/// computation in the CycleFold folding scheme.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RecursiveSNARK<E1>
+where E1: CurveCycleEquipped {
+  // Input
+  z0_primary: Vec<E1::Scalar>,
+
+  // primary circuit data, one running instance per circuit
+  r_W_primary: Vec<Option<RelaxedR1CSWitness<E1>>>,
+  r_U_primary: Vec<Option<RelaxedR1CSInstance<E1>>>,
+  l_w_primary: R1CSWitness<E1>,
+  l_u_primary: R1CSInstance<E1>,
+
+  // cyclefold circuit data
+  r_W_cyclefold: RelaxedR1CSWitness<Dual<E1>>,
+  r_U_cyclefold: RelaxedR1CSInstance<Dual<E1>>,
+
+  i:          usize,
+  zi_primary: Vec<E1::Scalar>,
+
+  // the program counter of the next step, and the circuit that produced
+  // `l_u_primary`
+  program_counter:         E1::Scalar,
+  augmented_circuit_index: usize,
+}
+
+impl<E1> RecursiveSNARK<E1>
+where E1: CurveCycleEquipped
+{
+  /// Create a new instance of a recursive SNARK, running the initial circuit
+  /// of `non_uniform_circuit` as the first step
+  pub fn new<NC: NonUniformCircuit<E1>>(
+    pp: &PublicParams<E1>,
+    non_uniform_circuit: &NC,
+    c_primary: &NC::C1,
+    z0_primary: &[E1::Scalar],
+  ) -> Result<Self, NovaError> {
+    let num_circuits = non_uniform_circuit.num_circuits();
+    let circuit_index = non_uniform_circuit.initial_circuit_index();
+
+    if num_circuits != pp.num_circuits() || circuit_index != c_primary.circuit_index() {
+      return Err(NovaError::InvalidIndex);
+    }
+    if pp.circuit_shapes_primary.iter().any(|circuit| circuit.F_arity != z0_primary.len()) {
+      return Err(NovaError::InvalidInitialInputLength);
+    }
+
+    let r1cs_primary = &pp.circuit_shapes_primary[circuit_index].r1cs_shape;
+    let r1cs_cyclefold = &pp.circuit_shape_cyclefold.r1cs_shape;
+
+    let r_U_cyclefold = RelaxedR1CSInstance::default(&pp.ck_cyclefold, r1cs_cyclefold);
+    let r_W_cyclefold = RelaxedR1CSWitness::default(r1cs_cyclefold);
+
+    let r_U_primary = vec![None; num_circuits];
+    let r_W_primary = vec![None; num_circuits];
+
+    let mut cs_primary = SatisfyingAssignment::<E1>::new();
+    let inputs_primary: NIVCAugmentedCircuitInputs<'_, Dual<E1>, E1> =
+      NIVCAugmentedCircuitInputs::new(
+        scalar_as_base::<E1>(pp.digest()),
+        <Dual<E1> as Engine>::Base::from(0u64),
+        z0_primary.to_vec(),
+        None,
+        E1::Scalar::from(circuit_index as u64),
+        E1::Scalar::from(circuit_index as u64),
+        &r_U_primary,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+      );
+
+    let circuit_primary = NIVCAugmentedCircuit::new(
+      &pp.augmented_circuit_params,
+      pp.ro_consts_circuit_primary.clone(),
+      Some(inputs_primary),
+      c_primary,
+      num_circuits,
+    );
+
+    let (program_counter, zi_primary) = circuit_primary.synthesize(&mut cs_primary)?;
+    let (l_u_primary, l_w_primary) =
+      cs_primary.r1cs_instance_and_witness(r1cs_primary, &pp.ck_primary)?;
+
+    let program_counter = program_counter.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+    let zi_primary = zi_primary
+      .iter()
+      .map(|v| v.get_value().ok_or(SynthesisError::AssignmentMissing))
+      .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Self {
+      z0_primary: z0_primary.to_vec(),
+      r_W_primary,
+      r_U_primary,
+      l_w_primary,
+      l_u_primary,
+      r_W_cyclefold,
+      r_U_cyclefold,
+      i: 0,
+      zi_primary,
+      program_counter,
+      augmented_circuit_index: circuit_index,
+    })
+  }
+
+  /// Returns the program counter, i.e. the index of the circuit to prove next
+  pub const fn program_counter(&self) -> E1::Scalar { self.program_counter }
+
+  /// Update the `RecursiveSNARK` by proving a step of the incremental
+  /// computation with `c_primary`, which must be the circuit selected by the
+  /// program counter.
+  pub fn prove_step<C1: StepCircuit<E1::Scalar>>(
+    &mut self,
+    pp: &PublicParams<E1>,
+    c_primary: &C1,
+  ) -> Result<(), NovaError> {
+    if self.i == 0 {
+      self.i = 1;
+      return Ok(());
+    }
+
+    let circuit_index = c_primary.circuit_index();
+    if circuit_index >= pp.num_circuits()
+      || self.program_counter != E1::Scalar::from(circuit_index as u64)
+    {
+      return Err(NovaError::InvalidIndex);
+    }
+
+    // fold the last instance into the running instance of the circuit that
+    // produced it
+    let j = self.augmented_circuit_index;
+    let r1cs_j = &pp.circuit_shapes_primary[j].r1cs_shape;
+    let U_j = self.r_U_primary[j]
+      .clone()
+      .unwrap_or_else(|| RelaxedR1CSInstance::default(&pp.ck_primary, r1cs_j));
+    let W_j = self.r_W_primary[j].clone().unwrap_or_else(|| RelaxedR1CSWitness::default(r1cs_j));
+
+    let (nifs_primary, (r_U_primary, r_W_primary), r) = PrimaryNIFS::<E1, Dual<E1>>::prove(
+      &pp.ck_primary,
+      &pp.ro_consts_primary,
+      &pp.digest(),
+      r1cs_j,
+      &U_j,
+      &W_j,
+      &self.l_u_primary,
+      &self.l_w_primary,
+    )?;
+
+    let r_bools = r
+      .to_le_bits()
+      .iter()
+      .map(|b| Some(*b))
+      .take(NUM_CHALLENGE_BITS)
+      .collect::<Option<Vec<_>>>()
+      .map(|v| v.try_into().unwrap());
+
+    let comm_T = Commitment::<E1>::decompress(&nifs_primary.comm_T)?;
+    let E_new = U_j.comm_E + comm_T * r;
+
+    let W_new = U_j.comm_W + self.l_u_primary.comm_W * r;
+
+    let mut cs_cyclefold_E = SatisfyingAssignment::<Dual<E1>>::with_capacity(
+      pp.circuit_shape_cyclefold.r1cs_shape.num_io + 1,
+      pp.circuit_shape_cyclefold.r1cs_shape.num_vars,
+    );
+
+    let circuit_cyclefold_E: CycleFoldCircuit<E1> =
+      CycleFoldCircuit::new(Some(U_j.comm_E), Some(comm_T), r_bools);
+
+    let _ = circuit_cyclefold_E.synthesize(&mut cs_cyclefold_E);
+
+    let (l_u_cyclefold_E, l_w_cyclefold_E) = cs_cyclefold_E
+      .r1cs_instance_and_witness(&pp.circuit_shape_cyclefold.r1cs_shape, &pp.ck_cyclefold)
+      .map_err(|_| NovaError::UnSat)?;
+
+    let (nifs_cyclefold_E, (r_U_cyclefold_E, r_W_cyclefold_E)) = CycleFoldNIFS::prove(
+      &pp.ck_cyclefold,
+      &pp.ro_consts_cyclefold,
+      &scalar_as_base::<E1>(pp.digest()),
+      &pp.circuit_shape_cyclefold.r1cs_shape,
+      &self.r_U_cyclefold,
+      &self.r_W_cyclefold,
+      &l_u_cyclefold_E,
+      &l_w_cyclefold_E,
+    )?;
+
+    let comm_T_E = Commitment::<Dual<E1>>::decompress(&nifs_cyclefold_E.comm_T)?;
+
+    let mut cs_cyclefold_W = SatisfyingAssignment::<Dual<E1>>::with_capacity(
+      pp.circuit_shape_cyclefold.r1cs_shape.num_io + 1,
+      pp.circuit_shape_cyclefold.r1cs_shape.num_vars,
+    );
+
+    let circuit_cyclefold_W: CycleFoldCircuit<E1> =
+      CycleFoldCircuit::new(Some(U_j.comm_W), Some(self.l_u_primary.comm_W), r_bools);
+
+    let _ = circuit_cyclefold_W.synthesize(&mut cs_cyclefold_W);
+
+    let (l_u_cyclefold_W, l_w_cyclefold_W) = cs_cyclefold_W
+      .r1cs_instance_and_witness(&pp.circuit_shape_cyclefold.r1cs_shape, &pp.ck_cyclefold)
+      .map_err(|_| NovaError::UnSat)?;
+
+    let (nifs_cyclefold_W, (r_U_cyclefold_W, r_W_cyclefold_W)) = CycleFoldNIFS::prove(
+      &pp.ck_cyclefold,
+      &pp.ro_consts_cyclefold,
+      &scalar_as_base::<E1>(pp.digest()),
+      &pp.circuit_shape_cyclefold.r1cs_shape,
+      &r_U_cyclefold_E,
+      &r_W_cyclefold_E,
+      &l_u_cyclefold_W,
+      &l_w_cyclefold_W,
+    )?;
+
+    let comm_T_W = Commitment::<Dual<E1>>::decompress(&nifs_cyclefold_W.comm_T)?;
+
+    let r1cs_primary = &pp.circuit_shapes_primary[circuit_index].r1cs_shape;
+    let mut cs_primary =
+      SatisfyingAssignment::<E1>::with_capacity(r1cs_primary.num_io + 1, r1cs_primary.num_vars);
+
+    let data_c_E = FoldingData::new(self.r_U_cyclefold.clone(), l_u_cyclefold_E, comm_T_E);
+    let data_c_W = FoldingData::new(r_U_cyclefold_E, l_u_cyclefold_W, comm_T_W);
+
+    let inputs_primary: NIVCAugmentedCircuitInputs<'_, Dual<E1>, E1> =
+      NIVCAugmentedCircuitInputs::new(
+        scalar_as_base::<E1>(pp.digest()),
+        <Dual<E1> as Engine>::Base::from(self.i as u64),
+        self.z0_primary.clone(),
+        Some(self.zi_primary.clone()),
+        self.program_counter,
+        E1::Scalar::from(j as u64),
+        &self.r_U_primary,
+        Some(self.l_u_primary.clone()),
+        Some(comm_T),
+        Some(data_c_E),
+        Some(data_c_W),
+        Some(E_new),
+        Some(W_new),
+      );
+
+    let circuit_primary: NIVCAugmentedCircuit<'_, Dual<E1>, E1, C1> = NIVCAugmentedCircuit::new(
+      &pp.augmented_circuit_params,
+      pp.ro_consts_circuit_primary.clone(),
+      Some(inputs_primary),
+      c_primary,
+      pp.num_circuits(),
+    );
+
+    let (program_counter, zi_primary) = circuit_primary.synthesize(&mut cs_primary)?;
+
+    let (l_u_primary, l_w_primary) = cs_primary
+      .r1cs_instance_and_witness(r1cs_primary, &pp.ck_primary)
+      .map_err(|_| NovaError::UnSat)?;
+
+    self.program_counter = program_counter.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+    self.zi_primary = zi_primary
+      .iter()
+      .map(|v| v.get_value().ok_or(SynthesisError::AssignmentMissing))
+      .collect::<Result<Vec<_>, _>>()?;
+
+    self.r_U_primary[j] = Some(r_U_primary);
+    self.r_W_primary[j] = Some(r_W_primary);
+    self.l_u_primary = l_u_primary;
+    self.l_w_primary = l_w_primary;
+    self.r_U_cyclefold = r_U_cyclefold_W;
+    self.r_W_cyclefold = r_W_cyclefold_W;
+    self.augmented_circuit_index = circuit_index;
+
+    self.i += 1;
+
+    Ok(())
+  }
+
+  /// Verify the correctness of the `RecursiveSNARK`
+  pub fn verify(
+    &self,
+    pp: &PublicParams<E1>,
+    num_steps: usize,
+    z0_primary: &[E1::Scalar],
+  ) -> Result<Vec<E1::Scalar>, NovaError> {
+    // number of steps cannot be zero
+    let is_num_steps_zero = num_steps == 0;
+
+    // check if the provided proof has executed num_steps
+    let is_num_steps_not_match = self.i != num_steps;
+
+    // check if the initial inputs match
+    let is_inputs_not_match = self.z0_primary != z0_primary;
+
+    // check there is one running instance per circuit, each with two public
+    // outputs
+    let is_instances_not_match = self.r_U_primary.len() != pp.num_circuits()
+      || self.r_W_primary.len() != pp.num_circuits()
+      || self.r_U_primary.iter().flatten().any(|U| U.X.len() != 2)
+      || self.augmented_circuit_index >= pp.num_circuits();
+
+    if is_num_steps_zero || is_num_steps_not_match || is_inputs_not_match || is_instances_not_match
+    {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    // missing running instances are hashed as the default instance, as in the
+    // circuit
+    let r_U_primary = self
+      .r_U_primary
+      .iter()
+      .zip(&pp.circuit_shapes_primary)
+      .map(|(U, circuit)| {
+        U.clone()
+          .unwrap_or_else(|| RelaxedR1CSInstance::default(&pp.ck_primary, &circuit.r1cs_shape))
+      })
+      .collect::<Vec<_>>();
+
+    // Calculate the hashes of the primary running instances and cyclefold running
+    // instance
+    let (hash_primary, hash_cyclefold) = {
+      let mut hasher = <Dual<E1> as Engine>::RO::new(
+        pp.ro_consts_primary.clone(),
+        num_fe_primary_ro(self.z0_primary.len(), pp.num_circuits()),
+      );
+      hasher.absorb(pp.digest());
+      hasher.absorb(E1::Scalar::from(num_steps as u64));
+      hasher.absorb(self.program_counter);
+      for e in z0_primary {
+        hasher.absorb(*e);
+      }
+      for e in &self.zi_primary {
+        hasher.absorb(*e);
+      }
+      for U in &r_U_primary {
+        absorb_primary_relaxed_r1cs::<E1, Dual<E1>>(U, &mut hasher);
+      }
+      let hash_primary = hasher.squeeze(NUM_HASH_BITS);
+
+      let mut hasher = <Dual<E1> as Engine>::RO::new(
+        pp.ro_consts_cyclefold.clone(),
+        1 + 1 + 3 + 3 + 1 + NIO_CYCLE_FOLD * BN_N_LIMBS,
+      );
+      hasher.absorb(pp.digest());
+      hasher.absorb(E1::Scalar::from(num_steps as u64));
+      self.r_U_cyclefold.absorb_in_ro(&mut hasher);
+      let hash_cyclefold = hasher.squeeze(NUM_HASH_BITS);
+
+      (hash_primary, hash_cyclefold)
+    };
+
+    // Verify the hashes equal the public IO for the final primary instance
+    if scalar_as_base::<Dual<E1>>(hash_primary) != self.l_u_primary.X[0]
+      || scalar_as_base::<Dual<E1>>(hash_cyclefold) != self.l_u_primary.X[1]
+    {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    // Verify the satisfiability of the running relaxed instances that were
+    // folded into, the final primary instance and the cyclefold instance.
+    let (res_r_primary, (res_l_primary, res_r_cyclefold)) = rayon::join(
+      || {
+        self.r_U_primary.iter().zip(&self.r_W_primary).zip(&pp.circuit_shapes_primary).try_for_each(
+          |((U, W), circuit)| match (U, W) {
+            (Some(U), Some(W)) => circuit.r1cs_shape.is_sat_relaxed(&pp.ck_primary, U, W),
+            (None, None) => Ok(()),
+            _ => Err(NovaError::ProofVerifyError),
+          },
+        )
+      },
+      || {
+        rayon::join(
+          || {
+            pp.circuit_shapes_primary[self.augmented_circuit_index].r1cs_shape.is_sat(
+              &pp.ck_primary,
+              &self.l_u_primary,
+              &self.l_w_primary,
+            )
+          },
+          || {
+            pp.circuit_shape_cyclefold.r1cs_shape.is_sat_relaxed(
+              &pp.ck_cyclefold,
+              &self.r_U_cyclefold,
+              &self.r_W_cyclefold,
+            )
+          },
+        )
+      },
+    );
+
+    res_r_primary?;
+    res_l_primary?;
+    res_r_cyclefold?;
+
+    Ok(self.zi_primary.clone())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::marker::PhantomData;
+
+  use bellpepper_core::num::AllocatedNum;
+  use ff::{Field, PrimeField};
+
+  use super::*;
+  use crate::{
+    provider::{Bn256EngineKZG, PallasEngine},
+    supernova::circuit::TrivialCircuit,
+    traits::snark::default_ck_hint,
+  };
+
+  const NUM_STEPS: usize = 5;
+
+  /// Cubes the register in circuit 0 and squares it in circuit 1, then
+  /// switches to the other circuit
+  #[derive(Clone, Debug)]
+  struct AlternatingCircuit<F> {
+    circuit_index: usize,
+    _p:            PhantomData<F>,
+  }
+
+  impl<F: PrimeField> StepCircuit<F> for AlternatingCircuit<F> {
+    fn arity(&self) -> usize { 1 }
+
+    fn circuit_index(&self) -> usize { self.circuit_index }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+      &self,
+      cs: &mut CS,
+      _pc: Option<&AllocatedNum<F>>,
+      z: &[AllocatedNum<F>],
+    ) -> Result<(Option<AllocatedNum<F>>, Vec<AllocatedNum<F>>), SynthesisError> {
+      let z_sq = z[0].square(cs.namespace(|| "z_i^2"))?;
+      let z_next =
+        if self.circuit_index == 0 { z_sq.mul(cs.namespace(|| "z_i^3"), &z[0])? } else { z_sq };
+
+      let next_index = F::from(1 - self.circuit_index as u64);
+      let next_pc = AllocatedNum::alloc(cs.namespace(|| "next_pc"), || Ok(next_index))?;
+      cs.enforce(
+        || "next_pc = 1 - circuit_index",
+        |lc| lc + CS::one(),
+        |lc| lc + next_pc.get_variable(),
+        |lc| lc + (next_index, CS::one()),
+      );
+
+      Ok((Some(next_pc), vec![z_next]))
+    }
+  }
+
+  struct AlternatingProgram<E1>(PhantomData<E1>);
+
+  impl<E1: CurveCycleEquipped> NonUniformCircuit<E1> for AlternatingProgram<E1> {
+    type C1 = AlternatingCircuit<E1::Scalar>;
+    type C2 = TrivialCircuit<<Dual<E1> as Engine>::Scalar>;
+
+    fn num_circuits(&self) -> usize { 2 }
+
+    fn primary_circuit(&self, circuit_index: usize) -> Self::C1 {
+      AlternatingCircuit { circuit_index, _p: PhantomData }
+    }
+
+    fn secondary_circuit(&self) -> Self::C2 { Default::default() }
+  }
+
+  fn prove_alternating<E1: CurveCycleEquipped>(
+  ) -> (PublicParams<E1>, AlternatingProgram<E1>, RecursiveSNARK<E1>, Vec<E1::Scalar>) {
+    let program = AlternatingProgram(PhantomData);
+    let pp = PublicParams::setup(&program, &*default_ck_hint(), &*default_ck_hint());
+
+    let z0 = vec![E1::Scalar::from(2u64)];
+    let mut recursive_snark =
+      RecursiveSNARK::new(&pp, &program, &program.primary_circuit(0), &z0).unwrap();
+
+    let mut expected = z0[0];
+    for i in 0..NUM_STEPS {
+      let circuit_index = i % 2;
+      recursive_snark.prove_step(&pp, &program.primary_circuit(circuit_index)).unwrap();
+      expected = if circuit_index == 0 { expected.square() * expected } else { expected.square() };
+
+      // the program counter switches to the other circuit after every step
+      assert_eq!(recursive_snark.program_counter(), E1::Scalar::from(1 - circuit_index as u64));
+      let zi = recursive_snark.verify(&pp, i + 1, &z0).unwrap();
+      assert_eq!(zi, vec![expected]);
+    }
+
+    (pp, program, recursive_snark, z0)
+  }
+
+  fn test_nivc_alternating_with<E1: CurveCycleEquipped>() {
+    let (pp, _, recursive_snark, _) = prove_alternating::<E1>();
+
+    // both circuits have a running instance
+    assert!(recursive_snark.r_U_primary.iter().all(Option::is_some));
+    assert_eq!(pp.num_circuits(), 2);
+  }
+
+  #[test]
+  fn test_nivc_alternating() {
+    test_nivc_alternating_with::<PallasEngine>();
+    test_nivc_alternating_with::<Bn256EngineKZG>();
+  }
+
+  fn test_nivc_rejects_with<E1: CurveCycleEquipped>() {
+    let (pp, program, recursive_snark, z0) = prove_alternating::<E1>();
+
+    // proving with the circuit that is not selected by the program counter
+    let mut wrong_circuit = recursive_snark.clone();
+    let pc = NUM_STEPS % 2;
+    assert!(matches!(
+      wrong_circuit.prove_step(&pp, &program.primary_circuit(1 - pc)),
+      Err(NovaError::InvalidIndex)
+    ));
+    assert!(matches!(
+      wrong_circuit.prove_step(&pp, &program.primary_circuit(2)),
+      Err(NovaError::InvalidIndex)
+    ));
+    // a rejected step leaves the proof untouched
+    wrong_circuit.verify(&pp, NUM_STEPS, &z0).unwrap();
+
+    let mut tampered_zi = recursive_snark.clone();
+    tampered_zi.zi_primary[0] += E1::Scalar::ONE;
+    assert!(tampered_zi.verify(&pp, NUM_STEPS, &z0).is_err());
+
+    let mut tampered_pc = recursive_snark.clone();
+    tampered_pc.program_counter = E1::Scalar::from(1 - pc as u64);
+    assert!(tampered_pc.verify(&pp, NUM_STEPS, &z0).is_err());
+
+    let wrong_z0 = vec![z0[0] + E1::Scalar::ONE];
+    assert!(recursive_snark.verify(&pp, NUM_STEPS, &wrong_z0).is_err());
+    assert!(recursive_snark.verify(&pp, NUM_STEPS + 1, &z0).is_err());
+  }
+
+  #[test]
+  fn test_nivc_rejects() {
+    test_nivc_rejects_with::<PallasEngine>();
+    test_nivc_rejects_with::<Bn256EngineKZG>();
+  }
+}
@@ -0,0 +1,591 @@
+//! This module defines the SuperNova augmented circuit used for CycleFold.
+//!
+//! It follows the Nova augmented circuit in `nova_circuit.rs`, but keeps one
+//! emulated running instance per circuit of the program. The incoming instance
+//! is folded into the running instance selected by
+//! `last_augmented_circuit_index`, and the program counter is carried in the
+//! public IO hash as in SuperNova.
+
+use bellpepper::gadgets::{
+  boolean::Boolean, boolean_utils::conditionally_select_slice, num::AllocatedNum, Assignment,
+};
+use bellpepper_core::{boolean::AllocatedBit, ConstraintSystem, SynthesisError};
+use ff::Field;
+
+use super::{
+  gadgets::{emulated, AllocatedCycleFoldData},
+  nova_circuit::AugmentedCircuitParams,
+  util::FoldingData,
+};
+use crate::{
+  constants::{BN_N_LIMBS, NIO_CYCLE_FOLD, NUM_FE_IN_EMULATED_POINT, NUM_HASH_BITS},
+  gadgets::{
+    alloc_num_equals, alloc_scalar_as_base, alloc_zero, le_bits_to_num,
+    AllocatedRelaxedR1CSInstance,
+  },
+  r1cs::{R1CSInstance, RelaxedR1CSInstance},
+  supernova::{utils::get_selector_vec_from_index, StepCircuit},
+  traits::{commitment::CommitmentTrait, Engine, ROCircuitTrait, ROConstantsCircuit},
+  Commitment,
+};
+
+/// The number of field elements absorbed to hash the primary IO: pp, i, pc,
+/// z0, zi and the running instances
+pub(super) const fn num_fe_primary_ro(arity: usize, num_circuits: usize) -> usize {
+  3 + 2 * arity + num_circuits * (2 * NUM_FE_IN_EMULATED_POINT + 3)
+}
+
+#[derive(Debug)]
+pub struct NIVCAugmentedCircuitInputs<'a, E1, E2>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>, {
+  pp_digest: E1::Scalar,
+  i:         E1::Base,
+  z0:        Vec<E1::Base>,
+
+  zi: Option<Vec<E1::Base>>,
+  program_counter: E1::Base,
+  last_augmented_circuit_index: E1::Base,
+
+  U_p: &'a [Option<RelaxedR1CSInstance<E2>>],
+  u_p: Option<R1CSInstance<E2>>,
+  T_p: Option<Commitment<E2>>,
+
+  data_c_1: Option<FoldingData<E1>>,
+  data_c_2: Option<FoldingData<E1>>,
+
+  E_new: Option<Commitment<E2>>,
+  W_new: Option<Commitment<E2>>,
+}
+
+impl<'a, E1, E2> NIVCAugmentedCircuitInputs<'a, E1, E2>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+{
+  #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+  pub fn new(
+    pp_digest: E1::Scalar,
+    i: E1::Base,
+    z0: Vec<E1::Base>,
+    zi: Option<Vec<E1::Base>>,
+    program_counter: E1::Base,
+    last_augmented_circuit_index: E1::Base,
+    U_p: &'a [Option<RelaxedR1CSInstance<E2>>],
+    u_p: Option<R1CSInstance<E2>>,
+    T_p: Option<Commitment<E2>>,
+    data_c_1: Option<FoldingData<E1>>,
+    data_c_2: Option<FoldingData<E1>>,
+    E_new: Option<Commitment<E2>>,
+    W_new: Option<Commitment<E2>>,
+  ) -> Self {
+    Self {
+      pp_digest,
+      i,
+      z0,
+      zi,
+      program_counter,
+      last_augmented_circuit_index,
+      U_p,
+      u_p,
+      T_p,
+      data_c_1,
+      data_c_2,
+      E_new,
+      W_new,
+    }
+  }
+}
+
+/// The allocated incoming primary instance and the commitment to its cross
+/// term
+struct AllocatedIncoming<E: Engine> {
+  u_W:  emulated::AllocatedEmulPoint<E::GE>,
+  u_x0: AllocatedNum<E::Base>,
+  u_x1: AllocatedNum<E::Base>,
+  T:    emulated::AllocatedEmulPoint<E::GE>,
+}
+
+pub struct NIVCAugmentedCircuit<'a, E1, E2, SC>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  SC: StepCircuit<E2::Scalar>, {
+  params:       &'a AugmentedCircuitParams,
+  ro_consts:    ROConstantsCircuit<E1>,
+  inputs:       Option<NIVCAugmentedCircuitInputs<'a, E1, E2>>,
+  step_circuit: &'a SC,
+  num_circuits: usize,
+}
+
+impl<'a, E1, E2, SC> NIVCAugmentedCircuit<'a, E1, E2, SC>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  SC: StepCircuit<E2::Scalar>,
+{
+  pub const fn new(
+    params: &'a AugmentedCircuitParams,
+    ro_consts: ROConstantsCircuit<E1>,
+    inputs: Option<NIVCAugmentedCircuitInputs<'a, E1, E2>>,
+    step_circuit: &'a SC,
+    num_circuits: usize,
+  ) -> Self {
+    Self { params, ro_consts, inputs, step_circuit, num_circuits }
+  }
+
+  #[allow(clippy::type_complexity)]
+  fn alloc_witness<CS: ConstraintSystem<<E1 as Engine>::Base>>(
+    &self,
+    mut cs: CS,
+    arity: usize,
+  ) -> Result<
+    (
+      AllocatedNum<E1::Base>,                              // pp_digest
+      AllocatedNum<E1::Base>,                              // i
+      Vec<AllocatedNum<E1::Base>>,                         // z0
+      Vec<AllocatedNum<E1::Base>>,                         // zi
+      AllocatedNum<E1::Base>,                              // program_counter
+      Vec<Boolean>,                                        // last_augmented_circuit_selector
+      Vec<emulated::AllocatedEmulRelaxedR1CSInstance<E1>>, // U_p
+      AllocatedIncoming<E1>,                               // u_p, T_p
+      AllocatedCycleFoldData<E1>,                          // data_c_1
+      AllocatedCycleFoldData<E1>,                          // data_c_2
+      emulated::AllocatedEmulPoint<E1::GE>,                // E_new
+      emulated::AllocatedEmulPoint<E1::GE>,                // W_new
+    ),
+    SynthesisError,
+  > {
+    let (limb_width, n_limbs) = (self.params.limb_width, self.params.n_limbs);
+
+    let pp_digest = alloc_scalar_as_base::<E1, _>(
+      cs.namespace(|| "params"),
+      self.inputs.as_ref().map(|inputs| inputs.pp_digest),
+    )?;
+
+    let i = AllocatedNum::alloc(cs.namespace(|| "i"), || Ok(self.inputs.get()?.i))?;
+
+    let z_0 = (0..arity)
+      .map(|i| {
+        AllocatedNum::alloc(cs.namespace(|| format!("z0_{i}")), || Ok(self.inputs.get()?.z0[i]))
+      })
+      .collect::<Result<Vec<AllocatedNum<E1::Base>>, _>>()?;
+
+    // Allocate zi. If inputs.zi is not provided (base case) allocate default value
+    // 0
+    let zero = vec![E1::Base::ZERO; arity];
+    let z_i = (0..arity)
+      .map(|i| {
+        AllocatedNum::alloc(cs.namespace(|| format!("zi_{i}")), || {
+          Ok(self.inputs.get()?.zi.as_ref().unwrap_or(&zero)[i])
+        })
+      })
+      .collect::<Result<Vec<AllocatedNum<E1::Base>>, _>>()?;
+
+    let program_counter = AllocatedNum::alloc(cs.namespace(|| "program_counter"), || {
+      Ok(self.inputs.get()?.program_counter)
+    })?;
+
+    let last_augmented_circuit_index =
+      AllocatedNum::alloc(cs.namespace(|| "last_augmented_circuit_index"), || {
+        Ok(self.inputs.get()?.last_augmented_circuit_index)
+      })?;
+    let last_augmented_circuit_selector = get_selector_vec_from_index(
+      cs.namespace(|| "instance selector"),
+      &last_augmented_circuit_index,
+      self.num_circuits,
+    )?;
+
+    // Allocate the running instances, a missing instance is allocated as the
+    // default instance
+    let U_p = (0..self.num_circuits)
+      .map(|j| {
+        emulated::AllocatedEmulRelaxedR1CSInstance::alloc(
+          cs.namespace(|| format!("allocate U_p {j}")),
+          self.inputs.as_ref().and_then(|inputs| inputs.U_p.get(j).and_then(Option::as_ref)),
+          limb_width,
+          n_limbs,
+        )
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    let u_p = self.inputs.as_ref().and_then(|inputs| inputs.u_p.as_ref());
+    let incoming = AllocatedIncoming {
+      u_W:  emulated::AllocatedEmulPoint::alloc(
+        cs.namespace(|| "allocate u_W"),
+        u_p.map(|u| u.comm_W.to_coordinates()),
+        limb_width,
+        n_limbs,
+      )?,
+      u_x0: AllocatedNum::alloc(cs.namespace(|| "allocate u_x0"), || {
+        Ok(u_p.map_or(E1::Base::ZERO, |u| u.X[0]))
+      })?,
+      u_x1: AllocatedNum::alloc(cs.namespace(|| "allocate u_x1"), || {
+        Ok(u_p.map_or(E1::Base::ZERO, |u| u.X[1]))
+      })?,
+      T:    emulated::AllocatedEmulPoint::alloc(
+        cs.namespace(|| "allocate T"),
+        self.inputs.as_ref().and_then(|inputs| inputs.T_p.as_ref()).map(|T| T.to_coordinates()),
+        limb_width,
+        n_limbs,
+      )?,
+    };
+
+    let data_c_1 = AllocatedCycleFoldData::alloc(
+      cs.namespace(|| "data_c_1"),
+      self.inputs.as_ref().and_then(|inputs| inputs.data_c_1.as_ref()),
+      limb_width,
+      n_limbs,
+    )?;
+
+    let data_c_2 = AllocatedCycleFoldData::alloc(
+      cs.namespace(|| "data_c_2"),
+      self.inputs.as_ref().and_then(|inputs| inputs.data_c_2.as_ref()),
+      limb_width,
+      n_limbs,
+    )?;
+
+    let E_new = emulated::AllocatedEmulPoint::alloc(
+      cs.namespace(|| "E_new"),
+      self.inputs.as_ref().and_then(|inputs| inputs.E_new.as_ref()).map(|E| E.to_coordinates()),
+      limb_width,
+      n_limbs,
+    )?;
+
+    let W_new = emulated::AllocatedEmulPoint::alloc(
+      cs.namespace(|| "W_new"),
+      self.inputs.as_ref().and_then(|inputs| inputs.W_new.as_ref()).map(|W| W.to_coordinates()),
+      limb_width,
+      n_limbs,
+    )?;
+
+    Ok((
+      pp_digest,
+      i,
+      z_0,
+      z_i,
+      program_counter,
+      last_augmented_circuit_selector,
+      U_p,
+      incoming,
+      data_c_1,
+      data_c_2,
+      E_new,
+      W_new,
+    ))
+  }
+
+  #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+  fn synthesize_non_base_case<CS: ConstraintSystem<<E1 as Engine>::Base>>(
+    &self,
+    mut cs: CS,
+    pp_digest: &AllocatedNum<E1::Base>,
+    i: &AllocatedNum<E1::Base>,
+    program_counter: &AllocatedNum<E1::Base>,
+    z_0: &[AllocatedNum<E1::Base>],
+    z_i: &[AllocatedNum<E1::Base>],
+    last_augmented_circuit_selector: &[Boolean],
+    U_p: &[emulated::AllocatedEmulRelaxedR1CSInstance<E1>],
+    incoming: &AllocatedIncoming<E1>,
+    data_c_1: &AllocatedCycleFoldData<E1>,
+    data_c_2: &AllocatedCycleFoldData<E1>,
+    E_new: emulated::AllocatedEmulPoint<E1::GE>,
+    W_new: emulated::AllocatedEmulPoint<E1::GE>,
+  ) -> Result<
+    (
+      AllocatedRelaxedR1CSInstance<E1, NIO_CYCLE_FOLD>,
+      Vec<emulated::AllocatedEmulRelaxedR1CSInstance<E1>>,
+      AllocatedBit,
+    ),
+    SynthesisError,
+  > {
+    // Calculate the hash of the non-deterministic advice for the primary circuit
+    let mut ro_p =
+      E1::ROCircuit::new(self.ro_consts.clone(), num_fe_primary_ro(z_0.len(), self.num_circuits));
+
+    ro_p.absorb(pp_digest);
+    ro_p.absorb(i);
+    ro_p.absorb(program_counter);
+    for e in z_0 {
+      ro_p.absorb(e)
+    }
+    for e in z_i {
+      ro_p.absorb(e)
+    }
+    for (j, U) in U_p.iter().enumerate() {
+      U.absorb_in_ro(cs.namespace(|| format!("absorb U_p {j}")), &mut ro_p)?;
+    }
+
+    let hash_bits_p = ro_p.squeeze(cs.namespace(|| "primary hash bits"), NUM_HASH_BITS)?;
+    let hash_p = le_bits_to_num(cs.namespace(|| "primary hash"), &hash_bits_p)?;
+
+    // check the hash matches the public IO from the last primary instance
+    let check_primary = alloc_num_equals(
+      cs.namespace(|| "u.X[0] = H(params, i, pc, z0, zi, U_p)"),
+      &incoming.u_x0,
+      &hash_p,
+    )?;
+
+    // Calculate the hash of the non-deterministic advice for the secondary circuit
+    let mut ro_c = E1::ROCircuit::new(
+      self.ro_consts.clone(),
+      1 + 1 + 3 + 3 + 1 + NIO_CYCLE_FOLD * BN_N_LIMBS, // pp + i + W + E + u + X
+    );
+
+    ro_c.absorb(pp_digest);
+    ro_c.absorb(i);
+    data_c_1.U.absorb_in_ro(cs.namespace(|| "absorb U_c"), &mut ro_c)?;
+    let hash_c_bits = ro_c.squeeze(cs.namespace(|| "cyclefold hash bits"), NUM_HASH_BITS)?;
+    let hash_c = le_bits_to_num(cs.namespace(|| "cyclefold hash"), &hash_c_bits)?;
+
+    // check the hash matches the public IO from the last primary instance
+    let check_cyclefold =
+      alloc_num_equals(cs.namespace(|| "u.X[1] = H(params, U_c)"), &incoming.u_x1, &hash_c)?;
+
+    let check_io =
+      AllocatedBit::and(cs.namespace(|| "both IOs match"), &check_primary, &check_cyclefold)?;
+
+    // Run NIVC.V on U_c, u_c_1, T_c_1
+    let U_int = data_c_1.apply_fold(
+      cs.namespace(|| "fold u_c_1 into U_c"),
+      pp_digest,
+      self.ro_consts.clone(),
+      self.params.limb_width,
+      self.params.n_limbs,
+    )?;
+
+    // Calculate h_int = H(pp, U_c_int)
+    let mut ro_c_int = E1::ROCircuit::new(
+      self.ro_consts.clone(),
+      1 + 3 + 3 + 1 + NIO_CYCLE_FOLD * BN_N_LIMBS, // pp + W + E + u + X
+    );
+    ro_c_int.absorb(pp_digest);
+    U_int.absorb_in_ro(cs.namespace(|| "absorb U_c_int"), &mut ro_c_int)?;
+    let h_c_int_bits =
+      ro_c_int.squeeze(cs.namespace(|| "intermediate hash bits"), NUM_HASH_BITS)?;
+    let h_c_int = le_bits_to_num(cs.namespace(|| "intermediate hash"), &h_c_int_bits)?;
+
+    // Calculate h_1 = H(pp, U_c_1)
+    let mut ro_c_1 = E1::ROCircuit::new(
+      self.ro_consts.clone(),
+      1 + 3 + 3 + 1 + NIO_CYCLE_FOLD * BN_N_LIMBS, // pp + W + E + u + X
+    );
+
+    ro_c_1.absorb(pp_digest);
+    data_c_2.U.absorb_in_ro(cs.namespace(|| "absorb U_c_1"), &mut ro_c_1)?;
+    let h_c_1_bits = ro_c_1.squeeze(cs.namespace(|| "cyclefold_1 hash bits"), NUM_HASH_BITS)?;
+    let h_c_1 = le_bits_to_num(cs.namespace(|| "cyclefold_1 hash"), &h_c_1_bits)?;
+
+    // Check the intermediate-calculated running instance matches the
+    // non-deterministic advice provided to the prover
+    let check_cyclefold_int = alloc_num_equals(cs.namespace(|| "h_int = h_c_1"), &h_c_int, &h_c_1)?;
+
+    let checks_pass =
+      AllocatedBit::and(cs.namespace(|| "all checks passed"), &check_io, &check_cyclefold_int)?;
+
+    // calculate the folded CycleFold accumulator
+    let U_c = data_c_2.apply_fold(
+      cs.namespace(|| "fold u_c_2 into U_c_1"),
+      pp_digest,
+      self.ro_consts.clone(),
+      self.params.limb_width,
+      self.params.n_limbs,
+    )?;
+
+    // select the running instance of the circuit that produced the incoming
+    // instance, exactly one selector bit is set
+    let (U_first, U_rest) = U_p.split_first().ok_or(SynthesisError::Unsatisfiable)?;
+    let mut U_selected = None;
+    for (j, (U, bit)) in U_rest.iter().zip(&last_augmented_circuit_selector[1..]).enumerate() {
+      U_selected = Some(U.conditionally_select(
+        cs.namespace(|| format!("select U_p {}", j + 1)),
+        U_selected.as_ref().unwrap_or(U_first),
+        bit,
+      )?);
+    }
+    let U_selected = U_selected.as_ref().unwrap_or(U_first);
+
+    // calculate the folded primary circuit accumulator
+    let U_folded = U_selected.fold_with_r1cs(
+      cs.namespace(|| "fold u_p into U_p"),
+      pp_digest,
+      W_new,
+      E_new,
+      &incoming.u_W,
+      &incoming.u_x0,
+      &incoming.u_x1,
+      &incoming.T,
+      self.ro_consts.clone(),
+    )?;
+
+    // replace the selected running instance with the folded one
+    let U_p_new = U_p
+      .iter()
+      .zip(last_augmented_circuit_selector)
+      .enumerate()
+      .map(|(j, (U, bit))| {
+        U_folded.conditionally_select(cs.namespace(|| format!("update U_p {j}")), U, bit)
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((U_c, U_p_new, checks_pass))
+  }
+
+  pub fn synthesize<CS: ConstraintSystem<<E1 as Engine>::Base>>(
+    self,
+    cs: &mut CS,
+  ) -> Result<(AllocatedNum<E1::Base>, Vec<AllocatedNum<E1::Base>>), SynthesisError> {
+    let arity = self.step_circuit.arity();
+
+    // Allocate the witness
+    let (
+      pp_digest,
+      i,
+      z_0,
+      z_i,
+      program_counter,
+      last_augmented_circuit_selector,
+      U_p,
+      incoming,
+      data_c_1,
+      data_c_2,
+      E_new,
+      W_new,
+    ) = self.alloc_witness(cs.namespace(|| "alloc_witness"), arity)?;
+
+    let zero = alloc_zero(cs.namespace(|| "zero"));
+    let is_base_case = alloc_num_equals(cs.namespace(|| "is base case"), &i, &zero)?;
+
+    // In the first folding step the running instances are all default
+    let U_c_default = AllocatedRelaxedR1CSInstance::default(
+      cs.namespace(|| "Allocate U_c_default"),
+      self.params.limb_width,
+      self.params.n_limbs,
+    )?;
+    let U_p_default = emulated::AllocatedEmulRelaxedR1CSInstance::default(
+      cs.namespace(|| "Allocate U_p_default"),
+      self.params.limb_width,
+      self.params.n_limbs,
+    )?;
+
+    let (U_new_c_non_base, U_new_p_non_base, check_non_base_pass) = self.synthesize_non_base_case(
+      cs.namespace(|| "synthesize non base case"),
+      &pp_digest,
+      &i,
+      &program_counter,
+      &z_0,
+      &z_i,
+      &last_augmented_circuit_selector,
+      &U_p,
+      &incoming,
+      &data_c_1,
+      &data_c_2,
+      E_new,
+      W_new,
+    )?;
+
+    let should_be_false = AllocatedBit::nor(
+      cs.namespace(|| "check_non_base_pass nor base_case"),
+      &check_non_base_pass,
+      &is_base_case,
+    )?;
+    cs.enforce(
+      || "check_non_base_pass nor base_case = false",
+      |lc| lc + should_be_false.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc,
+    );
+
+    // select the new running primary instances
+    let Unew_p = U_new_p_non_base
+      .iter()
+      .enumerate()
+      .map(|(j, U)| {
+        U_p_default.conditionally_select(
+          cs.namespace(|| format!("compute Unew_p {j}")),
+          U,
+          &Boolean::from(is_base_case.clone()),
+        )
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    // select the new running CycleFold instance
+    let Unew_c = U_c_default.conditionally_select(
+      cs.namespace(|| "compute Unew_c"),
+      &U_new_c_non_base,
+      &Boolean::from(is_base_case.clone()),
+    )?;
+
+    // Compute i + 1
+    let i_new =
+      AllocatedNum::alloc(cs.namespace(|| "i + 1"), || Ok(*i.get_value().get()? + E1::Base::ONE))?;
+    cs.enforce(
+      || "check i + 1",
+      |lc| lc,
+      |lc| lc,
+      |lc| lc + i_new.get_variable() - CS::one() - i.get_variable(),
+    );
+
+    // Compute z_{i+1}
+    let z_input = conditionally_select_slice(
+      cs.namespace(|| "select input to F"),
+      &z_0,
+      &z_i,
+      &Boolean::from(is_base_case),
+    )?;
+
+    // the program counter must select this circuit
+    let circuit_index = E1::Base::from(self.step_circuit.circuit_index() as u64);
+    cs.enforce(
+      || "pc matches circuit index",
+      |lc| lc + program_counter.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + (circuit_index, CS::one()),
+    );
+
+    let (program_counter_new, z_next) =
+      self.step_circuit.synthesize(&mut cs.namespace(|| "F"), Some(&program_counter), &z_input)?;
+    let program_counter_new = program_counter_new.ok_or(SynthesisError::AssignmentMissing)?;
+
+    if z_next.len() != arity {
+      return Err(SynthesisError::IncompatibleLengthVector("z_next".to_string()));
+    }
+
+    // Calculate the first component of the public IO as the hash of the calculated
+    // primary running instances
+    let mut ro_p =
+      E1::ROCircuit::new(self.ro_consts.clone(), num_fe_primary_ro(arity, self.num_circuits));
+    ro_p.absorb(&pp_digest);
+    ro_p.absorb(&i_new);
+    ro_p.absorb(&program_counter_new);
+    for e in &z_0 {
+      ro_p.absorb(e);
+    }
+    for e in &z_next {
+      ro_p.absorb(e);
+    }
+    for (j, U) in Unew_p.iter().enumerate() {
+      U.absorb_in_ro(cs.namespace(|| format!("absorb Unew_p {j}")), &mut ro_p)?;
+    }
+    let hash_p_bits = ro_p.squeeze(cs.namespace(|| "hash_p_bits"), NUM_HASH_BITS)?;
+    let hash_p = le_bits_to_num(cs.namespace(|| "hash_p"), &hash_p_bits)?;
+
+    // Calculate the second component of the public IO as the hash of the calculated
+    // CycleFold running instance
+    let mut ro_c = E1::ROCircuit::new(
+      self.ro_consts,
+      1 + 1 + 3 + 3 + 1 + NIO_CYCLE_FOLD * BN_N_LIMBS, // pp + i + W + E + u + X
+    );
+    ro_c.absorb(&pp_digest);
+    ro_c.absorb(&i_new);
+    Unew_c.absorb_in_ro(cs.namespace(|| "absorb Unew_c"), &mut ro_c)?;
+    let hash_c_bits = ro_c.squeeze(cs.namespace(|| "hash_c_bits"), NUM_HASH_BITS)?;
+    let hash_c = le_bits_to_num(cs.namespace(|| "hash_c"), &hash_c_bits)?;
+
+    hash_p.inputize(cs.namespace(|| "u_p.x[0] = hash_p"))?;
+    hash_c.inputize(cs.namespace(|| "u_p.x[1] = hash_c"))?;
+
+    Ok((program_counter_new, z_next))
+  }
+}
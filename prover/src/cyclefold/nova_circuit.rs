@@ -24,8 +24,8 @@ use crate::{
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AugmentedCircuitParams {
-  limb_width: usize,
-  n_limbs:    usize,
+  pub(super) limb_width: usize,
+  pub(super) n_limbs:    usize,
 }
 
 impl AugmentedCircuitParams {
@@ -1,5 +1,8 @@
 //! This module defines the Cyclefold `RecursiveSNARK` type with its `new`,
-//! `prove_step`, and `verify` methods.
+//! `prove_step`, and `verify` methods, and the `CompressedSNARK` that proves
+//! the knowledge of a valid `RecursiveSNARK`.
+
+use std::sync::Arc;
 
 use bellpepper_core::{ConstraintSystem, SynthesisError};
 use ff::PrimeFieldBits;
@@ -30,8 +33,8 @@ use crate::{
   },
   supernova::StepCircuit,
   traits::{
-    commitment::CommitmentTrait, AbsorbInROTrait, CurveCycleEquipped, Dual, Engine,
-    ROConstantsCircuit, ROTrait,
+    commitment::CommitmentTrait, snark::RelaxedR1CSSNARKTrait, AbsorbInROTrait, CurveCycleEquipped,
+    Dual, Engine, ROConstantsCircuit, ROTrait,
   },
   Commitment, CommitmentKey, DigestComputer, R1CSWithArity, ROConstants, ResourceBuffer,
   SimpleDigestible,
@@ -46,12 +49,12 @@ where E1: CurveCycleEquipped {
   F_arity_primary:           usize,
   ro_consts_primary:         ROConstants<Dual<E1>>,
   ro_consts_circuit_primary: ROConstantsCircuit<Dual<E1>>,
-  ck_primary:                CommitmentKey<E1>,
+  ck_primary:                Arc<CommitmentKey<E1>>,
   circuit_shape_primary:     R1CSWithArity<E1>,
   augmented_circuit_params:  AugmentedCircuitParams,
 
   ro_consts_cyclefold:     ROConstants<Dual<E1>>,
-  ck_cyclefold:            CommitmentKey<Dual<E1>>,
+  ck_cyclefold:            Arc<CommitmentKey<Dual<E1>>>,
   circuit_shape_cyclefold: R1CSWithArity<Dual<E1>>,
   #[serde(skip, default = "OnceCell::new")]
   digest:                  OnceCell<E1::Scalar>,
@@ -84,6 +87,7 @@ where E1: CurveCycleEquipped
     let mut cs: ShapeCS<E1> = ShapeCS::new();
     let _ = circuit_primary.synthesize(&mut cs);
     let (r1cs_shape_primary, ck_primary) = cs.r1cs_shape_and_key(ck_hint_primary);
+    let ck_primary = Arc::new(ck_primary);
     let circuit_shape_primary = R1CSWithArity::new(r1cs_shape_primary, F_arity_primary);
 
     let ro_consts_cyclefold = ROConstants::<Dual<E1>>::default();
@@ -91,6 +95,7 @@ where E1: CurveCycleEquipped
     let circuit_cyclefold: CycleFoldCircuit<E1> = CycleFoldCircuit::default();
     let _ = circuit_cyclefold.synthesize(&mut cs);
     let (r1cs_shape_cyclefold, ck_cyclefold) = cs.r1cs_shape_and_key(ck_hint_cyclefold);
+    let ck_cyclefold = Arc::new(ck_cyclefold);
     let circuit_shape_cyclefold = R1CSWithArity::new(r1cs_shape_cyclefold, 0);
 
     Self {
@@ -418,33 +423,14 @@ where E1: CurveCycleEquipped
 
     // Calculate the hashes of the primary running instance and cyclefold running
     // instance
-    let (hash_primary, hash_cyclefold) = {
-      let mut hasher = <Dual<E1> as Engine>::RO::new(
-        pp.ro_consts_primary.clone(),
-        2 + 2 * pp.F_arity_primary + 2 * NUM_FE_IN_EMULATED_POINT + 3,
-      );
-      hasher.absorb(pp.digest());
-      hasher.absorb(E1::Scalar::from(num_steps as u64));
-      for e in z0_primary {
-        hasher.absorb(*e);
-      }
-      for e in &self.zi_primary {
-        hasher.absorb(*e);
-      }
-      absorb_primary_relaxed_r1cs::<E1, Dual<E1>>(&self.r_U_primary, &mut hasher);
-      let hash_primary = hasher.squeeze(NUM_HASH_BITS);
-
-      let mut hasher = <Dual<E1> as Engine>::RO::new(
-        pp.ro_consts_cyclefold.clone(),
-        1 + 1 + 3 + 3 + 1 + NIO_CYCLE_FOLD * BN_N_LIMBS,
-      );
-      hasher.absorb(pp.digest());
-      hasher.absorb(E1::Scalar::from(num_steps as u64));
-      self.r_U_cyclefold.absorb_in_ro(&mut hasher);
-      let hash_cyclefold = hasher.squeeze(NUM_HASH_BITS);
-
-      (hash_primary, hash_cyclefold)
-    };
+    let (hash_primary, hash_cyclefold) = io_hashes(
+      pp,
+      num_steps,
+      z0_primary,
+      &self.zi_primary,
+      &self.r_U_primary,
+      &self.r_U_cyclefold,
+    );
 
     // Verify the hashes equal the public IO for the final primary instance
     if scalar_as_base::<Dual<E1>>(hash_primary) != self.l_u_primary.X[0]
@@ -491,65 +477,312 @@ where E1: CurveCycleEquipped
   }
 }
 
-// #[cfg(test)]
-// mod test {
-//     use std::marker::PhantomData;
-
-//     use bellpepper_core::num::AllocatedNum;
-
-//     use super::*;
-//     use crate::{
-//         provider::{Bn256EngineKZG, PallasEngine, Secp256k1Engine},
-//         traits::snark::default_ck_hint,
-//     };
-
-//     #[derive(Clone)]
-//     struct SquareCircuit<F> {
-//         _p: PhantomData<F>,
-//     }
-
-//     impl<F: PrimeField> StepCircuit<F> for SquareCircuit<F> {
-//         fn arity(&self) -> usize {
-//             1
-//         }
-
-//         fn synthesize<CS: ConstraintSystem<F>>(
-//             &self,
-//             cs: &mut CS,
-//             z: &[AllocatedNum<F>],
-//         ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
-//             let x = &z[0];
-//             let x_sq = x.square(cs.namespace(|| "x_sq"))?;
-
-//             Ok(vec![x_sq])
-//         }
-//     }
-
-//     fn test_trivial_cyclefold_prove_verify_with<E: CurveCycleEquipped>() {
-//         let primary_circuit = SquareCircuit::<E::Scalar> { _p: PhantomData };
-
-//         let pp =
-//             PublicParams::<E>::setup(&primary_circuit, &*default_ck_hint(),
-// &*default_ck_hint());
-
-//         let z0 = vec![E::Scalar::from(2u64)];
-
-//         let mut recursive_snark = RecursiveSNARK::new(&pp, &primary_circuit,
-// &z0).unwrap();
-
-//         (1..5).for_each(|iter| {
-//             let res_proof = recursive_snark.prove_step(&pp,
-// &primary_circuit);             res_proof.unwrap();
-
-//             let res_verify = recursive_snark.verify(&pp, iter, &z0);
-//             res_verify.unwrap();
-//         });
-//     }
-
-//     #[test]
-//     fn test_cyclefold_prove_verify() {
-//         test_trivial_cyclefold_prove_verify_with::<PallasEngine>();
-//         test_trivial_cyclefold_prove_verify_with::<Bn256EngineKZG>();
-//         test_trivial_cyclefold_prove_verify_with::<Secp256k1Engine>();
-//     }
-// }
+/// Computes the hashes of the primary and cyclefold running instances that the
+/// primary circuit outputs as its public IO after `num_steps` steps
+fn io_hashes<E1>(
+  pp: &PublicParams<E1>,
+  num_steps: usize,
+  z0_primary: &[E1::Scalar],
+  zn_primary: &[E1::Scalar],
+  r_U_primary: &RelaxedR1CSInstance<E1>,
+  r_U_cyclefold: &RelaxedR1CSInstance<Dual<E1>>,
+) -> (E1::Base, E1::Base)
+where
+  E1: CurveCycleEquipped,
+{
+  let mut hasher = <Dual<E1> as Engine>::RO::new(
+    pp.ro_consts_primary.clone(),
+    2 + 2 * pp.F_arity_primary + 2 * NUM_FE_IN_EMULATED_POINT + 3,
+  );
+  hasher.absorb(pp.digest());
+  hasher.absorb(E1::Scalar::from(num_steps as u64));
+  for e in z0_primary {
+    hasher.absorb(*e);
+  }
+  for e in zn_primary {
+    hasher.absorb(*e);
+  }
+  absorb_primary_relaxed_r1cs::<E1, Dual<E1>>(r_U_primary, &mut hasher);
+  let hash_primary = hasher.squeeze(NUM_HASH_BITS);
+
+  let mut hasher = <Dual<E1> as Engine>::RO::new(
+    pp.ro_consts_cyclefold.clone(),
+    1 + 1 + 3 + 3 + 1 + NIO_CYCLE_FOLD * BN_N_LIMBS,
+  );
+  hasher.absorb(pp.digest());
+  hasher.absorb(E1::Scalar::from(num_steps as u64));
+  r_U_cyclefold.absorb_in_ro(&mut hasher);
+  let hash_cyclefold = hasher.squeeze(NUM_HASH_BITS);
+
+  (hash_primary, hash_cyclefold)
+}
+
+/// A type that holds the prover key for `CompressedSNARK`
+#[derive(Clone, Debug)]
+pub struct ProverKey<E1, S1, S2>
+where
+  E1: CurveCycleEquipped,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E1>>, {
+  pub pk_primary:   S1::ProverKey,
+  pub pk_cyclefold: S2::ProverKey,
+}
+
+/// A type that holds the verifier key for `CompressedSNARK`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct VerifierKey<E1, S1, S2>
+where
+  E1: CurveCycleEquipped,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E1>>, {
+  pub vk_primary:   S1::VerifierKey,
+  pub vk_cyclefold: S2::VerifierKey,
+}
+
+/// A SNARK that proves the knowledge of a valid `RecursiveSNARK` in the
+/// CycleFold folding scheme.
+///
+/// The last primary instance is folded into the primary running instance,
+/// and Spartan proves the knowledge of witnesses for the folded primary
+/// instance and the cyclefold running instance.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CompressedSNARK<E1, S1, S2>
+where
+  E1: CurveCycleEquipped,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E1>>, {
+  r_U_primary:       RelaxedR1CSInstance<E1>,
+  l_u_primary:       R1CSInstance<E1>,
+  nifs_primary:      PrimaryNIFS<E1, Dual<E1>>,
+  f_W_snark_primary: S1,
+
+  r_U_cyclefold:       RelaxedR1CSInstance<Dual<E1>>,
+  r_W_snark_cyclefold: S2,
+
+  num_steps:  usize,
+  zn_primary: Vec<E1::Scalar>,
+}
+
+impl<E1, S1, S2> CompressedSNARK<E1, S1, S2>
+where
+  E1: CurveCycleEquipped,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E1>>,
+{
+  /// Creates prover and verifier keys for `CompressedSNARK`
+  #[allow(clippy::type_complexity)]
+  pub fn setup(
+    pp: &PublicParams<E1>,
+  ) -> Result<(ProverKey<E1, S1, S2>, VerifierKey<E1, S1, S2>), NovaError> {
+    let (pk_primary, vk_primary) =
+      S1::setup(pp.ck_primary.clone(), &pp.circuit_shape_primary.r1cs_shape)?;
+    let (pk_cyclefold, vk_cyclefold) =
+      S2::setup(pp.ck_cyclefold.clone(), &pp.circuit_shape_cyclefold.r1cs_shape)?;
+
+    Ok((ProverKey { pk_primary, pk_cyclefold }, VerifierKey { vk_primary, vk_cyclefold }))
+  }
+
+  /// Create a new `CompressedSNARK`
+  pub fn prove(
+    pp: &PublicParams<E1>,
+    pk: &ProverKey<E1, S1, S2>,
+    recursive_snark: &RecursiveSNARK<E1>,
+  ) -> Result<Self, NovaError> {
+    // fold the last primary instance with its running instance
+    let (nifs_primary, (f_U_primary, f_W_primary), _) = PrimaryNIFS::<E1, Dual<E1>>::prove(
+      &pp.ck_primary,
+      &pp.ro_consts_primary,
+      &pp.digest(),
+      &pp.circuit_shape_primary.r1cs_shape,
+      &recursive_snark.r_U_primary,
+      &recursive_snark.r_W_primary,
+      &recursive_snark.l_u_primary,
+      &recursive_snark.l_w_primary,
+    )?;
+
+    // create SNARKs proving the knowledge of f_W_primary and r_W_cyclefold
+    let (f_W_snark_primary, r_W_snark_cyclefold) = rayon::join(
+      || {
+        S1::prove(
+          &pp.ck_primary,
+          &pk.pk_primary,
+          &pp.circuit_shape_primary.r1cs_shape,
+          &f_U_primary,
+          &f_W_primary,
+        )
+      },
+      || {
+        S2::prove(
+          &pp.ck_cyclefold,
+          &pk.pk_cyclefold,
+          &pp.circuit_shape_cyclefold.r1cs_shape,
+          &recursive_snark.r_U_cyclefold,
+          &recursive_snark.r_W_cyclefold,
+        )
+      },
+    );
+
+    Ok(Self {
+      r_U_primary: recursive_snark.r_U_primary.clone(),
+      l_u_primary: recursive_snark.l_u_primary.clone(),
+      nifs_primary,
+      f_W_snark_primary: f_W_snark_primary?,
+
+      r_U_cyclefold: recursive_snark.r_U_cyclefold.clone(),
+      r_W_snark_cyclefold: r_W_snark_cyclefold?,
+
+      num_steps: recursive_snark.i,
+      zn_primary: recursive_snark.zi_primary.clone(),
+    })
+  }
+
+  /// The number of steps proven by the `CompressedSNARK`
+  pub const fn num_steps(&self) -> usize { self.num_steps }
+
+  /// Outputs of the primary circuit
+  pub fn zn_primary(&self) -> &[E1::Scalar] { &self.zn_primary }
+
+  /// Verify the correctness of the `CompressedSNARK`
+  pub fn verify(
+    &self,
+    pp: &PublicParams<E1>,
+    vk: &VerifierKey<E1, S1, S2>,
+    z0_primary: &[E1::Scalar],
+  ) -> Result<Vec<E1::Scalar>, NovaError> {
+    // the number of steps cannot be zero
+    if self.num_steps == 0 {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    // check if the (relaxed) R1CS instances have two public outputs
+    if self.l_u_primary.X.len() != 2 || self.r_U_primary.X.len() != 2 {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    // check if the output hashes in the last primary instance point to the
+    // right running instances
+    let (hash_primary, hash_cyclefold) = io_hashes(
+      pp,
+      self.num_steps,
+      z0_primary,
+      &self.zn_primary,
+      &self.r_U_primary,
+      &self.r_U_cyclefold,
+    );
+
+    if scalar_as_base::<Dual<E1>>(hash_primary) != self.l_u_primary.X[0]
+      || scalar_as_base::<Dual<E1>>(hash_cyclefold) != self.l_u_primary.X[1]
+    {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    // fold the primary running instance with the last instance to get a folded
+    // instance
+    let f_U_primary = self.nifs_primary.verify(
+      &pp.ro_consts_primary,
+      &pp.digest(),
+      &self.r_U_primary,
+      &self.l_u_primary,
+    )?;
+
+    // check the satisfiability of the folded primary instance and the cyclefold
+    // running instance using SNARKs proving the knowledge of their witnesses
+    let (res_primary, res_cyclefold) = rayon::join(
+      || self.f_W_snark_primary.verify(&vk.vk_primary, &f_U_primary),
+      || self.r_W_snark_cyclefold.verify(&vk.vk_cyclefold, &self.r_U_cyclefold),
+    );
+
+    res_primary?;
+    res_cyclefold?;
+
+    Ok(self.zn_primary.clone())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::marker::PhantomData;
+
+  use bellpepper_core::num::AllocatedNum;
+  use ff::{Field, PrimeField};
+  use halo2curves::bn256::Bn256;
+
+  use super::*;
+  use crate::{
+    provider::{hyperkzg, ipa_pc, Bn256EngineKZG, GrumpkinEngine},
+    spartan::snark::RelaxedR1CSSNARK,
+  };
+
+  type S1 = RelaxedR1CSSNARK<Bn256EngineKZG, hyperkzg::EvaluationEngine<Bn256, Bn256EngineKZG>>;
+  type S2 = RelaxedR1CSSNARK<GrumpkinEngine, ipa_pc::EvaluationEngine<GrumpkinEngine>>;
+
+  const NUM_STEPS: usize = 3;
+
+  #[derive(Clone)]
+  struct SquareCircuit<F> {
+    _p: PhantomData<F>,
+  }
+
+  impl<F: PrimeField> StepCircuit<F> for SquareCircuit<F> {
+    fn arity(&self) -> usize { 1 }
+
+    fn circuit_index(&self) -> usize { 0 }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+      &self,
+      cs: &mut CS,
+      pc: Option<&AllocatedNum<F>>,
+      z: &[AllocatedNum<F>],
+    ) -> Result<(Option<AllocatedNum<F>>, Vec<AllocatedNum<F>>), SynthesisError> {
+      let x_sq = z[0].square(cs.namespace(|| "x_sq"))?;
+      Ok((pc.cloned(), vec![x_sq]))
+    }
+  }
+
+  #[test]
+  fn test_cyclefold_compression_bn256_grumpkin() {
+    type E1 = Bn256EngineKZG;
+
+    let circuit = SquareCircuit { _p: PhantomData };
+    let pp = PublicParams::<E1>::setup(&circuit, &*S1::ck_floor(), &*S2::ck_floor());
+
+    let z0 = vec![<E1 as Engine>::Scalar::from(2u64)];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for i in 0..NUM_STEPS {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+      recursive_snark.verify(&pp, i + 1, &z0).unwrap();
+    }
+
+    let (pk, vk) = CompressedSNARK::<E1, S1, S2>::setup(&pp).unwrap();
+    let compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+    let expected = (0..NUM_STEPS).fold(z0[0], |z, _| z.square());
+    assert_eq!(compressed_snark.verify(&pp, &vk, &z0).unwrap(), vec![expected]);
+
+    // the verifier key survives a round trip
+    let vk_bytes = bincode::serialize(&vk).unwrap();
+    let vk: VerifierKey<E1, S1, S2> = bincode::deserialize(&vk_bytes).unwrap();
+    assert_eq!(compressed_snark.verify(&pp, &vk, &z0).unwrap(), vec![expected]);
+
+    // tampered proofs and wrong inputs are rejected
+    let mut tampered = compressed_snark.clone();
+    tampered.zn_primary[0] += <E1 as Engine>::Scalar::ONE;
+    assert!(tampered.verify(&pp, &vk, &z0).is_err());
+
+    let mut tampered = compressed_snark.clone();
+    tampered.num_steps += 1;
+    assert!(tampered.verify(&pp, &vk, &z0).is_err());
+
+    // a Spartan proof of another step does not prove the folded instance
+    let mut longer_snark = recursive_snark.clone();
+    longer_snark.prove_step(&pp, &circuit).unwrap();
+    let other_snark = CompressedSNARK::<E1, S1, S2>::prove(&pp, &pk, &longer_snark).unwrap();
+    let mut tampered = compressed_snark.clone();
+    tampered.f_W_snark_primary = other_snark.f_W_snark_primary;
+    assert!(tampered.verify(&pp, &vk, &z0).is_err());
+
+    let wrong_z0 = vec![z0[0] + <E1 as Engine>::Scalar::ONE];
+    assert!(compressed_snark.verify(&pp, &vk, &wrong_z0).is_err());
+  }
+}
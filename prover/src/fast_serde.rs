@@ -7,17 +7,99 @@
 //! In the future, it can be extended to do direct memory access to the
 //! javascript runtime. For now it does a single copy of the data into
 //! the rust runtime.
+//!
+//! # Container format
+//!
+//! Every [`FastSerde`] encoding is a container of typed sections, written with
+//! [`SectionWriter`] and read with [`SectionReader`]:
+//!
+//! ```text
+//! [0..4]    - Magic number (4 bytes)
+//! [4]       - Zero byte, distinguishes the container from the unversioned format
+//! [5..7]    - Format version (u16)
+//! [7]       - Serde type (u8)
+//! [8]       - Reserved (u8 = 0)
+//! [9..11]   - Number of sections (u16)
+//! Section table (repeated for each section):
+//!   [N]       - Section id (u8)
+//!   [N+1]     - Section flags (u8), bit 0 marks the section as optional
+//!   [N+2..10] - Offset of the section data from the start of the container (u64)
+//!   [N+10..18]- Length of the section data (u64)
+//! Section data (in table order, padded where a section requires alignment)
+//! [end-32..] - SHA3-256 digest of everything before it
+//! ```
+//!
+//! Readers reject containers whose digest does not match, so truncated or
+//! corrupted downloads are detected before any of their contents are used.
+//! Sections that a reader does not know are skipped if they are flagged
+//! optional, which lets newer writers add data without breaking older readers.
+//!
+//! Files written in the unversioned format (magic number directly followed by
+//! the serde type) are rejected with [`SerdeByteError::UnversionedFormat`] and
+//! must be regenerated.
 
-use std::io::{Cursor, Read};
+use std::ops::Range;
 
+use ff::PrimeField;
+use group::GroupEncoding;
+use sha3::{Digest, Sha3_256};
 use thiserror::Error;
 
+use crate::{
+  traits::{commitment::CommitmentTrait, Engine},
+  Commitment, CompressedCommitment,
+};
+
+pub mod mapped;
+
 pub static MAGIC_NUMBER: [u8; 4] = [0x50, 0x4C, 0x55, 0x54];
+
+/// The version of the container format written by [`SectionWriter`]
+pub const FORMAT_VERSION: u16 = 2;
+
+/// Section flag marking a section that readers may skip if they do not know it
+const OPTIONAL: u8 = 0x01;
+
+/// Size of the fixed container header
+const HEADER_SIZE: usize = 11;
+
+/// Size of one section table entry
+const TABLE_ENTRY_SIZE: usize = 18;
+
+/// Size of the trailing SHA3-256 digest
+const DIGEST_SIZE: usize = 32;
+
 pub enum SerdeByteTypes {
   AuxParams = 0x01,
   UniversalKZGParam = 0x02,
   CommitmentKey = 0x03,
   ProverParams = 0x04,
+  ProofBundle = 0x05,
+  CompressedSNARK = 0x06,
+  RecursiveSNARK = 0x07,
+  ProverKey = 0x08,
+  SpartanProverKey = 0x09,
+  KZGProverKey = 0x0A,
+  IPAProverKey = 0x0B,
+  R1CSWitness = 0x0C,
+  RelaxedR1CSWitness = 0x0D,
+  SparseMatrix = 0x0E,
+  R1CSShape = 0x0F,
+  R1CSWithArity = 0x10,
+  MappedProverParams = 0x11,
+  CeremonyTranscript = 0x12,
+  R1CSInstance = 0x13,
+  RelaxedR1CSInstance = 0x14,
+  NIFS = 0x15,
+  SumcheckProof = 0x16,
+  SpartanSNARK = 0x17,
+  SpartanPPSNARK = 0x18,
+  BatchedSpartanSNARK = 0x19,
+  BatchedSpartanPPSNARK = 0x1A,
+  IPAEvaluationArgument = 0x1B,
+  HyperKZGEvaluationArgument = 0x1C,
+  ZeromorphEvaluationArgument = 0x1D,
+  Blinding = 0x1E,
 }
 
 #[derive(Debug, Error)]
@@ -32,6 +114,16 @@ pub enum SerdeByteError {
   InvalidSectionType,
   #[error("{}", "invalid section size")]
   InvalidSectionSize,
+  #[error("unsupported format version {0}")]
+  UnsupportedVersion(u32),
+  #[error("{}", "unversioned format, the file needs to be regenerated")]
+  UnversionedFormat,
+  #[error("{}", "digest mismatch, the data is corrupted")]
+  DigestMismatch,
+  #[error("missing section {0}")]
+  MissingSection(u8),
+  #[error("unknown required section {0}")]
+  UnknownSection(u8),
   #[error(transparent)]
   IoError(#[from] std::io::Error),
   #[error(transparent)]
@@ -40,61 +132,409 @@ pub enum SerdeByteError {
   G1DecodeError,
   #[error("{}", "g2 decode error")]
   G2DecodeError,
+  #[error("{}", "scalar decode error")]
+  ScalarDecodeError,
+  #[error("{}", "commitment decode error")]
+  CommitmentDecodeError,
+  #[error("{}", "invalid sparse matrix")]
+  InvalidSparseMatrix,
+  #[error("{}", "invalid r1cs shape")]
+  InvalidR1CSShape,
 }
 
 /// A trait for fast conversions to bytes
 pub trait FastSerde: Sized {
   fn to_bytes(&self) -> Vec<u8>;
   fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError>;
+}
+
+/// A section queued in a [`SectionWriter`]
+struct PendingSection {
+  id:    u8,
+  flags: u8,
+  align: usize,
+  data:  Vec<u8>,
+}
+
+/// Builds a container, see the [module documentation](self) for the layout
+pub struct SectionWriter {
+  serde_type: SerdeByteTypes,
+  sections:   Vec<PendingSection>,
+}
+
+impl SectionWriter {
+  /// Starts an empty container of type `serde_type`
+  pub fn new(serde_type: SerdeByteTypes) -> Self { Self { serde_type, sections: Vec::new() } }
+
+  /// Adds a required section
+  pub fn section(&mut self, id: u8, data: impl Into<Vec<u8>>) {
+    self.sections.push(PendingSection { id, flags: 0, align: 1, data: data.into() });
+  }
+
+  /// Adds a section that readers which do not know `id` will skip
+  pub fn optional_section(&mut self, id: u8, data: impl Into<Vec<u8>>) {
+    self.sections.push(PendingSection { id, flags: OPTIONAL, align: 1, data: data.into() });
+  }
+
+  /// Adds a required section whose data starts at an offset of the container
+  /// that is a multiple of `align`
+  pub fn aligned_section(&mut self, id: u8, align: usize, data: impl Into<Vec<u8>>) {
+    self.sections.push(PendingSection { id, flags: 0, align, data: data.into() });
+  }
+
+  /// Lays out the header, section table and data, and appends the digest
+  pub fn finish(self) -> Vec<u8> {
+    let table_end = HEADER_SIZE + self.sections.len() * TABLE_ENTRY_SIZE;
+    let mut offsets = Vec::with_capacity(self.sections.len());
+    let mut end = table_end;
+    for section in &self.sections {
+      let offset = end.next_multiple_of(section.align);
+      offsets.push(offset);
+      end = offset + section.data.len();
+    }
 
-  fn validate_header(
-    cursor: &mut Cursor<&[u8]>,
-    expected_type: SerdeByteTypes,
-    expected_sections: u8,
-  ) -> Result<(), SerdeByteError> {
-    let mut magic = [0u8; 4];
-    cursor.read_exact(&mut magic)?;
-    if magic != MAGIC_NUMBER {
+    let mut out = Vec::with_capacity(end + DIGEST_SIZE);
+    out.extend_from_slice(&MAGIC_NUMBER);
+    out.push(0);
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    out.push(self.serde_type as u8);
+    out.push(0);
+    out.extend_from_slice(&(self.sections.len() as u16).to_le_bytes());
+
+    for (section, offset) in self.sections.iter().zip(&offsets) {
+      out.push(section.id);
+      out.push(section.flags);
+      out.extend_from_slice(&(*offset as u64).to_le_bytes());
+      out.extend_from_slice(&(section.data.len() as u64).to_le_bytes());
+    }
+
+    for (section, offset) in self.sections.iter().zip(&offsets) {
+      out.resize(*offset, 0);
+      out.extend_from_slice(&section.data);
+    }
+
+    let digest = Sha3_256::digest(&out);
+    out.extend_from_slice(&digest);
+    out
+  }
+}
+
+/// An entry of a container's section table
+struct SectionEntry {
+  id:    u8,
+  flags: u8,
+  range: Range<usize>,
+  read:  bool,
+}
+
+/// Reads a container written by [`SectionWriter`]
+///
+/// Creating the reader checks the header and the digest. Sections are then
+/// looked up by id, and [`SectionReader::finish`] checks that no required
+/// section was left unread.
+pub struct SectionReader<'a> {
+  bytes:    &'a [u8],
+  sections: Vec<SectionEntry>,
+}
+
+impl<'a> SectionReader<'a> {
+  /// Opens the container in `bytes`, which must be of type `expected_type`
+  pub fn new(bytes: &'a [u8], expected_type: SerdeByteTypes) -> Result<Self, SerdeByteError> {
+    let too_short = || std::io::Error::from(std::io::ErrorKind::UnexpectedEof);
+    if bytes.len() < 5 {
+      return Err(too_short().into());
+    }
+    if bytes[..4] != MAGIC_NUMBER {
       return Err(SerdeByteError::InvalidMagicNumber);
     }
+    if bytes[4] != 0 {
+      return Err(SerdeByteError::UnversionedFormat);
+    }
+    if bytes.len() < HEADER_SIZE + DIGEST_SIZE {
+      return Err(too_short().into());
+    }
 
-    let mut serde_type = [0u8; 1];
-    cursor.read_exact(&mut serde_type)?;
-    if serde_type[0] != expected_type as u8 {
-      return Err(SerdeByteError::InvalidSerdeType);
+    let version = u16::from_le_bytes([bytes[5], bytes[6]]);
+    if version != FORMAT_VERSION {
+      return Err(SerdeByteError::UnsupportedVersion(version as u32));
     }
 
-    let mut num_sections = [0u8; 1];
-    cursor.read_exact(&mut num_sections)?;
-    if num_sections[0] != expected_sections {
-      return Err(SerdeByteError::InvalidSectionCount);
+    let (body, digest) = bytes.split_at(bytes.len() - DIGEST_SIZE);
+    if Sha3_256::digest(body).as_slice() != digest {
+      return Err(SerdeByteError::DigestMismatch);
     }
 
-    Ok(())
+    if bytes[7] != expected_type as u8 {
+      return Err(SerdeByteError::InvalidSerdeType);
+    }
+
+    let num_sections = u16::from_le_bytes([bytes[9], bytes[10]]) as usize;
+    let table = body
+      .get(HEADER_SIZE..HEADER_SIZE + num_sections * TABLE_ENTRY_SIZE)
+      .ok_or(SerdeByteError::InvalidSectionCount)?;
+
+    let sections = table
+      .chunks_exact(TABLE_ENTRY_SIZE)
+      .map(|entry| {
+        let offset = u64::from_le_bytes(entry[2..10].try_into().unwrap()) as usize;
+        let length = u64::from_le_bytes(entry[10..18].try_into().unwrap()) as usize;
+        let range = offset..offset.checked_add(length).ok_or(SerdeByteError::InvalidSectionSize)?;
+        if range.end > body.len() {
+          return Err(SerdeByteError::InvalidSectionSize);
+        }
+        Ok(SectionEntry { id: entry[0], flags: entry[1], range, read: false })
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Self { bytes: body, sections })
   }
 
-  fn read_section_bytes(
-    cursor: &mut Cursor<&[u8]>,
-    expected_type: u8,
-  ) -> Result<Vec<u8>, SerdeByteError> {
-    let mut section_type = [0u8; 1];
-    cursor.read_exact(&mut section_type)?;
-    if section_type[0] != expected_type {
-      return Err(SerdeByteError::InvalidSectionType);
+  /// Returns the byte range of section `id` within the container
+  pub fn section_range(&mut self, id: u8) -> Result<Range<usize>, SerdeByteError> {
+    let entry = self
+      .sections
+      .iter_mut()
+      .find(|entry| entry.id == id)
+      .ok_or(SerdeByteError::MissingSection(id))?;
+    entry.read = true;
+    Ok(entry.range.clone())
+  }
+
+  /// Returns the data of the required section `id`
+  pub fn section(&mut self, id: u8) -> Result<&'a [u8], SerdeByteError> {
+    let range = self.section_range(id)?;
+    Ok(&self.bytes[range])
+  }
+
+  /// Returns the data of section `id`, if present
+  pub fn optional_section(&mut self, id: u8) -> Option<&'a [u8]> { self.section(id).ok() }
+
+  /// Checks that every required section has been read
+  pub fn finish(self) -> Result<(), SerdeByteError> {
+    match self.sections.iter().find(|entry| !entry.read && entry.flags & OPTIONAL == 0) {
+      Some(entry) => Err(SerdeByteError::UnknownSection(entry.id)),
+      None => Ok(()),
     }
+  }
+}
+
+/// Encodes a list of byte strings, each prefixed with its length (u64)
+pub fn list_to_bytes<T: AsRef<[u8]>>(items: &[T]) -> Vec<u8> {
+  let mut out = Vec::new();
+  for item in items {
+    out.extend_from_slice(&(item.as_ref().len() as u64).to_le_bytes());
+    out.extend_from_slice(item.as_ref());
+  }
+  out
+}
+
+/// Decodes a list written by [`list_to_bytes`]
+pub fn list_from_bytes(mut bytes: &[u8]) -> Result<Vec<&[u8]>, SerdeByteError> {
+  let mut items = Vec::new();
+  while !bytes.is_empty() {
+    let len =
+      bytes.get(..8).ok_or(SerdeByteError::InvalidSectionSize).and_then(usize_from_bytes)?;
+    let end = len.checked_add(8).ok_or(SerdeByteError::InvalidSectionSize)?;
+    let item = bytes.get(8..end).ok_or(SerdeByteError::InvalidSectionSize)?;
+    items.push(item);
+    bytes = &bytes[end..];
+  }
+  Ok(items)
+}
+
+/// Encodes field elements as their concatenated canonical representations
+pub fn scalars_to_bytes<F: PrimeField>(scalars: &[F]) -> Vec<u8> {
+  scalars.iter().flat_map(|s| s.to_repr().as_ref().to_vec()).collect()
+}
+
+/// Decodes field elements from their concatenated canonical representations
+pub fn scalars_from_bytes<F: PrimeField>(bytes: &[u8]) -> Result<Vec<F>, SerdeByteError> {
+  let size = F::Repr::default().as_ref().len();
+  if bytes.len() % size != 0 {
+    return Err(SerdeByteError::InvalidSectionSize);
+  }
+  bytes.chunks(size).map(scalar_from_bytes).collect()
+}
+
+/// Decodes a single field element from its canonical representation
+pub fn scalar_from_bytes<F: PrimeField>(bytes: &[u8]) -> Result<F, SerdeByteError> {
+  let mut repr = F::Repr::default();
+  if repr.as_ref().len() != bytes.len() {
+    return Err(SerdeByteError::InvalidSectionSize);
+  }
+  repr.as_mut().copy_from_slice(bytes);
+  F::from_repr(repr).into_option().ok_or(SerdeByteError::ScalarDecodeError)
+}
+
+/// Decodes a fixed number of field elements from their concatenated canonical representations
+pub fn scalar_array_from_bytes<F: PrimeField, const N: usize>(
+  bytes: &[u8],
+) -> Result<[F; N], SerdeByteError> {
+  scalars_from_bytes(bytes)?.try_into().map_err(|_| SerdeByteError::InvalidSectionSize)
+}
+
+/// Groups decoded items into arrays of `N` items
+pub fn into_arrays<T, const N: usize>(items: Vec<T>) -> Result<Vec<[T; N]>, SerdeByteError> {
+  let len = items.len();
+  if N == 0 || len % N != 0 {
+    return Err(SerdeByteError::InvalidSectionSize);
+  }
+  let mut items = items.into_iter();
+  Ok((0..len / N).map(|_| std::array::from_fn(|_| items.next().unwrap())).collect())
+}
+
+/// Encodes group elements as their concatenated compressed encodings
+pub fn points_to_bytes<P: GroupEncoding>(points: &[P]) -> Vec<u8> {
+  points.iter().flat_map(|p| p.to_bytes().as_ref().to_vec()).collect()
+}
+
+/// Decodes group elements from their concatenated compressed encodings
+pub fn points_from_bytes<P: GroupEncoding>(bytes: &[u8]) -> Result<Vec<P>, SerdeByteError> {
+  let size = P::Repr::default().as_ref().len();
+  if bytes.len() % size != 0 {
+    return Err(SerdeByteError::InvalidSectionSize);
+  }
+  bytes.chunks(size).map(point_from_bytes).collect()
+}
+
+/// Decodes a single group element from its compressed encoding
+pub fn point_from_bytes<P: GroupEncoding>(bytes: &[u8]) -> Result<P, SerdeByteError> {
+  let mut repr = P::Repr::default();
+  if repr.as_ref().len() != bytes.len() {
+    return Err(SerdeByteError::InvalidSectionSize);
+  }
+  repr.as_mut().copy_from_slice(bytes);
+  P::from_bytes(&repr).into_option().ok_or(SerdeByteError::G1DecodeError)
+}
+
+/// Encodes compressed commitments as their concatenated canonical encodings
+pub fn compressed_commitments_to_bytes<E: Engine>(comms: &[CompressedCommitment<E>]) -> Vec<u8> {
+  comms.iter().flat_map(Commitment::<E>::compressed_to_bytes).collect()
+}
+
+/// Decodes compressed commitments from their concatenated canonical encodings
+pub fn compressed_commitments_from_bytes<E: Engine>(
+  bytes: &[u8],
+) -> Result<Vec<CompressedCommitment<E>>, SerdeByteError> {
+  let size = Commitment::<E>::compressed_to_bytes(&Commitment::<E>::default().compress()).len();
+  if bytes.len() % size != 0 {
+    return Err(SerdeByteError::InvalidSectionSize);
+  }
+  bytes
+    .chunks(size)
+    .map(|c| {
+      Commitment::<E>::compressed_from_bytes(c).map_err(|_| SerdeByteError::CommitmentDecodeError)
+    })
+    .collect()
+}
+
+/// Encodes commitments as their concatenated compressed encodings
+pub fn commitments_to_bytes<E: Engine>(comms: &[Commitment<E>]) -> Vec<u8> {
+  comms.iter().flat_map(|c| Commitment::<E>::compressed_to_bytes(&c.compress())).collect()
+}
+
+/// Decodes commitments from their concatenated compressed encodings
+pub fn commitments_from_bytes<E: Engine>(
+  bytes: &[u8],
+) -> Result<Vec<Commitment<E>>, SerdeByteError> {
+  compressed_commitments_from_bytes::<E>(bytes)?
+    .iter()
+    .map(|c| Commitment::<E>::decompress(c).map_err(|_| SerdeByteError::CommitmentDecodeError))
+    .collect()
+}
+
+/// Decodes a single commitment from its compressed encoding
+pub fn commitment_from_bytes<E: Engine>(bytes: &[u8]) -> Result<Commitment<E>, SerdeByteError> {
+  let c = Commitment::<E>::compressed_from_bytes(bytes)
+    .map_err(|_| SerdeByteError::InvalidSectionSize)?;
+  Commitment::<E>::decompress(&c).map_err(|_| SerdeByteError::CommitmentDecodeError)
+}
+
+/// Decodes a little-endian `u64` section as a `usize`
+pub fn usize_from_bytes(bytes: &[u8]) -> Result<usize, SerdeByteError> {
+  let bytes: [u8; 8] = bytes.try_into().map_err(|_| SerdeByteError::InvalidSectionSize)?;
+  Ok(u64::from_le_bytes(bytes) as usize)
+}
+
+/// Encodes indices as little-endian `u32`s
+///
+/// # Panics
+///
+/// Panics if an index does not fit in a `u32`.
+pub fn indices_to_bytes(indices: &[usize]) -> Vec<u8> {
+  indices
+    .iter()
+    .flat_map(|&i| u32::try_from(i).expect("index does not fit in u32").to_le_bytes())
+    .collect()
+}
+
+/// Decodes indices from little-endian `u32`s
+pub fn indices_from_bytes(bytes: &[u8]) -> Result<Vec<usize>, SerdeByteError> {
+  if bytes.len() % 4 != 0 {
+    return Err(SerdeByteError::InvalidSectionSize);
+  }
+  Ok(bytes.chunks_exact(4).map(|c| u32::from_le_bytes(c.try_into().unwrap()) as usize).collect())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn container() -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::AuxParams);
+    out.section(1, vec![1, 2, 3]);
+    out.aligned_section(2, 8, vec![4; 16]);
+    out.optional_section(3, vec![5]);
+    out.finish()
+  }
+
+  #[test]
+  fn test_section_container_roundtrip() {
+    let bytes = container();
+    let mut sections = SectionReader::new(&bytes, SerdeByteTypes::AuxParams).unwrap();
+    assert_eq!(sections.section(2).unwrap(), &[4; 16]);
+    assert_eq!(sections.section_range(2).unwrap().start % 8, 0);
+    assert_eq!(sections.section(1).unwrap(), &[1, 2, 3]);
+    assert_eq!(sections.optional_section(4), None);
+    assert!(matches!(sections.section(4), Err(SerdeByteError::MissingSection(4))));
+    // the optional section 3 may be left unread
+    sections.finish().unwrap();
+
+    let sections = SectionReader::new(&bytes, SerdeByteTypes::AuxParams).unwrap();
+    assert!(matches!(sections.finish(), Err(SerdeByteError::UnknownSection(1))));
+
+    assert!(matches!(
+      SectionReader::new(&bytes, SerdeByteTypes::ProverKey),
+      Err(SerdeByteError::InvalidSerdeType)
+    ));
+  }
+
+  #[test]
+  fn test_section_container_rejects_corruption() {
+    let mut bytes = container();
+    let len = bytes.len();
+    bytes[len - DIGEST_SIZE - 1] ^= 1;
+    assert!(matches!(
+      SectionReader::new(&bytes, SerdeByteTypes::AuxParams),
+      Err(SerdeByteError::DigestMismatch)
+    ));
 
-    let mut section_size = [0u8; 4];
-    cursor.read_exact(&mut section_size)?;
-    let size = u32::from_le_bytes(section_size) as usize;
-    let mut section_data = vec![0u8; size];
-    cursor.read_exact(&mut section_data)?;
+    let bytes = container();
+    assert!(SectionReader::new(&bytes[..bytes.len() - 1], SerdeByteTypes::AuxParams).is_err());
 
-    Ok(section_data)
+    // unversioned format: magic number, serde type, number of sections
+    let mut unversioned = MAGIC_NUMBER.to_vec();
+    unversioned.extend_from_slice(&[SerdeByteTypes::AuxParams as u8, 0]);
+    assert!(matches!(
+      SectionReader::new(&unversioned, SerdeByteTypes::AuxParams),
+      Err(SerdeByteError::UnversionedFormat)
+    ));
   }
 
-  fn write_section_bytes(out: &mut Vec<u8>, section_type: u8, data: &[u8]) {
-    out.push(section_type);
-    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
-    out.extend_from_slice(data);
+  #[test]
+  fn test_list_roundtrip() {
+    let items = vec![vec![], vec![1u8], vec![2, 3]];
+    assert_eq!(list_from_bytes(&list_to_bytes(&items)).unwrap(), items);
+    assert!(list_from_bytes(&[1, 0, 0, 0, 0, 0, 0, 0]).is_err());
   }
 }
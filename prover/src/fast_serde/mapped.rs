@@ -0,0 +1,287 @@
+//! Memory-mapped, zero-copy storage for large arrays of curve points.
+//!
+//! Commitment keys are by far the largest part of a setup. Reading them with
+//! [`FastSerde::from_bytes`] needs the whole file in memory plus a decoded copy
+//! of every point, which doubles peak memory on constrained devices.
+//!
+//! [`Points`] either owns its points or borrows them in place from a read-only
+//! [`Mapping`] of a file. Points written with [`write_points_section`] are
+//! stored in their raw in-memory representation at an aligned offset, so that
+//! [`read_points_section`] can hand them out as a slice without copying. When
+//! the platform's layout of the point type does not match the raw encoding, or
+//! the data is misaligned, the points are decoded into an owned vector instead.
+//!
+//! [`FastSerde::from_bytes`]: super::FastSerde::from_bytes
+
+use std::{fmt, fs::File, mem, ops::Deref, path::Path, slice, sync::Arc};
+
+use group::prime::PrimeCurveAffine;
+use halo2curves::serde::SerdeObject;
+use memmap2::Mmap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{SectionReader, SectionWriter, SerdeByteError};
+
+/// A read-only memory mapping of a file
+///
+/// The file must not be modified while it is mapped; the mapping reflects
+/// changes made by other processes, and points borrowed from it would change
+/// underneath the prover.
+#[derive(Clone)]
+pub struct Mapping(Arc<Mmap>);
+
+impl Mapping {
+  /// Maps the file at `path` into memory
+  pub fn open(path: &Path) -> Result<Self, SerdeByteError> {
+    let file = File::open(path)?;
+    // SAFETY: the mapping is read-only, and callers are told not to modify
+    // mapped files (see the type documentation).
+    let mmap = unsafe { Mmap::map(&file)? };
+    Ok(Self(Arc::new(mmap)))
+  }
+
+  /// Returns the mapped bytes
+  pub fn bytes(&self) -> &[u8] { &self.0 }
+}
+
+impl fmt::Debug for Mapping {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Mapping").field("len", &self.0.len()).finish()
+  }
+}
+
+/// An array of curve points, either owned or borrowed from a [`Mapping`]
+///
+/// Dereferences to a slice, so read-only code does not need to care where the
+/// points live. Mutation goes through [`Points::to_mut`], which copies mapped
+/// points into an owned vector first.
+pub struct Points<A>(Storage<A>);
+
+/// Where the points of a [`Points`] live
+///
+/// Kept private so that mapped storage can only be created by
+/// [`Points::from_mapping`], which checks the invariants `Deref` relies on.
+enum Storage<A> {
+  /// Points owned by this value
+  Owned(Vec<A>),
+  /// Points stored in their raw representation in a mapped file
+  Mapped {
+    /// The mapping holding the points
+    mapping: Mapping,
+    /// Byte offset of the first point in the mapping
+    offset:  usize,
+    /// Number of points
+    len:     usize,
+  },
+}
+
+impl<A> Points<A> {
+  /// Returns whether the points are borrowed from a mapped file
+  pub fn is_mapped(&self) -> bool { matches!(self.0, Storage::Mapped { .. }) }
+}
+
+impl<A: Clone> Points<A> {
+  /// Returns a mutable reference to the owned points, copying them out of the
+  /// mapping first if needed
+  pub fn to_mut(&mut self) -> &mut Vec<A> {
+    if self.is_mapped() {
+      self.0 = Storage::Owned(self.to_vec());
+    }
+    match &mut self.0 {
+      Storage::Owned(points) => points,
+      Storage::Mapped { .. } => unreachable!(),
+    }
+  }
+
+  /// Converts the points into an owned vector, copying them if they are mapped
+  pub fn into_vec(self) -> Vec<A> {
+    match self.0 {
+      Storage::Owned(points) => points,
+      Storage::Mapped { .. } => self.to_vec(),
+    }
+  }
+}
+
+impl<A: PrimeCurveAffine + SerdeObject> Points<A> {
+  /// Reads points stored in their raw representation in `mapping` at
+  /// `offset..offset + size`
+  ///
+  /// The points are borrowed in place if the in-memory layout of `A` matches
+  /// its raw encoding and the data is suitably aligned. Borrowed points are not
+  /// checked to lie on the curve, so only map files from a trusted source.
+  /// Otherwise every point is decoded and validated into an owned vector.
+  pub fn from_mapping(
+    mapping: &Mapping,
+    offset: usize,
+    size: usize,
+  ) -> Result<Self, SerdeByteError> {
+    let point_size = raw_size::<A>();
+    let bytes = offset
+      .checked_add(size)
+      .and_then(|end| mapping.bytes().get(offset..end))
+      .ok_or(SerdeByteError::InvalidSectionSize)?;
+    if size % point_size != 0 {
+      return Err(SerdeByteError::InvalidSectionSize);
+    }
+
+    if layout_matches_raw::<A>() && (bytes.as_ptr() as usize) % mem::align_of::<A>() == 0 {
+      let len = size / point_size;
+      return Ok(Self(Storage::Mapped { mapping: mapping.clone(), offset, len }));
+    }
+
+    bytes
+      .chunks(point_size)
+      .map(|bytes| A::from_raw_bytes(bytes).ok_or(SerdeByteError::G1DecodeError))
+      .collect::<Result<Vec<_>, _>>()
+      .map(Self::from)
+  }
+}
+
+/// Size of the raw encoding of a point
+fn raw_size<A: PrimeCurveAffine + SerdeObject>() -> usize { A::identity().to_raw_bytes().len() }
+
+/// Returns whether the in-memory representation of `A` is exactly its raw
+/// encoding, which is what allows reinterpreting mapped bytes as points
+fn layout_matches_raw<A: PrimeCurveAffine + SerdeObject>() -> bool {
+  if cfg!(target_endian = "big") || mem::size_of::<A>() != raw_size::<A>() {
+    return false;
+  }
+  let generator = A::generator();
+  // SAFETY: `A` is `Copy`, and its size equals that of its raw encoding, which
+  // has no room for padding bytes, so every byte of `generator` is initialized.
+  let in_memory =
+    unsafe { slice::from_raw_parts(&generator as *const A as *const u8, mem::size_of::<A>()) };
+  in_memory == generator.to_raw_bytes().as_slice()
+}
+
+impl<A> Deref for Points<A> {
+  type Target = [A];
+
+  fn deref(&self) -> &[A] {
+    match &self.0 {
+      Storage::Owned(points) => points,
+      // SAFETY: `from_mapping` only creates this variant after checking that
+      // `len` points of raw size `size_of::<A>()` fit in the mapping at
+      // `offset`, that the data is aligned for `A`, and that the raw encoding
+      // is the in-memory layout of `A`. The mapping is kept alive by `self`.
+      Storage::Mapped { mapping, offset, len } => unsafe {
+        slice::from_raw_parts(mapping.bytes().as_ptr().add(*offset) as *const A, *len)
+      },
+    }
+  }
+}
+
+impl<A> Default for Points<A> {
+  fn default() -> Self { Self(Storage::Owned(Vec::new())) }
+}
+
+impl<A> From<Vec<A>> for Points<A> {
+  fn from(points: Vec<A>) -> Self { Self(Storage::Owned(points)) }
+}
+
+impl<A> FromIterator<A> for Points<A> {
+  fn from_iter<I: IntoIterator<Item = A>>(iter: I) -> Self {
+    Self(Storage::Owned(iter.into_iter().collect()))
+  }
+}
+
+/// Mapped points are shared rather than copied, which also keeps clones of
+/// large keys cheap
+impl<A: Clone> Clone for Points<A> {
+  fn clone(&self) -> Self {
+    Self(match &self.0 {
+      Storage::Owned(points) => Storage::Owned(points.clone()),
+      Storage::Mapped { mapping, offset, len } =>
+        Storage::Mapped { mapping: mapping.clone(), offset: *offset, len: *len },
+    })
+  }
+}
+
+impl<A: fmt::Debug> fmt::Debug for Points<A> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list().entries(self.iter()).finish()
+  }
+}
+
+impl<A: PartialEq> PartialEq for Points<A> {
+  fn eq(&self, other: &Self) -> bool { **self == **other }
+}
+
+impl<A: Eq> Eq for Points<A> {}
+
+/// Serializes exactly like a `Vec<A>`, so digests do not depend on where the
+/// points live
+impl<A: Serialize> Serialize for Points<A> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(self.iter())
+  }
+}
+
+impl<'de, A: Deserialize<'de>> Deserialize<'de> for Points<A> {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    Vec::deserialize(deserializer).map(Self::from)
+  }
+}
+
+/// Adds `points` in their raw representation as a section of `writer` whose
+/// data is aligned for `A`
+///
+/// The alignment is relative to the start of the container, so the points can
+/// only be borrowed when the container is the whole mapped file.
+pub fn write_points_section<A: SerdeObject>(writer: &mut SectionWriter, id: u8, points: &[A]) {
+  let data = points.iter().flat_map(|p| p.to_raw_bytes()).collect::<Vec<u8>>();
+  writer.aligned_section(id, mem::align_of::<A>(), data);
+}
+
+/// Reads a section written by [`write_points_section`] from a reader over the
+/// bytes of `mapping`, borrowing the points in place where possible
+pub fn read_points_section<A: PrimeCurveAffine + SerdeObject>(
+  reader: &mut SectionReader<'_>,
+  mapping: &Mapping,
+  id: u8,
+) -> Result<Points<A>, SerdeByteError> {
+  let range = reader.section_range(id)?;
+  Points::from_mapping(mapping, range.start, range.len())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::Write;
+
+  use group::{Curve, Group};
+  use halo2curves::bn256::{G1Affine, G1};
+  use rand_chacha::ChaCha20Rng;
+  use rand_core::SeedableRng;
+
+  use super::*;
+  use crate::fast_serde::SerdeByteTypes;
+
+  #[test]
+  fn test_points_section_roundtrip() {
+    let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+    let points = (0..64).map(|_| G1::random(&mut rng).to_affine()).collect::<Vec<G1Affine>>();
+
+    // a short section first forces the points to be padded
+    let mut writer = SectionWriter::new(SerdeByteTypes::CommitmentKey);
+    writer.section(1, vec![0u8; 3]);
+    write_points_section(&mut writer, 2, &points);
+    write_points_section(&mut writer, 3, &points[..7]);
+    let out = writer.finish();
+
+    let path = std::env::temp_dir().join(format!("points-{}.bytes", std::process::id()));
+    File::create(&path).unwrap().write_all(&out).unwrap();
+    let mapping = Mapping::open(&path).unwrap();
+
+    let mut reader = SectionReader::new(mapping.bytes(), SerdeByteTypes::CommitmentKey).unwrap();
+    assert_eq!(reader.section(1).unwrap(), &[0u8; 3]);
+    let read = read_points_section::<G1Affine>(&mut reader, &mapping, 2).unwrap();
+    let read_prefix = read_points_section::<G1Affine>(&mut reader, &mapping, 3).unwrap();
+    reader.finish().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(read.is_mapped());
+    assert_eq!(*read, *points);
+    assert_eq!(*read_prefix, points[..7]);
+    assert_eq!(read.clone().into_vec(), points);
+  }
+}
@@ -49,6 +49,25 @@ impl<G: Group> AllocatedPoint<G> {
     Ok(Self { x, y, is_infinity })
   }
 
+  /// Builds a point from already allocated coordinates, such as the inputs of
+  /// a step circuit. Only checks that `is_infinity` is a bit; callers that do
+  /// not trust the coordinates should also call `check_on_curve`.
+  pub fn from_coordinates<CS: ConstraintSystem<G::Base>>(
+    mut cs: CS,
+    x: AllocatedNum<G::Base>,
+    y: AllocatedNum<G::Base>,
+    is_infinity: AllocatedNum<G::Base>,
+  ) -> Self {
+    cs.enforce(
+      || "is_infinity is bit",
+      |lc| lc + is_infinity.get_variable(),
+      |lc| lc + CS::one() - is_infinity.get_variable(),
+      |lc| lc,
+    );
+
+    Self { x, y, is_infinity }
+  }
+
   /// checks if `self` is on the curve or if it is infinity
   pub fn check_on_curve<CS>(&self, mut cs: CS) -> Result<(), SynthesisError>
   where CS: ConstraintSystem<G::Base> {
@@ -102,7 +121,6 @@ impl<G: Group> AllocatedPoint<G> {
   }
 
   /// Returns coordinates associated with the point.
-  #[allow(unused)]
   pub const fn get_coordinates(
     &self,
   ) -> (&AllocatedNum<G::Base>, &AllocatedNum<G::Base>, &AllocatedNum<G::Base>) {
@@ -751,7 +769,8 @@ mod tests {
     },
     provider::{
       bn256_grumpkin::{bn256, grumpkin},
-      Bn256EngineIPA, Bn256EngineKZG, GrumpkinEngine,
+      secp_secq::secp256k1,
+      Bn256EngineIPA, Bn256EngineKZG, GrumpkinEngine, Secp256k1Engine, Secq256k1Engine,
     },
     traits::{snark::default_ck_hint, Engine},
   };
@@ -859,6 +878,7 @@ mod tests {
   fn test_ecc_ops() {
     test_ecc_ops_with::<bn256::Affine, <Bn256EngineIPA as Engine>::GE>();
     test_ecc_ops_with::<grumpkin::Affine, <GrumpkinEngine as Engine>::GE>();
+    test_ecc_ops_with::<secp256k1::Affine, <Secp256k1Engine as Engine>::GE>();
   }
 
   fn test_ecc_ops_with<C, G>()
@@ -985,6 +1005,7 @@ mod tests {
   fn test_ecc_circuit_add_equal() {
     test_ecc_circuit_add_equal_with::<Bn256EngineKZG, GrumpkinEngine>();
     test_ecc_circuit_add_equal_with::<GrumpkinEngine, Bn256EngineKZG>();
+    test_ecc_circuit_add_equal_with::<Secp256k1Engine, Secq256k1Engine>();
   }
 
   fn test_ecc_circuit_add_equal_with<E1, E2>()
@@ -1,6 +1,6 @@
 //! This module implements various gadgets necessary for Nova and applications
 //! built with Nova.
-mod ecc;
+pub mod ecc;
 pub(crate) use ecc::AllocatedPoint;
 
 mod nonnative;
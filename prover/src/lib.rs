@@ -7,16 +7,20 @@ pub mod digest;
 pub mod nifs;
 
 // public modules
+pub mod abi;
 pub mod constants;
 pub mod errors;
 pub mod fast_serde;
 pub mod gadgets;
+pub mod metrics;
 pub mod provider;
 pub mod r1cs;
 pub mod spartan;
 pub mod traits;
 
+pub mod ccs;
 pub mod cyclefold;
+pub mod protogalaxy;
 pub mod supernova;
 
 use std::sync::Arc;
@@ -25,6 +29,7 @@ use bellpepper_core::{ConstraintSystem, SynthesisError};
 use circuit::{NovaAugmentedCircuit, NovaAugmentedCircuitInputs, NovaAugmentedCircuitParams};
 use constants::{BN_LIMB_WIDTH, BN_N_LIMBS, NUM_FE_WITHOUT_IO_FOR_CRHF, NUM_HASH_BITS};
 use errors::NovaError;
+use fast_serde::{FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes};
 use ff::Field;
 use gadgets::scalar_as_base;
 use nifs::NIFS;
@@ -72,6 +77,28 @@ impl<E: Engine> R1CSWithArity<E> {
   }
 }
 
+impl<E: Engine> FastSerde for R1CSWithArity<E> {
+  /// Sections:
+  ///   1: F_arity (u64)
+  ///   2: r1cs_shape (FastSerde)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::R1CSWithArity);
+    out.section(1, (self.F_arity as u64).to_le_bytes());
+    out.section(2, self.r1cs_shape.to_bytes());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::R1CSWithArity)?;
+
+    let F_arity = fast_serde::usize_from_bytes(sections.section(1)?)?;
+    let r1cs_shape = R1CSShape::from_bytes(sections.section(2)?)?;
+
+    sections.finish()?;
+    Ok(Self { F_arity, r1cs_shape })
+  }
+}
+
 /// A type that holds public parameters of Nova
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(bound = "")]
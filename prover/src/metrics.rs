@@ -0,0 +1,137 @@
+//! Timings of proving phases
+//!
+//! Phases deep inside a proof, such as the sum-checks and the polynomial commitment openings of
+//! the Spartan SNARKs or the witness generation of a step circuit, are timed with [`timed`] into
+//! a recorder that [`record`] installs on the current thread. This breaks a proof down without
+//! threading a sink through the SNARK and circuit traits. Phases timed outside of [`record`], or
+//! on other threads, are not recorded.
+
+use std::{cell::Cell, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+/// A phase of a proof that is timed with [`timed`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+  /// Witness generation of a step circuit
+  WitnessGeneration,
+  /// Sum-check proofs
+  Sumcheck,
+  /// Polynomial commitment openings
+  Pcs,
+}
+
+/// Accumulated durations of the phases timed inside [`record`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseTimings {
+  /// Time spent generating witnesses of step circuits
+  #[serde(with = "duration_ms")]
+  pub witness_generation: Duration,
+  /// Time spent in sum-check proofs
+  #[serde(with = "duration_ms")]
+  pub sumcheck:           Duration,
+  /// Time spent opening polynomial commitments
+  #[serde(with = "duration_ms")]
+  pub pcs:                Duration,
+}
+
+impl PhaseTimings {
+  /// The accumulated duration of `phase`
+  fn get_mut(&mut self, phase: Phase) -> &mut Duration {
+    match phase {
+      Phase::WitnessGeneration => &mut self.witness_generation,
+      Phase::Sumcheck => &mut self.sumcheck,
+      Phase::Pcs => &mut self.pcs,
+    }
+  }
+}
+
+thread_local! {
+  /// Recorder of the innermost [`record`] on this thread
+  static TIMINGS: Cell<Option<PhaseTimings>> = const { Cell::new(None) };
+}
+
+/// Restores the recorder of an enclosing [`record`], also when `f` unwinds
+struct RestoreTimings(Option<PhaseTimings>);
+
+impl Drop for RestoreTimings {
+  fn drop(&mut self) { TIMINGS.set(self.0); }
+}
+
+/// Runs `f` and returns the durations of the phases it timed on this thread
+///
+/// Calls can be nested, in which case the phases timed inside the inner call are only reported
+/// to it.
+pub fn record<T>(f: impl FnOnce() -> T) -> (T, PhaseTimings) {
+  let restore = RestoreTimings(TIMINGS.replace(Some(PhaseTimings::default())));
+  let result = f();
+  let timings = TIMINGS.get().unwrap_or_default();
+  drop(restore);
+  (result, timings)
+}
+
+/// Runs `f` as `phase`, adding its duration to the recorder of the enclosing [`record`], if any
+pub fn timed<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+  let start = Instant::now();
+  let result = f();
+  let elapsed = start.elapsed();
+  if let Some(mut timings) = TIMINGS.get() {
+    *timings.get_mut(phase) += elapsed;
+    TIMINGS.set(Some(timings));
+  }
+  result
+}
+
+/// Serializes a [`Duration`] as fractional milliseconds, for dashboards
+pub mod duration_ms {
+  use std::time::Duration;
+
+  use serde::{Deserialize, Deserializer, Serializer};
+
+  /// Serializes `duration` as fractional milliseconds
+  pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_nanos() as f64 / 1e6)
+  }
+
+  /// Deserializes a [`Duration`] from fractional milliseconds
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let millis = f64::deserialize(deserializer)?;
+    if !millis.is_finite() || millis < 0.0 {
+      return Err(serde::de::Error::custom(
+        "duration must be a non-negative number of milliseconds",
+      ));
+    }
+    Ok(Duration::from_nanos((millis * 1e6).round() as u64))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_record_nested() {
+    let (_, outer) = record(|| {
+      timed(Phase::Sumcheck, || std::thread::sleep(Duration::from_millis(2)));
+      let (_, inner) =
+        record(|| timed(Phase::Pcs, || std::thread::sleep(Duration::from_millis(2))));
+      assert!(inner.pcs >= Duration::from_millis(2));
+      assert_eq!(inner.sumcheck, Duration::ZERO);
+    });
+    assert!(outer.sumcheck >= Duration::from_millis(2));
+    assert_eq!(outer.pcs, Duration::ZERO);
+
+    // phases outside of `record` are not recorded
+    timed(Phase::Pcs, || ());
+    assert_eq!(TIMINGS.get(), None);
+  }
+
+  #[test]
+  fn test_duration_ms_roundtrip() {
+    let timings = PhaseTimings { sumcheck: Duration::from_micros(1500), ..Default::default() };
+    let json = serde_json::to_string(&timings).unwrap();
+    assert_eq!(json, r#"{"witness_generation":0.0,"sumcheck":1.5,"pcs":0.0}"#);
+    assert_eq!(serde_json::from_str::<PhaseTimings>(&json).unwrap(), timings);
+  }
+}
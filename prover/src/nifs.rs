@@ -4,8 +4,10 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-  constants::{NUM_CHALLENGE_BITS, NUM_FE_FOR_RO, NUM_FE_WITHOUT_IO_FOR_NOVA_FOLD},
+  abi::{self, AbiEncode, Word},
+  constants::{BN_N_LIMBS, NUM_CHALLENGE_BITS, NUM_FE_FOR_RO, NUM_FE_WITHOUT_IO_FOR_NOVA_FOLD},
   errors::NovaError,
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
   r1cs::{
     R1CSInstance, R1CSResult, R1CSShape, R1CSWitness, RelaxedR1CSInstance, RelaxedR1CSWitness,
   },
@@ -135,6 +137,51 @@ impl<E: Engine> NIFS<E> {
     Ok((Self { comm_T: comm_T.compress() }, r))
   }
 
+  /// [`NIFS::prove_mut`] without a buffer for the cross-term `T`
+  ///
+  /// The commitment to `T` is computed in chunks of `chunk_len` constraints
+  /// with [`R1CSShape::commit_T_chunked`], and the entries of `T` are
+  /// recomputed from the products in `ABC_Z_1` and `ABC_Z_2` when folding the
+  /// witness, so that the full cross-term is never held in memory.
+  #[allow(clippy::too_many_arguments)]
+  #[tracing::instrument(skip_all, level = "trace", name = "NIFS::prove_mut_chunked")]
+  pub fn prove_mut_chunked(
+    ck: &CommitmentKey<E>,
+    ro_consts: &ROConstants<E>,
+    pp_digest: &E::Scalar,
+    S: &R1CSShape<E>,
+    U1: &mut RelaxedR1CSInstance<E>,
+    W1: &mut RelaxedR1CSWitness<E>,
+    U2: &R1CSInstance<E>,
+    W2: &R1CSWitness<E>,
+    ABC_Z_1: &mut R1CSResult<E>,
+    ABC_Z_2: &mut R1CSResult<E>,
+    chunk_len: usize,
+  ) -> Result<(Self, E::Scalar), NovaError> {
+    // initialize a new RO
+    let mut ro = E::RO::new(ro_consts.clone(), NUM_FE_FOR_RO);
+
+    // append the digest of pp and U2 to the transcript
+    ro.absorb(scalar_as_base::<E>(*pp_digest));
+    U2.absorb_in_ro(&mut ro);
+
+    // compute a commitment to the cross-term
+    let comm_T = S.commit_T_chunked(ck, U1, W1, U2, W2, ABC_Z_1, ABC_Z_2, chunk_len)?;
+
+    // append `comm_T` to the transcript and obtain a challenge
+    comm_T.absorb_in_ro(&mut ro);
+    let r = ro.squeeze(NUM_CHALLENGE_BITS);
+
+    // fold the instance, keeping the `u` the cross-term was computed with
+    let u_1 = U1.u;
+    U1.fold_mut(U2, &comm_T, &r);
+
+    // fold the witness, recomputing `T` from the products
+    W1.fold_mut_chunked(W2, ABC_Z_1, ABC_Z_2, &u_1, &r)?;
+
+    Ok((Self { comm_T: comm_T.compress() }, r))
+  }
+
   /// Takes as input a relaxed R1CS instance `U1` and R1CS instance `U2`
   /// with the same shape and defined with respect to the same parameters,
   /// and outputs a folded instance `U` with the same shape,
@@ -170,6 +217,119 @@ impl<E: Engine> NIFS<E> {
     // return the folded instance
     Ok(U)
   }
+
+  /// Takes as input two Relaxed R1CS instance-witness tuples `(U1, W1)` and
+  /// `(U2, W2)` with the same structure `shape` and defined with respect to
+  /// the same `ck`, and outputs a folded Relaxed R1CS instance-witness tuple
+  /// `(U, W)` of the same shape, with the guarantee that `W` satisfies `U`
+  /// if and only if `W1` satisfies `U1` and `W2` satisfies `U2`.
+  ///
+  /// Unlike [`NIFS::prove`], neither instance is bound to the other through
+  /// its public IO, so both are absorbed in the RO. This is used to fold a
+  /// random satisfying instance into a running instance before compression,
+  /// which makes the folded witness independent of the original one.
+  #[allow(clippy::too_many_arguments)]
+  #[tracing::instrument(skip_all, level = "trace", name = "NIFS::prove_relaxed")]
+  pub fn prove_relaxed(
+    ck: &CommitmentKey<E>,
+    ro_consts: &ROConstants<E>,
+    pp_digest: &E::Scalar,
+    S: &R1CSShape<E>,
+    U1: &RelaxedR1CSInstance<E>,
+    W1: &RelaxedR1CSWitness<E>,
+    U2: &RelaxedR1CSInstance<E>,
+    W2: &RelaxedR1CSWitness<E>,
+  ) -> Result<(Self, (RelaxedR1CSInstance<E>, RelaxedR1CSWitness<E>)), NovaError> {
+    let mut ro = Self::relaxed_ro(ro_consts, pp_digest, U1, U2)?;
+
+    // compute a commitment to the cross-term
+    let (T, comm_T) = S.commit_T_relaxed(ck, U1, W1, U2, W2)?;
+
+    // append `comm_T` to the transcript and obtain a challenge
+    comm_T.absorb_in_ro(&mut ro);
+    let r = ro.squeeze(NUM_CHALLENGE_BITS);
+
+    // fold the instance and the witness using `r`, `comm_T` and `T`
+    let U = U1.fold_relaxed(U2, &comm_T, &r);
+    let W = W1.fold_relaxed(W2, &T, &r)?;
+
+    Ok((Self { comm_T: comm_T.compress() }, (U, W)))
+  }
+
+  /// Takes as input two relaxed R1CS instances `U1` and `U2` with the same
+  /// shape and defined with respect to the same parameters, and outputs a
+  /// folded instance `U` that is satisfiable if and only if `U1` and `U2`
+  /// are satisfiable.
+  pub fn verify_relaxed(
+    &self,
+    ro_consts: &ROConstants<E>,
+    pp_digest: &E::Scalar,
+    U1: &RelaxedR1CSInstance<E>,
+    U2: &RelaxedR1CSInstance<E>,
+  ) -> Result<RelaxedR1CSInstance<E>, NovaError> {
+    let mut ro = Self::relaxed_ro(ro_consts, pp_digest, U1, U2)?;
+
+    // append `comm_T` to the transcript and obtain a challenge
+    let comm_T = Commitment::<E>::decompress(&self.comm_T)?;
+    comm_T.absorb_in_ro(&mut ro);
+    let r = ro.squeeze(NUM_CHALLENGE_BITS);
+
+    // fold the instance using `r` and `comm_T`
+    Ok(U1.fold_relaxed(U2, &comm_T, &r))
+  }
+
+  /// Initializes the RO for folding two relaxed instances and absorbs the
+  /// digest of pp and both instances in it.
+  fn relaxed_ro(
+    ro_consts: &ROConstants<E>,
+    pp_digest: &E::Scalar,
+    U1: &RelaxedR1CSInstance<E>,
+    U2: &RelaxedR1CSInstance<E>,
+  ) -> Result<E::RO, NovaError> {
+    let io_arity = U1.X.len();
+    if io_arity != U2.X.len() {
+      return Err(NovaError::InvalidInputLength);
+    }
+
+    // pp digest, then comm_W, comm_E, u and the limbs of X for each instance,
+    // then comm_T
+    let num_absorbs = 1 + 2 * (7 + io_arity * BN_N_LIMBS) + 3;
+    let mut ro = E::RO::new(ro_consts.clone(), num_absorbs);
+    ro.absorb(scalar_as_base::<E>(*pp_digest));
+    U1.absorb_in_ro(&mut ro);
+    U2.absorb_in_ro(&mut ro);
+    Ok(ro)
+  }
+}
+
+impl<E: Engine> AbiEncode for NIFS<E> {
+  /// Words: comm_T (point)
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    abi::encode_commitment::<E>(out, &Commitment::<E>::decompress(&self.comm_T)?);
+    Ok(())
+  }
+}
+
+impl<E: Engine> FastSerde for NIFS<E> {
+  /// Sections:
+  ///   1: comm_T (compressed commitment)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::NIFS);
+    out.section(1, Commitment::<E>::compressed_to_bytes(&self.comm_T));
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::NIFS)?;
+
+    let [comm_T]: [CompressedCommitment<E>; 1] =
+      fast_serde::compressed_commitments_from_bytes::<E>(sections.section(1)?)?
+        .try_into()
+        .map_err(|_| SerdeByteError::InvalidSectionSize)?;
+
+    sections.finish()?;
+    Ok(Self { comm_T })
+  }
 }
 
 #[cfg(test)]
@@ -367,4 +527,99 @@ mod tests {
 
   #[test]
   fn test_tiny_r1cs() { test_tiny_r1cs_with::<Bn256EngineKZG>(); }
+
+  fn test_relaxed_fold_with<E: Engine>() {
+    let S = R1CSShape::<E>::random(16, 16, 3, 32, &mut OsRng);
+    let ck = commitment_key(&S, &*default_ck_hint());
+    let ro_consts = <<E as Engine>::RO as ROTrait<
+            <E as Engine>::Base,
+            <E as Engine>::Scalar,
+        >>::Constants::default();
+    let pp_digest = E::Scalar::random(&mut OsRng);
+
+    let (W1, U1) = S.random_witness_instance(&ck, &mut OsRng);
+    let (W2, U2) = S.random_witness_instance(&ck, &mut OsRng);
+
+    let (nifs, (U, W)) =
+      NIFS::prove_relaxed(&ck, &ro_consts, &pp_digest, &S, &U1, &W1, &U2, &W2).unwrap();
+    S.is_sat_relaxed(&ck, &U, &W).unwrap();
+    assert_eq!(nifs.verify_relaxed(&ro_consts, &pp_digest, &U1, &U2).unwrap(), U);
+
+    // a witness that does not satisfy its instance does not fold into one that
+    // does
+    let mut W_bad = W2.clone();
+    W_bad.W[0] += E::Scalar::ONE;
+    let (_, (U, W)) =
+      NIFS::prove_relaxed(&ck, &ro_consts, &pp_digest, &S, &U1, &W1, &U2, &W_bad).unwrap();
+    assert!(S.is_sat_relaxed(&ck, &U, &W).is_err());
+  }
+
+  #[test]
+  fn test_relaxed_fold() { test_relaxed_fold_with::<Bn256EngineKZG>(); }
+
+  fn test_prove_mut_chunked_with<E: Engine>() {
+    let S = R1CSShape::<E>::random(16, 16, 3, 32, &mut OsRng);
+    let ck = commitment_key(&S, &*default_ck_hint());
+    let ro_consts = <<E as Engine>::RO as ROTrait<
+            <E as Engine>::Base,
+            <E as Engine>::Scalar,
+        >>::Constants::default();
+    let pp_digest = E::Scalar::random(&mut OsRng);
+
+    let (r_W, r_U) = S.random_witness_instance(&ck, &mut OsRng);
+    let W2 = R1CSWitness::new(&S, (0..S.num_vars).map(|_| E::Scalar::random(&mut OsRng)).collect())
+      .unwrap();
+    let U2 = R1CSInstance::new(
+      &S,
+      W2.commit(&ck),
+      (0..S.num_io).map(|_| E::Scalar::random(&mut OsRng)).collect(),
+    )
+    .unwrap();
+
+    let (mut U, mut W) = (r_U.clone(), r_W.clone());
+    let mut T = Vec::new();
+    let (mut ABC_Z_1, mut ABC_Z_2) =
+      (R1CSResult::default(S.num_cons), R1CSResult::default(S.num_cons));
+    let (nifs, r) = NIFS::prove_mut(
+      &ck,
+      &ro_consts,
+      &pp_digest,
+      &S,
+      &mut U,
+      &mut W,
+      &U2,
+      &W2,
+      &mut T,
+      &mut ABC_Z_1,
+      &mut ABC_Z_2,
+    )
+    .unwrap();
+
+    // chunks that do and do not divide the number of constraints, and a single chunk
+    for chunk_len in [1, 5, S.num_cons] {
+      let (mut U_chunked, mut W_chunked) = (r_U.clone(), r_W.clone());
+      let (nifs_chunked, r_chunked) = NIFS::prove_mut_chunked(
+        &ck,
+        &ro_consts,
+        &pp_digest,
+        &S,
+        &mut U_chunked,
+        &mut W_chunked,
+        &U2,
+        &W2,
+        &mut ABC_Z_1,
+        &mut ABC_Z_2,
+        chunk_len,
+      )
+      .unwrap();
+
+      assert_eq!(nifs_chunked.comm_T, nifs.comm_T);
+      assert_eq!(r_chunked, r);
+      assert_eq!(U_chunked, U);
+      assert_eq!(W_chunked, W);
+    }
+  }
+
+  #[test]
+  fn test_prove_mut_chunked() { test_prove_mut_chunked_with::<Bn256EngineKZG>(); }
 }
@@ -0,0 +1,418 @@
+//! This module implements a ProtoGalaxy-style folding scheme, which folds `k`
+//! fresh R1CS instances of the same shape into an accumulator at once.
+//!
+//! An accumulator claims `∑_i pow_i(β)·f_i(z) = e`, where `f_i(z)` is the
+//! `i`-th entry of `Az ∘ Bz - Cz` and `pow_i(β) = ∏_{l : i_l = 1} β_l`. The
+//! instances are combined with the Lagrange basis over the domain
+//! `{0, 1, ..., k}`, which keeps the constant entry of `z` equal to one, so no
+//! relaxation scalar or error vector is needed and the prover commits to
+//! nothing while folding.
+//!
+//! Only the native folding scheme is provided: amortizing the recursion
+//! overhead of a `RecursiveSNARK` across `k` steps also needs the verifier of
+//! [`ProtoGalaxy`] as an augmented circuit.
+#![allow(non_snake_case)]
+
+use ff::{Field, PrimeField};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  errors::NovaError,
+  r1cs::{R1CSInstance, R1CSShape, R1CSWitness},
+  spartan::math::Math,
+  traits::{commitment::CommitmentEngineTrait, Engine, TranscriptEngineTrait, TranscriptReprTrait},
+  Commitment, CommitmentKey, CE,
+};
+
+/// A type that holds an accumulated R1CS instance, which claims that the
+/// `pow(β)`-weighted sum of the constraint evaluations equals `e`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct AccumulatorInstance<E: Engine> {
+  pub(crate) comm_W: Commitment<E>,
+  pub(crate) X:      Vec<E::Scalar>,
+  pub(crate) betas:  Vec<E::Scalar>,
+  pub(crate) e:      E::Scalar,
+}
+
+/// A type that holds a witness for an accumulated R1CS instance
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct AccumulatorWitness<E: Engine> {
+  pub(crate) W: Vec<E::Scalar>,
+}
+
+/// A proof that `k` R1CS instances were folded into an accumulator
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ProtoGalaxy<E: Engine> {
+  /// `F(1), ..., F(t)` for `F(X) = ∑_i pow_i(β + X·δ)·f_i(z)`
+  F_evals: Vec<E::Scalar>,
+  /// `K(k + 1), ..., K(2k)` for `G(X) = F(α)·L_0(X) + Z(X)·K(X)`
+  K_evals: Vec<E::Scalar>,
+}
+
+/// The number of variables of `pow(β)`, i.e. the length of `β`
+fn num_betas<E: Engine>(S: &R1CSShape<E>) -> usize { S.num_cons.next_power_of_two().log_2() }
+
+/// Computes `pow_i(β)` for every `i` in `0..2^t`
+fn pow_evals<F: Field>(betas: &[F]) -> Vec<F> {
+  let mut evals = vec![F::ONE];
+  for beta in betas {
+    let high = evals.iter().map(|e| *e * beta).collect::<Vec<_>>();
+    evals.extend(high);
+  }
+  evals
+}
+
+/// Computes `∑_i pow_i(β)·f_i`
+fn pow_sum<F: Field>(betas: &[F], f: &[F]) -> F {
+  pow_evals(betas).par_iter().zip(f).map(|(pow, f)| *pow * f).sum()
+}
+
+/// Evaluates the Lagrange basis over the domain `{start, ..., start + n - 1}`
+/// at `x`
+fn lagrange_basis<F: PrimeField>(start: u64, n: usize, x: &F) -> Vec<F> {
+  let points = (0..n as u64).map(|i| F::from(start + i)).collect::<Vec<_>>();
+  points
+    .iter()
+    .enumerate()
+    .map(|(j, x_j)| {
+      let (num, den) = points
+        .iter()
+        .enumerate()
+        .filter(|(m, _)| *m != j)
+        .fold((F::ONE, F::ONE), |(num, den), (_, x_m)| (num * (*x - x_m), den * (*x_j - x_m)));
+      num * den.invert().unwrap()
+    })
+    .collect()
+}
+
+/// Evaluates the vanishing polynomial of `{0, ..., k}` at `x`
+fn vanishing<F: PrimeField>(k: usize, x: &F) -> F {
+  (0..=k as u64).map(|m| *x - F::from(m)).product()
+}
+
+fn dot<F: Field>(a: &[F], b: &[F]) -> F { a.iter().zip(b).map(|(a, b)| *a * b).sum() }
+
+/// Combines vectors of equal length with the given coefficients
+fn combine<F: Field>(coeffs: &[F], vectors: &[&[F]]) -> Vec<F> {
+  (0..vectors[0].len())
+    .into_par_iter()
+    .map(|i| coeffs.iter().zip(vectors).map(|(c, v)| *c * v[i]).sum())
+    .collect()
+}
+
+/// The challenges of the folding scheme, which the prover and the verifier
+/// derive from the same transcript
+struct Challenges<E: Engine> {
+  transcript: E::TE,
+  deltas:     Vec<E::Scalar>,
+}
+
+impl<E: Engine> Challenges<E> {
+  fn new(
+    pp_digest: &E::Scalar,
+    U1: &AccumulatorInstance<E>,
+    U2: &[R1CSInstance<E>],
+  ) -> Result<Self, NovaError> {
+    let mut transcript = E::TE::new(b"ProtoGalaxy");
+    transcript.absorb(b"pp", pp_digest);
+    transcript.absorb(b"U1", U1);
+    transcript.absorb(b"U2", &U2);
+
+    // δ, δ^2, δ^4, ..., so that `pow(β + X·δ)` has degree `t` in `X`
+    let delta = transcript.squeeze(b"d")?;
+    let deltas =
+      std::iter::successors(Some(delta), |d| Some(d.square())).take(U1.betas.len()).collect();
+    Ok(Self { transcript, deltas })
+  }
+
+  /// Returns `α`, `β* = β + α·δ` and `F(α)`
+  fn squeeze_alpha(
+    &mut self,
+    U1: &AccumulatorInstance<E>,
+    F_evals: &[E::Scalar],
+  ) -> Result<(Vec<E::Scalar>, E::Scalar), NovaError> {
+    self.transcript.absorb(b"F", &F_evals);
+    let alpha = self.transcript.squeeze(b"a")?;
+
+    let betas =
+      U1.betas.iter().zip(&self.deltas).map(|(beta, delta)| *beta + alpha * delta).collect();
+    let F_evals = [&[U1.e], F_evals].concat();
+    let F_alpha = dot(&lagrange_basis(0, F_evals.len(), &alpha), &F_evals);
+    Ok((betas, F_alpha))
+  }
+
+  /// Returns `γ` and `e* = F(α)·L_0(γ) + Z(γ)·K(γ)`
+  fn squeeze_gamma(
+    &mut self,
+    k: usize,
+    F_alpha: &E::Scalar,
+    K_evals: &[E::Scalar],
+  ) -> Result<(Vec<E::Scalar>, E::Scalar), NovaError> {
+    self.transcript.absorb(b"K", &K_evals);
+    let gamma = self.transcript.squeeze(b"g")?;
+
+    let L = lagrange_basis(0, k + 1, &gamma);
+    let K_gamma = dot(&lagrange_basis(k as u64 + 1, k, &gamma), K_evals);
+    let e = *F_alpha * L[0] + vanishing(k, &gamma) * K_gamma;
+    Ok((L, e))
+  }
+}
+
+impl<E: Engine> ProtoGalaxy<E> {
+  /// Takes an accumulator instance-witness pair `(U1, W1)` and `k` R1CS
+  /// instance-witness pairs `(U2, W2)` of the shape `S`, and folds them into
+  /// a new accumulator instance-witness pair.
+  #[tracing::instrument(skip_all, level = "trace", name = "ProtoGalaxy::prove")]
+  pub fn prove(
+    pp_digest: &E::Scalar,
+    S: &R1CSShape<E>,
+    U1: &AccumulatorInstance<E>,
+    W1: &AccumulatorWitness<E>,
+    U2: &[R1CSInstance<E>],
+    W2: &[R1CSWitness<E>],
+  ) -> Result<(Self, (AccumulatorInstance<E>, AccumulatorWitness<E>)), NovaError> {
+    let k = U2.len();
+    if k == 0 || k != W2.len() || U1.betas.len() != num_betas(S) {
+      return Err(NovaError::InvalidInputLength);
+    }
+    if U2.iter().any(|U| U.X.len() != U1.X.len()) || W2.iter().any(|W| W.W.len() != W1.W.len()) {
+      return Err(NovaError::InvalidInputLength);
+    }
+
+    let mut challenges = Challenges::new(pp_digest, U1, U2)?;
+
+    // F(X) has degree t, and F(0) = e is known to the verifier
+    let f = S.compute_E(&W1.W, &E::Scalar::ONE, &U1.X)?;
+    let F_evals = (1..=U1.betas.len() as u64)
+      .map(|x| {
+        let x = E::Scalar::from(x);
+        let betas = U1
+          .betas
+          .iter()
+          .zip(&challenges.deltas)
+          .map(|(beta, delta)| *beta + x * delta)
+          .collect::<Vec<_>>();
+        pow_sum(&betas, &f)
+      })
+      .collect::<Vec<_>>();
+
+    let (betas, F_alpha) = challenges.squeeze_alpha(U1, &F_evals)?;
+
+    // G(X) - F(α)·L_0(X) has degree 2k and vanishes on {0, ..., k}, so the
+    // quotient K(X) has degree k - 1 and is determined by k evaluations outside
+    // the domain
+    let Ws = [&W1.W[..]].into_iter().chain(W2.iter().map(|W| &W.W[..])).collect::<Vec<_>>();
+    let Xs = [&U1.X[..]].into_iter().chain(U2.iter().map(|U| &U.X[..])).collect::<Vec<_>>();
+    let K_evals = (k as u64 + 1..=2 * k as u64)
+      .map(|x| {
+        let x = E::Scalar::from(x);
+        let L = lagrange_basis(0, k + 1, &x);
+        let f = S.compute_E(&combine(&L, &Ws), &E::Scalar::ONE, &combine(&L, &Xs))?;
+        let G = pow_sum(&betas, &f);
+        Ok((G - F_alpha * L[0]) * vanishing(k, &x).invert().unwrap())
+      })
+      .collect::<Result<Vec<_>, NovaError>>()?;
+
+    let (L, e) = challenges.squeeze_gamma(k, &F_alpha, &K_evals)?;
+
+    let U = Self::fold(U1, U2, &L, betas, e);
+    let W = AccumulatorWitness { W: combine(&L, &Ws) };
+
+    Ok((Self { F_evals, K_evals }, (U, W)))
+  }
+
+  /// Takes an accumulator instance `U1` and `k` R1CS instances `U2`, and
+  /// folds them into a new accumulator instance.
+  pub fn verify(
+    &self,
+    pp_digest: &E::Scalar,
+    S: &R1CSShape<E>,
+    U1: &AccumulatorInstance<E>,
+    U2: &[R1CSInstance<E>],
+  ) -> Result<AccumulatorInstance<E>, NovaError> {
+    let k = U2.len();
+    if k == 0 || U1.betas.len() != num_betas(S) {
+      return Err(NovaError::InvalidInputLength);
+    }
+    if U2.iter().any(|U| U.X.len() != U1.X.len()) {
+      return Err(NovaError::InvalidInputLength);
+    }
+    if self.F_evals.len() != U1.betas.len() || self.K_evals.len() != k {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    let mut challenges = Challenges::new(pp_digest, U1, U2)?;
+    let (betas, F_alpha) = challenges.squeeze_alpha(U1, &self.F_evals)?;
+    let (L, e) = challenges.squeeze_gamma(k, &F_alpha, &self.K_evals)?;
+
+    Ok(Self::fold(U1, U2, &L, betas, e))
+  }
+
+  fn fold(
+    U1: &AccumulatorInstance<E>,
+    U2: &[R1CSInstance<E>],
+    L: &[E::Scalar],
+    betas: Vec<E::Scalar>,
+    e: E::Scalar,
+  ) -> AccumulatorInstance<E> {
+    let comm_W = U2.iter().zip(&L[1..]).fold(U1.comm_W * L[0], |acc, (U, L)| acc + U.comm_W * *L);
+    let Xs = [&U1.X[..]].into_iter().chain(U2.iter().map(|U| &U.X[..])).collect::<Vec<_>>();
+    AccumulatorInstance { comm_W, X: combine(L, &Xs), betas, e }
+  }
+}
+
+impl<E: Engine> AccumulatorInstance<E> {
+  /// Turns the first R1CS instance into an accumulator, with `β` derived
+  /// from the instance so that `e = 0` checks every constraint
+  pub fn from_r1cs_instance(
+    pp_digest: &E::Scalar,
+    S: &R1CSShape<E>,
+    U: &R1CSInstance<E>,
+  ) -> Result<Self, NovaError> {
+    if U.X.len() != S.num_io {
+      return Err(NovaError::InvalidInputLength);
+    }
+
+    let mut transcript = E::TE::new(b"ProtoGalaxyInit");
+    transcript.absorb(b"pp", pp_digest);
+    transcript.absorb(b"U", U);
+    let betas = (0..num_betas(S)).map(|_| transcript.squeeze(b"b")).collect::<Result<_, _>>()?;
+
+    Ok(Self { comm_W: U.comm_W, X: U.X.clone(), betas, e: E::Scalar::ZERO })
+  }
+}
+
+impl<E: Engine> From<R1CSWitness<E>> for AccumulatorWitness<E> {
+  fn from(W: R1CSWitness<E>) -> Self { Self { W: W.W } }
+}
+
+impl<E: Engine> TranscriptReprTrait<E::GE> for AccumulatorInstance<E> {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    [
+      self.comm_W.to_transcript_bytes(),
+      self.X.as_slice().to_transcript_bytes(),
+      self.betas.as_slice().to_transcript_bytes(),
+      self.e.to_transcript_bytes(),
+    ]
+    .concat()
+  }
+}
+
+impl<E: Engine> R1CSShape<E> {
+  /// Checks if the accumulated R1CS instance is satisfiable given a witness
+  pub fn is_sat_accumulated(
+    &self,
+    ck: &CommitmentKey<E>,
+    U: &AccumulatorInstance<E>,
+    W: &AccumulatorWitness<E>,
+  ) -> Result<(), NovaError> {
+    if U.betas.len() != num_betas(self) {
+      return Err(NovaError::UnSat);
+    }
+
+    let f = self.compute_E(&W.W, &E::Scalar::ONE, &U.X)?;
+    if pow_sum(&U.betas, &f) != U.e {
+      return Err(NovaError::UnSat);
+    }
+
+    if U.comm_W != CE::<E>::commit(ck, &W.W) {
+      return Err(NovaError::UnSat);
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ::bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+
+  use super::*;
+  use crate::{
+    bellpepper::{
+      r1cs::{NovaShape, NovaWitness},
+      solver::SatisfyingAssignment,
+      test_shape_cs::TestShapeCS,
+    },
+    provider::{Bn256EngineIPA, Bn256EngineKZG, GrumpkinEngine},
+    traits::snark::default_ck_hint,
+  };
+
+  fn synthesize_tiny_r1cs_bellpepper<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    x_val: Option<Scalar>,
+  ) -> Result<(), SynthesisError> {
+    // Consider a cubic equation: `x^3 + x + 5 = y`, where `x` and `y` are
+    // respectively the input and output.
+    let x = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || x_val.unwrap());
+    let _ = x.inputize(cs.namespace(|| "x is input"));
+
+    let x_sq = x.square(cs.namespace(|| "x_sq"))?;
+    let x_cu = x_sq.mul(cs.namespace(|| "x_cu"), &x)?;
+    let y = AllocatedNum::alloc(cs.namespace(|| "y"), || {
+      Ok(x_cu.get_value().unwrap() + x.get_value().unwrap() + Scalar::from(5u64))
+    })?;
+    let _ = y.inputize(cs.namespace(|| "y is output"));
+
+    cs.enforce(
+      || "y = x^3 + x + 5",
+      |lc| lc + x_cu.get_variable() + x.get_variable() + (Scalar::from(5u64), CS::one()),
+      |lc| lc + CS::one(),
+      |lc| lc + y.get_variable(),
+    );
+
+    Ok(())
+  }
+
+  fn test_tiny_r1cs_protogalaxy_with<E: Engine>(k: usize) {
+    let mut cs: TestShapeCS<E> = TestShapeCS::new();
+    let _ = synthesize_tiny_r1cs_bellpepper(&mut cs, None);
+    let (shape, ck) = cs.r1cs_shape_and_key(&*default_ck_hint());
+    let pp_digest = E::Scalar::ZERO;
+
+    let instance = |x: u64| {
+      let mut cs = SatisfyingAssignment::<E>::new();
+      let _ = synthesize_tiny_r1cs_bellpepper(&mut cs, Some(E::Scalar::from(x)));
+      cs.r1cs_instance_and_witness(&shape, &ck).unwrap()
+    };
+
+    let (U, W) = instance(1);
+    let mut r_U = AccumulatorInstance::from_r1cs_instance(&pp_digest, &shape, &U).unwrap();
+    let mut r_W = AccumulatorWitness::from(W);
+    shape.is_sat_accumulated(&ck, &r_U, &r_W).unwrap();
+
+    for step in 0..3 {
+      let (Us, Ws): (Vec<_>, Vec<_>) =
+        (0..k as u64).map(|i| instance(2 + step * k as u64 + i)).unzip();
+
+      let (proof, (U_folded, W_folded)) =
+        ProtoGalaxy::prove(&pp_digest, &shape, &r_U, &r_W, &Us, &Ws).unwrap();
+      let U_verified = proof.verify(&pp_digest, &shape, &r_U, &Us).unwrap();
+      assert_eq!(U_folded, U_verified);
+
+      shape.is_sat_accumulated(&ck, &U_folded, &W_folded).unwrap();
+      r_U = U_folded;
+      r_W = W_folded;
+    }
+
+    // folding an unsatisfied instance yields an unsatisfied accumulator
+    let (U, mut W) = instance(42);
+    W.W[0] += E::Scalar::ONE;
+    let U = R1CSInstance { comm_W: CE::<E>::commit(&ck, &W.W), X: U.X };
+    let (_, (U_folded, W_folded)) =
+      ProtoGalaxy::prove(&pp_digest, &shape, &r_U, &r_W, &[U], &[W]).unwrap();
+    assert!(shape.is_sat_accumulated(&ck, &U_folded, &W_folded).is_err());
+  }
+
+  #[test]
+  fn test_tiny_r1cs_protogalaxy() {
+    for k in [1, 2, 4] {
+      test_tiny_r1cs_protogalaxy_with::<Bn256EngineIPA>(k);
+      test_tiny_r1cs_protogalaxy_with::<Bn256EngineKZG>(k);
+      test_tiny_r1cs_protogalaxy_with::<GrumpkinEngine>(k);
+    }
+  }
+}
@@ -29,7 +29,9 @@ use ref_cast::RefCast as _;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
+  abi::{self, AbiEncode, Word},
   errors::NovaError,
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
   provider::{
     kzg_commitment::{KZGCommitmentEngine, KZGProverKey, KZGVerifierKey, UniversalKZGParam},
     pedersen::Commitment,
@@ -58,6 +60,57 @@ pub struct EvaluationArgument<E: Engine> {
   C_H:   E::G1Affine,
 }
 
+impl<E: Engine> AbiEncode for EvaluationArgument<E>
+where E::G1: DlogGroup
+{
+  /// Words: comms (points), number of evaluation vectors, evals (fields for
+  /// each vector), R_x (fields), C_Q, C_H (points)
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    abi::encode_affine_points(out, &self.comms);
+    out.push(abi::uint(self.evals.len() as u64));
+    self.evals.iter().for_each(|evals| abi::encode_fields(out, evals));
+    abi::encode_fields(out, &self.R_x);
+    abi::encode_point(out, &self.C_Q.to_curve());
+    abi::encode_point(out, &self.C_H.to_curve());
+    Ok(())
+  }
+}
+
+impl<E: Engine> FastSerde for EvaluationArgument<E> {
+  /// Sections:
+  ///   1: comms (points)
+  ///   2: evals (list of scalars)
+  ///   3: R_x (scalars)
+  ///   4: C_Q, C_H (points)
+  fn to_bytes(&self) -> Vec<u8> {
+    let evals = self.evals.iter().map(|e| fast_serde::scalars_to_bytes(e)).collect::<Vec<_>>();
+
+    let mut out = SectionWriter::new(SerdeByteTypes::HyperKZGEvaluationArgument);
+    out.section(1, fast_serde::points_to_bytes(&self.comms));
+    out.section(2, fast_serde::list_to_bytes(&evals));
+    out.section(3, fast_serde::scalars_to_bytes(&self.R_x));
+    out.section(4, fast_serde::points_to_bytes(&[self.C_Q, self.C_H]));
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::HyperKZGEvaluationArgument)?;
+
+    let comms = fast_serde::points_from_bytes(sections.section(1)?)?;
+    let evals = fast_serde::list_from_bytes(sections.section(2)?)?
+      .into_iter()
+      .map(fast_serde::scalars_from_bytes)
+      .collect::<Result<Vec<_>, _>>()?;
+    let R_x = fast_serde::scalars_from_bytes(sections.section(3)?)?;
+    let [C_Q, C_H]: [E::G1Affine; 2] = fast_serde::points_from_bytes(sections.section(4)?)?
+      .try_into()
+      .map_err(|_| SerdeByteError::InvalidSectionSize)?;
+
+    sections.finish()?;
+    Ok(Self { comms, evals, R_x, C_Q, C_H })
+  }
+}
+
 /// Provides an implementation of a polynomial evaluation engine using KZG
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EvaluationEngine<E, NE> {
@@ -823,6 +876,12 @@ mod tests {
       .unwrap();
     expect!["432"].assert_eq(&proof_bytes.len().to_string());
 
+    // the native encoding must round-trip to a proof that still verifies
+    let proof = EvaluationArgument::<E>::from_bytes(&proof.to_bytes()).unwrap();
+    let mut verifier_transcript = Keccak256Transcript::<NE>::new(b"TestEval");
+    EvaluationEngine::<E, NE>::verify(&vk, &mut verifier_transcript, &C, &point, &eval, &proof)
+      .unwrap();
+
     // Change the proof and expect verification to fail
     let mut bad_proof = proof.clone();
     bad_proof.comms[0] = (bad_proof.comms[0] + bad_proof.comms[0] * Fr::from(123)).to_affine();
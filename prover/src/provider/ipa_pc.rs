@@ -8,8 +8,10 @@ use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+  abi::{self, AbiEncode, Word},
   digest::SimpleDigestible,
   errors::{NovaError, PCSError},
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
   provider::{pedersen::CommitmentKeyExtTrait, traits::DlogGroup, util::field::batch_invert},
   spartan::polys::eq::EqPolynomial,
   traits::{
@@ -27,6 +29,27 @@ pub struct ProverKey<E: Engine> {
   pub ck_s: CommitmentKey<E>,
 }
 
+impl<E: Engine> FastSerde for ProverKey<E>
+where CommitmentKey<E>: FastSerde
+{
+  /// Sections:
+  ///   1: ck_s (FastSerde)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::IPAProverKey);
+    out.section(1, self.ck_s.to_bytes());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::IPAProverKey)?;
+
+    let ck_s = <CommitmentKey<E> as FastSerde>::from_bytes(sections.section(1)?)?;
+
+    sections.finish()?;
+    Ok(Self { ck_s })
+  }
+}
+
 /// Provides an implementation of the verifier key
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -143,6 +166,41 @@ pub struct InnerProductArgument<E: Engine> {
   pub(in crate::provider) a_hat: E::Scalar,
 }
 
+impl<E: Engine> AbiEncode for InnerProductArgument<E> {
+  /// Words: L_vec (points), R_vec (points), a_hat (field)
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    abi::encode_compressed_commitments::<E>(out, &self.L_vec)?;
+    abi::encode_compressed_commitments::<E>(out, &self.R_vec)?;
+    out.push(abi::field(&self.a_hat));
+    Ok(())
+  }
+}
+
+impl<E: Engine> FastSerde for InnerProductArgument<E> {
+  /// Sections:
+  ///   1: L_vec (compressed commitments)
+  ///   2: R_vec (compressed commitments)
+  ///   3: a_hat (scalar)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::IPAEvaluationArgument);
+    out.section(1, fast_serde::compressed_commitments_to_bytes::<E>(&self.L_vec));
+    out.section(2, fast_serde::compressed_commitments_to_bytes::<E>(&self.R_vec));
+    out.section(3, fast_serde::scalars_to_bytes(&[self.a_hat]));
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::IPAEvaluationArgument)?;
+
+    let L_vec = fast_serde::compressed_commitments_from_bytes::<E>(sections.section(1)?)?;
+    let R_vec = fast_serde::compressed_commitments_from_bytes::<E>(sections.section(2)?)?;
+    let a_hat = fast_serde::scalar_from_bytes(sections.section(3)?)?;
+
+    sections.finish()?;
+    Ok(Self { L_vec, R_vec, a_hat })
+  }
+}
+
 impl<E> InnerProductArgument<E>
 where
   E: Engine,
@@ -1,19 +1,21 @@
 //! Commitment engine for KZG commitments
 
-use std::{io::Cursor, marker::PhantomData, sync::Arc};
+use std::{marker::PhantomData, sync::Arc};
 
 use ff::{Field, PrimeField, PrimeFieldBits};
 use group::{prime::PrimeCurveAffine, Curve, Group as _};
 use halo2curves::serde::SerdeObject;
-use pairing::Engine;
-use rand::rngs::StdRng;
+use pairing::{Engine, MillerLoopResult, MultiMillerLoop};
+use rand::rngs::{OsRng, StdRng};
 use rand_core::{CryptoRng, RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
 
 use crate::{
   digest::SimpleDigestible,
   fast_serde,
-  fast_serde::{FastSerde, SerdeByteError, SerdeByteTypes},
+  fast_serde::{
+    mapped::Points, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes,
+  },
   provider::{pedersen::Commitment, traits::DlogGroup, util::fb_msm},
   traits::{
     commitment::{CommitmentEngineTrait, Len},
@@ -21,6 +23,8 @@ use crate::{
   },
 };
 
+pub mod ceremony;
+
 /// `UniversalParams` are the universal parameters for the KZG10 scheme.
 #[derive(Debug, Clone, Eq, Serialize, Deserialize)]
 #[serde(bound(
@@ -29,8 +33,8 @@ use crate::{
 ))]
 pub struct UniversalKZGParam<E: Engine> {
   /// Group elements of the form `{ β^i G }`, where `i` ranges from 0 to
-  /// `degree`.
-  pub powers_of_g: Vec<E::G1Affine>,
+  /// `degree`. These may be borrowed from a memory-mapped setup file.
+  pub powers_of_g: Points<E::G1Affine>,
   /// Group elements of the form `{ β^i H }`, where `i` ranges from 0 to
   /// `degree`.
   pub powers_of_h: Vec<E::G2Affine>,
@@ -130,59 +134,70 @@ where
   E::G1Affine: SerdeObject,
   E::G2Affine: SerdeObject,
 {
-  /// Byte format:
-  ///
-  /// [0..4]   - Magic number (4 bytes)
-  /// [4]      - Serde type: UniversalKZGParam (u8)
-  /// [5]      - Number of sections (u8 = 2)
-  /// [6]      - Section 1 type: powers_of_g (u8)
-  /// [7..11]  - Section 1 size (u32)
-  /// [11..]   - Section 1 data
-  /// [...+1]  - Section 2 type: powers_of_h (u8)
-  /// [...+5]  - Section 2 size (u32)
-  /// [...end] - Section 2 data
+  /// Sections:
+  ///   1: powers_of_g (raw points)
+  ///   2: powers_of_h (raw points)
   fn to_bytes(&self) -> Vec<u8> {
-    let mut out = Vec::new();
-
-    out.extend_from_slice(&fast_serde::MAGIC_NUMBER);
-    out.push(fast_serde::SerdeByteTypes::UniversalKZGParam as u8);
-    out.push(2); // num_sections
-
-    Self::write_section_bytes(
-      &mut out,
-      1,
-      &self.powers_of_g.iter().flat_map(|p| p.to_raw_bytes()).collect::<Vec<u8>>(),
-    );
-
-    Self::write_section_bytes(
-      &mut out,
-      2,
-      &self.powers_of_h.iter().flat_map(|p| p.to_raw_bytes()).collect::<Vec<u8>>(),
-    );
-
-    out
+    let mut out = SectionWriter::new(SerdeByteTypes::UniversalKZGParam);
+    out.section(1, self.powers_of_g.iter().flat_map(|p| p.to_raw_bytes()).collect::<Vec<u8>>());
+    out.section(2, self.powers_of_h.iter().flat_map(|p| p.to_raw_bytes()).collect::<Vec<u8>>());
+    out.finish()
   }
 
   fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
-    let mut cursor = Cursor::new(bytes);
-
-    Self::validate_header(&mut cursor, SerdeByteTypes::UniversalKZGParam, 2)?;
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::UniversalKZGParam)?;
 
     // Read sections of points
-    let powers_of_g = Self::read_section_bytes(&mut cursor, 1)?
+    let powers_of_g = sections
+      .section(1)?
       .chunks(E::G1Affine::identity().to_raw_bytes().len())
       .map(|bytes| E::G1Affine::from_raw_bytes(bytes).ok_or(SerdeByteError::G1DecodeError))
-      .collect::<Result<Vec<_>, _>>()?;
+      .collect::<Result<Points<_>, _>>()?;
 
-    let powers_of_h = Self::read_section_bytes(&mut cursor, 2)?
+    let powers_of_h = sections
+      .section(2)?
       .chunks(E::G2Affine::identity().to_raw_bytes().len())
       .map(|bytes| E::G2Affine::from_raw_bytes(bytes).ok_or(SerdeByteError::G2DecodeError))
       .collect::<Result<Vec<_>, _>>()?;
 
+    sections.finish()?;
     Ok(Self { powers_of_g, powers_of_h })
   }
 }
 
+impl<E: Engine> FastSerde for KZGProverKey<E>
+where
+  E::G1Affine: SerdeObject,
+  E::G2Affine: SerdeObject,
+{
+  /// Sections:
+  ///   1: uv_params (FastSerde)
+  ///   2: offset (u64)
+  ///   3: supported_size (u64)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::KZGProverKey);
+    out.section(1, self.uv_params.to_bytes());
+    out.section(2, (self.offset as u64).to_le_bytes());
+    out.section(3, (self.supported_size as u64).to_le_bytes());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::KZGProverKey)?;
+
+    let uv_params = Arc::new(UniversalKZGParam::from_bytes(sections.section(1)?)?);
+    let offset = fast_serde::usize_from_bytes(sections.section(2)?)?;
+    let supported_size = fast_serde::usize_from_bytes(sections.section(3)?)?;
+    sections.finish()?;
+
+    if uv_params.max_degree() < offset + supported_size {
+      return Err(SerdeByteError::InvalidSectionSize);
+    }
+
+    Ok(Self { uv_params, offset, supported_size })
+  }
+}
+
 impl<E: Engine> UniversalKZGParam<E>
 where E::Fr: PrimeFieldBits
 {
@@ -224,10 +239,59 @@ where E::Fr: PrimeFieldBits
       || E::G2::batch_normalize(&powers_of_h_projective, &mut powers_of_h),
     );
 
-    Self { powers_of_g, powers_of_h }
+    Self { powers_of_g: powers_of_g.into(), powers_of_h }
+  }
+}
+
+impl<E: MultiMillerLoop> UniversalKZGParam<E>
+where E::G1: DlogGroup<ScalarExt = E::Fr, AffineExt = E::G1Affine>
+{
+  /// Checks that the parameters are of the form `{ τ^i G }`, `{ H, τ H }` for
+  /// the standard generators `G`, `H` and a non-zero `τ`.
+  ///
+  /// Only the first two powers of `H` are checked, as they are the only ones
+  /// [`UniversalKZGParam::trim`] uses. Instead of one pairing per power, this
+  /// checks `e(Σ ρ^i τ^{i+1} G, H) = e(Σ ρ^i τ^i G, τ H)` for a random `ρ`,
+  /// which fails with overwhelming probability unless every pair of
+  /// successive powers is consistent with `τ H`.
+  pub fn check_powers(&self) -> bool {
+    let (Some(&g), [h, tau_h, ..]) = (self.powers_of_g.first(), self.powers_of_h.as_slice()) else {
+      return false;
+    };
+    if g != E::G1Affine::generator() || *h != E::G2Affine::generator() {
+      return false;
+    }
+    if bool::from(tau_h.is_identity()) || !g2_in_subgroup::<E>(tau_h) {
+      return false;
+    }
+
+    let rho = E::Fr::random(OsRng);
+    let scalars = std::iter::successors(Some(E::Fr::ONE), |acc| Some(*acc * rho))
+      .take(self.powers_of_g.len() - 1)
+      .collect::<Vec<_>>();
+    let (shifted, unshifted) = rayon::join(
+      || E::G1::vartime_multiscalar_mul(&scalars, &self.powers_of_g[1..]),
+      || E::G1::vartime_multiscalar_mul(&scalars, &self.powers_of_g[..scalars.len()]),
+    );
+
+    pairing_check::<E>(&[(shifted.to_affine(), *h), ((-unshifted).to_affine(), *tau_h)])
   }
 }
 
+/// Returns whether `p` lies in the prime order subgroup of G2, which has a
+/// cofactor: a point of order `r` satisfies `(r - 1) p = -p`
+fn g2_in_subgroup<E: Engine>(p: &E::G2Affine) -> bool {
+  let p = p.to_curve();
+  p * -E::Fr::ONE == -p
+}
+
+/// Returns whether the product of the pairings of `pairs` is the identity
+fn pairing_check<E: MultiMillerLoop>(pairs: &[(E::G1Affine, E::G2Affine)]) -> bool {
+  let prepared = pairs.iter().map(|(g1, g2)| (g1, E::G2Prepared::from(*g2))).collect::<Vec<_>>();
+  let refs = prepared.iter().map(|(g1, g2)| (*g1, g2)).collect::<Vec<_>>();
+  E::multi_miller_loop(&refs).final_exponentiation().is_identity().into()
+}
+
 /// Commitments
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Serialize, Deserialize)]
 #[serde(bound(
@@ -271,8 +335,8 @@ where
   type CommitmentKey = UniversalKZGParam<E>;
 
   fn setup(label: &'static [u8], n: usize) -> Self::CommitmentKey {
-    // TODO: this is just for testing, replace by grabbing from a real setup for
-    // production
+    // this is just for testing, production setups load a ceremony SRS with
+    // `provider::srs` and pass it to `PublicParams::setup_with_commitment_key`
     let mut bytes = [0u8; 32];
     let len = label.len().min(32);
     bytes[..len].copy_from_slice(&label[..len]);
@@ -284,6 +348,17 @@ where
     assert!(ck.length() >= v.len());
     Commitment { comm: E::G1::vartime_multiscalar_mul(v, &ck.powers_of_g[..v.len()]) }
   }
+
+  fn commit_at(
+    ck: &Self::CommitmentKey,
+    v: &[<E::G1 as Group>::Scalar],
+    offset: usize,
+  ) -> Self::Commitment {
+    assert!(ck.length() >= offset + v.len());
+    Commitment {
+      comm: E::G1::vartime_multiscalar_mul(v, &ck.powers_of_g[offset..offset + v.len()]),
+    }
+  }
 }
 
 impl<E: Engine, NE: NovaEngine<GE = E::G1, Scalar = E::Fr>> From<Commitment<NE>>
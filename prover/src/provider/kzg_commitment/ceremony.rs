@@ -0,0 +1,367 @@
+//! A multi-party powers-of-tau ceremony for [`UniversalKZGParam`].
+//!
+//! A ceremony starts from existing parameters, for example the trivial ones
+//! returned by [`Transcript::start`] or an SRS imported with
+//! [`crate::provider::srs`]. Each participant loads the transcript file, calls
+//! [`Transcript::contribute`] and stores the transcript for the next one. A
+//! contribution multiplies the secret `τ` by a fresh secret `s`, which the
+//! participant discards afterwards, so the final `τ` is unknown as long as a
+//! single participant was honest.
+//!
+//! Every contribution is recorded with a [`ContributionProof`], holding the
+//! new `τ G`, `s G`, `s H` and a Schnorr proof of knowledge of `s`.
+//! [`Transcript::verify`] checks the chain of contributions with pairings and
+//! that the current parameters are successive powers of the final `τ`, so
+//! anyone can audit a transcript offline before using its parameters.
+
+use std::path::Path;
+
+use ff::{Field, FromUniformBytes, PrimeField};
+use group::{prime::PrimeCurveAffine, Curve};
+use halo2curves::serde::SerdeObject;
+use pairing::{Engine, MultiMillerLoop};
+use rand_core::{CryptoRng, RngCore};
+use rayon::prelude::*;
+use sha3::{Digest, Sha3_512};
+use thiserror::Error;
+
+use super::{g2_in_subgroup, pairing_check, UniversalKZGParam};
+use crate::{
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
+  provider::traits::DlogGroup,
+};
+
+/// Domain separator of the proof of knowledge challenges
+const CHALLENGE_LABEL: &[u8] = b"kzg_ceremony_contribution";
+
+/// Errors returned by a ceremony
+#[derive(Debug, Error)]
+pub enum CeremonyError {
+  #[error("the parameters are not successive powers of a secret")]
+  InvalidParams,
+  #[error("contribution {0} is invalid")]
+  InvalidContribution(usize),
+  #[error("the parameters do not match the last contribution")]
+  ParamsMismatch,
+  #[error(transparent)]
+  SerdeByteError(#[from] SerdeByteError),
+  #[error(transparent)]
+  IoError(#[from] std::io::Error),
+}
+
+/// A record of one contribution to a ceremony
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContributionProof<E: Engine> {
+  /// `τ G` after the contribution
+  pub tau_g:          E::G1Affine,
+  /// `s G` for the contributed secret `s`
+  pub s_g:            E::G1Affine,
+  /// `s H` for the contributed secret `s`
+  pub s_h:            E::G2Affine,
+  /// Commitment `r G` of the proof of knowledge of `s`
+  pub pok_commitment: E::G1Affine,
+  /// Response `r + c s` of the proof of knowledge of `s`
+  pub pok_response:   E::Fr,
+}
+
+impl<E> ContributionProof<E>
+where
+  E: MultiMillerLoop,
+  E::G1: DlogGroup<ScalarExt = E::Fr, AffineExt = E::G1Affine>,
+  E::G1Affine: SerdeObject,
+  E::G2Affine: SerdeObject,
+  E::Fr: FromUniformBytes<64>,
+{
+  /// Computes the Fiat-Shamir challenge of the proof of knowledge, binding it
+  /// to the position of the contribution and the `τ G` it was applied to
+  fn challenge(&self, index: usize, previous_tau_g: &E::G1Affine) -> E::Fr {
+    let mut hasher = Sha3_512::new();
+    hasher.update(CHALLENGE_LABEL);
+    hasher.update((index as u64).to_le_bytes());
+    hasher.update(previous_tau_g.to_raw_bytes());
+    hasher.update(self.tau_g.to_raw_bytes());
+    hasher.update(self.s_g.to_raw_bytes());
+    hasher.update(self.s_h.to_raw_bytes());
+    hasher.update(self.pok_commitment.to_raw_bytes());
+    let mut bytes = [0u8; 64];
+    bytes.copy_from_slice(&hasher.finalize());
+    E::Fr::from_uniform_bytes(&bytes)
+  }
+
+  /// Checks that the contribution at position `index` multiplied the secret
+  /// behind `previous_tau_g` by a secret its contributor knew
+  ///
+  /// With a random `ρ`, a single pairing check covers both
+  /// `e(s G, H) = e(G, s H)` and `e(τ' G, H) = e(τ G, s H)`.
+  fn verify(&self, index: usize, previous_tau_g: &E::G1Affine) -> bool {
+    if bool::from(self.s_g.is_identity()) || !g2_in_subgroup::<E>(&self.s_h) {
+      return false;
+    }
+
+    let c = self.challenge(index, previous_tau_g);
+    let g = E::G1Affine::generator();
+    if g * self.pok_response != self.pok_commitment.to_curve() + self.s_g * c {
+      return false;
+    }
+
+    let rho = E::Fr::random(rand::rngs::OsRng);
+    let lhs = (self.s_g.to_curve() + self.tau_g * rho).to_affine();
+    let rhs = -(g.to_curve() + *previous_tau_g * rho);
+    pairing_check::<E>(&[(lhs, E::G2Affine::generator()), (rhs.to_affine(), self.s_h)])
+  }
+
+  /// Encodes the proof as raw points followed by the response
+  fn to_raw_bytes(&self) -> Vec<u8> {
+    [
+      self.tau_g.to_raw_bytes(),
+      self.s_g.to_raw_bytes(),
+      self.s_h.to_raw_bytes(),
+      self.pok_commitment.to_raw_bytes(),
+      self.pok_response.to_repr().as_ref().to_vec(),
+    ]
+    .concat()
+  }
+
+  /// Decodes a proof written by [`ContributionProof::to_raw_bytes`]
+  fn from_raw_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let g1_size = E::G1Affine::identity().to_raw_bytes().len();
+    let g2_size = E::G2Affine::identity().to_raw_bytes().len();
+    if bytes.len() != 3 * g1_size + g2_size + E::Fr::ZERO.to_repr().as_ref().len() {
+      return Err(SerdeByteError::InvalidSectionSize);
+    }
+
+    let (tau_g, rest) = bytes.split_at(g1_size);
+    let (s_g, rest) = rest.split_at(g1_size);
+    let (s_h, rest) = rest.split_at(g2_size);
+    let (pok_commitment, pok_response) = rest.split_at(g1_size);
+    let g1 = |bytes| E::G1Affine::from_raw_bytes(bytes).ok_or(SerdeByteError::G1DecodeError);
+
+    Ok(Self {
+      tau_g:          g1(tau_g)?,
+      s_g:            g1(s_g)?,
+      s_h:            E::G2Affine::from_raw_bytes(s_h).ok_or(SerdeByteError::G2DecodeError)?,
+      pok_commitment: g1(pok_commitment)?,
+      pok_response:   fast_serde::scalar_from_bytes(pok_response)?,
+    })
+  }
+}
+
+/// The state of a ceremony: where it started, every contribution so far and
+/// the resulting parameters
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transcript<E: Engine> {
+  /// `τ G` of the parameters the ceremony started from
+  initial_tau_g: E::G1Affine,
+  /// The contributions, in order
+  contributions: Vec<ContributionProof<E>>,
+  /// The parameters after the last contribution
+  params:        UniversalKZGParam<E>,
+}
+
+impl<E> Transcript<E>
+where
+  E: MultiMillerLoop,
+  E::G1: DlogGroup<ScalarExt = E::Fr, AffineExt = E::G1Affine>,
+  E::G1Affine: SerdeObject,
+  E::G2Affine: SerdeObject,
+  E::Fr: FromUniformBytes<64>,
+{
+  /// Starts a ceremony from the trivial parameters for `max_degree`, whose
+  /// secret is `τ = 1`
+  ///
+  /// These parameters are only useful once at least one honest participant
+  /// has contributed.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `max_degree` is zero.
+  pub fn start(max_degree: usize) -> Self {
+    let params = UniversalKZGParam {
+      powers_of_g: vec![E::G1Affine::generator(); max_degree + 1].into(),
+      powers_of_h: vec![E::G2Affine::generator(); 2],
+    };
+    Self::new(params).expect("trivial parameters are valid")
+  }
+
+  /// Starts a ceremony from existing parameters of degree at least one
+  ///
+  /// Only the first two powers of `H` are kept, as they are the only ones
+  /// [`UniversalKZGParam::trim`] uses.
+  pub fn new(mut params: UniversalKZGParam<E>) -> Result<Self, CeremonyError> {
+    if params.powers_of_g.len() < 2 || !params.check_powers() {
+      return Err(CeremonyError::InvalidParams);
+    }
+    params.powers_of_h.truncate(2);
+    Ok(Self { initial_tau_g: params.powers_of_g[1], contributions: Vec::new(), params })
+  }
+
+  /// Returns `τ G` of the parameters the ceremony started from
+  pub fn initial_tau_g(&self) -> &E::G1Affine { &self.initial_tau_g }
+
+  /// Returns the contributions so far
+  pub fn contributions(&self) -> &[ContributionProof<E>] { &self.contributions }
+
+  /// Returns the current parameters
+  pub fn params(&self) -> &UniversalKZGParam<E> { &self.params }
+
+  /// Returns the current parameters, consuming the transcript
+  pub fn into_params(self) -> UniversalKZGParam<E> { self.params }
+
+  /// Contributes a secret sampled from `rng` to the ceremony
+  ///
+  /// Every power `τ^i` is multiplied by `s^i`, and the contribution is
+  /// recorded with a proof of knowledge of `s`. The secret is dropped before
+  /// this returns.
+  pub fn contribute<R: RngCore + CryptoRng>(&mut self, mut rng: &mut R) -> &ContributionProof<E> {
+    let s = loop {
+      let s = E::Fr::random(&mut rng);
+      if !bool::from(s.is_zero()) {
+        break s;
+      }
+    };
+
+    let powers_of_s = std::iter::successors(Some(E::Fr::ONE), |acc| Some(*acc * s))
+      .take(self.params.powers_of_g.len())
+      .collect::<Vec<_>>();
+    let powers_of_g_projective = self
+      .params
+      .powers_of_g
+      .par_iter()
+      .zip(powers_of_s.par_iter())
+      .map(|(g, s)| *g * *s)
+      .collect::<Vec<E::G1>>();
+    let mut powers_of_g = vec![E::G1Affine::identity(); powers_of_g_projective.len()];
+    E::G1::batch_normalize(&powers_of_g_projective, &mut powers_of_g);
+    let powers_of_h =
+      vec![self.params.powers_of_h[0], (self.params.powers_of_h[1] * s).to_affine()];
+
+    let r = E::Fr::random(&mut rng);
+    let mut proof = ContributionProof {
+      tau_g:          powers_of_g[1],
+      s_g:            (E::G1Affine::generator() * s).to_affine(),
+      s_h:            (E::G2Affine::generator() * s).to_affine(),
+      pok_commitment: (E::G1Affine::generator() * r).to_affine(),
+      pok_response:   E::Fr::ZERO,
+    };
+    let c = proof.challenge(self.contributions.len(), &self.params.powers_of_g[1]);
+    proof.pok_response = r + c * s;
+
+    self.params = UniversalKZGParam { powers_of_g: powers_of_g.into(), powers_of_h };
+    self.contributions.push(proof);
+    self.contributions.last().unwrap()
+  }
+
+  /// Verifies the chain of contributions from the initial `τ G` and that the
+  /// current parameters are successive powers of the resulting secret
+  pub fn verify(&self) -> Result<(), CeremonyError> {
+    let mut tau_g = self.initial_tau_g;
+    for (index, proof) in self.contributions.iter().enumerate() {
+      if !proof.verify(index, &tau_g) {
+        return Err(CeremonyError::InvalidContribution(index));
+      }
+      tau_g = proof.tau_g;
+    }
+
+    if self.params.powers_of_g.get(1) != Some(&tau_g) {
+      return Err(CeremonyError::ParamsMismatch);
+    }
+    if !self.params.check_powers() {
+      return Err(CeremonyError::InvalidParams);
+    }
+    Ok(())
+  }
+
+  /// Stores the transcript to a file
+  pub fn store_file(&self, path: &Path) -> Result<(), CeremonyError> {
+    Ok(std::fs::write(path, self.to_bytes())?)
+  }
+
+  /// Loads a transcript from a file
+  ///
+  /// The transcript is not verified, see [`Transcript::verify`].
+  pub fn load_file(path: &Path) -> Result<Self, CeremonyError> {
+    Ok(Self::from_bytes(&std::fs::read(path)?)?)
+  }
+}
+
+impl<E> FastSerde for Transcript<E>
+where
+  E: MultiMillerLoop,
+  E::G1: DlogGroup<ScalarExt = E::Fr, AffineExt = E::G1Affine>,
+  E::G1Affine: SerdeObject,
+  E::G2Affine: SerdeObject,
+  E::Fr: FromUniformBytes<64>,
+{
+  /// Sections:
+  ///   1: initial_tau_g (raw point)
+  ///   2: contributions (list of raw proofs)
+  ///   3: params (FastSerde)
+  fn to_bytes(&self) -> Vec<u8> {
+    let contributions =
+      self.contributions.iter().map(ContributionProof::to_raw_bytes).collect::<Vec<_>>();
+
+    let mut out = SectionWriter::new(SerdeByteTypes::CeremonyTranscript);
+    out.section(1, self.initial_tau_g.to_raw_bytes());
+    out.section(2, fast_serde::list_to_bytes(&contributions));
+    out.section(3, self.params.to_bytes());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::CeremonyTranscript)?;
+
+    let initial_tau_g =
+      E::G1Affine::from_raw_bytes(sections.section(1)?).ok_or(SerdeByteError::G1DecodeError)?;
+    let contributions = fast_serde::list_from_bytes(sections.section(2)?)?
+      .into_iter()
+      .map(ContributionProof::from_raw_bytes)
+      .collect::<Result<Vec<_>, _>>()?;
+    let params = UniversalKZGParam::from_bytes(sections.section(3)?)?;
+    sections.finish()?;
+
+    Ok(Self { initial_tau_g, contributions, params })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2curves::bn256::{Bn256, G1Affine};
+  use rand_chacha::ChaCha20Rng;
+  use rand_core::SeedableRng;
+
+  use super::*;
+
+  #[test]
+  fn test_ceremony() {
+    let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+    let mut transcript = Transcript::<Bn256>::start(16);
+    transcript.verify().unwrap();
+    transcript.contribute(&mut rng);
+    transcript.contribute(&mut rng);
+    transcript.verify().unwrap();
+    assert_ne!(transcript.params().powers_of_g[1], G1Affine::generator());
+
+    let path = std::env::temp_dir().join(format!("transcript-{}.bytes", std::process::id()));
+    transcript.store_file(&path).unwrap();
+    let mut transcript = Transcript::<Bn256>::load_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    transcript.verify().unwrap();
+
+    // a participant can continue from a verified transcript
+    transcript.contribute(&mut rng);
+    transcript.verify().unwrap();
+
+    // the parameters and the contributions must agree
+    let mut reordered = transcript.clone();
+    reordered.contributions.swap(0, 1);
+    assert!(matches!(reordered.verify(), Err(CeremonyError::InvalidContribution(0))));
+
+    let mut forged = transcript.clone();
+    forged.contributions[2].pok_response += <Bn256 as Engine>::Fr::ONE;
+    assert!(matches!(forged.verify(), Err(CeremonyError::InvalidContribution(2))));
+
+    let mut replaced = transcript.clone();
+    replaced.params = Transcript::<Bn256>::start(16).into_params();
+    assert!(matches!(replaced.verify(), Err(CeremonyError::ParamsMismatch)));
+  }
+}
@@ -4,29 +4,38 @@
 // public modules to be used as an evaluation engine with Spartan
 pub mod hyperkzg;
 pub mod ipa_pc;
+// a non-hiding variant of kzg, with a ceremony for its SRS
+pub mod kzg_commitment;
+pub mod non_hiding_zeromorph;
+// loaders for production KZG SRS
+pub mod srs;
 
 // crate-public modules, made crate-public mostly for tests
 pub(crate) mod bn256_grumpkin;
+pub(crate) mod pasta;
 mod pedersen;
 pub(crate) mod poseidon;
+pub(crate) mod secp_secq;
 pub(crate) mod traits;
-// a non-hiding variant of kzg
-mod kzg_commitment;
 pub(crate) mod util;
 
 // crate-private modules
 mod keccak;
+mod poseidon_transcript;
 mod tests;
 
 use halo2curves::bn256::Bn256;
 
 use self::kzg_commitment::KZGCommitmentEngine;
+pub use self::poseidon_transcript::{PoseidonTranscript, PoseidonTranscriptCircuit};
 use crate::{
   provider::{
     bn256_grumpkin::{bn256, grumpkin},
     keccak::Keccak256Transcript,
+    pasta::{pallas, vesta},
     pedersen::CommitmentEngine as PedersenCommitmentEngine,
     poseidon::{PoseidonRO, PoseidonROCircuit},
+    secp_secq::{secp256k1, secq256k1},
   },
   traits::{CurveCycleEquipped, Engine},
 };
@@ -62,7 +71,8 @@ impl Engine for GrumpkinEngine {
 }
 
 /// An implementation of the Nova `Engine` trait with BN254 curve and Zeromorph
-/// commitment scheme
+/// commitment scheme, to be used with
+/// [`non_hiding_zeromorph::ZMPCS`](crate::provider::non_hiding_zeromorph::ZMPCS)
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Bn256EngineZM;
 
@@ -89,6 +99,84 @@ impl Engine for Bn256EngineKZG {
   type TE = Keccak256Transcript<Self>;
 }
 
+/// An implementation of Nova traits with HyperKZG over the BN256 curve and a
+/// Poseidon transcript, for proofs that are verified inside a circuit
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Bn256EngineKZGPoseidon;
+
+impl Engine for Bn256EngineKZGPoseidon {
+  type Base = bn256::Base;
+  type CE = KZGCommitmentEngine<Bn256>;
+  type GE = bn256::Point;
+  type RO = PoseidonRO<Self::Base, Self::Scalar>;
+  type ROCircuit = PoseidonROCircuit<Self::Base>;
+  type Scalar = bn256::Scalar;
+  type TE = PoseidonTranscript<Self>;
+}
+
+/// An implementation of the Nova `Engine` trait with Pallas curve and Pedersen
+/// commitment scheme
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PallasEngine;
+
+/// An implementation of the Nova `Engine` trait with Vesta curve and Pedersen
+/// commitment scheme
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VestaEngine;
+
+impl Engine for PallasEngine {
+  type Base = pallas::Base;
+  type CE = PedersenCommitmentEngine<Self>;
+  type GE = pallas::Point;
+  type RO = PoseidonRO<Self::Base, Self::Scalar>;
+  type ROCircuit = PoseidonROCircuit<Self::Base>;
+  type Scalar = pallas::Scalar;
+  type TE = Keccak256Transcript<Self>;
+}
+
+impl Engine for VestaEngine {
+  type Base = vesta::Base;
+  type CE = PedersenCommitmentEngine<Self>;
+  type GE = vesta::Point;
+  type RO = PoseidonRO<Self::Base, Self::Scalar>;
+  type ROCircuit = PoseidonROCircuit<Self::Base>;
+  type Scalar = vesta::Scalar;
+  type TE = Keccak256Transcript<Self>;
+}
+
+/// An implementation of the Nova `Engine` trait with Secp256k1 curve and
+/// Pedersen commitment scheme
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Secp256k1Engine;
+
+/// An implementation of the Nova `Engine` trait with Secq256k1 curve and
+/// Pedersen commitment scheme. Its scalar field is the base field of
+/// secp256k1, so step circuits of a cycle with `Secq256k1Engine` as the
+/// primary can do secp256k1 point arithmetic (e.g. ECDSA verification)
+/// natively.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Secq256k1Engine;
+
+impl Engine for Secp256k1Engine {
+  type Base = secp256k1::Base;
+  type CE = PedersenCommitmentEngine<Self>;
+  type GE = secp256k1::Point;
+  type RO = PoseidonRO<Self::Base, Self::Scalar>;
+  type ROCircuit = PoseidonROCircuit<Self::Base>;
+  type Scalar = secp256k1::Scalar;
+  type TE = Keccak256Transcript<Self>;
+}
+
+impl Engine for Secq256k1Engine {
+  type Base = secq256k1::Base;
+  type CE = PedersenCommitmentEngine<Self>;
+  type GE = secq256k1::Point;
+  type RO = PoseidonRO<Self::Base, Self::Scalar>;
+  type ROCircuit = PoseidonROCircuit<Self::Base>;
+  type Scalar = secq256k1::Scalar;
+  type TE = Keccak256Transcript<Self>;
+}
+
 impl CurveCycleEquipped for Bn256EngineIPA {
   type Secondary = GrumpkinEngine;
 }
@@ -101,6 +189,26 @@ impl CurveCycleEquipped for Bn256EngineZM {
   type Secondary = GrumpkinEngine;
 }
 
+impl CurveCycleEquipped for Bn256EngineKZGPoseidon {
+  type Secondary = GrumpkinEngine;
+}
+
+impl CurveCycleEquipped for PallasEngine {
+  type Secondary = VestaEngine;
+}
+
+impl CurveCycleEquipped for VestaEngine {
+  type Secondary = PallasEngine;
+}
+
+impl CurveCycleEquipped for Secp256k1Engine {
+  type Secondary = Secq256k1Engine;
+}
+
+impl CurveCycleEquipped for Secq256k1Engine {
+  type Secondary = Secp256k1Engine;
+}
+
 #[cfg(test)]
 mod test {
   use std::io::Read;
@@ -114,6 +222,8 @@ mod test {
 
   use crate::provider::{
     bn256_grumpkin::{bn256, grumpkin},
+    pasta::{pallas, vesta},
+    secp_secq::{secp256k1, secq256k1},
     traits::DlogGroup,
     util::msm::cpu_best_msm,
   };
@@ -161,10 +271,24 @@ mod test {
   fn test_msm() {
     test_msm_with::<bn256::Scalar, bn256::Affine>();
     test_msm_with::<grumpkin::Scalar, grumpkin::Affine>();
+    test_msm_with::<pallas::Scalar, pallas::Affine>();
+    test_msm_with::<vesta::Scalar, vesta::Affine>();
+    test_msm_with::<secp256k1::Scalar, secp256k1::Affine>();
+    test_msm_with::<secq256k1::Scalar, secq256k1::Affine>();
   }
 
   #[test]
   fn test_bn256_from_label() {
     impl_cycle_pair_test!(bn256);
   }
+
+  #[test]
+  fn test_pallas_from_label() {
+    impl_cycle_pair_test!(pallas);
+  }
+
+  #[test]
+  fn test_secp256k1_from_label() {
+    impl_cycle_pair_test!(secp256k1);
+  }
 }
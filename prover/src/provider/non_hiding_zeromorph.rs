@@ -0,0 +1,475 @@
+//! This module implements Nova's evaluation engine using Zeromorph
+//! (`<https://eprint.iacr.org/2023/917>`), a reduction from multilinear to
+//! univariate KZG openings.
+//!
+//! A multilinear polynomial `f` in `n` variables, given by its `N = 2^n`
+//! evaluations, is committed to as the univariate polynomial
+//! `U_n(f)(X) = Σ_i f_i X^i`, which is exactly what [`KZGCommitmentEngine`]
+//! computes. `f(u) = v` holds iff there are multilinear quotients `q_k` in the
+//! first `k` variables with `f - v = Σ_k (X_k - u_k) q_k`. Under `U_n` this
+//! becomes the univariate identity
+//! `U_n(f) - v Φ_n(X) = Σ_k (X^{2^k} Φ_{n-k-1}(X^{2^{k+1}}) - u_k Φ_{n-k}(X^{2^k})) U_k(q_k)`,
+//! with `Φ_m(X) = Σ_{i < 2^m} X^i`, which the verifier checks at a random
+//! point with a single KZG opening.
+//!
+//! The identity is only sound if each `U_k(q_k)` has degree less than `2^k`.
+//! The prover shows this by committing to `Σ_k y^k X^{D - 2^k} U_k(q_k)` for
+//! a random `y`, where `D` is the number of powers of `τ` in G1: a commitment
+//! can only be formed for a polynomial of degree less than `D`. Unlike the
+//! paper, this needs no powers of `τ` in G2 beyond `τ H`, so it works with
+//! the same SRS as [`crate::provider::hyperkzg`].
+#![allow(non_snake_case)]
+use core::marker::PhantomData;
+use std::sync::Arc;
+
+use ff::{Field, PrimeField, PrimeFieldBits};
+use group::{prime::PrimeCurveAffine as _, Curve};
+use pairing::{Engine, MillerLoopResult, MultiMillerLoop};
+use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+  abi::{self, AbiEncode, Word},
+  digest::SimpleDigestible,
+  errors::{NovaError, PCSError},
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
+  provider::{
+    kzg_commitment::{KZGCommitmentEngine, KZGProverKey, KZGVerifierKey, UniversalKZGParam},
+    pedersen::Commitment,
+    traits::DlogGroup,
+  },
+  spartan::polys::univariate::UniPoly,
+  traits::{
+    commitment::{CommitmentEngineTrait, Len},
+    evaluation::EvaluationEngineTrait,
+    Engine as NovaEngine, Group, TranscriptEngineTrait, TranscriptReprTrait,
+  },
+};
+
+/// A Zeromorph evaluation argument
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+  serialize = "E::G1Affine: Serialize",
+  deserialize = "E::G1Affine: Deserialize<'de>"
+))]
+pub struct ZMProof<E: Engine> {
+  /// commitments to the quotients `U_k(q_k)`
+  c_quotients: Vec<E::G1Affine>,
+  /// commitment to the batched, lifted quotient `Σ_k y^k X^{D - 2^k} U_k(q_k)`
+  c_q_hat:     E::G1Affine,
+  /// KZG opening proof of the combined polynomial at `x`
+  pi:          E::G1Affine,
+}
+
+impl<E: Engine> AbiEncode for ZMProof<E>
+where E::G1: DlogGroup
+{
+  /// Words: c_quotients (points), c_q_hat, pi (points)
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    abi::encode_affine_points(out, &self.c_quotients);
+    abi::encode_point(out, &self.c_q_hat.to_curve());
+    abi::encode_point(out, &self.pi.to_curve());
+    Ok(())
+  }
+}
+
+impl<E: Engine> FastSerde for ZMProof<E> {
+  /// Sections:
+  ///   1: c_quotients (points)
+  ///   2: c_q_hat, pi (points)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::ZeromorphEvaluationArgument);
+    out.section(1, fast_serde::points_to_bytes(&self.c_quotients));
+    out.section(2, fast_serde::points_to_bytes(&[self.c_q_hat, self.pi]));
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::ZeromorphEvaluationArgument)?;
+
+    let c_quotients = fast_serde::points_from_bytes(sections.section(1)?)?;
+    let [c_q_hat, pi]: [E::G1Affine; 2] = fast_serde::points_from_bytes(sections.section(2)?)?
+      .try_into()
+      .map_err(|_| SerdeByteError::InvalidSectionSize)?;
+
+    sections.finish()?;
+    Ok(Self { c_quotients, c_q_hat, pi })
+  }
+}
+
+/// The verifier key of the Zeromorph evaluation engine
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+  serialize = "E::G1Affine: Serialize, E::G2Affine: Serialize",
+  deserialize = "E::G1Affine: Deserialize<'de>, E::G2Affine: Deserialize<'de>"
+))]
+pub struct ZMVerifierKey<E: Engine> {
+  /// the univariate KZG verifier key
+  vk:         KZGVerifierKey<E>,
+  /// the number `D` of powers of `τ` in G1, which bounds committed degrees
+  num_powers: usize,
+}
+
+impl<E: Engine> SimpleDigestible for ZMVerifierKey<E>
+where
+  E::G1Affine: Serialize,
+  E::G2Affine: Serialize,
+{
+}
+
+/// Provides an implementation of a polynomial evaluation engine using
+/// Zeromorph
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ZMPCS<E, NE> {
+  _p: PhantomData<(E, NE)>,
+}
+
+/// Computes the multilinear quotients `q_k` with `f - f(point) = Σ_k (X_k -
+/// u_k) q_k`, along with `f(point)`. `X_0` is the least significant variable
+/// of the evaluation index, i.e. `u_k = point[n - 1 - k]`, and `q_k` has `2^k`
+/// evaluations.
+fn quotients<F: PrimeField>(poly: &[F], point: &[F]) -> (Vec<Vec<F>>, F) {
+  assert_eq!(poly.len(), 1 << point.len());
+
+  let mut remainder = poly.to_vec();
+  let mut quotients = point
+    .iter()
+    .map(|u| {
+      let half = remainder.len() / 2;
+      let (lo, hi) = remainder.split_at_mut(half);
+      // f = lo + X (hi - lo) = (lo + u (hi - lo)) + (X - u) (hi - lo)
+      let quotient = lo
+        .par_iter_mut()
+        .zip(hi.par_iter())
+        .map(|(lo, hi)| {
+          let q = *hi - *lo;
+          *lo += q * u;
+          q
+        })
+        .collect::<Vec<_>>();
+      remainder.truncate(half);
+      quotient
+    })
+    .collect::<Vec<_>>();
+  quotients.reverse();
+
+  (quotients, remainder[0])
+}
+
+/// Evaluates `Φ_m(a) = Σ_{i < 2^m} a^i = Π_{j < m} (1 + a^{2^j})`
+fn phi<F: Field>(m: usize, a: F) -> F {
+  let mut res = F::ONE;
+  let mut pow = a;
+  for _ in 0..m {
+    res *= F::ONE + pow;
+    pow = pow.square();
+  }
+  res
+}
+
+/// Returns the scalars by which the evaluation (times the generator) and
+/// each quotient commitment enter the combined polynomial
+/// `S(X) + z (U_n(f) - v Φ_n(x) - Σ_k c_k U_k(q_k)) - Σ_k y^k x^{D - 2^k}
+/// U_k(q_k)`, which vanishes at `x` for an honest prover. The combined
+/// polynomial also has the lifted quotient `S` with scalar one and `U_n(f)`
+/// with scalar `z`.
+fn eval_and_quotient_scalars<F: PrimeField>(
+  y: F,
+  x: F,
+  z: F,
+  point: &[F],
+  num_powers: usize,
+) -> (F, Vec<F>) {
+  let n = point.len();
+
+  // x^{2^k}, for k = 0..n
+  let x_pows = std::iter::successors(Some(x), |p| Some(p.square())).take(n + 1).collect::<Vec<_>>();
+  let y_pows = std::iter::successors(Some(F::ONE), |p| Some(*p * y)).take(n).collect::<Vec<_>>();
+
+  let eval_scalar = -z * phi(n, x);
+  let q_scalars = (0..n)
+    .map(|k| {
+      let u_k = point[n - 1 - k];
+      let c_k = x_pows[k] * phi(n - k - 1, x_pows[k + 1]) - u_k * phi(n - k, x_pows[k]);
+      let degree_check = y_pows[k] * x.pow_vartime([(num_powers - (1 << k)) as u64]);
+      -(degree_check + z * c_k)
+    })
+    .collect();
+
+  (eval_scalar, q_scalars)
+}
+
+impl<E, NE> ZMPCS<E, NE>
+where
+  E: Engine,
+  NE: NovaEngine<GE = E::G1, Scalar = E::Fr, CE = KZGCommitmentEngine<E>>,
+  E::G1: DlogGroup<ScalarExt = E::Fr, AffineExt = E::G1Affine>,
+  E::Fr: PrimeFieldBits + TranscriptReprTrait<E::G1>,
+  E::G1Affine: TranscriptReprTrait<E::G1>,
+  <E::G1 as Group>::Base: TranscriptReprTrait<E::G1>,
+{
+  fn compute_challenges(
+    c_quotients: &[E::G1Affine],
+    c_q_hat: &E::G1Affine,
+    transcript: &mut impl TranscriptEngineTrait<NE>,
+  ) -> Result<(E::Fr, E::Fr, E::Fr), NovaError> {
+    transcript.absorb(b"quo", &c_quotients);
+    let y = transcript.squeeze(b"y")?;
+    transcript.absorb(b"q_hat", c_q_hat);
+    let x = transcript.squeeze(b"x")?;
+    let z = transcript.squeeze(b"z")?;
+    Ok((y, x, z))
+  }
+}
+
+impl<E, NE> EvaluationEngineTrait<NE> for ZMPCS<E, NE>
+where
+  E: MultiMillerLoop,
+  NE: NovaEngine<GE = E::G1, Scalar = E::Fr, CE = KZGCommitmentEngine<E>>,
+  E::Fr: Serialize + DeserializeOwned,
+  E::G1Affine: Serialize + DeserializeOwned,
+  E::G2Affine: Serialize + DeserializeOwned,
+  E::G1: DlogGroup<ScalarExt = E::Fr, AffineExt = E::G1Affine>,
+  <E::G1 as Group>::Base: TranscriptReprTrait<E::G1>,
+  E::Fr: PrimeFieldBits, // TODO due to use of gen_srs_for_testing, make optional
+  E::Fr: TranscriptReprTrait<E::G1>,
+  E::G1Affine: TranscriptReprTrait<E::G1>,
+{
+  type EvaluationArgument = ZMProof<E>;
+  type ProverKey = KZGProverKey<E>;
+  type VerifierKey = ZMVerifierKey<E>;
+
+  fn setup(ck: Arc<UniversalKZGParam<E>>) -> (Self::ProverKey, Self::VerifierKey) {
+    let num_powers = ck.length();
+    let (pk, vk) = UniversalKZGParam::trim(ck, num_powers - 1);
+    (pk, ZMVerifierKey { vk, num_powers })
+  }
+
+  fn prove(
+    ck: &UniversalKZGParam<E>,
+    _pk: &Self::ProverKey,
+    transcript: &mut <NE as NovaEngine>::TE,
+    _C: &Commitment<NE>,
+    poly: &[E::Fr],
+    point: &[E::Fr],
+    eval: &E::Fr,
+  ) -> Result<Self::EvaluationArgument, NovaError> {
+    let n = point.len();
+    let N = poly.len();
+    let num_powers = ck.length();
+    if N != 1 << n || N > num_powers {
+      return Err(NovaError::PCSError(PCSError::LengthError));
+    }
+
+    // commit to the quotients U_k(q_k), k = 0..n
+    let (quotients, remainder) = quotients(poly, point);
+    debug_assert_eq!(remainder, *eval);
+    let c_quotients = {
+      let comms = quotients
+        .par_iter()
+        .map(|q| <NE::CE as CommitmentEngineTrait<NE>>::commit(ck, q).comm)
+        .collect::<Vec<_>>();
+      let mut comms_affine = vec![E::G1Affine::identity(); comms.len()];
+      E::G1::batch_normalize(&comms, &mut comms_affine);
+      comms_affine
+    };
+
+    // the lifted quotient S = Σ_k y^k X^{D - 2^k} U_k(q_k) only has terms of
+    // degree at least D - N, so it is committed to with the last N powers
+    transcript.absorb(b"quo", &c_quotients.as_slice());
+    let y = transcript.squeeze(b"y")?;
+    let mut q_hat = vec![E::Fr::ZERO; N];
+    let mut y_k = E::Fr::ONE;
+    for q in &quotients {
+      q_hat[N - q.len()..].par_iter_mut().zip(q.par_iter()).for_each(|(h, q)| *h += y_k * q);
+      y_k *= y;
+    }
+    let c_q_hat =
+      E::G1::vartime_multiscalar_mul(&q_hat, &ck.powers_of_g[num_powers - N..]).to_affine();
+
+    transcript.absorb(b"q_hat", &c_q_hat);
+    let x = transcript.squeeze(b"x")?;
+    let z = transcript.squeeze(b"z")?;
+
+    // assemble the combined polynomial, which vanishes at x, and open it
+    let (eval_scalar, q_scalars) = eval_and_quotient_scalars(y, x, z, point, num_powers);
+    let mut F = vec![E::Fr::ZERO; num_powers];
+    F[..N].par_iter_mut().zip(poly.par_iter()).for_each(|(f, p)| *f = z * p);
+    F[0] += eval_scalar * eval;
+    F[num_powers - N..].par_iter_mut().zip(q_hat.par_iter()).for_each(|(f, h)| *f += h);
+    for (q, scalar) in quotients.iter().zip(q_scalars) {
+      F[..q.len()].par_iter_mut().zip(q.par_iter()).for_each(|(f, q)| *f += scalar * q);
+    }
+    let F = UniPoly::new(F);
+    debug_assert_eq!(F.evaluate(&x), E::Fr::ZERO);
+
+    let h = F.divide_minus_u(x);
+    let pi = <NE::CE as CommitmentEngineTrait<NE>>::commit(ck, &h.coeffs).comm.to_affine();
+
+    Ok(ZMProof { c_quotients, c_q_hat, pi })
+  }
+
+  /// A method to verify purported evaluations of a batch of polynomials
+  fn verify(
+    vk: &Self::VerifierKey,
+    transcript: &mut <NE as NovaEngine>::TE,
+    C: &Commitment<NE>,
+    point: &[E::Fr],
+    eval: &E::Fr,
+    pi: &Self::EvaluationArgument,
+  ) -> Result<(), NovaError> {
+    let n = point.len();
+    if pi.c_quotients.len() != n || 1 << n > vk.num_powers {
+      return Err(NovaError::PCSError(PCSError::LengthError));
+    }
+
+    let (y, x, z) = Self::compute_challenges(&pi.c_quotients, &pi.c_q_hat, transcript)?;
+    let (eval_scalar, q_scalars) = eval_and_quotient_scalars(y, x, z, point, vk.num_powers);
+
+    // commitment to the combined polynomial
+    let scalars = [vec![E::Fr::ONE, z, eval_scalar * eval], q_scalars].concat();
+    let bases = [vec![pi.c_q_hat, C.comm.to_affine(), vk.vk.g], pi.c_quotients.clone()].concat();
+    let C_F = E::G1::vartime_multiscalar_mul(&scalars, &bases);
+
+    // e(pi, τ H) = e(C_F + x pi, H)
+    let pairing_inputs: Vec<(E::G1Affine, E::G2Prepared)> =
+      vec![(pi.pi, vk.vk.beta_h.into()), ((-(C_F + pi.pi * x)).to_affine(), vk.vk.h.into())];
+
+    #[allow(clippy::map_identity)]
+    let pairing_input_refs = pairing_inputs.iter().map(|(a, b)| (a, b)).collect::<Vec<_>>();
+
+    let pairing_result = E::multi_miller_loop(pairing_input_refs.as_slice()).final_exponentiation();
+    let successful: bool = pairing_result.is_identity().into();
+    if !successful {
+      return Err(NovaError::PCSError(PCSError::ZMError));
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ff::Field;
+  use rand::{rngs::StdRng, SeedableRng};
+
+  use super::*;
+  use crate::{
+    provider::{
+      keccak::Keccak256Transcript, util::test_utils::prove_verify_from_num_vars, Bn256EngineZM,
+    },
+    spartan::polys::multilinear::MultilinearPolynomial,
+    CommitmentKey,
+  };
+
+  type E = halo2curves::bn256::Bn256;
+  type NE = Bn256EngineZM;
+  type Fr = <NE as NovaEngine>::Scalar;
+
+  #[test]
+  fn test_quotients() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for num_vars in 0..5 {
+      let poly = MultilinearPolynomial::<Fr>::random(num_vars, &mut rng);
+      let point = (0..num_vars).map(|_| Fr::random(&mut rng)).collect::<Vec<_>>();
+      let (quotients, remainder) = quotients(poly.evaluations(), &point);
+      assert_eq!(remainder, poly.evaluate(&point));
+
+      // f(r) - f(u) = Σ_k (r_k - u_k) q_k(r_0, ..., r_{k-1}) at a random r,
+      // where the variables of q_k are the last k coordinates of the point
+      let r = (0..num_vars).map(|_| Fr::random(&mut rng)).collect::<Vec<_>>();
+      let rhs = quotients
+        .iter()
+        .enumerate()
+        .map(|(k, q)| {
+          (r[num_vars - 1 - k] - point[num_vars - 1 - k])
+            * MultilinearPolynomial::evaluate_with(q, &r[num_vars - k..])
+        })
+        .sum::<Fr>();
+      assert_eq!(poly.evaluate(&r) - remainder, rhs);
+    }
+  }
+
+  #[test]
+  fn test_phi() {
+    let a = Fr::from(3);
+    for m in 0..5 {
+      let expected = (0..1u64 << m).map(|i| a.pow_vartime([i])).sum::<Fr>();
+      assert_eq!(phi(m, a), expected);
+    }
+  }
+
+  #[test]
+  fn test_zm_eval() {
+    // Test with poly(X1, X2) = 1 + X1 + X2 + X1*X2
+    let n = 4;
+    let ck: CommitmentKey<NE> =
+      <KZGCommitmentEngine<E> as CommitmentEngineTrait<NE>>::setup(b"test", n);
+    let ck = Arc::new(ck);
+    let (pk, vk) = ZMPCS::<E, NE>::setup(ck.clone());
+
+    // poly is in eval. representation; evaluated at [(0,0), (0,1), (1,0), (1,1)]
+    let poly = vec![Fr::from(1), Fr::from(2), Fr::from(2), Fr::from(4)];
+
+    let C = <KZGCommitmentEngine<E> as CommitmentEngineTrait<NE>>::commit(&ck, &poly);
+
+    let test_inner = |point: Vec<Fr>, eval: Fr| -> Result<(), NovaError> {
+      let mut tr = Keccak256Transcript::<NE>::new(b"TestEval");
+      let proof = ZMPCS::<E, NE>::prove(&ck, &pk, &mut tr, &C, &poly, &point, &eval).unwrap();
+      let mut tr = Keccak256Transcript::new(b"TestEval");
+      ZMPCS::<E, NE>::verify(&vk, &mut tr, &C, &point, &eval, &proof)
+    };
+
+    test_inner(vec![Fr::from(0), Fr::from(0)], Fr::ONE).unwrap();
+    test_inner(vec![Fr::from(0), Fr::from(1)], Fr::from(2)).unwrap();
+    test_inner(vec![Fr::from(1), Fr::from(1)], Fr::from(4)).unwrap();
+    test_inner(vec![Fr::from(0), Fr::from(2)], Fr::from(3)).unwrap();
+    test_inner(vec![Fr::from(2), Fr::from(2)], Fr::from(9)).unwrap();
+
+    // Try a couple incorrect evaluations and expect failure
+    assert!(test_inner(vec![Fr::from(2), Fr::from(2)], Fr::from(50)).is_err());
+    assert!(test_inner(vec![Fr::from(0), Fr::from(2)], Fr::from(4)).is_err());
+  }
+
+  #[test]
+  fn test_zm_proof_roundtrip() {
+    let n = 8;
+    let poly = (1..=n as u64).map(Fr::from).collect::<Vec<_>>();
+    let point = vec![Fr::from(4), Fr::from(3), Fr::from(8)];
+    let eval = MultilinearPolynomial::evaluate_with(&poly, &point);
+
+    let ck: CommitmentKey<NE> =
+      <KZGCommitmentEngine<E> as CommitmentEngineTrait<NE>>::setup(b"test", n);
+    let ck = Arc::new(ck);
+    let (pk, vk) = ZMPCS::<E, NE>::setup(ck.clone());
+    let C = <KZGCommitmentEngine<E> as CommitmentEngineTrait<NE>>::commit(&ck, &poly);
+
+    let mut prover_transcript = Keccak256Transcript::new(b"TestEval");
+    let proof =
+      ZMPCS::<E, NE>::prove(&ck, &pk, &mut prover_transcript, &C, &poly, &point, &eval).unwrap();
+
+    let proof_bytes = bincode::serialize(&proof).unwrap();
+    let proof: ZMProof<E> = bincode::deserialize(&proof_bytes).unwrap();
+    let proof = ZMProof::<E>::from_bytes(&proof.to_bytes()).unwrap();
+
+    let mut verifier_transcript = Keccak256Transcript::<NE>::new(b"TestEval");
+    ZMPCS::<E, NE>::verify(&vk, &mut verifier_transcript, &C, &point, &eval, &proof).unwrap();
+
+    // a tampered quotient commitment must be rejected
+    let mut bad_proof = proof.clone();
+    bad_proof.c_quotients[0] =
+      (bad_proof.c_quotients[0] + bad_proof.c_quotients[0] * Fr::from(123)).to_affine();
+    let mut verifier_transcript = Keccak256Transcript::<NE>::new(b"TestEval");
+    assert!(
+      ZMPCS::<E, NE>::verify(&vk, &mut verifier_transcript, &C, &point, &eval, &bad_proof).is_err()
+    );
+  }
+
+  #[test]
+  fn test_zm_more() {
+    // test the Zeromorph prover and verifier with random instances (derived from
+    // a seed)
+    for num_vars in [4, 5, 6] {
+      prove_verify_from_num_vars::<_, ZMPCS<E, NE>>(num_vars);
+    }
+  }
+}
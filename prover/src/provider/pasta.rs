@@ -0,0 +1,139 @@
+//! This module implements the Nova traits for `pallas::Point`,
+//! `pallas::Scalar`, `vesta::Point`, `vesta::Scalar`.
+use std::io::Read;
+
+use digest::{ExtendableOutput, Update};
+use ff::{FromUniformBytes, PrimeField};
+use group::{prime::PrimeCurveAffine, Curve, Group as AnotherGroup};
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+use grumpkin_msm::pasta::{pallas as pallas_msm, vesta as vesta_msm};
+use halo2curves::{CurveAffine, CurveExt};
+use num_bigint::BigInt;
+use num_traits::Num;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha3::Shake256;
+
+use crate::{
+  impl_traits,
+  provider::{traits::DlogGroup, util::msm::cpu_best_msm},
+  traits::{Group, PrimeFieldExt, TranscriptReprTrait},
+};
+
+// The pasta curves encode points as plain byte arrays, which have no place for
+// the `TranscriptReprTrait` impl that `impl_traits!` expects on the compressed
+// representation, so they are wrapped in a local type.
+macro_rules! impl_compressed_wrapper {
+  ($wrapper:ident, $curve:literal) => {
+    #[doc = concat!("A wrapper for compressed group elements of ", $curve)]
+    #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub struct $wrapper([u8; 32]);
+
+    impl From<[u8; 32]> for $wrapper {
+      fn from(bytes: [u8; 32]) -> Self { Self(bytes) }
+    }
+
+    impl From<$wrapper> for [u8; 32] {
+      fn from(wrapper: $wrapper) -> Self { wrapper.0 }
+    }
+
+    impl AsRef<[u8]> for $wrapper {
+      fn as_ref(&self) -> &[u8] { &self.0 }
+    }
+  };
+}
+
+impl_compressed_wrapper!(PallasCompressedElementWrapper, "pallas");
+impl_compressed_wrapper!(VestaCompressedElementWrapper, "vesta");
+
+/// Re-exports that give access to the standard aliases used in the code base,
+/// for pallas
+pub mod pallas {
+  pub use halo2curves::pasta::pallas::{Affine, Base, Point, Scalar};
+
+  pub use super::PallasCompressedElementWrapper as Compressed;
+}
+
+/// Re-exports that give access to the standard aliases used in the code base,
+/// for vesta
+pub mod vesta {
+  pub use halo2curves::pasta::vesta::{Affine, Base, Point, Scalar};
+
+  pub use super::VestaCompressedElementWrapper as Compressed;
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+impl_traits!(
+  pallas,
+  "40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001",
+  "40000000000000000000000000000000224698fc094cf91b992d30ed00000001",
+  pallas_msm
+);
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+impl_traits!(
+  pallas,
+  "40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001",
+  "40000000000000000000000000000000224698fc094cf91b992d30ed00000001"
+);
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+impl_traits!(
+  vesta,
+  "40000000000000000000000000000000224698fc094cf91b992d30ed00000001",
+  "40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001",
+  vesta_msm
+);
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+impl_traits!(
+  vesta,
+  "40000000000000000000000000000000224698fc094cf91b992d30ed00000001",
+  "40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001"
+);
+
+#[cfg(test)]
+mod tests {
+  use ff::Field;
+  use group::{Group, GroupEncoding};
+  use rand::thread_rng;
+
+  use crate::provider::{
+    pasta::{pallas, vesta},
+    traits::DlogGroup,
+    util::msm::cpu_best_msm,
+  };
+
+  #[test]
+  fn test_pallas_msm_correctness() {
+    let npoints = 1usize << 16;
+    let points = pallas::Point::from_label(b"test", npoints);
+
+    let mut rng = thread_rng();
+    let scalars = (0..npoints).map(|_| pallas::Scalar::random(&mut rng)).collect::<Vec<_>>();
+
+    let cpu_msm = cpu_best_msm(&points, &scalars);
+    let gpu_msm = pallas::Point::vartime_multiscalar_mul(&scalars, &points);
+
+    assert_eq!(cpu_msm, gpu_msm);
+  }
+
+  #[test]
+  fn test_vesta_msm_correctness() {
+    let npoints = 1usize << 16;
+    let points = vesta::Point::from_label(b"test", npoints);
+
+    let mut rng = thread_rng();
+    let scalars = (0..npoints).map(|_| vesta::Scalar::random(&mut rng)).collect::<Vec<_>>();
+
+    let cpu_msm = cpu_best_msm(&points, &scalars);
+    let gpu_msm = vesta::Point::vartime_multiscalar_mul(&scalars, &points);
+
+    assert_eq!(cpu_msm, gpu_msm);
+  }
+
+  #[test]
+  fn test_pallas_compressed_roundtrip() {
+    let point = pallas::Point::random(thread_rng());
+    let compressed: pallas::Compressed = point.to_bytes().into();
+    assert_eq!(pallas::Point::from_bytes(&compressed.into()).unwrap(), point);
+  }
+}
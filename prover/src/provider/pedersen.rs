@@ -4,7 +4,6 @@ use core::{
   marker::PhantomData,
   ops::{Add, Mul, MulAssign},
 };
-use std::io::Cursor;
 
 use ff::Field;
 use group::{
@@ -17,8 +16,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
   errors::NovaError,
-  fast_serde,
-  fast_serde::{FastSerde, SerdeByteError, SerdeByteTypes},
+  fast_serde::{
+    mapped::Points, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes,
+  },
   provider::traits::DlogGroup,
   traits::{
     commitment::{CommitmentEngineTrait, CommitmentTrait, Len},
@@ -33,7 +33,7 @@ pub struct CommitmentKey<E>
 where
   E: Engine,
   E::GE: DlogGroup<ScalarExt = E::Scalar>, {
-  pub ck: Vec<<E::GE as PrimeCurve>::Affine>,
+  pub ck: Points<<E::GE as PrimeCurve>::Affine>,
 }
 
 impl<E> Len for CommitmentKey<E>
@@ -49,44 +49,27 @@ where
   <E::GE as PrimeCurve>::Affine: SerdeObject,
   E::GE: DlogGroup<ScalarExt = E::Scalar>,
 {
-  /// Byte format:
-  ///
-  /// [0..4]   - Magic number (4 bytes)
-  /// [4]      - Serde type: CommitmentKey (u8)
-  /// [5]      - Number of sections (u8 = 1)
-  /// [6]      - Section 1 type: ck (u8)
-  /// [7..11]  - Section 1 size (u32)
-  /// [11..]   - Section 1 data
+  /// Sections:
+  ///   1: ck (raw points)
   fn to_bytes(&self) -> Vec<u8> {
-    let mut out = Vec::new();
-
-    out.extend_from_slice(&fast_serde::MAGIC_NUMBER);
-    out.push(fast_serde::SerdeByteTypes::CommitmentKey as u8);
-    out.push(1); // num_sections
-
-    Self::write_section_bytes(
-      &mut out,
-      1,
-      &self.ck.iter().flat_map(|p| p.to_raw_bytes()).collect::<Vec<u8>>(),
-    );
-
-    out
+    let mut out = SectionWriter::new(SerdeByteTypes::CommitmentKey);
+    out.section(1, self.ck.iter().flat_map(|p| p.to_raw_bytes()).collect::<Vec<u8>>());
+    out.finish()
   }
 
   fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
-    let mut cursor = Cursor::new(bytes);
-
-    // Validate header
-    Self::validate_header(&mut cursor, SerdeByteTypes::CommitmentKey, 1)?;
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::CommitmentKey)?;
 
     // Read ck section
-    let ck = Self::read_section_bytes(&mut cursor, 1)?
+    let ck = sections
+      .section(1)?
       .chunks(<E::GE as PrimeCurve>::Affine::identity().to_raw_bytes().len())
       .map(|bytes| {
         <E::GE as PrimeCurve>::Affine::from_raw_bytes(bytes).ok_or(SerdeByteError::G1DecodeError)
       })
-      .collect::<Result<Vec<_>, _>>()?;
+      .collect::<Result<Points<_>, _>>()?;
 
+    sections.finish()?;
     Ok(Self { ck })
   }
 }
@@ -128,6 +111,20 @@ where
     };
     Ok(Self { comm })
   }
+
+  fn compressed_to_bytes(c: &Self::CompressedCommitment) -> Vec<u8> {
+    let repr: <E::GE as GroupEncoding>::Repr = c.comm.clone().into();
+    repr.as_ref().to_vec()
+  }
+
+  fn compressed_from_bytes(bytes: &[u8]) -> Result<Self::CompressedCommitment, NovaError> {
+    let mut repr = <E::GE as GroupEncoding>::Repr::default();
+    if repr.as_ref().len() != bytes.len() {
+      return Err(NovaError::DecompressionError);
+    }
+    repr.as_mut().copy_from_slice(bytes);
+    Ok(CompressedCommitment { comm: repr.into() })
+  }
 }
 
 impl<E> Default for Commitment<E>
@@ -224,13 +221,18 @@ where
   type CommitmentKey = CommitmentKey<E>;
 
   fn setup(label: &'static [u8], n: usize) -> Self::CommitmentKey {
-    Self::CommitmentKey { ck: E::GE::from_label(label, n.next_power_of_two()) }
+    Self::CommitmentKey { ck: E::GE::from_label(label, n.next_power_of_two()).into() }
   }
 
   fn commit(ck: &Self::CommitmentKey, v: &[E::Scalar]) -> Self::Commitment {
     assert!(ck.ck.len() >= v.len());
     Commitment { comm: E::GE::vartime_multiscalar_mul(v, &ck.ck[..v.len()]) }
   }
+
+  fn commit_at(ck: &Self::CommitmentKey, v: &[E::Scalar], offset: usize) -> Self::Commitment {
+    assert!(ck.ck.len() >= offset + v.len());
+    Commitment { comm: E::GE::vartime_multiscalar_mul(v, &ck.ck[offset..offset + v.len()]) }
+  }
 }
 
 /// A trait listing properties of a commitment key that can be managed in a
@@ -268,12 +270,12 @@ where
   E::GE: DlogGroup<ScalarExt = E::Scalar>,
 {
   fn split_at(mut self, n: usize) -> (Self, Self) {
-    let right = self.ck.split_off(n);
-    (self, Self { ck: right })
+    let right = self.ck.to_mut().split_off(n);
+    (self, Self { ck: right.into() })
   }
 
   fn combine(&self, other: &Self) -> Self {
-    let ck = { self.ck.iter().cloned().chain(other.ck.iter().cloned()).collect::<Vec<_>>() };
+    let ck = { self.ck.iter().cloned().chain(other.ck.iter().cloned()).collect::<Points<_>>() };
     Self { ck }
   }
 
@@ -288,13 +290,13 @@ where
     let mut ck_affine = vec![<E::GE as PrimeCurve>::Affine::identity(); L.ck.len()];
     E::GE::batch_normalize(&ck_curve, &mut ck_affine);
 
-    Self { ck: ck_affine }
+    Self { ck: ck_affine.into() }
   }
 
   /// Scales each element in `self` by `r`
   fn scale(&mut self, r: &E::Scalar) {
     let ck_scaled: Vec<E::GE> = self.ck.par_iter().map(|g| *g * r).collect();
-    E::GE::batch_normalize(&ck_scaled, &mut self.ck);
+    E::GE::batch_normalize(&ck_scaled, self.ck.to_mut());
   }
 
   /// reinterprets a vector of commitments as a set of generators
@@ -305,6 +307,6 @@ where
       .collect::<Result<Vec<E::GE>, NovaError>>()?;
     let mut ck = vec![<E::GE as PrimeCurve>::Affine::identity(); d.len()];
     E::GE::batch_normalize(&d, &mut ck);
-    Ok(Self { ck })
+    Ok(Self { ck: ck.into() })
   }
 }
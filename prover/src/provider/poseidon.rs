@@ -24,7 +24,7 @@ use crate::traits::{ROCircuitTrait, ROTrait};
 
 /// All Poseidon Constants that are used in Nova
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct PoseidonConstantsCircuit<Scalar: PrimeField>(PoseidonConstants<Scalar, U24>);
+pub struct PoseidonConstantsCircuit<Scalar: PrimeField>(pub(crate) PoseidonConstants<Scalar, U24>);
 
 impl<Scalar: PrimeField> Default for PoseidonConstantsCircuit<Scalar> {
   /// Generate Poseidon constants
@@ -0,0 +1,487 @@
+//! This module provides an implementation of `TranscriptEngineTrait` using
+//! a Poseidon sponge, along with a matching gadget to replay the transcript
+//! inside a circuit
+use core::{any::TypeId, marker::PhantomData};
+use std::{
+  any::Any,
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use bellpepper_core::{
+  boolean::{AllocatedBit, Boolean},
+  num::{AllocatedNum, Num},
+  ConstraintSystem, LinearCombination, SynthesisError,
+};
+use ff::{PrimeField, PrimeFieldBits};
+use generic_array::typenum::U24;
+use neptune::{
+  circuit2::Elt,
+  poseidon::PoseidonConstants,
+  sponge::{
+    api::{IOPattern, SpongeAPI, SpongeOp},
+    circuit::SpongeCircuit,
+    vanilla::{Mode::Simplex, Sponge, SpongeTrait},
+  },
+};
+use once_cell::sync::Lazy;
+
+use crate::{
+  errors::NovaError,
+  provider::poseidon::PoseidonConstantsCircuit,
+  traits::{Engine, TranscriptEngineTrait, TranscriptReprTrait},
+};
+
+const PERSONA_TAG: &[u8] = b"NoTR";
+const DOM_SEP_TAG: &[u8] = b"NoDS";
+/// Number of bytes packed in a field element, small enough for any field
+/// with at least 249 bits
+const BYTES_PER_ELEMENT: usize = 31;
+
+/// Poseidon constants are expensive to generate, so they are shared between
+/// all transcripts over the same field
+static CONSTANTS: Lazy<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>> =
+  Lazy::new(Default::default);
+
+fn constants<F: PrimeField>() -> Arc<PoseidonConstants<F, U24>> {
+  let mut constants = CONSTANTS.lock().unwrap();
+  let entry = constants
+    .entry(TypeId::of::<F>())
+    .or_insert_with(|| Arc::new(PoseidonConstantsCircuit::<F>::default().0));
+  entry.clone().downcast().unwrap()
+}
+
+/// Provides an implementation of `TranscriptEngine` using a Poseidon sponge
+/// over the scalar field.
+///
+/// Absorbed bytes are buffered until the next challenge is squeezed. The
+/// challenge is the Poseidon hash of the previous challenge, the round, the
+/// number of buffered bytes and the buffered bytes packed into field
+/// elements, so that it can be recomputed in a circuit over the same field
+/// with [`PoseidonTranscriptCircuit`].
+#[derive(Debug)]
+pub struct PoseidonTranscript<E: Engine> {
+  round:  u16,
+  state:  E::Scalar,
+  buffer: Vec<u8>,
+  _p:     PhantomData<E>,
+}
+
+/// Packs little-endian bytes into field elements
+fn pack_bytes<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+  bytes
+    .chunks(BYTES_PER_ELEMENT)
+    .map(|chunk| {
+      chunk.iter().rev().fold(F::ZERO, |acc, b| acc * F::from(256) + F::from(u64::from(*b)))
+    })
+    .collect()
+}
+
+impl<E: Engine> TranscriptEngineTrait<E> for PoseidonTranscript<E> {
+  fn new(label: &'static [u8]) -> Self {
+    Self {
+      round:  0u16,
+      state:  E::Scalar::ZERO,
+      buffer: [PERSONA_TAG, label].concat(),
+      _p:     PhantomData,
+    }
+  }
+
+  fn squeeze(&mut self, label: &'static [u8]) -> Result<E::Scalar, NovaError> {
+    self.buffer.extend_from_slice(DOM_SEP_TAG);
+    self.buffer.extend_from_slice(label);
+
+    let input = [
+      vec![
+        self.state,
+        E::Scalar::from(u64::from(self.round)),
+        E::Scalar::from(self.buffer.len() as u64),
+      ],
+      pack_bytes(&self.buffer),
+    ]
+    .concat();
+
+    let constants = constants::<E::Scalar>();
+    let mut sponge = Sponge::new_with_constants(&*constants, Simplex);
+    let acc = &mut ();
+    let parameter = IOPattern(vec![SpongeOp::Absorb(input.len() as u32), SpongeOp::Squeeze(1u32)]);
+
+    sponge.start(parameter, None, acc);
+    SpongeAPI::absorb(&mut sponge, input.len() as u32, &input, acc);
+    let hash = SpongeAPI::squeeze(&mut sponge, 1, acc);
+    sponge.finish(acc).map_err(|_| NovaError::InternalTranscriptError)?;
+
+    // update state
+    self.round = self.round.checked_add(1).ok_or(NovaError::InternalTranscriptError)?;
+    self.state = hash[0];
+    self.buffer.clear();
+
+    Ok(hash[0])
+  }
+
+  fn absorb<T: TranscriptReprTrait<E::GE>>(&mut self, label: &'static [u8], o: &T) {
+    self.buffer.extend_from_slice(label);
+    self.buffer.extend_from_slice(&o.to_transcript_bytes());
+  }
+
+  fn dom_sep(&mut self, bytes: &'static [u8]) {
+    self.buffer.extend_from_slice(DOM_SEP_TAG);
+    self.buffer.extend_from_slice(bytes);
+  }
+}
+
+/// A gadget that replays a [`PoseidonTranscript`] inside a circuit over the
+/// same scalar field.
+///
+/// Absorbed values are given as little-endian bits of their transcript bytes,
+/// so the caller decomposes them the same way `TranscriptReprTrait` encodes
+/// them natively. Scalars can be absorbed directly with
+/// [`PoseidonTranscriptCircuit::absorb_num`], and points of the curve whose
+/// scalar field is `Scalar` with
+/// [`PoseidonTranscriptCircuit::absorb_commitment`] and
+/// [`PoseidonTranscriptCircuit::absorb_affine_point`].
+#[derive(Debug)]
+pub struct PoseidonTranscriptCircuit<Scalar: PrimeField> {
+  round:  u16,
+  state:  Elt<Scalar>,
+  buffer: Vec<Boolean>,
+}
+
+/// Returns the little-endian bits of constant bytes
+fn constant_bits(bytes: &[u8]) -> impl Iterator<Item = Boolean> + '_ {
+  bytes.iter().flat_map(|b| (0..8).map(move |i| Boolean::Constant((b >> i) & 1 == 1)))
+}
+
+/// Returns a constant element
+fn constant_elt<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(value: Scalar) -> Elt<Scalar> {
+  Elt::Num(Num::zero().add_bool_with_coeff(CS::one(), &Boolean::Constant(true), value))
+}
+
+/// Returns the bits of a byte whose lowest bit is `flag`
+fn flag_byte(flag: Boolean) -> Vec<Boolean> {
+  [flag].into_iter().chain(constant_bits(&[0]).skip(1)).collect()
+}
+
+/// Returns the little-endian bits of the canonical representation of an
+/// element of `Base` given as limbs of `limb_width` bits
+fn coordinate_bits<Base, Scalar, CS>(
+  mut cs: CS,
+  limbs: &[AllocatedNum<Scalar>],
+  limb_width: usize,
+) -> Result<Vec<Boolean>, SynthesisError>
+where
+  Base: PrimeField,
+  Scalar: PrimeField + PrimeFieldBits,
+  CS: ConstraintSystem<Scalar>,
+{
+  let mut bits = Vec::with_capacity(limbs.len() * limb_width);
+  for (i, limb) in limbs.iter().enumerate() {
+    let mut cs = cs.namespace(|| format!("limb {i}"));
+    let values = limb.get_value().map(|v| v.to_le_bits());
+    let limb_bits = (0..limb_width)
+      .map(|j| {
+        let value = values.as_ref().map(|v| v[j]);
+        AllocatedBit::alloc(cs.namespace(|| format!("bit {j}")), value).map(Boolean::from)
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    let mut coeff = Scalar::ONE;
+    let sum = limb_bits.iter().fold(LinearCombination::zero(), |lc, bit| {
+      let lc = lc + &bit.lc(CS::one(), coeff);
+      coeff = coeff.double();
+      lc
+    });
+    cs.enforce(
+      || "limb from bits",
+      |lc| lc + &sum,
+      |lc| lc + CS::one(),
+      |lc| lc + limb.get_variable(),
+    );
+    bits.extend(limb_bits);
+  }
+
+  // the limbs may span more bits than the representation, which must then be
+  // zero
+  let repr_bits = Base::Repr::default().as_ref().len() * 8;
+  if bits.len() > repr_bits {
+    for (i, bit) in bits.drain(repr_bits..).enumerate() {
+      Boolean::enforce_equal(
+        cs.namespace(|| format!("high bit {i}")),
+        &bit,
+        &Boolean::Constant(false),
+      )?;
+    }
+  }
+  bits.resize(repr_bits, Boolean::Constant(false));
+  Ok(bits)
+}
+
+impl<Scalar: PrimeField + PrimeFieldBits> PoseidonTranscriptCircuit<Scalar> {
+  /// Initializes the transcript, see [`TranscriptEngineTrait::new`]
+  pub fn new(label: &'static [u8]) -> Self {
+    Self {
+      round:  0u16,
+      state:  Elt::Num(Num::zero()),
+      buffer: constant_bits(&[PERSONA_TAG, label].concat()).collect(),
+    }
+  }
+
+  /// Absorbs the little-endian bits of a value's transcript bytes under a
+  /// label
+  ///
+  /// Fails if the bits do not make up whole bytes.
+  pub fn absorb_bits(
+    &mut self,
+    label: &'static [u8],
+    bits: &[Boolean],
+  ) -> Result<(), SynthesisError> {
+    if bits.len() % 8 != 0 {
+      return Err(SynthesisError::Unsatisfiable);
+    }
+    self.buffer.extend(constant_bits(label));
+    self.buffer.extend_from_slice(bits);
+    Ok(())
+  }
+
+  /// Absorbs a scalar under a label, matching the native absorption of its
+  /// canonical little-endian representation
+  pub fn absorb_num<CS: ConstraintSystem<Scalar>>(
+    &mut self,
+    mut cs: CS,
+    label: &'static [u8],
+    num: &AllocatedNum<Scalar>,
+  ) -> Result<(), SynthesisError> {
+    let mut bits = num.to_bits_le_strict(cs.namespace(|| "num to bits"))?;
+    let repr_bits = Scalar::Repr::default().as_ref().len() * 8;
+    bits.resize(repr_bits, Boolean::Constant(false));
+    self.absorb_bits(label, &bits)
+  }
+
+  /// Absorbs a commitment under a label, matching the native absorption of a
+  /// `pedersen::Commitment`: the canonical representations of `x` and `y`,
+  /// followed by a byte that is 1 unless the commitment is the identity
+  ///
+  /// The coordinates live in the base field `Base` of the curve and are given
+  /// as little-endian limbs of `limb_width` bits, like the emulated points of
+  /// the CycleFold circuits. The identity must have coordinates `(0, 0)`. The
+  /// limbs are range checked, but it is up to the caller to check that the
+  /// coordinates are canonical and on the curve.
+  pub fn absorb_commitment<Base: PrimeField, CS: ConstraintSystem<Scalar>>(
+    &mut self,
+    mut cs: CS,
+    label: &'static [u8],
+    x: &[AllocatedNum<Scalar>],
+    y: &[AllocatedNum<Scalar>],
+    limb_width: usize,
+    is_infinity: &Boolean,
+  ) -> Result<(), SynthesisError> {
+    let bits = [
+      coordinate_bits::<Base, Scalar, _>(cs.namespace(|| "x"), x, limb_width)?,
+      coordinate_bits::<Base, Scalar, _>(cs.namespace(|| "y"), y, limb_width)?,
+      flag_byte(is_infinity.not()),
+    ]
+    .concat();
+    self.absorb_bits(label, &bits)
+  }
+
+  /// Absorbs an affine point under a label, matching the native absorption of
+  /// an affine point such as a HyperKZG commitment: the canonical
+  /// representations of `x` and `y`, followed by a zero byte
+  ///
+  /// The coordinates are given as for
+  /// [`PoseidonTranscriptCircuit::absorb_commitment`].
+  pub fn absorb_affine_point<Base: PrimeField, CS: ConstraintSystem<Scalar>>(
+    &mut self,
+    mut cs: CS,
+    label: &'static [u8],
+    x: &[AllocatedNum<Scalar>],
+    y: &[AllocatedNum<Scalar>],
+    limb_width: usize,
+  ) -> Result<(), SynthesisError> {
+    let bits = [
+      coordinate_bits::<Base, Scalar, _>(cs.namespace(|| "x"), x, limb_width)?,
+      coordinate_bits::<Base, Scalar, _>(cs.namespace(|| "y"), y, limb_width)?,
+      flag_byte(Boolean::Constant(false)),
+    ]
+    .concat();
+    self.absorb_bits(label, &bits)
+  }
+
+  /// Adds a domain separator
+  pub fn dom_sep(&mut self, bytes: &'static [u8]) {
+    self.buffer.extend(constant_bits(DOM_SEP_TAG));
+    self.buffer.extend(constant_bits(bytes));
+  }
+
+  /// Computes the next challenge, see [`TranscriptEngineTrait::squeeze`]
+  pub fn squeeze<CS: ConstraintSystem<Scalar>>(
+    &mut self,
+    mut cs: CS,
+    label: &'static [u8],
+  ) -> Result<AllocatedNum<Scalar>, SynthesisError> {
+    self.buffer.extend(constant_bits(DOM_SEP_TAG));
+    self.buffer.extend(constant_bits(label));
+
+    let mut input = vec![
+      self.state.clone(),
+      constant_elt::<Scalar, CS>(Scalar::from(u64::from(self.round))),
+      constant_elt::<Scalar, CS>(Scalar::from((self.buffer.len() / 8) as u64)),
+    ];
+    input.extend(self.buffer.chunks(8 * BYTES_PER_ELEMENT).map(|chunk| {
+      let mut coeff = Scalar::ONE;
+      let num = chunk.iter().fold(Num::zero(), |num, bit| {
+        let num = num.add_bool_with_coeff(CS::one(), bit, coeff);
+        coeff = coeff.double();
+        num
+      });
+      Elt::Num(num)
+    }));
+
+    let constants = constants::<Scalar>();
+    let parameter = IOPattern(vec![SpongeOp::Absorb(input.len() as u32), SpongeOp::Squeeze(1u32)]);
+    let mut ns = cs.namespace(|| "sponge");
+    let hash = {
+      let mut sponge = SpongeCircuit::new_with_constants(&*constants, Simplex);
+      let acc = &mut ns;
+
+      sponge.start(parameter, None, acc);
+      SpongeAPI::absorb(&mut sponge, input.len() as u32, &input, acc);
+      let output = SpongeAPI::squeeze(&mut sponge, 1, acc);
+      sponge.finish(acc).map_err(|_| SynthesisError::Unsatisfiable)?;
+      output
+    };
+    let hash = Elt::ensure_allocated(&hash[0], &mut ns.namespace(|| "ensure allocated"), true)?;
+
+    // update state
+    self.round = self.round.checked_add(1).ok_or(SynthesisError::Unsatisfiable)?;
+    self.state = Elt::Allocated(hash.clone());
+    self.buffer.clear();
+
+    Ok(hash)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ff::Field;
+  use group::{Curve, Group};
+  use rand::rngs::OsRng;
+
+  use super::*;
+  use crate::{
+    bellpepper::solver::SatisfyingAssignment,
+    constants::{BN_LIMB_WIDTH, BN_N_LIMBS},
+    gadgets::{f_to_nat, nat_to_limbs},
+    provider::{pedersen::Commitment, Bn256EngineKZG, Bn256EngineKZGPoseidon, GrumpkinEngine},
+    traits::commitment::CommitmentTrait,
+  };
+
+  fn test_poseidon_transcript_circuit_with<E: Engine>() {
+    let s1 = E::Scalar::random(OsRng);
+    let s2 = E::Scalar::random(OsRng);
+
+    let mut transcript = PoseidonTranscript::<E>::new(b"test");
+    transcript.dom_sep(b"ds");
+    transcript.absorb(b"s1", &s1);
+    transcript.absorb(b"s2", &s2);
+    let c1 = transcript.squeeze(b"c1").unwrap();
+    let c2 = transcript.squeeze(b"c2").unwrap();
+    assert_ne!(c1, c2);
+
+    let mut cs = SatisfyingAssignment::<E>::new();
+    let mut gadget = PoseidonTranscriptCircuit::<E::Scalar>::new(b"test");
+    let s1 = AllocatedNum::alloc_infallible(cs.namespace(|| "s1"), || s1);
+    let s2 = AllocatedNum::alloc_infallible(cs.namespace(|| "s2"), || s2);
+    gadget.dom_sep(b"ds");
+    gadget.absorb_num(cs.namespace(|| "absorb s1"), b"s1", &s1).unwrap();
+    gadget.absorb_num(cs.namespace(|| "absorb s2"), b"s2", &s2).unwrap();
+    let c1_gadget = gadget.squeeze(cs.namespace(|| "c1"), b"c1").unwrap();
+    let c2_gadget = gadget.squeeze(cs.namespace(|| "c2"), b"c2").unwrap();
+
+    assert_eq!(c1_gadget.get_value(), Some(c1));
+    assert_eq!(c2_gadget.get_value(), Some(c2));
+  }
+
+  #[test]
+  fn test_poseidon_transcript_circuit() {
+    test_poseidon_transcript_circuit_with::<Bn256EngineKZG>();
+    test_poseidon_transcript_circuit_with::<GrumpkinEngine>();
+  }
+
+  /// Allocates the coordinates of a point as limbs over the scalar field
+  fn alloc_coordinate<E: Engine, CS: ConstraintSystem<E::Scalar>>(
+    mut cs: CS,
+    c: &E::Base,
+  ) -> Vec<AllocatedNum<E::Scalar>> {
+    nat_to_limbs::<E::Scalar>(&f_to_nat(c), BN_LIMB_WIDTH, BN_N_LIMBS)
+      .unwrap()
+      .into_iter()
+      .enumerate()
+      .map(|(i, limb)| {
+        AllocatedNum::alloc_infallible(cs.namespace(|| format!("limb {i}")), || limb)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_poseidon_transcript_circuit_points() {
+    type E = Bn256EngineKZGPoseidon;
+
+    let point = <E as Engine>::GE::random(OsRng);
+    let comm = Commitment::<E> { comm: point };
+    let identity = Commitment::<E>::default();
+    let affine = point.to_affine();
+
+    let mut transcript = PoseidonTranscript::<E>::new(b"test");
+    transcript.absorb(b"comm", &comm);
+    transcript.absorb(b"identity", &identity);
+    transcript.absorb(b"affine", &affine);
+    let c = transcript.squeeze(b"c").unwrap();
+
+    let mut cs = SatisfyingAssignment::<E>::new();
+    let mut gadget = PoseidonTranscriptCircuit::<<E as Engine>::Scalar>::new(b"test");
+    let (x, y, _) = comm.to_coordinates();
+    let x = alloc_coordinate::<E, _>(cs.namespace(|| "x"), &x);
+    let y = alloc_coordinate::<E, _>(cs.namespace(|| "y"), &y);
+    let zero = alloc_coordinate::<E, _>(cs.namespace(|| "zero"), &<E as Engine>::Base::ZERO);
+    gadget
+      .absorb_commitment::<<E as Engine>::Base, _>(
+        cs.namespace(|| "comm"),
+        b"comm",
+        &x,
+        &y,
+        BN_LIMB_WIDTH,
+        &Boolean::Constant(false),
+      )
+      .unwrap();
+    gadget
+      .absorb_commitment::<<E as Engine>::Base, _>(
+        cs.namespace(|| "identity"),
+        b"identity",
+        &zero,
+        &zero,
+        BN_LIMB_WIDTH,
+        &Boolean::Constant(true),
+      )
+      .unwrap();
+    gadget
+      .absorb_affine_point::<<E as Engine>::Base, _>(
+        cs.namespace(|| "affine"),
+        b"affine",
+        &x,
+        &y,
+        BN_LIMB_WIDTH,
+      )
+      .unwrap();
+    let c_gadget = gadget.squeeze(cs.namespace(|| "c"), b"c").unwrap();
+
+    assert_eq!(c_gadget.get_value(), Some(c));
+  }
+
+  #[test]
+  fn test_poseidon_transcript_circuit_partial_byte() {
+    let mut gadget = PoseidonTranscriptCircuit::<<Bn256EngineKZG as Engine>::Scalar>::new(b"test");
+    let bits = vec![Boolean::Constant(true); 7];
+    assert!(gadget.absorb_bits(b"bits", &bits).is_err());
+  }
+}
@@ -0,0 +1,360 @@
+//! Loaders for KZG structured reference strings (SRS) produced by public
+//! powers-of-tau ceremonies over BN254.
+//!
+//! The SRS sampled by [`UniversalKZGParam::gen_srs_for_testing`] is derived
+//! from a known seed, so anyone can recover its secret `τ` and open
+//! commitments to arbitrary values. Production setups should instead use an
+//! SRS from a ceremony whose secret nobody knows:
+//!
+//! - [`from_ptau`] reads the `.ptau` files of snarkjs and the Perpetual Powers of Tau ceremony.
+//! - [`from_ignition`] reads the transcripts of Aztec's Ignition ceremony.
+//!
+//! Both loaders check that every point lies on its curve and, with
+//! [`UniversalKZGParam::check_powers`], that the G1 points are successive
+//! powers of the `τ` in the G2 point. The result
+//! can be passed to [`PublicParams::setup_with_commitment_key`].
+//!
+//! [`PublicParams::setup_with_commitment_key`]: crate::supernova::PublicParams::setup_with_commitment_key
+
+use ff::PrimeField;
+use group::prime::PrimeCurveAffine;
+use halo2curves::{
+  bn256::{Bn256, Fq, Fq2, G1Affine, G2Affine},
+  serde::SerdeObject,
+  CurveAffine,
+};
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::provider::kzg_commitment::UniversalKZGParam;
+
+/// The BN254 base field modulus, little-endian, as stored in `.ptau` headers
+const BN254_BASE_MODULUS: [u8; 32] = [
+  0x47, 0xfd, 0x7c, 0xd8, 0x16, 0x8c, 0x20, 0x3c, 0x8d, 0xca, 0x71, 0x68, 0x91, 0x6a, 0x81, 0x97,
+  0x5d, 0x58, 0x81, 0x81, 0xb6, 0x45, 0x50, 0xb8, 0x29, 0xa0, 0x31, 0xe1, 0x72, 0x4e, 0x64, 0x30,
+];
+
+/// Size of an uncompressed G1 point in both ceremony formats
+const G1_SIZE: usize = 64;
+
+/// Size of an uncompressed G2 point in both ceremony formats
+const G2_SIZE: usize = 128;
+
+/// Errors returned when loading an SRS
+#[derive(Debug, Error)]
+pub enum SrsError {
+  #[error("invalid {0} file: {1}")]
+  InvalidFormat(&'static str, &'static str),
+  #[error("invalid point for power {0}")]
+  InvalidPoint(usize),
+  #[error("the SRS has {available} powers, but {requested} were requested")]
+  NotEnoughPowers { available: usize, requested: usize },
+  #[error("the SRS points are not successive powers of the same secret")]
+  InconsistentPowers,
+}
+
+/// Loads the first `max_degree + 1` powers of a snarkjs `.ptau` file
+///
+/// The `τ` powers in G1 (section 2) become `powers_of_g`, and the first two
+/// `τ` powers in G2 (section 3) become `powers_of_h`. Points are stored
+/// uncompressed, as little-endian coordinates in Montgomery form.
+pub fn from_ptau(bytes: &[u8], max_degree: usize) -> Result<UniversalKZGParam<Bn256>, SrsError> {
+  let invalid = |reason| SrsError::InvalidFormat("ptau", reason);
+  let truncated = || invalid("truncated");
+
+  let mut rest = bytes;
+  if take(&mut rest, 4) != Some(&b"ptau"[..]) {
+    return Err(invalid("magic number"));
+  }
+  let _version = read_u32_le(&mut rest).ok_or_else(truncated)?;
+  let num_sections = read_u32_le(&mut rest).ok_or_else(truncated)?;
+
+  let mut sections = Vec::new();
+  for _ in 0..num_sections {
+    let id = read_u32_le(&mut rest).ok_or_else(truncated)?;
+    let size = read_u64_le(&mut rest).ok_or_else(truncated)?;
+    let data = usize::try_from(size).ok().and_then(|size| take(&mut rest, size));
+    sections.push((id, data.ok_or_else(truncated)?));
+  }
+  let section = |id: u32| {
+    sections.iter().find(|(s, _)| *s == id).map(|(_, data)| *data).ok_or(invalid("missing section"))
+  };
+
+  let mut header = section(1)?;
+  let n8 = read_u32_le(&mut header).ok_or_else(truncated)?;
+  if n8 != 32 || take(&mut header, 32) != Some(&BN254_BASE_MODULUS[..]) {
+    return Err(invalid("not a BN254 ceremony"));
+  }
+
+  let tau_g1 = section(2)?;
+  let tau_g2 = section(3)?;
+  let requested = max_degree + 1;
+  let available = tau_g1.len() / G1_SIZE;
+  if available < requested {
+    return Err(SrsError::NotEnoughPowers { available, requested });
+  }
+  if tau_g2.len() < 2 * G2_SIZE {
+    return Err(invalid("fewer than two G2 points"));
+  }
+
+  let powers_of_g = tau_g1[..requested * G1_SIZE]
+    .par_chunks(G1_SIZE)
+    .enumerate()
+    .map(|(i, point)| G1Affine::from_raw_bytes(point).ok_or(SrsError::InvalidPoint(i)))
+    .collect::<Result<Vec<_>, _>>()?;
+  let powers_of_h = tau_g2[..2 * G2_SIZE]
+    .chunks(G2_SIZE)
+    .enumerate()
+    .map(|(i, point)| G2Affine::from_raw_bytes(point).ok_or(SrsError::InvalidPoint(i)))
+    .collect::<Result<Vec<_>, _>>()?;
+
+  checked_params(powers_of_g, powers_of_h)
+}
+
+/// Loads the first `max_degree + 1` powers from Aztec Ignition transcripts
+///
+/// `transcripts` are the contents of `transcript00.dat`, `transcript01.dat`,
+/// ... in order; only as many as are needed for `max_degree` have to be
+/// given. The transcripts start at `τ G`, so the generator is prepended, and
+/// the first G2 point of `transcript00.dat` is `τ H`. Each transcript starts
+/// with a manifest of seven big-endian `u32`s, followed by the G1 and G2
+/// points, whose coordinates are stored as four big-endian 64-bit limbs,
+/// least significant limb first.
+pub fn from_ignition<T: AsRef<[u8]>>(
+  transcripts: &[T],
+  max_degree: usize,
+) -> Result<UniversalKZGParam<Bn256>, SrsError> {
+  let invalid = |reason| SrsError::InvalidFormat("Ignition transcript", reason);
+  let truncated = || invalid("truncated");
+
+  let requested = max_degree + 1;
+  let mut powers_of_g = vec![G1Affine::generator()];
+  let mut tau_h = None;
+
+  for transcript in transcripts {
+    if powers_of_g.len() == requested && tau_h.is_some() {
+      break;
+    }
+
+    let mut rest = transcript.as_ref();
+    let mut manifest = [0u32; 7];
+    for field in &mut manifest {
+      *field = read_u32_be(&mut rest).ok_or_else(truncated)?;
+    }
+    let [_, _, _, _, num_g1_points, num_g2_points, start_from] = manifest.map(|f| f as usize);
+    if start_from + 1 != powers_of_g.len() {
+      return Err(invalid("transcripts out of order"));
+    }
+
+    let g1_points = take(&mut rest, num_g1_points * G1_SIZE).ok_or_else(truncated)?;
+    let g2_points = take(&mut rest, num_g2_points * G2_SIZE).ok_or_else(truncated)?;
+
+    let needed = (requested - powers_of_g.len()).min(num_g1_points);
+    let offset = powers_of_g.len();
+    let points = g1_points[..needed * G1_SIZE]
+      .par_chunks(G1_SIZE)
+      .enumerate()
+      .map(|(i, point)| ignition_g1(point).ok_or(SrsError::InvalidPoint(offset + i)))
+      .collect::<Result<Vec<_>, _>>()?;
+    powers_of_g.extend(points);
+
+    if tau_h.is_none() && num_g2_points > 0 {
+      tau_h = Some(ignition_g2(&g2_points[..G2_SIZE]).ok_or(SrsError::InvalidPoint(1))?);
+    }
+  }
+
+  if powers_of_g.len() < requested {
+    return Err(SrsError::NotEnoughPowers { available: powers_of_g.len(), requested });
+  }
+  let tau_h = tau_h.ok_or(invalid("missing G2 point"))?;
+  let powers_of_h = vec![G2Affine::generator(), tau_h];
+
+  checked_params(powers_of_g, powers_of_h)
+}
+
+/// Builds the parameters and checks that they are successive powers of `τ`
+fn checked_params(
+  powers_of_g: Vec<G1Affine>,
+  powers_of_h: Vec<G2Affine>,
+) -> Result<UniversalKZGParam<Bn256>, SrsError> {
+  let params = UniversalKZGParam { powers_of_g: powers_of_g.into(), powers_of_h };
+  if !params.check_powers() {
+    return Err(SrsError::InconsistentPowers);
+  }
+  Ok(params)
+}
+
+/// Decodes an Ignition base field element
+fn ignition_fq(bytes: &[u8]) -> Option<Fq> {
+  let mut repr = [0u8; 32];
+  for (limb, out) in bytes.chunks_exact(8).zip(repr.chunks_exact_mut(8)) {
+    out.copy_from_slice(&u64::from_be_bytes(limb.try_into().unwrap()).to_le_bytes());
+  }
+  Fq::from_repr(repr).into()
+}
+
+/// Decodes an Ignition G1 point
+fn ignition_g1(bytes: &[u8]) -> Option<G1Affine> {
+  let x = ignition_fq(&bytes[..32])?;
+  let y = ignition_fq(&bytes[32..])?;
+  G1Affine::from_xy(x, y).into()
+}
+
+/// Decodes an Ignition G2 point
+fn ignition_g2(bytes: &[u8]) -> Option<G2Affine> {
+  let coordinates = bytes.chunks_exact(32).map(ignition_fq).collect::<Option<Vec<_>>>()?;
+  let x = Fq2 { c0: coordinates[0], c1: coordinates[1] };
+  let y = Fq2 { c0: coordinates[2], c1: coordinates[3] };
+  G2Affine::from_xy(x, y).into()
+}
+
+/// Splits off the first `n` bytes of `bytes`
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+  if bytes.len() < n {
+    return None;
+  }
+  let (head, tail) = bytes.split_at(n);
+  *bytes = tail;
+  Some(head)
+}
+
+/// Reads a little-endian `u32`
+fn read_u32_le(bytes: &mut &[u8]) -> Option<u32> {
+  take(bytes, 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+}
+
+/// Reads a big-endian `u32`
+fn read_u32_be(bytes: &mut &[u8]) -> Option<u32> {
+  take(bytes, 4).map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+}
+
+/// Reads a little-endian `u64`
+fn read_u64_le(bytes: &mut &[u8]) -> Option<u64> {
+  take(bytes, 8).map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+  use ff::Field;
+  use group::{Curve, Group};
+  use halo2curves::bn256::{Fr, G1, G2};
+  use rand::rngs::OsRng;
+
+  use super::*;
+
+  const DEGREE: usize = 16;
+
+  fn powers(tau: Fr) -> (Vec<G1Affine>, Vec<G2Affine>) {
+    let powers = std::iter::successors(Some(Fr::ONE), |acc| Some(*acc * tau))
+      .take(2 * DEGREE)
+      .collect::<Vec<_>>();
+    let g1 = powers.iter().map(|p| (G1::generator() * p).to_affine()).collect();
+    let g2 = powers[..2].iter().map(|p| (G2::generator() * p).to_affine()).collect();
+    (g1, g2)
+  }
+
+  fn ptau(g1: &[G1Affine], g2: &[G2Affine]) -> Vec<u8> {
+    let mut header = 32u32.to_le_bytes().to_vec();
+    header.extend_from_slice(&BN254_BASE_MODULUS);
+    header.extend_from_slice(&5u32.to_le_bytes()); // power
+    header.extend_from_slice(&5u32.to_le_bytes()); // ceremony power
+    let sections = [
+      (1u32, header),
+      (2, g1.iter().flat_map(|p| p.to_raw_bytes()).collect()),
+      (3, g2.iter().flat_map(|p| p.to_raw_bytes()).collect()),
+    ];
+
+    let mut out = b"ptau".to_vec();
+    out.extend_from_slice(&1u32.to_le_bytes());
+    out.extend_from_slice(&(sections.len() as u32).to_le_bytes());
+    for (id, data) in sections {
+      out.extend_from_slice(&id.to_le_bytes());
+      out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+      out.extend_from_slice(&data);
+    }
+    out
+  }
+
+  fn ignition_coordinate(f: &Fq) -> Vec<u8> {
+    f.to_repr()
+      .chunks_exact(8)
+      .flat_map(|limb| u64::from_le_bytes(limb.try_into().unwrap()).to_be_bytes())
+      .collect()
+  }
+
+  fn ignition(g1: &[G1Affine], g2: &G2Affine, start_from: usize) -> Vec<u8> {
+    let num_g2_points = u32::from(start_from == 0);
+    let mut out = [0, 0, 0, 0, g1.len() as u32, num_g2_points, start_from as u32]
+      .iter()
+      .flat_map(|f| f.to_be_bytes())
+      .collect::<Vec<_>>();
+    for p in g1 {
+      out.extend(ignition_coordinate(&p.x));
+      out.extend(ignition_coordinate(&p.y));
+    }
+    if start_from == 0 {
+      for f in [&g2.x.c0, &g2.x.c1, &g2.y.c0, &g2.y.c1] {
+        out.extend(ignition_coordinate(f));
+      }
+    }
+    out
+  }
+
+  #[test]
+  fn test_bn254_base_modulus() {
+    let mut q_minus_one = BN254_BASE_MODULUS;
+    q_minus_one[0] -= 1;
+    assert_eq!((-Fq::ONE).to_repr(), q_minus_one);
+  }
+
+  #[test]
+  fn test_from_ptau() {
+    let (g1, g2) = powers(Fr::random(OsRng));
+    let bytes = ptau(&g1, &g2);
+
+    let srs = from_ptau(&bytes, DEGREE).unwrap();
+    assert_eq!(*srs.powers_of_g, g1[..=DEGREE]);
+    assert_eq!(srs.powers_of_h, g2);
+
+    assert!(matches!(
+      from_ptau(&bytes, 2 * DEGREE),
+      Err(SrsError::NotEnoughPowers { available, requested })
+        if available == 2 * DEGREE && requested == 2 * DEGREE + 1
+    ));
+
+    // a G1 point from a different secret breaks the chain of powers
+    let mut tampered = g1.clone();
+    tampered[3] = powers(Fr::random(OsRng)).0[3];
+    assert!(matches!(from_ptau(&ptau(&tampered, &g2), DEGREE), Err(SrsError::InconsistentPowers)));
+
+    // a point off the curve is rejected while decoding
+    let mut off_curve = bytes.clone();
+    let first_power = off_curve.len() - 2 * G2_SIZE - 8 - 4 - (2 * DEGREE - 1) * G1_SIZE;
+    off_curve[first_power] ^= 1;
+    assert!(matches!(from_ptau(&off_curve, DEGREE), Err(SrsError::InvalidPoint(1))));
+  }
+
+  #[test]
+  fn test_from_ignition() {
+    let (g1, g2) = powers(Fr::random(OsRng));
+    // Ignition transcripts start at τ G and are split across files
+    let transcripts = [ignition(&g1[1..10], &g2[1], 0), ignition(&g1[10..], &g2[1], 9)];
+
+    let srs = from_ignition(&transcripts, DEGREE).unwrap();
+    assert_eq!(*srs.powers_of_g, g1[..=DEGREE]);
+    assert_eq!(srs.powers_of_h, g2);
+
+    // the first transcript alone is enough for small degrees
+    let srs = from_ignition(&transcripts[..1], 4).unwrap();
+    assert_eq!(*srs.powers_of_g, g1[..=4]);
+
+    assert!(matches!(
+      from_ignition(&transcripts[..1], DEGREE),
+      Err(SrsError::NotEnoughPowers { available: 10, .. })
+    ));
+    assert!(matches!(
+      from_ignition(&[&transcripts[1], &transcripts[0]], DEGREE),
+      Err(SrsError::InvalidFormat(..))
+    ));
+  }
+}
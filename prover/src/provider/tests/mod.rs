@@ -1,4 +1,6 @@
 mod ipa_pc;
+mod pasta;
+mod secp_secq;
 
 #[cfg(test)]
 pub mod solidity_compatibility_utils {
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod test {
+  use std::marker::PhantomData;
+
+  use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+  use ff::{Field, PrimeField};
+
+  use crate::{
+    cyclefold,
+    provider::{ipa_pc, PallasEngine},
+    spartan::snark::RelaxedR1CSSNARK,
+    supernova::{circuit::TrivialCircuit, StepCircuit},
+    traits::{snark::RelaxedR1CSSNARKTrait, CurveCycleEquipped, Dual, Engine},
+    CompressedSNARK, PublicParams, RecursiveSNARK,
+  };
+
+  type EE<E> = ipa_pc::EvaluationEngine<E>;
+  type S<E> = RelaxedR1CSSNARK<E, EE<E>>;
+
+  const NUM_STEPS: usize = 3;
+
+  #[derive(Clone, Debug, Default)]
+  struct CubicCircuit<F> {
+    _p: PhantomData<F>,
+  }
+
+  impl<F: PrimeField> CubicCircuit<F> {
+    fn output(z: F) -> F { z * z * z + z + F::from(5u64) }
+  }
+
+  impl<F: PrimeField> StepCircuit<F> for CubicCircuit<F> {
+    fn arity(&self) -> usize { 1 }
+
+    fn circuit_index(&self) -> usize { 0 }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+      &self,
+      cs: &mut CS,
+      pc: Option<&AllocatedNum<F>>,
+      z: &[AllocatedNum<F>],
+    ) -> Result<(Option<AllocatedNum<F>>, Vec<AllocatedNum<F>>), SynthesisError> {
+      // Consider a cubic equation: `x^3 + x + 5 = y`, where `x` and `y` are
+      // respectively the input and output.
+      let x = &z[0];
+      let x_sq = x.square(cs.namespace(|| "x_sq"))?;
+      let x_cu = x_sq.mul(cs.namespace(|| "x_cu"), x)?;
+      let y = AllocatedNum::alloc(cs.namespace(|| "y"), || {
+        x.get_value().map(Self::output).ok_or(SynthesisError::AssignmentMissing)
+      })?;
+
+      cs.enforce(
+        || "y = x^3 + x + 5",
+        |lc| lc + x_cu.get_variable() + x.get_variable() + (F::from(5u64), CS::one()),
+        |lc| lc + CS::one(),
+        |lc| lc + y.get_variable(),
+      );
+
+      Ok((pc.cloned(), vec![y]))
+    }
+  }
+
+  fn expected_output<F: PrimeField>(z0: F) -> F {
+    (0..NUM_STEPS).fold(z0, |z, _| CubicCircuit::<F>::output(z))
+  }
+
+  fn test_nova_with<E1: CurveCycleEquipped>()
+  where
+    S<E1>: RelaxedR1CSSNARKTrait<E1>,
+    S<Dual<E1>>: RelaxedR1CSSNARKTrait<Dual<E1>>, {
+    let circuit_primary = CubicCircuit::default();
+    let circuit_secondary = TrivialCircuit::default();
+
+    let pp = PublicParams::<E1>::setup(
+      &circuit_primary,
+      &circuit_secondary,
+      &*S::<E1>::ck_floor(),
+      &*S::<Dual<E1>>::ck_floor(),
+    )
+    .unwrap();
+
+    let z0_primary = vec![E1::Scalar::ONE];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::ZERO];
+
+    let mut recursive_snark =
+      RecursiveSNARK::new(&pp, &circuit_primary, &circuit_secondary, &z0_primary, &z0_secondary)
+        .unwrap();
+    for i in 0..NUM_STEPS {
+      recursive_snark.prove_step(&pp, &circuit_primary, &circuit_secondary).unwrap();
+      recursive_snark.verify(&pp, i + 1, &z0_primary, &z0_secondary).unwrap();
+    }
+
+    let (pk, vk) = CompressedSNARK::<_, S<E1>, S<Dual<E1>>>::setup(&pp).unwrap();
+    let compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+    let (zn_primary, zn_secondary) =
+      compressed_snark.verify(&vk, NUM_STEPS, &z0_primary, &z0_secondary).unwrap();
+
+    assert_eq!(zn_primary, vec![expected_output(E1::Scalar::ONE)]);
+    assert_eq!(zn_secondary, z0_secondary);
+  }
+
+  fn test_cyclefold_with<E1: CurveCycleEquipped>()
+  where
+    S<E1>: RelaxedR1CSSNARKTrait<E1>,
+    S<Dual<E1>>: RelaxedR1CSSNARKTrait<Dual<E1>>, {
+    let circuit_primary = CubicCircuit::default();
+
+    let pp = cyclefold::snark::PublicParams::<E1>::setup(
+      &circuit_primary,
+      &*S::<E1>::ck_floor(),
+      &*S::<Dual<E1>>::ck_floor(),
+    );
+
+    let z0_primary = vec![E1::Scalar::ONE];
+
+    let mut recursive_snark =
+      cyclefold::snark::RecursiveSNARK::new(&pp, &circuit_primary, &z0_primary).unwrap();
+    for i in 0..NUM_STEPS {
+      recursive_snark.prove_step(&pp, &circuit_primary).unwrap();
+      recursive_snark.verify(&pp, i + 1, &z0_primary).unwrap();
+    }
+
+    let (pk, vk) = cyclefold::snark::CompressedSNARK::<_, S<E1>, S<Dual<E1>>>::setup(&pp).unwrap();
+    let compressed_snark =
+      cyclefold::snark::CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+    let zn_primary = compressed_snark.verify(&pp, &vk, &z0_primary).unwrap();
+
+    assert_eq!(zn_primary, vec![expected_output(E1::Scalar::ONE)]);
+  }
+
+  #[test]
+  fn test_pasta_nova() { test_nova_with::<PallasEngine>(); }
+
+  #[test]
+  fn test_pasta_cyclefold() { test_cyclefold_with::<PallasEngine>(); }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod test {
+  use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+  use ff::Field;
+  use group::Group as _;
+
+  use crate::{
+    gadgets::ecc::AllocatedPoint,
+    provider::{ipa_pc, secp_secq::secp256k1, traits::DlogGroup, Secq256k1Engine},
+    spartan::snark::RelaxedR1CSSNARK,
+    supernova::{circuit::TrivialCircuit, StepCircuit},
+    traits::{snark::RelaxedR1CSSNARKTrait, Dual, Engine},
+    PublicParams, RecursiveSNARK,
+  };
+
+  type EE<E> = ipa_pc::EvaluationEngine<E>;
+  type S<E> = RelaxedR1CSSNARK<E, EE<E>>;
+
+  const NUM_STEPS: usize = 3;
+
+  /// Doubles a secp256k1 point, given as `(x, y, is_infinity)`, at every step.
+  /// Since the primary scalar field is the base field of secp256k1, this needs
+  /// no non-native arithmetic.
+  #[derive(Clone, Debug, Default)]
+  struct DoublingCircuit;
+
+  impl StepCircuit<secp256k1::Base> for DoublingCircuit {
+    fn arity(&self) -> usize { 3 }
+
+    fn circuit_index(&self) -> usize { 0 }
+
+    fn synthesize<CS: ConstraintSystem<secp256k1::Base>>(
+      &self,
+      cs: &mut CS,
+      pc: Option<&AllocatedNum<secp256k1::Base>>,
+      z: &[AllocatedNum<secp256k1::Base>],
+    ) -> Result<
+      (Option<AllocatedNum<secp256k1::Base>>, Vec<AllocatedNum<secp256k1::Base>>),
+      SynthesisError,
+    > {
+      let p = AllocatedPoint::<secp256k1::Point>::from_coordinates(
+        cs.namespace(|| "p"),
+        z[0].clone(),
+        z[1].clone(),
+        z[2].clone(),
+      );
+      p.check_on_curve(cs.namespace(|| "p on curve"))?;
+      let p2 = p.double(cs.namespace(|| "double p"))?;
+
+      let (x, y, is_infinity) = p2.get_coordinates();
+      Ok((pc.cloned(), vec![x.clone(), y.clone(), is_infinity.clone()]))
+    }
+  }
+
+  fn to_io(p: secp256k1::Point) -> Vec<secp256k1::Base> {
+    let (x, y, is_infinity) = p.to_coordinates();
+    vec![x, y, if is_infinity { secp256k1::Base::ONE } else { secp256k1::Base::ZERO }]
+  }
+
+  #[test]
+  fn test_secq_nova_doubles_secp_point() {
+    type E1 = Secq256k1Engine;
+
+    let circuit_primary = DoublingCircuit;
+    let circuit_secondary = TrivialCircuit::default();
+
+    let pp = PublicParams::<E1>::setup(
+      &circuit_primary,
+      &circuit_secondary,
+      &*S::<E1>::ck_floor(),
+      &*S::<Dual<E1>>::ck_floor(),
+    )
+    .unwrap();
+
+    let g = secp256k1::Point::generator();
+    let z0_primary = to_io(g);
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::ZERO];
+
+    let mut recursive_snark =
+      RecursiveSNARK::new(&pp, &circuit_primary, &circuit_secondary, &z0_primary, &z0_secondary)
+        .unwrap();
+    for i in 0..NUM_STEPS {
+      recursive_snark.prove_step(&pp, &circuit_primary, &circuit_secondary).unwrap();
+      recursive_snark.verify(&pp, i + 1, &z0_primary, &z0_secondary).unwrap();
+    }
+
+    let (zn_primary, _) =
+      recursive_snark.verify(&pp, NUM_STEPS, &z0_primary, &z0_secondary).unwrap();
+    let expected = (0..NUM_STEPS).fold(g, |p, _| p.double());
+    assert_eq!(zn_primary, to_io(expected));
+  }
+}
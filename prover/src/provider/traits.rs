@@ -50,7 +50,7 @@ pub trait DlogGroup:
 /// curves in:
 // - to_coordinates,
 // - vartime_multiscalar_mul, where it does not call into accelerated implementations.
-// A specific reimplementation exists for the pasta curves in their own module.
+// The pasta curves reuse it with a local wrapper as their compressed representation.
 #[macro_export]
 macro_rules! impl_traits {
   ($name:ident, $order_str:literal, $base_str:literal) => {
@@ -9,12 +9,14 @@ use once_cell::sync::OnceCell;
 use rand_core::{CryptoRng, RngCore};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-pub(crate) use sparse::SparseMatrix;
+pub use sparse::SparseMatrix;
 
 use crate::{
+  abi::{self, AbiEncode, Word},
   constants::{BN_LIMB_WIDTH, BN_N_LIMBS},
   digest::{DigestComputer, SimpleDigestible},
   errors::NovaError,
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
   gadgets::{f_to_nat, nat_to_limbs, scalar_as_base},
   traits::{
     commitment::CommitmentEngineTrait, AbsorbInROTrait, Engine, ROTrait, TranscriptReprTrait,
@@ -48,7 +50,7 @@ pub struct R1CSResult<E: Engine> {
 /// A type that holds a witness for a given R1CS instance
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct R1CSWitness<E: Engine> {
-  W: Vec<E::Scalar>,
+  pub(crate) W: Vec<E::Scalar>,
 }
 
 /// A type that holds an R1CS instance
@@ -280,7 +282,7 @@ impl<E: Engine> R1CSShape<E> {
   }
 
   /// Computes the error term E = Az * Bz - u*Cz.
-  fn compute_E(
+  pub(crate) fn compute_E(
     &self,
     W: &[E::Scalar],
     u: &E::Scalar,
@@ -453,6 +455,75 @@ impl<E: Engine> R1CSShape<E> {
     Ok(CE::<E>::commit(ck, T))
   }
 
+  /// A method to compute a commitment to the cross-term `T` in chunks of
+  /// `chunk_len` constraints
+  ///
+  /// This is [`R1CSShape::commit_T_into`] without materializing `T`: each chunk
+  /// is computed from the products left in the buffers and committed at its
+  /// offset, so at most `chunk_len` entries of `T` are alive at a time. The
+  /// witness is then folded with [`RelaxedR1CSWitness::fold_mut_chunked`],
+  /// which recomputes the entries from the same buffers.
+  pub fn commit_T_chunked(
+    &self,
+    ck: &CommitmentKey<E>,
+    U1: &RelaxedR1CSInstance<E>,
+    W1: &RelaxedR1CSWitness<E>,
+    U2: &R1CSInstance<E>,
+    W2: &R1CSWitness<E>,
+    ABC_Z_1: &mut R1CSResult<E>,
+    ABC_Z_2: &mut R1CSResult<E>,
+    chunk_len: usize,
+  ) -> Result<Commitment<E>, NovaError> {
+    tracing::info_span!("AZ_1, BZ_1, CZ_1")
+      .in_scope(|| self.multiply_witness_into(&W1.W, &U1.u, &U1.X, ABC_Z_1))?;
+    tracing::info_span!("AZ_2, BZ_2, CZ_2")
+      .in_scope(|| self.multiply_witness_into(&W2.W, &E::Scalar::ONE, &U2.X, ABC_Z_2))?;
+
+    let chunk_len = chunk_len.max(1);
+    let num_cons = ABC_Z_1.AZ.len();
+    let mut T = Vec::with_capacity(chunk_len.min(num_cons));
+    let mut comm_T = Commitment::<E>::default();
+    tracing::info_span!("T").in_scope(|| {
+      for start in (0..num_cons).step_by(chunk_len) {
+        let end = (start + chunk_len).min(num_cons);
+        (start..end)
+          .into_par_iter()
+          .map(|i| cross_term(ABC_Z_1, ABC_Z_2, &U1.u, i))
+          .collect_into_vec(&mut T);
+        comm_T = comm_T + CE::<E>::commit_at(ck, &T, start);
+      }
+    });
+
+    Ok(comm_T)
+  }
+
+  /// A method to compute a commitment to the cross-term `T` given two
+  /// Relaxed R1CS instance-witness pairs
+  pub fn commit_T_relaxed(
+    &self,
+    ck: &CommitmentKey<E>,
+    U1: &RelaxedR1CSInstance<E>,
+    W1: &RelaxedR1CSWitness<E>,
+    U2: &RelaxedR1CSInstance<E>,
+    W2: &RelaxedR1CSWitness<E>,
+  ) -> Result<(Vec<E::Scalar>, Commitment<E>), NovaError> {
+    let (res_1, res_2) = rayon::join(
+      || self.multiply_witness(&W1.W, &U1.u, &U1.X),
+      || self.multiply_witness(&W2.W, &U2.u, &U2.X),
+    );
+    let ((AZ_1, BZ_1, CZ_1), (AZ_2, BZ_2, CZ_2)) = (res_1?, res_2?);
+
+    // T = AZ_1 ∘ BZ_2 + AZ_2 ∘ BZ_1 - u_1 * CZ_2 - u_2 * CZ_1
+    let T = (0..AZ_1.len())
+      .into_par_iter()
+      .map(|i| AZ_1[i] * BZ_2[i] + AZ_2[i] * BZ_1[i] - U1.u * CZ_2[i] - U2.u * CZ_1[i])
+      .collect::<Vec<E::Scalar>>();
+
+    let comm_T = CE::<E>::commit(ck, &T);
+
+    Ok((T, comm_T))
+  }
+
   /// Pads the `R1CSShape` so that the shape passes `is_regular_shape`
   /// Renumbers variables to accommodate padded variables
   pub fn pad(&self) -> Self {
@@ -556,6 +627,12 @@ impl<E: Engine> R1CSInstance<E> {
   }
 }
 
+impl<E: Engine> TranscriptReprTrait<E::GE> for R1CSInstance<E> {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    [self.comm_W.to_transcript_bytes(), self.X.as_slice().to_transcript_bytes()].concat()
+  }
+}
+
 impl<E: Engine> AbsorbInROTrait<E> for R1CSInstance<E> {
   fn absorb_in_ro(&self, ro: &mut E::RO) {
     self.comm_W.absorb_in_ro(ro);
@@ -617,6 +694,54 @@ impl<E: Engine> RelaxedR1CSWitness<E> {
     Ok(())
   }
 
+  /// Folds an incoming `R1CSWitness` into the current one, recomputing the
+  /// cross-term `T` from the products [`R1CSShape::commit_T_chunked`] left in
+  /// the buffers
+  ///
+  /// `u_1` is the `u` of the running instance before it was folded.
+  pub fn fold_mut_chunked(
+    &mut self,
+    W2: &R1CSWitness<E>,
+    ABC_Z_1: &R1CSResult<E>,
+    ABC_Z_2: &R1CSResult<E>,
+    u_1: &E::Scalar,
+    r: &E::Scalar,
+  ) -> Result<(), NovaError> {
+    if self.W.len() != W2.W.len() || self.E.len() != ABC_Z_1.AZ.len() {
+      return Err(NovaError::InvalidWitnessLength);
+    }
+
+    self.W.par_iter_mut().zip_eq(&W2.W).for_each(|(a, b)| *a += *r * *b);
+    self
+      .E
+      .par_iter_mut()
+      .enumerate()
+      .for_each(|(i, a)| *a += *r * cross_term(ABC_Z_1, ABC_Z_2, u_1, i));
+
+    Ok(())
+  }
+
+  /// Folds an incoming `RelaxedR1CSWitness` into the current one
+  pub fn fold_relaxed(
+    &self,
+    W2: &RelaxedR1CSWitness<E>,
+    T: &[E::Scalar],
+    r: &E::Scalar,
+  ) -> Result<Self, NovaError> {
+    let (W1, E1) = (&self.W, &self.E);
+    let (W2, E2) = (&W2.W, &W2.E);
+
+    if W1.len() != W2.len() || E1.len() != E2.len() {
+      return Err(NovaError::InvalidWitnessLength);
+    }
+
+    let r_sq = r.square();
+    let W = zip_with!((W1.par_iter(), W2), |a, b| *a + *r * *b).collect::<Vec<E::Scalar>>();
+    let E = zip_with!((E1.par_iter(), T, E2), |a, b, c| *a + *r * *b + r_sq * *c)
+      .collect::<Vec<E::Scalar>>();
+    Ok(Self { W, E })
+  }
+
   /// Pads the provided witness to the correct length
   pub fn pad(&self, S: &R1CSShape<E>) -> Self {
     let mut W = self.W.clone();
@@ -678,6 +803,24 @@ impl<E: Engine> RelaxedR1CSInstance<E> {
     Self { comm_W, comm_E, X, u }
   }
 
+  /// Folds an incoming `RelaxedR1CSInstance` into the current one
+  pub fn fold_relaxed(
+    &self,
+    U2: &RelaxedR1CSInstance<E>,
+    comm_T: &Commitment<E>,
+    r: &E::Scalar,
+  ) -> Self {
+    let r_sq = r.square();
+
+    // weighted sum of X, comm_W, comm_E, and u
+    let X = zip_with!((self.X.par_iter(), &U2.X), |a, b| *a + *r * *b).collect::<Vec<E::Scalar>>();
+    let comm_W = self.comm_W + U2.comm_W * *r;
+    let comm_E = self.comm_E + *comm_T * *r + U2.comm_E * r_sq;
+    let u = self.u + *r * U2.u;
+
+    Self { comm_W, comm_E, X, u }
+  }
+
   /// Mutably folds an incoming `RelaxedR1CSInstance` into the current one
   pub fn fold_mut(&mut self, U2: &R1CSInstance<E>, comm_T: &Commitment<E>, r: &E::Scalar) {
     let (X2, comm_W_2) = (&U2.X, &U2.comm_W);
@@ -720,9 +863,172 @@ impl<E: Engine> AbsorbInROTrait<E> for RelaxedR1CSInstance<E> {
   }
 }
 
+/// Returns the `i`-th entry of the cross-term `T` from the products of the
+/// running pair in `ABC_Z_1` and of the incoming pair in `ABC_Z_2`
+fn cross_term<E: Engine>(
+  ABC_Z_1: &R1CSResult<E>,
+  ABC_Z_2: &R1CSResult<E>,
+  u_1: &E::Scalar,
+  i: usize,
+) -> E::Scalar {
+  ABC_Z_1.AZ[i] * ABC_Z_2.BZ[i] + ABC_Z_2.AZ[i] * ABC_Z_1.BZ[i]
+    - (*u_1 * ABC_Z_2.CZ[i] + ABC_Z_1.CZ[i])
+}
+
 /// Empty buffer for `commit_T_into`
 pub fn default_T<E: Engine>(num_cons: usize) -> Vec<E::Scalar> { Vec::with_capacity(num_cons) }
 
+impl<E: Engine> FastSerde for R1CSWitness<E> {
+  /// Sections:
+  ///   1: W (scalars)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::R1CSWitness);
+    out.section(1, fast_serde::scalars_to_bytes(&self.W));
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::R1CSWitness)?;
+
+    let W = fast_serde::scalars_from_bytes(sections.section(1)?)?;
+
+    sections.finish()?;
+    Ok(Self { W })
+  }
+}
+
+impl<E: Engine> FastSerde for RelaxedR1CSWitness<E> {
+  /// Sections:
+  ///   1: W (scalars)
+  ///   2: E (scalars)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::RelaxedR1CSWitness);
+    out.section(1, fast_serde::scalars_to_bytes(&self.W));
+    out.section(2, fast_serde::scalars_to_bytes(&self.E));
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::RelaxedR1CSWitness)?;
+
+    let W = fast_serde::scalars_from_bytes(sections.section(1)?)?;
+    let E = fast_serde::scalars_from_bytes(sections.section(2)?)?;
+
+    sections.finish()?;
+    Ok(Self { W, E })
+  }
+}
+
+impl<E: Engine> FastSerde for R1CSInstance<E> {
+  /// Sections:
+  ///   1: comm_W (commitment)
+  ///   2: X (scalars)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::R1CSInstance);
+    out.section(1, fast_serde::commitments_to_bytes::<E>(&[self.comm_W]));
+    out.section(2, fast_serde::scalars_to_bytes(&self.X));
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::R1CSInstance)?;
+
+    let comm_W = fast_serde::commitment_from_bytes::<E>(sections.section(1)?)?;
+    let X = fast_serde::scalars_from_bytes(sections.section(2)?)?;
+
+    sections.finish()?;
+    Ok(Self { comm_W, X })
+  }
+}
+
+impl<E: Engine> FastSerde for RelaxedR1CSInstance<E> {
+  /// Sections:
+  ///   1: comm_W, comm_E (commitments)
+  ///   2: X (scalars)
+  ///   3: u (scalar)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::RelaxedR1CSInstance);
+    out.section(1, fast_serde::commitments_to_bytes::<E>(&[self.comm_W, self.comm_E]));
+    out.section(2, fast_serde::scalars_to_bytes(&self.X));
+    out.section(3, fast_serde::scalars_to_bytes(&[self.u]));
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::RelaxedR1CSInstance)?;
+
+    let [comm_W, comm_E]: [Commitment<E>; 2] =
+      fast_serde::commitments_from_bytes(sections.section(1)?)?
+        .try_into()
+        .map_err(|_| SerdeByteError::InvalidSectionSize)?;
+    let X = fast_serde::scalars_from_bytes(sections.section(2)?)?;
+    let u = fast_serde::scalar_from_bytes(sections.section(3)?)?;
+
+    sections.finish()?;
+    Ok(Self { comm_W, comm_E, X, u })
+  }
+}
+
+impl<E: Engine> AbiEncode for R1CSInstance<E> {
+  /// Words: comm_W (point), X (fields)
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    abi::encode_commitment::<E>(out, &self.comm_W);
+    abi::encode_fields(out, &self.X);
+    Ok(())
+  }
+}
+
+impl<E: Engine> AbiEncode for RelaxedR1CSInstance<E> {
+  /// Words: comm_W, comm_E (points), X (fields), u (field)
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    abi::encode_commitment::<E>(out, &self.comm_W);
+    abi::encode_commitment::<E>(out, &self.comm_E);
+    abi::encode_fields(out, &self.X);
+    out.push(abi::field(&self.u));
+    Ok(())
+  }
+}
+
+impl<E: Engine> FastSerde for R1CSShape<E> {
+  /// Sections:
+  ///   1: num_cons (u64)
+  ///   2: num_vars (u64)
+  ///   3: num_io (u64)
+  ///   4: A (FastSerde)
+  ///   5: B (FastSerde)
+  ///   6: C (FastSerde)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::R1CSShape);
+    out.section(1, (self.num_cons as u64).to_le_bytes());
+    out.section(2, (self.num_vars as u64).to_le_bytes());
+    out.section(3, (self.num_io as u64).to_le_bytes());
+    out.section(4, self.A.to_bytes());
+    out.section(5, self.B.to_bytes());
+    out.section(6, self.C.to_bytes());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::R1CSShape)?;
+
+    let num_cons = fast_serde::usize_from_bytes(sections.section(1)?)?;
+    let num_vars = fast_serde::usize_from_bytes(sections.section(2)?)?;
+    let num_io = fast_serde::usize_from_bytes(sections.section(3)?)?;
+    let A = SparseMatrix::from_bytes(sections.section(4)?)?;
+    let B = SparseMatrix::from_bytes(sections.section(5)?)?;
+    let C = SparseMatrix::from_bytes(sections.section(6)?)?;
+    sections.finish()?;
+
+    // `pad` may raise `num_cons` without adding empty rows, so only an excess of rows is invalid
+    let cols = num_vars + num_io + 1;
+    if [&A, &B, &C].iter().any(|M| M.num_rows() > num_cons || M.num_cols() != cols) {
+      return Err(SerdeByteError::InvalidR1CSShape);
+    }
+
+    Self::new(num_cons, num_vars, num_io, A, B, C).map_err(|_| SerdeByteError::InvalidR1CSShape)
+  }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
   use ff::Field;
@@ -830,4 +1136,52 @@ pub(crate) mod tests {
 
   #[test]
   fn test_random_r1cs() { test_random_r1cs_with::<Bn256EngineIPA>(); }
+
+  fn test_r1cs_shape_fast_serde_with<E: Engine>() {
+    let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+
+    let shapes = [
+      tiny_r1cs::<E>(3),
+      tiny_r1cs::<E>(3).pad(),
+      R1CSShape::<E>::random(256, 256, 2, 1024, &mut rng),
+    ];
+
+    for S in shapes {
+      let bytes = S.to_bytes();
+      let S2 = R1CSShape::<E>::from_bytes(&bytes).unwrap();
+      assert_eq!(S, S2);
+      assert_eq!(S.digest(), S2.digest());
+    }
+
+    // a truncated encoding must be rejected
+    let bytes = tiny_r1cs::<E>(3).to_bytes();
+    assert!(R1CSShape::<E>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+  }
+
+  #[test]
+  fn test_r1cs_shape_fast_serde() { test_r1cs_shape_fast_serde_with::<Bn256EngineKZG>(); }
+
+  fn test_r1cs_instance_fast_serde_with<E: Engine>() {
+    let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+
+    let ck = E::CE::setup(b"ipa", 1024);
+    let S = R1CSShape::<E>::random(256, 256, 2, 1024, &mut rng);
+    let (_, U) = S.random_witness_instance(&ck, &mut rng);
+
+    let bytes = U.to_bytes();
+    assert_eq!(RelaxedR1CSInstance::<E>::from_bytes(&bytes).unwrap(), U);
+
+    let u = R1CSInstance::<E> { comm_W: U.comm_W, X: U.X.clone() };
+    let bytes = u.to_bytes();
+    assert_eq!(R1CSInstance::<E>::from_bytes(&bytes).unwrap(), u);
+
+    // an instance is not a relaxed instance
+    assert!(RelaxedR1CSInstance::<E>::from_bytes(&bytes).is_err());
+  }
+
+  #[test]
+  fn test_r1cs_instance_fast_serde() {
+    test_r1cs_instance_fast_serde_with::<Bn256EngineIPA>();
+    test_r1cs_instance_fast_serde_with::<Bn256EngineKZG>();
+  }
 }
@@ -4,7 +4,10 @@
 //! Specifically, we implement sparse matrix / dense vector multiplication
 //! to compute the `A z`, `B z`, and `C z` in Nova.
 
-use std::{cmp::Ordering, collections::BTreeSet};
+use std::{
+  cmp::Ordering,
+  collections::{BTreeSet, HashMap},
+};
 
 use ff::PrimeField;
 use itertools::Itertools as _;
@@ -13,6 +16,10 @@ use rayon::prelude::*;
 use ref_cast::RefCast;
 use serde::{Deserialize, Serialize};
 
+use crate::fast_serde::{
+  self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes,
+};
+
 /// CSR format sparse matrix, We follow the names used by scipy.
 /// Detailed explanation here: <https://stackoverflow.com/questions/52299420/scipy-csr-matrix-understand-indptr>
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -220,6 +227,68 @@ impl<F: PrimeField> SparseMatrix<F> {
   pub fn num_cols(&self) -> usize { self.cols }
 }
 
+impl<F: PrimeField> FastSerde for SparseMatrix<F> {
+  /// Sections:
+  ///   1: cols (u64)
+  ///   2: indptr (u32 each)
+  ///   3: indices (u32 each)
+  ///   4: table of distinct values (scalars)
+  ///   5: data, as positions into the value table (u32 each)
+  ///
+  /// Circuit matrices hold few distinct coefficients, so storing each one once and referring to
+  /// it by position shrinks the encoding considerably compared to writing every entry in full.
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut table = Vec::new();
+    let mut positions = HashMap::new();
+    let data = self
+      .data
+      .iter()
+      .map(|value| {
+        *positions.entry(value.to_repr().as_ref().to_vec()).or_insert_with(|| {
+          table.push(*value);
+          table.len() - 1
+        })
+      })
+      .collect::<Vec<_>>();
+
+    let mut out = SectionWriter::new(SerdeByteTypes::SparseMatrix);
+    out.section(1, (self.cols as u64).to_le_bytes());
+    out.section(2, fast_serde::indices_to_bytes(&self.indptr));
+    out.section(3, fast_serde::indices_to_bytes(&self.indices));
+    out.section(4, fast_serde::scalars_to_bytes(&table));
+    out.section(5, fast_serde::indices_to_bytes(&data));
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::SparseMatrix)?;
+
+    let cols = fast_serde::usize_from_bytes(sections.section(1)?)?;
+    let indptr = fast_serde::indices_from_bytes(sections.section(2)?)?;
+    let indices = fast_serde::indices_from_bytes(sections.section(3)?)?;
+    let table: Vec<F> = fast_serde::scalars_from_bytes(sections.section(4)?)?;
+    let positions = fast_serde::indices_from_bytes(sections.section(5)?)?;
+    sections.finish()?;
+
+    // reject anything that would make the row iterators or the multiplication index out of bounds
+    if indptr.first() != Some(&0)
+      || indptr.last() != Some(&indices.len())
+      || !indptr.windows(2).all(|w| w[0] <= w[1])
+      || indices.len() != positions.len()
+      || indices.iter().any(|&col| col >= cols)
+    {
+      return Err(SerdeByteError::InvalidSparseMatrix);
+    }
+
+    let data = positions
+      .into_iter()
+      .map(|i| table.get(i).copied().ok_or(SerdeByteError::InvalidSparseMatrix))
+      .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Self { data, indices, indptr, cols })
+  }
+}
+
 /// Iterator for sparse matrix
 #[derive(Debug)]
 pub struct Iter<'a, F: PrimeField> {
@@ -8,7 +8,7 @@
 use core::slice;
 use std::{iter, sync::Arc};
 
-use ff::Field;
+use ff::{Field, PrimeField};
 use itertools::Itertools;
 use once_cell::sync::OnceCell;
 use rayon::prelude::*;
@@ -24,8 +24,11 @@ use super::{
   PolyEvalInstance, PolyEvalWitness,
 };
 use crate::{
+  abi::{self, AbiEncode, Word},
   digest::{DigestComputer, SimpleDigestible},
   errors::NovaError,
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
+  metrics::{self, Phase},
   r1cs::{R1CSShape, RelaxedR1CSInstance, RelaxedR1CSWitness, SparseMatrix},
   spartan::{
     polys::{multilinear::SparsePolynomial, power::PowPolynomial},
@@ -59,6 +62,83 @@ pub struct BatchedRelaxedR1CSSNARK<E: Engine, EE: EvaluationEngineTrait<E>> {
   eval_arg:       EE::EvaluationArgument,
 }
 
+impl<E: Engine, EE: EvaluationEngineTrait<E>> AbiEncode for BatchedRelaxedR1CSSNARK<E, EE>
+where EE::EvaluationArgument: AbiEncode
+{
+  /// Words: sc_proof_outer, claims_outer (fields, three per instance), evals_E
+  /// (fields), sc_proof_inner, evals_W (fields), sc_proof_batch, evals_batch
+  /// (fields), eval_arg
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    let claims_outer =
+      self.claims_outer.iter().flat_map(|&(Az, Bz, Cz)| [Az, Bz, Cz]).collect::<Vec<_>>();
+
+    self.sc_proof_outer.abi_encode(out)?;
+    abi::encode_fields(out, &claims_outer);
+    abi::encode_fields(out, &self.evals_E);
+    self.sc_proof_inner.abi_encode(out)?;
+    abi::encode_fields(out, &self.evals_W);
+    self.sc_proof_batch.abi_encode(out)?;
+    abi::encode_fields(out, &self.evals_batch);
+    self.eval_arg.abi_encode(out)
+  }
+}
+
+impl<E: Engine, EE: EvaluationEngineTrait<E>> FastSerde for BatchedRelaxedR1CSSNARK<E, EE> {
+  /// Sections:
+  ///   1: sc_proof_outer (FastSerde)
+  ///   2: claims_outer (scalars, three per instance)
+  ///   3: evals_E (scalars)
+  ///   4: sc_proof_inner (FastSerde)
+  ///   5: evals_W (scalars)
+  ///   6: sc_proof_batch (FastSerde)
+  ///   7: evals_batch (scalars)
+  ///   8: eval_arg (FastSerde)
+  fn to_bytes(&self) -> Vec<u8> {
+    let claims_outer =
+      self.claims_outer.iter().flat_map(|&(Az, Bz, Cz)| [Az, Bz, Cz]).collect::<Vec<_>>();
+
+    let mut out = SectionWriter::new(SerdeByteTypes::BatchedSpartanSNARK);
+    out.section(1, self.sc_proof_outer.to_bytes());
+    out.section(2, fast_serde::scalars_to_bytes(&claims_outer));
+    out.section(3, fast_serde::scalars_to_bytes(&self.evals_E));
+    out.section(4, self.sc_proof_inner.to_bytes());
+    out.section(5, fast_serde::scalars_to_bytes(&self.evals_W));
+    out.section(6, self.sc_proof_batch.to_bytes());
+    out.section(7, fast_serde::scalars_to_bytes(&self.evals_batch));
+    out.section(8, self.eval_arg.to_bytes());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::BatchedSpartanSNARK)?;
+
+    let sc_proof_outer = SumcheckProof::from_bytes(sections.section(1)?)?;
+    let claims_outer =
+      fast_serde::into_arrays::<_, 3>(fast_serde::scalars_from_bytes(sections.section(2)?)?)?
+        .into_iter()
+        .map(|[Az, Bz, Cz]| (Az, Bz, Cz))
+        .collect();
+    let evals_E = fast_serde::scalars_from_bytes(sections.section(3)?)?;
+    let sc_proof_inner = SumcheckProof::from_bytes(sections.section(4)?)?;
+    let evals_W = fast_serde::scalars_from_bytes(sections.section(5)?)?;
+    let sc_proof_batch = SumcheckProof::from_bytes(sections.section(6)?)?;
+    let evals_batch = fast_serde::scalars_from_bytes(sections.section(7)?)?;
+    let eval_arg = EE::EvaluationArgument::from_bytes(sections.section(8)?)?;
+
+    sections.finish()?;
+    Ok(Self {
+      sc_proof_outer,
+      claims_outer,
+      evals_E,
+      sc_proof_inner,
+      evals_W,
+      sc_proof_batch,
+      evals_batch,
+      eval_arg,
+    })
+  }
+}
+
 /// A type that represents the prover's key
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -67,6 +147,30 @@ pub struct ProverKey<E: Engine, EE: EvaluationEngineTrait<E>> {
   pub vk_digest: E::Scalar, // digest of the verifier's key
 }
 
+impl<E: Engine, EE: EvaluationEngineTrait<E>> FastSerde for ProverKey<E, EE>
+where EE::ProverKey: FastSerde
+{
+  /// Sections:
+  ///   1: pk_ee (FastSerde)
+  ///   2: vk_digest (scalar)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::SpartanProverKey);
+    out.section(1, self.pk_ee.to_bytes());
+    out.section(2, self.vk_digest.to_repr().as_ref().to_vec());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::SpartanProverKey)?;
+
+    let pk_ee = EE::ProverKey::from_bytes(sections.section(1)?)?;
+    let vk_digest = fast_serde::scalar_from_bytes(sections.section(2)?)?;
+
+    sections.finish()?;
+    Ok(Self { pk_ee, vk_digest })
+  }
+}
+
 /// A type that represents the verifier's key
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -205,17 +309,19 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> BatchedRelaxedR1CSSNARKTrait<E>
     let outer_r_powers = powers(&outer_r, num_instances);
 
     // Verify outer sumcheck: Az * Bz - uCz_E for each instance
-    let (sc_proof_outer, r_x, claims_outer) = SumcheckProof::prove_cubic_with_additive_term_batch(
-      &vec![E::Scalar::ZERO; num_instances],
-      &num_rounds_x,
-      polys_tau,
-      polys_Az.into_iter().map(MultilinearPolynomial::new).collect(),
-      polys_Bz.into_iter().map(MultilinearPolynomial::new).collect(),
-      polys_uCz_E.into_iter().map(MultilinearPolynomial::new).collect(),
-      &outer_r_powers,
-      comb_func_outer,
-      &mut transcript,
-    )?;
+    let (sc_proof_outer, r_x, claims_outer) = metrics::timed(Phase::Sumcheck, || {
+      SumcheckProof::prove_cubic_with_additive_term_batch(
+        &vec![E::Scalar::ZERO; num_instances],
+        &num_rounds_x,
+        polys_tau,
+        polys_Az.into_iter().map(MultilinearPolynomial::new).collect(),
+        polys_Bz.into_iter().map(MultilinearPolynomial::new).collect(),
+        polys_uCz_E.into_iter().map(MultilinearPolynomial::new).collect(),
+        &outer_r_powers,
+        comb_func_outer,
+        &mut transcript,
+      )
+    })?;
 
     let r_x = num_rounds_x
       .iter()
@@ -285,15 +391,17 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> BatchedRelaxedR1CSSNARKTrait<E>
     };
 
     let (sc_proof_inner, r_y, _claims_inner): (SumcheckProof<E>, Vec<E::Scalar>, (Vec<_>, Vec<_>)) =
-      SumcheckProof::prove_quad_batch(
-        &claims_inner_joint,
-        &num_rounds_y,
-        polys_ABCs,
-        polys_Z,
-        &inner_r_powers,
-        comb_func,
-        &mut transcript,
-      )?;
+      metrics::timed(Phase::Sumcheck, || {
+        SumcheckProof::prove_quad_batch(
+          &claims_inner_joint,
+          &num_rounds_y,
+          polys_ABCs,
+          polys_Z,
+          &inner_r_powers,
+          comb_func,
+          &mut transcript,
+        )
+      })?;
 
     let r_y = num_rounds_y
       .iter()
@@ -325,17 +433,19 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> BatchedRelaxedR1CSSNARKTrait<E>
     };
 
     let (batched_u, batched_w, sc_proof_batch, claims_batch_left) =
-      batch_eval_reduce(u_vec, &w_vec, &mut transcript)?;
+      metrics::timed(Phase::Sumcheck, || batch_eval_reduce(u_vec, &w_vec, &mut transcript))?;
 
-    let eval_arg = EE::prove(
-      ck,
-      &pk.pk_ee,
-      &mut transcript,
-      &batched_u.c,
-      &batched_w.p,
-      &batched_u.x,
-      &batched_u.e,
-    )?;
+    let eval_arg = metrics::timed(Phase::Pcs, || {
+      EE::prove(
+        ck,
+        &pk.pk_ee,
+        &mut transcript,
+        &batched_u.c,
+        &batched_w.p,
+        &batched_u.x,
+        &batched_u.e,
+      )
+    })?;
 
     Ok(Self {
       sc_proof_outer,
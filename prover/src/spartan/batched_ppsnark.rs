@@ -10,8 +10,11 @@ use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+  abi::{self, AbiEncode, Word},
   digest::{DigestComputer, SimpleDigestible},
   errors::NovaError,
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
+  metrics::{self, Phase},
   r1cs::{R1CSShape, RelaxedR1CSInstance, RelaxedR1CSWitness},
   spartan::{
     math::Math,
@@ -121,6 +124,101 @@ pub struct BatchedRelaxedR1CSSNARK<E: Engine, EE: EvaluationEngineTrait<E>> {
   eval_arg: EE::EvaluationArgument,
 }
 
+impl<E: Engine, EE: EvaluationEngineTrait<E>> AbiEncode for BatchedRelaxedR1CSSNARK<E, EE>
+where EE::EvaluationArgument: AbiEncode
+{
+  /// Words: comms_Az_Bz_Cz (points, three per instance), comms_L_row_col
+  /// (points, two per instance), comms_mem_oracles (points, four per instance),
+  /// evals_Az_Bz_Cz_at_tau (fields, three per instance), sc, evals_Az_Bz_Cz_W_E
+  /// (fields, five per instance), evals_L_row_col (fields, two per instance),
+  /// evals_mem_oracle (fields, four per instance), evals_mem_preprocessed
+  /// (fields, seven per instance), eval_arg
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    abi::encode_compressed_commitments::<E>(out, &self.comms_Az_Bz_Cz.concat())?;
+    abi::encode_compressed_commitments::<E>(out, &self.comms_L_row_col.concat())?;
+    abi::encode_compressed_commitments::<E>(out, &self.comms_mem_oracles.concat())?;
+    abi::encode_fields(out, &self.evals_Az_Bz_Cz_at_tau.concat());
+    self.sc.abi_encode(out)?;
+    abi::encode_fields(out, &self.evals_Az_Bz_Cz_W_E.concat());
+    abi::encode_fields(out, &self.evals_L_row_col.concat());
+    abi::encode_fields(out, &self.evals_mem_oracle.concat());
+    abi::encode_fields(out, &self.evals_mem_preprocessed.concat());
+    self.eval_arg.abi_encode(out)
+  }
+}
+
+impl<E: Engine, EE: EvaluationEngineTrait<E>> FastSerde for BatchedRelaxedR1CSSNARK<E, EE> {
+  /// Sections:
+  ///   1: comms_Az_Bz_Cz (compressed commitments, three per instance)
+  ///   2: comms_L_row_col (compressed commitments, two per instance)
+  ///   3: comms_mem_oracles (compressed commitments, four per instance)
+  ///   4: evals_Az_Bz_Cz_at_tau (scalars, three per instance)
+  ///   5: sc (FastSerde)
+  ///   6: evals_Az_Bz_Cz_W_E (scalars, five per instance)
+  ///   7: evals_L_row_col (scalars, two per instance)
+  ///   8: evals_mem_oracle (scalars, four per instance)
+  ///   9: evals_mem_preprocessed (scalars, seven per instance)
+  ///   10: eval_arg (FastSerde)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::BatchedSpartanPPSNARK);
+    out.section(1, fast_serde::compressed_commitments_to_bytes::<E>(&self.comms_Az_Bz_Cz.concat()));
+    out
+      .section(2, fast_serde::compressed_commitments_to_bytes::<E>(&self.comms_L_row_col.concat()));
+    out.section(
+      3,
+      fast_serde::compressed_commitments_to_bytes::<E>(&self.comms_mem_oracles.concat()),
+    );
+    out.section(4, fast_serde::scalars_to_bytes(&self.evals_Az_Bz_Cz_at_tau.concat()));
+    out.section(5, self.sc.to_bytes());
+    out.section(6, fast_serde::scalars_to_bytes(&self.evals_Az_Bz_Cz_W_E.concat()));
+    out.section(7, fast_serde::scalars_to_bytes(&self.evals_L_row_col.concat()));
+    out.section(8, fast_serde::scalars_to_bytes(&self.evals_mem_oracle.concat()));
+    out.section(9, fast_serde::scalars_to_bytes(&self.evals_mem_preprocessed.concat()));
+    out.section(10, self.eval_arg.to_bytes());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::BatchedSpartanPPSNARK)?;
+
+    let comms_Az_Bz_Cz = fast_serde::into_arrays::<_, 3>(
+      fast_serde::compressed_commitments_from_bytes::<E>(sections.section(1)?)?,
+    )?;
+    let comms_L_row_col = fast_serde::into_arrays::<_, 2>(
+      fast_serde::compressed_commitments_from_bytes::<E>(sections.section(2)?)?,
+    )?;
+    let comms_mem_oracles = fast_serde::into_arrays::<_, 4>(
+      fast_serde::compressed_commitments_from_bytes::<E>(sections.section(3)?)?,
+    )?;
+    let evals_Az_Bz_Cz_at_tau =
+      fast_serde::into_arrays::<_, 3>(fast_serde::scalars_from_bytes(sections.section(4)?)?)?;
+    let sc = SumcheckProof::from_bytes(sections.section(5)?)?;
+    let evals_Az_Bz_Cz_W_E =
+      fast_serde::into_arrays::<_, 5>(fast_serde::scalars_from_bytes(sections.section(6)?)?)?;
+    let evals_L_row_col =
+      fast_serde::into_arrays::<_, 2>(fast_serde::scalars_from_bytes(sections.section(7)?)?)?;
+    let evals_mem_oracle =
+      fast_serde::into_arrays::<_, 4>(fast_serde::scalars_from_bytes(sections.section(8)?)?)?;
+    let evals_mem_preprocessed =
+      fast_serde::into_arrays::<_, 7>(fast_serde::scalars_from_bytes(sections.section(9)?)?)?;
+    let eval_arg = EE::EvaluationArgument::from_bytes(sections.section(10)?)?;
+
+    sections.finish()?;
+    Ok(Self {
+      comms_Az_Bz_Cz,
+      comms_L_row_col,
+      comms_mem_oracles,
+      evals_Az_Bz_Cz_at_tau,
+      sc,
+      evals_Az_Bz_Cz_W_E,
+      evals_L_row_col,
+      evals_mem_oracle,
+      evals_mem_preprocessed,
+      eval_arg,
+    })
+  }
+}
+
 impl<E: Engine, EE: EvaluationEngineTrait<E>> BatchedRelaxedR1CSSNARKTrait<E>
   for BatchedRelaxedR1CSSNARK<E, EE>
 {
@@ -499,14 +597,17 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> BatchedRelaxedR1CSSNARKTrait<E>
 
     // Run batched Sumcheck for the 3 claims for all instances.
     // Note that the polynomials for claims relating to instance i have size Ni.
-    let (sc, rand_sc, claims_outer, claims_inner, claims_mem, claims_witness) = Self::prove_helper(
-      num_rounds_sc,
-      mem_sc_inst,
-      outer_sc_inst,
-      inner_sc_inst,
-      witness_sc_inst,
-      &mut transcript,
-    )?;
+    let (sc, rand_sc, claims_outer, claims_inner, claims_mem, claims_witness) =
+      metrics::timed(Phase::Sumcheck, || {
+        Self::prove_helper(
+          num_rounds_sc,
+          mem_sc_inst,
+          outer_sc_inst,
+          inner_sc_inst,
+          witness_sc_inst,
+          &mut transcript,
+        )
+      })?;
 
     let (evals_Az_Bz_Cz_W_E, evals_L_row_col, evals_mem_oracle, evals_mem_preprocessed) = {
       let evals_Az_Bz =
@@ -641,8 +742,9 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> BatchedRelaxedR1CSSNARKTrait<E>
     let w_batch =
       PolyEvalWitness::<E>::batch_diff_size(&w_vec.iter().by_ref().collect::<Vec<_>>(), c);
 
-    let eval_arg =
-      EE::prove(ck, &pk.pk_ee, &mut transcript, &u_batch.c, &w_batch.p, &u_batch.x, &u_batch.e)?;
+    let eval_arg = metrics::timed(Phase::Pcs, || {
+      EE::prove(ck, &pk.pk_ee, &mut transcript, &u_batch.c, &w_batch.p, &u_batch.x, &u_batch.e)
+    })?;
 
     let comms_Az_Bz_Cz =
       comms_Az_Bz_Cz.into_iter().map(|comms| comms.map(|comm| comm.compress())).collect();
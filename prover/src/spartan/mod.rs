@@ -17,7 +17,7 @@ pub(crate) mod math;
 pub mod polys;
 pub mod ppsnark;
 pub mod snark;
-mod sumcheck;
+pub(crate) mod sumcheck;
 
 use ff::Field;
 use itertools::Itertools as _;
@@ -116,10 +116,13 @@ impl<Scalar: PrimeField> UniPoly<Scalar> {
   fn leading_coefficient(&self) -> Option<&Scalar> { self.coeffs.last() }
 
   pub fn from_evals(evals: &[Scalar]) -> Self {
-    // we only support degree-2 or degree-3 univariate polynomials
-    assert!(evals.len() == 3 || evals.len() == 4);
+    // degree-2 and degree-3 univariate polynomials have closed forms, higher
+    // degrees are interpolated
+    assert!(evals.len() >= 3);
     let two_inv = Scalar::from(2).invert().unwrap();
-    let coeffs = if evals.len() == 3 {
+    let coeffs = if evals.len() > 4 {
+      Self::interpolate(evals)
+    } else if evals.len() == 3 {
       // ax^2 + bx + c
       let c = evals[0];
       let a = two_inv * (evals[2] - evals[1] - evals[1] + c);
@@ -146,6 +149,33 @@ impl<Scalar: PrimeField> UniPoly<Scalar> {
     Self { coeffs }
   }
 
+  /// Computes the coefficients of the polynomial taking the values `evals`
+  /// at 0, 1, ..., by Lagrange interpolation
+  fn interpolate(evals: &[Scalar]) -> Vec<Scalar> {
+    let n = evals.len();
+    let mut coeffs = vec![Scalar::ZERO; n];
+    for (i, eval) in evals.iter().enumerate() {
+      // the numerator of the i-th Lagrange basis polynomial, ∏_{j≠i} (x - j)
+      let mut basis = vec![Scalar::ONE];
+      let mut denom = Scalar::ONE;
+      for j in (0..n).filter(|j| *j != i) {
+        let j = Scalar::from(j as u64);
+        basis = (0..=basis.len())
+          .map(|k| {
+            let shifted = if k > 0 { basis[k - 1] } else { Scalar::ZERO };
+            let scaled = if k < basis.len() { basis[k] * j } else { Scalar::ZERO };
+            shifted - scaled
+          })
+          .collect();
+        denom *= Scalar::from(i as u64) - j;
+      }
+
+      let scale = *eval * denom.invert().unwrap();
+      coeffs.iter_mut().zip(basis).for_each(|(c, b)| *c += scale * b);
+    }
+    coeffs
+  }
+
   pub fn degree(&self) -> usize { self.coeffs.len() - 1 }
 
   pub fn eval_at_zero(&self) -> Scalar { self.coeffs[0] }
@@ -170,6 +200,15 @@ impl<Scalar: PrimeField> UniPoly<Scalar> {
 }
 
 impl<Scalar: PrimeField> CompressedUniPoly<Scalar> {
+  /// Returns the coefficients of the polynomial, without its linear term
+  pub fn coeffs_except_linear_term(&self) -> &[Scalar] { &self.coeffs_except_linear_term }
+
+  /// Builds a compressed polynomial from its coefficients without the linear term, which must
+  /// include at least the constant term
+  pub fn from_coeffs_except_linear_term(coeffs_except_linear_term: Vec<Scalar>) -> Option<Self> {
+    (!coeffs_except_linear_term.is_empty()).then_some(Self { coeffs_except_linear_term })
+  }
+
   // we require eval(0) + eval(1) = hint, so we can solve for the linear term as:
   // linear_term = hint - 2 * constant_term - deg2 term - deg3 term
   pub fn decompress(&self, hint: &Scalar) -> UniPoly<Scalar> {
@@ -317,6 +356,19 @@ mod tests {
   #[test]
   fn test_from_evals_cubic() { test_from_evals_cubic_with::<bn256_grumpkin::bn256::Scalar>(); }
 
+  fn test_from_evals_quartic_with<F: PrimeField>() {
+    // polynomial is 2x^4 + x^3 + 2x^2 + 3x + 1
+    let poly = UniPoly::new(vec![F::ONE, F::from(3), F::from(2), F::ONE, F::from(2)]);
+    let evals = (0..5).map(|x| poly.evaluate(&F::from(x))).collect::<Vec<_>>();
+    let interpolated = UniPoly::from_evals(&evals);
+
+    assert_eq!(interpolated, poly);
+    assert_eq!(interpolated.evaluate(&F::from(7)), poly.evaluate(&F::from(7)));
+  }
+
+  #[test]
+  fn test_from_evals_quartic() { test_from_evals_quartic_with::<bn256_grumpkin::bn256::Scalar>(); }
+
   /// Perform a naive n^2 multiplication of `self` by `other`.
   pub fn naive_mul<F: PrimeField>(ours: &UniPoly<F>, other: &UniPoly<F>) -> UniPoly<F> {
     if ours.is_zero() || other.is_zero() {
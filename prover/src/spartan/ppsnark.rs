@@ -16,8 +16,11 @@ use serde::{Deserialize, Serialize};
 
 use super::polys::{masked_eq::MaskedEqPolynomial, multilinear::SparsePolynomial};
 use crate::{
+  abi::{self, AbiEncode, Word},
   digest::{DigestComputer, SimpleDigestible},
   errors::NovaError,
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
+  metrics::{self, Phase},
   r1cs::{R1CSShape, RelaxedR1CSInstance, RelaxedR1CSWitness},
   spartan::{
     math::Math,
@@ -244,8 +247,8 @@ pub struct ProverKey<E: Engine, EE: EvaluationEngineTrait<E>> {
 }
 
 /// A type that represents the verifier's key
-#[derive(Debug, Clone, Serialize)]
-#[serde(bound = "EE::VerifierKey: Serialize")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct VerifierKey<E: Engine, EE: EvaluationEngineTrait<E>> {
   num_cons: usize,
   num_vars: usize,
@@ -312,7 +315,148 @@ pub struct RelaxedR1CSSNARK<E: Engine, EE: EvaluationEngineTrait<E>> {
   eval_arg: EE::EvaluationArgument,
 }
 
+impl<E: Engine, EE: EvaluationEngineTrait<E>> AbiEncode for RelaxedR1CSSNARK<E, EE>
+where EE::EvaluationArgument: AbiEncode
+{
+  /// Words: comm_Az, comm_Bz, comm_Cz, comm_L_row, comm_L_col,
+  /// comm_t_plus_r_inv_row, comm_w_plus_r_inv_row, comm_t_plus_r_inv_col,
+  /// comm_w_plus_r_inv_col (points), eval_Az_at_tau, eval_Bz_at_tau,
+  /// eval_Cz_at_tau (fields), sc, the 18 evaluations in the order of section 4
+  /// of the FastSerde encoding (fields), eval_arg
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    for comm in self.comms() {
+      abi::encode_commitment::<E>(out, &Commitment::<E>::decompress(&comm)?);
+    }
+    out.extend(
+      [self.eval_Az_at_tau, self.eval_Bz_at_tau, self.eval_Cz_at_tau].iter().map(abi::field),
+    );
+    self.sc.abi_encode(out)?;
+    out.extend(self.evals().iter().map(abi::field));
+    self.eval_arg.abi_encode(out)
+  }
+}
+
+impl<E: Engine, EE: EvaluationEngineTrait<E>> FastSerde for RelaxedR1CSSNARK<E, EE> {
+  /// Sections:
+  ///   1: comm_Az, comm_Bz, comm_Cz, comm_L_row, comm_L_col, comm_t_plus_r_inv_row,
+  ///      comm_w_plus_r_inv_row, comm_t_plus_r_inv_col, comm_w_plus_r_inv_col (compressed
+  ///      commitments)
+  ///   2: eval_Az_at_tau, eval_Bz_at_tau, eval_Cz_at_tau (scalars)
+  ///   3: sc (FastSerde)
+  ///   4: eval_Az, eval_Bz, eval_Cz, eval_E, eval_L_row, eval_L_col, eval_val_A, eval_val_B,
+  ///      eval_val_C, eval_W, eval_t_plus_r_inv_row, eval_row, eval_w_plus_r_inv_row,
+  ///      eval_ts_row, eval_t_plus_r_inv_col, eval_col, eval_w_plus_r_inv_col, eval_ts_col
+  ///      (scalars)
+  ///   5: eval_arg (FastSerde)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::SpartanPPSNARK);
+    out.section(1, fast_serde::compressed_commitments_to_bytes::<E>(&self.comms()));
+    out.section(
+      2,
+      fast_serde::scalars_to_bytes(&[
+        self.eval_Az_at_tau,
+        self.eval_Bz_at_tau,
+        self.eval_Cz_at_tau,
+      ]),
+    );
+    out.section(3, self.sc.to_bytes());
+    out.section(4, fast_serde::scalars_to_bytes(&self.evals()));
+    out.section(5, self.eval_arg.to_bytes());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::SpartanPPSNARK)?;
+
+    let [comm_Az, comm_Bz, comm_Cz, comm_L_row, comm_L_col, comm_t_plus_r_inv_row, comm_w_plus_r_inv_row, comm_t_plus_r_inv_col, comm_w_plus_r_inv_col]: [CompressedCommitment<E>; 9] =
+      fast_serde::compressed_commitments_from_bytes::<E>(sections.section(1)?)?
+        .try_into()
+        .map_err(|_| SerdeByteError::InvalidSectionSize)?;
+    let [eval_Az_at_tau, eval_Bz_at_tau, eval_Cz_at_tau] =
+      fast_serde::scalar_array_from_bytes(sections.section(2)?)?;
+    let sc = SumcheckProof::from_bytes(sections.section(3)?)?;
+    let [eval_Az, eval_Bz, eval_Cz, eval_E, eval_L_row, eval_L_col, eval_val_A, eval_val_B, eval_val_C, eval_W, eval_t_plus_r_inv_row, eval_row, eval_w_plus_r_inv_row, eval_ts_row, eval_t_plus_r_inv_col, eval_col, eval_w_plus_r_inv_col, eval_ts_col] =
+      fast_serde::scalar_array_from_bytes(sections.section(4)?)?;
+    let eval_arg = EE::EvaluationArgument::from_bytes(sections.section(5)?)?;
+
+    sections.finish()?;
+    Ok(Self {
+      comm_Az,
+      comm_Bz,
+      comm_Cz,
+      comm_L_row,
+      comm_L_col,
+      comm_t_plus_r_inv_row,
+      comm_w_plus_r_inv_row,
+      comm_t_plus_r_inv_col,
+      comm_w_plus_r_inv_col,
+      eval_Az_at_tau,
+      eval_Bz_at_tau,
+      eval_Cz_at_tau,
+      sc,
+      eval_Az,
+      eval_Bz,
+      eval_Cz,
+      eval_E,
+      eval_L_row,
+      eval_L_col,
+      eval_val_A,
+      eval_val_B,
+      eval_val_C,
+      eval_W,
+      eval_t_plus_r_inv_row,
+      eval_row,
+      eval_w_plus_r_inv_row,
+      eval_ts_row,
+      eval_t_plus_r_inv_col,
+      eval_col,
+      eval_w_plus_r_inv_col,
+      eval_ts_col,
+      eval_arg,
+    })
+  }
+}
+
 impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARK<E, EE> {
+  /// Returns the commitments of the proof, in encoding order
+  fn comms(&self) -> [CompressedCommitment<E>; 9] {
+    [
+      self.comm_Az.clone(),
+      self.comm_Bz.clone(),
+      self.comm_Cz.clone(),
+      self.comm_L_row.clone(),
+      self.comm_L_col.clone(),
+      self.comm_t_plus_r_inv_row.clone(),
+      self.comm_w_plus_r_inv_row.clone(),
+      self.comm_t_plus_r_inv_col.clone(),
+      self.comm_w_plus_r_inv_col.clone(),
+    ]
+  }
+
+  /// Returns the evaluations of the proof after the sumcheck, in encoding order
+  fn evals(&self) -> [E::Scalar; 18] {
+    [
+      self.eval_Az,
+      self.eval_Bz,
+      self.eval_Cz,
+      self.eval_E,
+      self.eval_L_row,
+      self.eval_L_col,
+      self.eval_val_A,
+      self.eval_val_B,
+      self.eval_val_C,
+      self.eval_W,
+      self.eval_t_plus_r_inv_row,
+      self.eval_row,
+      self.eval_w_plus_r_inv_row,
+      self.eval_ts_row,
+      self.eval_t_plus_r_inv_col,
+      self.eval_col,
+      self.eval_w_plus_r_inv_col,
+      self.eval_ts_col,
+    ]
+  }
+
   fn prove_helper<T1, T2, T3, T4>(
     mem: &mut T1,
     outer: &mut T2,
@@ -640,13 +784,16 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
 
     let mut witness_sc_inst = WitnessBoundSumcheck::new(tau, W.clone(), S.num_vars);
 
-    let (sc, rand_sc, claims_mem, claims_outer, claims_inner, claims_witness) = Self::prove_helper(
-      &mut mem_sc_inst,
-      &mut outer_sc_inst,
-      &mut inner_sc_inst,
-      &mut witness_sc_inst,
-      &mut transcript,
-    )?;
+    let (sc, rand_sc, claims_mem, claims_outer, claims_inner, claims_witness) =
+      metrics::timed(Phase::Sumcheck, || {
+        Self::prove_helper(
+          &mut mem_sc_inst,
+          &mut outer_sc_inst,
+          &mut inner_sc_inst,
+          &mut witness_sc_inst,
+          &mut transcript,
+        )
+      })?;
 
     // claims from the end of the sum-check
     let eval_Az = claims_outer[0][0];
@@ -749,7 +896,9 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
     let w: PolyEvalWitness<E> = PolyEvalWitness::batch(&poly_vec, &c);
     let u: PolyEvalInstance<E> = PolyEvalInstance::batch(&comm_vec, rand_sc.clone(), &eval_vec, &c);
 
-    let eval_arg = EE::prove(ck, &pk.pk_ee, &mut transcript, &u.c, &w.p, &rand_sc, &u.e)?;
+    let eval_arg = metrics::timed(Phase::Pcs, || {
+      EE::prove(ck, &pk.pk_ee, &mut transcript, &u.c, &w.p, &rand_sc, &u.e)
+    })?;
 
     Ok(Self {
       comm_Az: comm_Az.compress(),
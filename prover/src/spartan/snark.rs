@@ -6,15 +6,18 @@
 
 use std::sync::Arc;
 
-use ff::Field;
+use ff::{Field, PrimeField};
 use itertools::Itertools as _;
 use once_cell::sync::OnceCell;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+  abi::{self, AbiEncode, Word},
   digest::{DigestComputer, SimpleDigestible},
   errors::NovaError,
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
+  metrics::{self, Phase},
   r1cs::{R1CSShape, RelaxedR1CSInstance, RelaxedR1CSWitness, SparseMatrix},
   spartan::{
     compute_eval_table_sparse,
@@ -42,8 +45,32 @@ pub struct ProverKey<E: Engine, EE: EvaluationEngineTrait<E>> {
   pub vk_digest: E::Scalar, // digest of the verifier's key
 }
 
+impl<E: Engine, EE: EvaluationEngineTrait<E>> FastSerde for ProverKey<E, EE>
+where EE::ProverKey: FastSerde
+{
+  /// Sections:
+  ///   1: pk_ee (FastSerde)
+  ///   2: vk_digest (scalar)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::SpartanProverKey);
+    out.section(1, self.pk_ee.to_bytes());
+    out.section(2, self.vk_digest.to_repr().as_ref().to_vec());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::SpartanProverKey)?;
+
+    let pk_ee = EE::ProverKey::from_bytes(sections.section(1)?)?;
+    let vk_digest = fast_serde::scalar_from_bytes(sections.section(2)?)?;
+
+    sections.finish()?;
+    Ok(Self { pk_ee, vk_digest })
+  }
+}
+
 /// A type that represents the verifier's key
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
 pub struct VerifierKey<E: Engine, EE: EvaluationEngineTrait<E>> {
   vk_ee:  EE::VerifierKey,
@@ -90,6 +117,71 @@ pub struct RelaxedR1CSSNARK<E: Engine, EE: EvaluationEngineTrait<E>> {
   eval_arg:       EE::EvaluationArgument,
 }
 
+impl<E: Engine, EE: EvaluationEngineTrait<E>> AbiEncode for RelaxedR1CSSNARK<E, EE>
+where EE::EvaluationArgument: AbiEncode
+{
+  /// Words: sc_proof_outer, claims_outer (3 fields), eval_E, eval_W (fields),
+  /// sc_proof_inner, sc_proof_batch, evals_batch (fields), eval_arg
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    let (claim_Az, claim_Bz, claim_Cz) = self.claims_outer;
+
+    self.sc_proof_outer.abi_encode(out)?;
+    out.extend([claim_Az, claim_Bz, claim_Cz, self.eval_E, self.eval_W].iter().map(abi::field));
+    self.sc_proof_inner.abi_encode(out)?;
+    self.sc_proof_batch.abi_encode(out)?;
+    abi::encode_fields(out, &self.evals_batch);
+    self.eval_arg.abi_encode(out)
+  }
+}
+
+impl<E: Engine, EE: EvaluationEngineTrait<E>> FastSerde for RelaxedR1CSSNARK<E, EE> {
+  /// Sections:
+  ///   1: sc_proof_outer (FastSerde)
+  ///   2: claims_outer (scalars)
+  ///   3: eval_E, eval_W (scalars)
+  ///   4: sc_proof_inner (FastSerde)
+  ///   5: sc_proof_batch (FastSerde)
+  ///   6: evals_batch (scalars)
+  ///   7: eval_arg (FastSerde)
+  fn to_bytes(&self) -> Vec<u8> {
+    let (claim_Az, claim_Bz, claim_Cz) = self.claims_outer;
+
+    let mut out = SectionWriter::new(SerdeByteTypes::SpartanSNARK);
+    out.section(1, self.sc_proof_outer.to_bytes());
+    out.section(2, fast_serde::scalars_to_bytes(&[claim_Az, claim_Bz, claim_Cz]));
+    out.section(3, fast_serde::scalars_to_bytes(&[self.eval_E, self.eval_W]));
+    out.section(4, self.sc_proof_inner.to_bytes());
+    out.section(5, self.sc_proof_batch.to_bytes());
+    out.section(6, fast_serde::scalars_to_bytes(&self.evals_batch));
+    out.section(7, self.eval_arg.to_bytes());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::SpartanSNARK)?;
+
+    let sc_proof_outer = SumcheckProof::from_bytes(sections.section(1)?)?;
+    let [claim_Az, claim_Bz, claim_Cz] = fast_serde::scalar_array_from_bytes(sections.section(2)?)?;
+    let [eval_E, eval_W] = fast_serde::scalar_array_from_bytes(sections.section(3)?)?;
+    let sc_proof_inner = SumcheckProof::from_bytes(sections.section(4)?)?;
+    let sc_proof_batch = SumcheckProof::from_bytes(sections.section(5)?)?;
+    let evals_batch = fast_serde::scalars_from_bytes(sections.section(6)?)?;
+    let eval_arg = EE::EvaluationArgument::from_bytes(sections.section(7)?)?;
+
+    sections.finish()?;
+    Ok(Self {
+      sc_proof_outer,
+      claims_outer: (claim_Az, claim_Bz, claim_Cz),
+      eval_E,
+      sc_proof_inner,
+      eval_W,
+      sc_proof_batch,
+      evals_batch,
+      eval_arg,
+    })
+  }
+}
+
 impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for RelaxedR1CSSNARK<E, EE> {
   type ProverKey = ProverKey<E, EE>;
   type VerifierKey = VerifierKey<E, EE>;
@@ -170,16 +262,18 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
        poly_C_comp: &E::Scalar,
        poly_D_comp: &E::Scalar|
        -> E::Scalar { *poly_A_comp * (*poly_B_comp * *poly_C_comp - *poly_D_comp) };
-    let (sc_proof_outer, r_x, claims_outer) = SumcheckProof::prove_cubic_with_additive_term(
-      &E::Scalar::ZERO, // claim is zero
-      num_rounds_x,
-      &mut poly_tau,
-      &mut poly_Az,
-      &mut poly_Bz,
-      &mut poly_uCz_E,
-      comb_func_outer,
-      &mut transcript,
-    )?;
+    let (sc_proof_outer, r_x, claims_outer) = metrics::timed(Phase::Sumcheck, || {
+      SumcheckProof::prove_cubic_with_additive_term(
+        &E::Scalar::ZERO, // claim is zero
+        num_rounds_x,
+        &mut poly_tau,
+        &mut poly_Az,
+        &mut poly_Bz,
+        &mut poly_uCz_E,
+        comb_func_outer,
+        &mut transcript,
+      )
+    })?;
 
     // claims from the end of sum-check
     let (claim_Az, claim_Bz): (E::Scalar, E::Scalar) = (claims_outer[1], claims_outer[2]);
@@ -215,14 +309,16 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
     let comb_func = |poly_A_comp: &E::Scalar, poly_B_comp: &E::Scalar| -> E::Scalar {
       *poly_A_comp * *poly_B_comp
     };
-    let (sc_proof_inner, r_y, _claims_inner) = SumcheckProof::prove_quad(
-      &claim_inner_joint,
-      num_rounds_y,
-      &mut MultilinearPolynomial::new(poly_ABC),
-      &mut MultilinearPolynomial::new(poly_z),
-      comb_func,
-      &mut transcript,
-    )?;
+    let (sc_proof_inner, r_y, _claims_inner) = metrics::timed(Phase::Sumcheck, || {
+      SumcheckProof::prove_quad(
+        &claim_inner_joint,
+        num_rounds_y,
+        &mut MultilinearPolynomial::new(poly_ABC),
+        &mut MultilinearPolynomial::new(poly_z),
+        comb_func,
+        &mut transcript,
+      )
+    })?;
 
     // Add additional claims about W and E polynomials to the list from CC
     // We will reduce a vector of claims of evaluations at different points into
@@ -243,17 +339,19 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
       }];
 
     let (batched_u, batched_w, sc_proof_batch, claims_batch_left) =
-      batch_eval_reduce(u_vec, &w_vec, &mut transcript)?;
-
-    let eval_arg = EE::prove(
-      ck,
-      &pk.pk_ee,
-      &mut transcript,
-      &batched_u.c,
-      &batched_w.p,
-      &batched_u.x,
-      &batched_u.e,
-    )?;
+      metrics::timed(Phase::Sumcheck, || batch_eval_reduce(u_vec, &w_vec, &mut transcript))?;
+
+    let eval_arg = metrics::timed(Phase::Pcs, || {
+      EE::prove(
+        ck,
+        &pk.pk_ee,
+        &mut transcript,
+        &batched_u.c,
+        &batched_w.p,
+        &batched_u.x,
+        &batched_u.e,
+      )
+    })?;
 
     Ok(Self {
       sc_proof_outer,
@@ -4,7 +4,9 @@ use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+  abi::{self, AbiEncode, Word},
   errors::NovaError,
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
   spartan::polys::{
     multilinear::MultilinearPolynomial,
     univariate::{CompressedUniPoly, UniPoly},
@@ -20,6 +22,49 @@ pub(crate) struct SumcheckProof<E: Engine> {
   compressed_polys: Vec<CompressedUniPoly<E::Scalar>>,
 }
 
+impl<E: Engine> AbiEncode for SumcheckProof<E> {
+  /// Words: number of rounds, then for each round the coefficients of its
+  /// polynomial except the linear term (fields)
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    out.push(abi::uint(self.compressed_polys.len() as u64));
+    for poly in &self.compressed_polys {
+      abi::encode_fields(out, poly.coeffs_except_linear_term());
+    }
+    Ok(())
+  }
+}
+
+impl<E: Engine> FastSerde for SumcheckProof<E> {
+  /// Sections:
+  ///   1: compressed_polys (list of scalars, one entry per round)
+  fn to_bytes(&self) -> Vec<u8> {
+    let polys = self
+      .compressed_polys
+      .iter()
+      .map(|poly| fast_serde::scalars_to_bytes(poly.coeffs_except_linear_term()))
+      .collect::<Vec<_>>();
+
+    let mut out = SectionWriter::new(SerdeByteTypes::SumcheckProof);
+    out.section(1, fast_serde::list_to_bytes(&polys));
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::SumcheckProof)?;
+
+    let compressed_polys = fast_serde::list_from_bytes(sections.section(1)?)?
+      .into_iter()
+      .map(|poly| {
+        CompressedUniPoly::from_coeffs_except_linear_term(fast_serde::scalars_from_bytes(poly)?)
+          .ok_or(SerdeByteError::InvalidSectionSize)
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    sections.finish()?;
+    Ok(Self { compressed_polys })
+  }
+}
+
 impl<E: Engine> SumcheckProof<E> {
   pub fn new(compressed_polys: Vec<CompressedUniPoly<E::Scalar>>) -> Self {
     Self { compressed_polys }
@@ -541,4 +586,69 @@ impl<E: Engine> SumcheckProof<E> {
       poly_D_final,
     ]))
   }
+
+  /// Proves the sum over the boolean hypercube of `comb_func` applied to the
+  /// evaluations of `polys`, where `comb_func` has degree at most `degree` in
+  /// each variable.
+  ///
+  /// Returns the proof, the challenges and the evaluations of `polys` at the
+  /// challenges.
+  pub fn prove_poly<F>(
+    claim: &E::Scalar,
+    num_rounds: usize,
+    polys: &mut [MultilinearPolynomial<E::Scalar>],
+    degree: usize,
+    comb_func: F,
+    transcript: &mut E::TE,
+  ) -> Result<(Self, Vec<E::Scalar>, Vec<E::Scalar>), NovaError>
+  where
+    F: Fn(&[E::Scalar]) -> E::Scalar + Sync,
+  {
+    let mut r: Vec<E::Scalar> = Vec::new();
+    let mut compressed_polys: Vec<CompressedUniPoly<E::Scalar>> = Vec::new();
+    let mut claim_per_round = *claim;
+
+    for _ in 0..num_rounds {
+      let poly = {
+        let len = polys[0].len() / 2;
+        let mut evals = (0..len)
+          .into_par_iter()
+          .map(|i| {
+            // walk along the top variable from 0 to `degree`
+            let mut point = polys.iter().map(|poly| poly[i]).collect::<Vec<_>>();
+            let step = polys.iter().map(|poly| poly[len + i] - poly[i]).collect::<Vec<_>>();
+            let mut evals = Vec::with_capacity(degree + 1);
+            evals.push(comb_func(&point));
+            for _ in 0..degree {
+              point.iter_mut().zip(&step).for_each(|(p, s)| *p += s);
+              evals.push(comb_func(&point));
+            }
+            evals
+          })
+          .reduce(
+            || vec![E::Scalar::ZERO; degree + 1],
+            |a, b| a.into_iter().zip(b).map(|(a, b)| a + b).collect(),
+          );
+        evals[1] = claim_per_round - evals[0];
+        UniPoly::from_evals(&evals)
+      };
+
+      // append the prover's message to the transcript
+      transcript.absorb(b"p", &poly);
+
+      // derive the verifier's challenge for the next round
+      let r_i = transcript.squeeze(b"c")?;
+      r.push(r_i);
+      compressed_polys.push(poly.compress());
+
+      // Set up next round
+      claim_per_round = poly.evaluate(&r_i);
+
+      // bound all tables to the verifier's challenge
+      polys.par_iter_mut().for_each(|poly| poly.bind_poly_var_top(&r_i));
+    }
+
+    let evals = polys.iter().map(|poly| poly[0]).collect();
+    Ok((Self { compressed_polys }, r, evals))
+  }
 }
@@ -20,4 +20,7 @@ pub enum SuperNovaError {
   /// Extended error for supernova
   #[error("UnSatIndex")]
   UnSatIndex(&'static str, usize),
+  /// A running witness could not be spilled or read back in the low-memory mode
+  #[error("SpillError: {0}")]
+  SpillError(String),
 }
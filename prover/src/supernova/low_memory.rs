@@ -0,0 +1,405 @@
+//! Low-memory proving for [`RecursiveSNARK`]
+//!
+//! Besides the commitment keys and the circuit shapes of the [`PublicParams`], a
+//! [`RecursiveSNARK`] holds a running witness for every circuit it has executed, the scratch
+//! buffers of both folds and their cross-terms. For mid-sized circuits this exceeds the memory
+//! available on mobile devices. [`RecursiveSNARK::enable_low_memory`] switches a SNARK to a mode
+//! that
+//! - spills the running witnesses of the circuits that are not executed next to files in a spill
+//!   directory, and reads them back when their circuit is executed again,
+//! - commits to the cross-terms in chunks without materializing them, see
+//!   [`NIFS::prove_mut_chunked`](crate::nifs::NIFS::prove_mut_chunked),
+//! - commits to the witnesses of each step with one multi-scalar multiplication per window of the
+//!   commitment key, see [`CommitmentEngineTrait::commit_windowed`].
+//!
+//! [`PublicParams::memory_estimate`] estimates the peak memory of proving with or without this
+//! mode, so that it can be reported before proving starts.
+
+use std::{
+  fmt, fs,
+  mem::size_of,
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+  },
+};
+
+use serde::{ser::Error as _, Deserialize, Serialize, Serializer};
+use tracing::debug;
+
+use super::{error::SuperNovaError, PublicParams, RecursiveSNARK};
+use crate::{
+  bellpepper::solver::SatisfyingAssignment,
+  errors::NovaError,
+  fast_serde::FastSerde,
+  r1cs::{R1CSInstance, R1CSShape, R1CSWitness, RelaxedR1CSWitness, SparseMatrix},
+  traits::{
+    commitment::{CommitmentEngineTrait, Len},
+    CurveCycleEquipped, Dual, Engine,
+  },
+  CommitmentKey,
+};
+
+/// Default number of constraints per chunk of the cross-term
+pub const DEFAULT_CHUNK_LEN: usize = 1 << 16;
+
+/// Default number of generators per multi-scalar multiplication
+pub const DEFAULT_MSM_WINDOW: usize = 1 << 16;
+
+/// Configuration of the low-memory mode of a [`RecursiveSNARK`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LowMemoryConfig {
+  /// Directory the running witnesses of inactive circuits are spilled to, created if missing
+  pub spill_dir:  PathBuf,
+  /// Number of constraints whose cross-terms are committed to at once
+  pub chunk_len:  usize,
+  /// Number of generators of the commitment key per multi-scalar multiplication
+  pub msm_window: usize,
+}
+
+impl LowMemoryConfig {
+  /// Creates a configuration spilling to `spill_dir` with the default chunk and window sizes
+  pub fn new(spill_dir: impl Into<PathBuf>) -> Self {
+    Self {
+      spill_dir:  spill_dir.into(),
+      chunk_len:  DEFAULT_CHUNK_LEN,
+      msm_window: DEFAULT_MSM_WINDOW,
+    }
+  }
+}
+
+/// Estimate of the peak memory of proving, in bytes
+///
+/// The estimate counts the dominant allocations only: the commitment keys and circuit shapes of the
+/// public parameters, the running witnesses, and the scratch memory of a step. Commitment keys
+/// that are memory-mapped are counted in full, although the operating system can page them out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryEstimate {
+  /// Commitment keys of the primary and the secondary curve
+  pub commitment_keys:   usize,
+  /// Matrices of the primary circuits and of the secondary circuit
+  pub shapes:            usize,
+  /// Running witnesses held in memory
+  pub running_witnesses: usize,
+  /// Witnesses of a step, the products of both folds and their cross-terms
+  pub step:              usize,
+}
+
+impl MemoryEstimate {
+  /// Total estimated peak memory, in bytes
+  pub fn total(&self) -> usize {
+    self.commitment_keys + self.shapes + self.running_witnesses + self.step
+  }
+}
+
+impl fmt::Display for MemoryEstimate {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mib = |bytes: usize| bytes as f64 / (1 << 20) as f64;
+    write!(
+      f,
+      "{:.1} MiB (commitment keys {:.1} MiB, shapes {:.1} MiB, running witnesses {:.1} MiB, step \
+       {:.1} MiB)",
+      mib(self.total()),
+      mib(self.commitment_keys),
+      mib(self.shapes),
+      mib(self.running_witnesses),
+      mib(self.step)
+    )
+  }
+}
+
+impl<E1> PublicParams<E1>
+where E1: CurveCycleEquipped
+{
+  /// Estimates the peak memory of proving with these parameters
+  ///
+  /// Pass the configuration of the low-memory mode to estimate proving in that mode, or `None` to
+  /// estimate regular proving.
+  pub fn memory_estimate(&self, low_memory: Option<&LowMemoryConfig>) -> MemoryEstimate {
+    let commitment_keys =
+      ck_bytes::<E1>(&self.ck_primary) + ck_bytes::<Dual<E1>>(&self.ck_secondary);
+
+    let shapes = self.circuit_shapes.iter().map(|c| shape_bytes(&c.r1cs_shape)).sum::<usize>()
+      + shape_bytes(&self.circuit_shape_secondary.r1cs_shape);
+
+    // every executed circuit keeps a running witness, unless all but one are spilled
+    let primary_witnesses =
+      self.circuit_shapes.iter().map(|c| relaxed_witness_bytes(&c.r1cs_shape));
+    let primary_witnesses = match low_memory {
+      Some(_) => primary_witnesses.max().unwrap_or_default(),
+      None => primary_witnesses.sum(),
+    };
+    let S_secondary = &self.circuit_shape_secondary.r1cs_shape;
+    let running_witnesses = primary_witnesses
+      + relaxed_witness_bytes(S_secondary)
+      + S_secondary.num_vars * size_of::<<Dual<E1> as Engine>::Scalar>();
+
+    let chunk_len = low_memory.map(|config| config.chunk_len);
+    let step = self
+      .circuit_shapes
+      .iter()
+      .map(|c| step_bytes(&c.r1cs_shape, chunk_len))
+      .max()
+      .unwrap_or_default()
+      + step_bytes(S_secondary, chunk_len);
+
+    MemoryEstimate { commitment_keys, shapes, running_witnesses, step }
+  }
+}
+
+/// Bytes of a commitment key, counting generators in affine coordinates
+fn ck_bytes<E: Engine>(ck: &CommitmentKey<E>) -> usize { ck.length() * 2 * size_of::<E::Base>() }
+
+/// Bytes of the matrices of a shape
+fn shape_bytes<E: Engine>(S: &R1CSShape<E>) -> usize {
+  let matrix_bytes = |M: &SparseMatrix<E::Scalar>| {
+    M.data.len() * size_of::<E::Scalar>() + (M.indices.len() + M.indptr.len()) * size_of::<usize>()
+  };
+  matrix_bytes(&S.A) + matrix_bytes(&S.B) + matrix_bytes(&S.C)
+}
+
+/// Bytes of a relaxed witness of a shape
+fn relaxed_witness_bytes<E: Engine>(S: &R1CSShape<E>) -> usize {
+  (S.num_vars + S.num_cons) * size_of::<E::Scalar>()
+}
+
+/// Bytes of the scratch memory of folding a step of a shape: the witness of the step, the products
+/// of both witnesses with the matrices, and the cross-term or a chunk of it
+fn step_bytes<E: Engine>(S: &R1CSShape<E>, chunk_len: Option<usize>) -> usize {
+  let T_len = chunk_len.map_or(S.num_cons, |chunk_len| chunk_len.min(S.num_cons));
+  (S.num_vars + 6 * S.num_cons + T_len) * size_of::<E::Scalar>()
+}
+
+/// Extracts the instance and witness of a satisfying assignment like
+/// [`NovaWitness::r1cs_instance_and_witness`](crate::bellpepper::r1cs::NovaWitness::r1cs_instance_and_witness),
+/// committing to the witness with multi-scalar multiplications over windows of `msm_window`
+/// generators
+pub(crate) fn r1cs_instance_and_witness_windowed<E: Engine>(
+  cs: SatisfyingAssignment<E>,
+  shape: &R1CSShape<E>,
+  ck: &CommitmentKey<E>,
+  msm_window: usize,
+) -> Result<(R1CSInstance<E>, R1CSWitness<E>), NovaError> {
+  let (input_assignment, aux_assignment) = cs.to_assignments();
+  let W = R1CSWitness::<E>::new(shape, aux_assignment)?;
+  let X = input_assignment[1..].to_owned();
+
+  let comm_W = E::CE::commit_windowed(ck, &W.W, msm_window);
+
+  let instance = R1CSInstance::<E>::new(shape, comm_W, X)?;
+
+  Ok((instance, W))
+}
+
+/// A spilled running witness, whose file is removed once the last handle to it is dropped
+#[derive(Debug)]
+struct SpillFile(PathBuf);
+
+impl Drop for SpillFile {
+  fn drop(&mut self) { let _ = fs::remove_file(&self.0); }
+}
+
+/// The low-memory mode of a [`RecursiveSNARK`]
+///
+/// Clones of a SNARK share their spill files, which are removed when the last clone restores or
+/// drops them.
+#[derive(Clone, Debug)]
+pub(crate) struct LowMemory {
+  /// Configuration the mode was enabled with
+  pub(crate) config: LowMemoryConfig,
+  /// Spill files of the running primary witnesses, by circuit index
+  spilled:           Vec<Option<Arc<SpillFile>>>,
+}
+
+impl LowMemory {
+  /// Creates the spill directory of `config` for a SNARK of `num_circuits` circuits
+  pub(crate) fn new(config: LowMemoryConfig, num_circuits: usize) -> Result<Self, SuperNovaError> {
+    fs::create_dir_all(&config.spill_dir).map_err(spill_error)?;
+    Ok(Self { config, spilled: vec![None; num_circuits] })
+  }
+
+  /// Whether the running witness of circuit `i` is spilled
+  pub(crate) fn is_spilled(&self, i: usize) -> bool { self.spilled[i].is_some() }
+
+  /// Writes the running witness of circuit `i` to a new spill file
+  pub(crate) fn spill<E: Engine>(
+    &mut self,
+    i: usize,
+    W: &RelaxedR1CSWitness<E>,
+  ) -> Result<(), SuperNovaError> {
+    static NEXT_SPILL: AtomicU64 = AtomicU64::new(0);
+
+    let path = self.config.spill_dir.join(format!(
+      "r_W_primary-{}-{}.bin",
+      std::process::id(),
+      NEXT_SPILL.fetch_add(1, Ordering::Relaxed)
+    ));
+    let file = SpillFile(path);
+    fs::write(&file.0, W.to_bytes()).map_err(spill_error)?;
+    self.spilled[i] = Some(Arc::new(file));
+    Ok(())
+  }
+
+  /// Reads the running witness of circuit `i` back, if it is spilled
+  pub(crate) fn load<E: Engine>(
+    &self,
+    i: usize,
+  ) -> Result<Option<RelaxedR1CSWitness<E>>, SuperNovaError> {
+    let Some(file) = &self.spilled[i] else {
+      return Ok(None);
+    };
+    let bytes = fs::read(&file.0).map_err(spill_error)?;
+    RelaxedR1CSWitness::from_bytes(&bytes).map(Some).map_err(spill_error)
+  }
+
+  /// Reads the running witness of circuit `i` back and releases its spill file
+  pub(crate) fn restore<E: Engine>(
+    &mut self,
+    i: usize,
+  ) -> Result<Option<RelaxedR1CSWitness<E>>, SuperNovaError> {
+    let W = self.load(i)?;
+    self.spilled[i] = None;
+    Ok(W)
+  }
+}
+
+/// Whether no running witness is spilled, in which case a [`RecursiveSNARK`] is serialized with
+/// `serde` without its low-memory mode
+pub(crate) fn has_no_spills(low_memory: &Option<LowMemory>) -> bool {
+  !low_memory.as_ref().is_some_and(|low_memory| low_memory.spilled.iter().any(Option::is_some))
+}
+
+/// Fails the `serde` serialization of a [`RecursiveSNARK`] with spilled witnesses, which would
+/// otherwise be written as missing
+pub(crate) fn reject_spills<S: Serializer>(
+  _low_memory: &Option<LowMemory>,
+  _serializer: S,
+) -> Result<S::Ok, S::Error> {
+  Err(S::Error::custom(
+    "running witnesses are spilled, call `RecursiveSNARK::disable_low_memory` before serializing",
+  ))
+}
+
+/// Wraps an error of the spill storage
+fn spill_error(err: impl fmt::Display) -> SuperNovaError {
+  SuperNovaError::SpillError(err.to_string())
+}
+
+impl<E1> RecursiveSNARK<E1>
+where E1: CurveCycleEquipped
+{
+  /// Switches this SNARK to the low-memory mode, see the [module docs](self)
+  ///
+  /// The cross-term buffers are released and the running witnesses of all circuits but the one
+  /// executed next are spilled right away. Proofs are identical to those of regular proving.
+  ///
+  /// Spilled witnesses are read back by [`RecursiveSNARK::verify`], compression and
+  /// [`RecursiveSNARK::try_to_bytes`], but not by `serde` serialization, which fails while a
+  /// witness is spilled. Call [`RecursiveSNARK::disable_low_memory`] before serializing with
+  /// `serde`.
+  ///
+  /// # Returns
+  ///
+  /// The estimated peak memory of proving in the low-memory mode, which is also logged.
+  ///
+  /// # Errors
+  ///
+  /// Returns [`SuperNovaError::SpillError`] if the spill directory cannot be created or a witness
+  /// cannot be spilled.
+  pub fn enable_low_memory(
+    &mut self,
+    pp: &PublicParams<E1>,
+    config: LowMemoryConfig,
+  ) -> Result<MemoryEstimate, SuperNovaError> {
+    let estimate = pp.memory_estimate(Some(&config));
+    debug!("low-memory proving, estimated peak memory {estimate}");
+
+    match &mut self.low_memory {
+      Some(low_memory) => {
+        fs::create_dir_all(&config.spill_dir).map_err(spill_error)?;
+        low_memory.config = config;
+      },
+      None => self.low_memory = Some(LowMemory::new(config, self.num_augmented_circuits)?),
+    }
+    self.buffer_primary.T = Vec::new();
+    self.buffer_secondary.T = Vec::new();
+    self.spill_inactive_witnesses()?;
+
+    Ok(estimate)
+  }
+
+  /// Leaves the low-memory mode, reading all spilled witnesses back into memory
+  ///
+  /// # Errors
+  ///
+  /// Returns [`SuperNovaError::SpillError`] if a spilled witness cannot be read back, in which case
+  /// the SNARK stays in the low-memory mode.
+  pub fn disable_low_memory(&mut self) -> Result<(), SuperNovaError> {
+    let Some(low_memory) = &mut self.low_memory else {
+      return Ok(());
+    };
+    for (i, W) in self.r_W_primary.iter_mut().enumerate() {
+      if let Some(spilled) = low_memory.restore(i)? {
+        *W = Some(spilled);
+      }
+    }
+    self.low_memory = None;
+    Ok(())
+  }
+
+  /// Whether this SNARK is in the low-memory mode
+  pub fn is_low_memory(&self) -> bool { self.low_memory.is_some() }
+
+  /// Spills the resident running witnesses of all circuits but the one the program counter selects
+  pub(crate) fn spill_inactive_witnesses(&mut self) -> Result<(), SuperNovaError> {
+    let Some(low_memory) = &mut self.low_memory else {
+      return Ok(());
+    };
+    for (i, W) in self.r_W_primary.iter_mut().enumerate() {
+      if E1::Scalar::from(i as u64) == self.program_counter {
+        continue;
+      }
+      if let Some(resident) = W {
+        low_memory.spill(i, resident)?;
+        *W = None;
+      }
+    }
+    Ok(())
+  }
+
+  /// Reads the running witness of circuit `i` back into memory, if it is spilled
+  pub(crate) fn restore_witness(&mut self, i: usize) -> Result<(), SuperNovaError> {
+    if let Some(W) = self.low_memory.as_mut().map(|lm| lm.restore(i)).transpose()?.flatten() {
+      self.r_W_primary[i] = Some(W);
+    }
+    Ok(())
+  }
+
+  /// The running witness of circuit `i`, read from its spill file if it is spilled
+  pub(crate) fn primary_witness(
+    &self,
+    i: usize,
+  ) -> Result<Option<RelaxedR1CSWitness<E1>>, SuperNovaError> {
+    match (&self.r_W_primary[i], &self.low_memory) {
+      (Some(W), _) => Ok(Some(W.clone())),
+      (None, Some(low_memory)) => low_memory.load(i),
+      (None, None) => Ok(None),
+    }
+  }
+
+  /// Whether the running witness of circuit `i` is spilled
+  pub(crate) fn is_spilled(&self, i: usize) -> bool {
+    self.low_memory.as_ref().is_some_and(|low_memory| low_memory.is_spilled(i))
+  }
+
+  /// Chunk length of the cross-terms, if this SNARK is in the low-memory mode
+  pub(crate) fn chunk_len(&self) -> Option<usize> {
+    self.low_memory.as_ref().map(|low_memory| low_memory.config.chunk_len)
+  }
+
+  /// Window of the multi-scalar multiplications, if this SNARK is in the low-memory mode
+  pub(crate) fn msm_window(&self) -> Option<usize> {
+    self.low_memory.as_ref().map(|low_memory| low_memory.config.msm_window)
+  }
+}
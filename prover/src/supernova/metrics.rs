@@ -0,0 +1,83 @@
+//! Metrics of SuperNova proving
+//!
+//! [`RecursiveSNARK::prove_step_with_metrics`](super::RecursiveSNARK::prove_step_with_metrics)
+//! fills a [`StepMetrics`] per step and
+//! [`CompressedSNARK::prove_with_metrics`](super::snark::CompressedSNARK::prove_with_metrics) a
+//! [`CompressionMetrics`]. Both serialize with `serde`, with durations in fractional milliseconds.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+use crate::metrics::{self, duration_ms};
+
+/// Sizes of the multi-scalar multiplications of one fold
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MsmSizes {
+  /// Number of scalars in the commitment to the witness of the step
+  pub witness:    usize,
+  /// Number of scalars in the commitment to the cross-term
+  pub cross_term: usize,
+}
+
+/// Metrics of a single step of a [`RecursiveSNARK`](super::RecursiveSNARK)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepMetrics {
+  /// Index of the primary circuit of the step
+  pub circuit_index:      usize,
+  /// Time spent generating the witness of the primary step circuit
+  #[serde(with = "duration_ms")]
+  pub witness_generation: Duration,
+  /// Time spent synthesizing both augmented circuits, excluding witness generation
+  #[serde(with = "duration_ms")]
+  pub synthesis:          Duration,
+  /// Time spent folding both circuits, including the commitments to the cross-terms
+  #[serde(with = "duration_ms")]
+  pub nifs:               Duration,
+  /// Time spent committing to the witnesses of both circuits
+  #[serde(with = "duration_ms")]
+  pub commit:             Duration,
+  /// Sizes of the multi-scalar multiplications of the primary fold
+  pub msm_primary:        MsmSizes,
+  /// Sizes of the multi-scalar multiplications of the secondary fold
+  pub msm_secondary:      MsmSizes,
+  /// Bytes held by the scratch buffers of both folds after the step
+  pub buffer_bytes:       usize,
+}
+
+/// Metrics of one of the SNARKs of a [`CompressedSNARK`](super::snark::CompressedSNARK)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnarkMetrics {
+  /// Time spent proving the SNARK
+  #[serde(with = "duration_ms")]
+  pub total:    Duration,
+  /// Time spent in sum-check proofs
+  #[serde(with = "duration_ms")]
+  pub sumcheck: Duration,
+  /// Time spent opening polynomial commitments
+  #[serde(with = "duration_ms")]
+  pub pcs:      Duration,
+}
+
+impl SnarkMetrics {
+  /// Runs the proof `f` and measures it
+  pub(crate) fn measure<T>(f: impl FnOnce() -> T) -> (T, Self) {
+    let time = Instant::now();
+    let (result, timings) = metrics::record(f);
+    let total = time.elapsed();
+    (result, Self { total, sumcheck: timings.sumcheck, pcs: timings.pcs })
+  }
+}
+
+/// Metrics of a [`CompressedSNARK`](super::snark::CompressedSNARK) proof
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressionMetrics {
+  /// Time spent folding the running instances, and blinding them if requested
+  #[serde(with = "duration_ms")]
+  pub folding:   Duration,
+  /// The batched SNARK of the primary circuits
+  pub primary:   SnarkMetrics,
+  /// The SNARK of the secondary circuit
+  pub secondary: SnarkMetrics,
+}
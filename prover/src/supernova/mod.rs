@@ -3,12 +3,13 @@
 use std::{ops::Index, sync::Arc};
 
 use bellpepper_core::{ConstraintSystem, SynthesisError};
-use ff::Field;
+use ff::{Field, PrimeField};
 use itertools::Itertools as _;
 use once_cell::sync::OnceCell;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
+use web_time::Instant;
 
 use crate::{
   bellpepper::{
@@ -26,7 +27,7 @@ use crate::{
   },
   scalar_as_base,
   traits::{
-    commitment::{CommitmentEngineTrait, CommitmentTrait},
+    commitment::{CommitmentEngineTrait, CommitmentTrait, Len},
     AbsorbInROTrait, CurveCycleEquipped, Dual, Engine, ROConstants, ROConstantsCircuit, ROTrait,
   },
   Commitment, CommitmentKey, R1CSWithArity,
@@ -36,6 +37,8 @@ mod circuit; // declare the module first
 pub use circuit::{StepCircuit, SuperNovaAugmentedCircuitParams, TrivialCircuit};
 use circuit::{SuperNovaAugmentedCircuit, SuperNovaAugmentedCircuitInputs};
 use error::SuperNovaError;
+use low_memory::LowMemory;
+use metrics::{MsmSizes, StepMetrics};
 
 /// A struct that manages all the digests of the primary circuits of a SuperNova
 /// instance
@@ -108,11 +111,9 @@ where E1: CurveCycleEquipped {
   pub digest: E1::Scalar,
 }
 
-use std::io::Cursor;
-
 use crate::{
   fast_serde,
-  fast_serde::{FastSerde, SerdeByteError, SerdeByteTypes},
+  fast_serde::{FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
 };
 
 impl<E1> FastSerde for AuxParams<E1>
@@ -121,93 +122,51 @@ where
   <E1::CE as CommitmentEngineTrait<E1>>::CommitmentKey: FastSerde,
   <<E1::Secondary as Engine>::CE as CommitmentEngineTrait<E1::Secondary>>::CommitmentKey: FastSerde,
 {
-  /// Byte format:
-  /// [0..4]   - Magic number (4 bytes)
-  /// [4]      - Serde type: AuxParams (u8)
-  /// [5]      - Number of sections (u8 = 8)
-  /// Sections (repeated 8 times):
-  ///   [N]      - Section type (u8)
-  ///   [N+1..5] - Section size (u32)
-  ///   [N+5..]  - Section data (variable length)
-  /// Section types:
-  ///   1: ro_consts_primary (bincode)
-  ///   2: ro_consts_circuit_primary (bincode)
+  /// Sections:
   ///   3: ck_primary (FastSerde)
-  ///   4: ro_consts_secondary (bincode)
-  ///   5: ro_consts_circuit_secondary (bincode)
   ///   6: ck_secondary (FastSerde)
-  ///   7: circuit_shape_secondary (json)
-  ///   8: digest (bincode)
+  ///   7: circuit_shape_secondary (FastSerde)
+  ///   8: digest (scalar)
+  ///
+  /// The random oracle constants are not stored: they are the defaults of
+  /// their engines, and are recreated on deserialization. Sections 1, 2, 4
+  /// and 5 held them in files written with bincode, which readers reject as
+  /// unknown required sections.
   fn to_bytes(&self) -> Vec<u8> {
-    let mut out = Vec::new();
-
-    // Write header
-    out.extend_from_slice(&fast_serde::MAGIC_NUMBER);
-    out.push(fast_serde::SerdeByteTypes::AuxParams as u8);
-    out.push(8); // num_sections
-
-    // Write sections
-    Self::write_section_bytes(&mut out, 1, &bincode::serialize(&self.ro_consts_primary).unwrap());
-    Self::write_section_bytes(
-      &mut out,
-      2,
-      &bincode::serialize(&self.ro_consts_circuit_primary).unwrap(),
-    );
-    Self::write_section_bytes(&mut out, 3, &self.ck_primary.to_bytes());
-    Self::write_section_bytes(&mut out, 4, &bincode::serialize(&self.ro_consts_secondary).unwrap());
-    Self::write_section_bytes(
-      &mut out,
-      5,
-      &bincode::serialize(&self.ro_consts_circuit_secondary).unwrap(),
-    );
-    Self::write_section_bytes(&mut out, 6, &self.ck_secondary.to_bytes());
-    Self::write_section_bytes(
-      &mut out,
-      7,
-      &bincode::serialize(&self.circuit_shape_secondary).unwrap(),
-    );
-    Self::write_section_bytes(&mut out, 8, &bincode::serialize(&self.digest).unwrap());
-
-    out
+    let mut out = SectionWriter::new(SerdeByteTypes::AuxParams);
+    out.section(3, self.ck_primary.to_bytes());
+    out.section(6, self.ck_secondary.to_bytes());
+    out.section(7, self.circuit_shape_secondary.to_bytes());
+    out.section(8, self.digest.to_repr().as_ref().to_vec());
+    out.finish()
   }
 
   fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
-    let mut cursor = Cursor::new(bytes);
-
-    // Validate header
-    Self::validate_header(&mut cursor, SerdeByteTypes::AuxParams, 8)?;
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::AuxParams)?;
 
     // Read all sections
-    let ro_consts_primary = bincode::deserialize(&Self::read_section_bytes(&mut cursor, 1)?)?;
-    let ro_consts_circuit_primary =
-      bincode::deserialize(&Self::read_section_bytes(&mut cursor, 2)?)?;
     let ck_primary = Arc::new(<E1::CE as CommitmentEngineTrait<E1>>::CommitmentKey::from_bytes(
-      &Self::read_section_bytes(&mut cursor, 3)?,
+      sections.section(3)?,
     )?);
-    let ro_consts_secondary = bincode::deserialize(&Self::read_section_bytes(&mut cursor, 4)?)?;
-    let ro_consts_circuit_secondary =
-      bincode::deserialize(&Self::read_section_bytes(&mut cursor, 5)?)?;
     let ck_secondary = Arc::new(<<E1::Secondary as Engine>::CE as CommitmentEngineTrait<
       E1::Secondary,
-    >>::CommitmentKey::from_bytes(&Self::read_section_bytes(
-      &mut cursor,
-      6,
-    )?)?);
-    let circuit_shape_secondary = bincode::deserialize(&Self::read_section_bytes(&mut cursor, 7)?)?;
-    let digest = bincode::deserialize(&Self::read_section_bytes(&mut cursor, 8)?)?;
+    >>::CommitmentKey::from_bytes(sections.section(6)?)?);
+    let circuit_shape_secondary = R1CSWithArity::from_bytes(sections.section(7)?)?;
+    let digest = fast_serde::scalar_from_bytes(sections.section(8)?)?;
+    sections.finish()?;
 
     // NOTE: This does not check the digest. Maybe we should.
     Ok(Self {
-      ro_consts_primary,
-      ro_consts_circuit_primary,
+      ro_consts_primary: ROConstants::<E1>::default(),
+      ro_consts_circuit_primary: ROConstantsCircuit::<Dual<E1>>::default(),
       ck_primary,
       augmented_circuit_params_primary: SuperNovaAugmentedCircuitParams::new(
         BN_LIMB_WIDTH,
         BN_N_LIMBS,
         true,
       ),
-      ro_consts_secondary,
-      ro_consts_circuit_secondary,
+      ro_consts_secondary: ROConstants::<Dual<E1>>::default(),
+      ro_consts_circuit_secondary: ROConstantsCircuit::<E1>::default(),
       ck_secondary,
       circuit_shape_secondary,
       augmented_circuit_params_secondary: SuperNovaAugmentedCircuitParams::new(
@@ -259,6 +218,44 @@ where E1: CurveCycleEquipped
     non_uniform_circuit: &NC,
     ck_hint1: &CommitmentKeyHint<E1>,
     ck_hint2: &CommitmentKeyHint<Dual<E1>>,
+  ) -> Self {
+    let circuit_shapes = get_circuit_shapes(non_uniform_circuit);
+    let ck_primary = Self::compute_primary_ck(&circuit_shapes, ck_hint1);
+    Self::setup_with_shapes(non_uniform_circuit, circuit_shapes, ck_primary, ck_hint2)
+  }
+
+  /// Construct a new [`PublicParams`] like [`PublicParams::setup`], but with
+  /// an externally supplied commitment key for the primary circuits instead
+  /// of one sampled by [`CommitmentEngineTrait::setup`].
+  ///
+  /// This is how a production SRS, such as a KZG SRS loaded from a
+  /// powers-of-tau ceremony with [`crate::provider::srs`], is used.
+  ///
+  /// # Errors
+  ///
+  /// Returns [`NovaError::InvalidCommitmentKeyLength`] if `ck_primary` is
+  /// shorter than the commitment key [`PublicParams::setup`] would sample for
+  /// the circuits and `ck_hint1`.
+  pub fn setup_with_commitment_key<NC: NonUniformCircuit<E1>>(
+    non_uniform_circuit: &NC,
+    ck_primary: CommitmentKey<E1>,
+    ck_hint1: &CommitmentKeyHint<E1>,
+    ck_hint2: &CommitmentKeyHint<Dual<E1>>,
+  ) -> Result<Self, NovaError> {
+    let circuit_shapes = get_circuit_shapes(non_uniform_circuit);
+    if ck_primary.length() < Self::primary_ck_size(&circuit_shapes, ck_hint1) {
+      return Err(NovaError::InvalidCommitmentKeyLength);
+    }
+    Ok(Self::setup_with_shapes(non_uniform_circuit, circuit_shapes, ck_primary, ck_hint2))
+  }
+
+  /// Builds the public parameters from the primary circuit shapes and
+  /// commitment key
+  fn setup_with_shapes<NC: NonUniformCircuit<E1>>(
+    non_uniform_circuit: &NC,
+    circuit_shapes: Vec<R1CSWithArity<E1>>,
+    ck_primary: CommitmentKey<E1>,
+    ck_hint2: &CommitmentKeyHint<Dual<E1>>,
   ) -> Self {
     let num_circuits = non_uniform_circuit.num_circuits();
 
@@ -270,9 +267,6 @@ where E1: CurveCycleEquipped
     let ro_consts_circuit_primary: ROConstantsCircuit<Dual<E1>> =
       ROConstantsCircuit::<Dual<E1>>::default();
 
-    let circuit_shapes = get_circuit_shapes(non_uniform_circuit);
-
-    let ck_primary = Self::compute_primary_ck(&circuit_shapes, ck_hint1);
     let ck_primary = Arc::new(ck_primary);
 
     let augmented_circuit_params_secondary =
@@ -371,6 +365,17 @@ where E1: CurveCycleEquipped
   /// Create a [`PublicParams`] from a vector of raw [`R1CSWithArity`] and
   /// auxiliary params.
   pub fn from_parts(circuit_shapes: Vec<R1CSWithArity<E1>>, aux_params: AuxParams<E1>) -> Self {
+    Self::try_from_parts(circuit_shapes, aux_params)
+      .expect("param data is invalid; aux_params contained the incorrect digest")
+  }
+
+  /// Create a [`PublicParams`] from a vector of raw [`R1CSWithArity`] and
+  /// auxiliary params, returning `None` if `aux_params.digest` is not the
+  /// digest of the created params.
+  pub fn try_from_parts(
+    circuit_shapes: Vec<R1CSWithArity<E1>>,
+    aux_params: AuxParams<E1>,
+  ) -> Option<Self> {
     let pp = Self {
       circuit_shapes,
       ro_consts_primary: aux_params.ro_consts_primary,
@@ -384,12 +389,7 @@ where E1: CurveCycleEquipped
       augmented_circuit_params_secondary: aux_params.augmented_circuit_params_secondary,
       digest: OnceCell::new(),
     };
-    assert_eq!(
-      aux_params.digest,
-      pp.digest(),
-      "param data is invalid; aux_params contained the incorrect digest"
-    );
-    pp
+    (aux_params.digest == pp.digest()).then_some(pp)
   }
 
   /// Create a [`PublicParams`] from a vector of raw [`R1CSWithArity`] and
@@ -420,13 +420,20 @@ where E1: CurveCycleEquipped
     circuit_params: &[R1CSWithArity<E1>],
     ck_hint1: &CommitmentKeyHint<E1>,
   ) -> CommitmentKey<E1> {
-    let size_primary = circuit_params
+    E1::CE::setup(b"ck", Self::primary_ck_size(circuit_params, ck_hint1))
+  }
+
+  /// The number of generators needed to commit to the largest of the
+  /// circuits in the provided `R1CSWithArity`.
+  fn primary_ck_size(
+    circuit_params: &[R1CSWithArity<E1>],
+    ck_hint1: &CommitmentKeyHint<E1>,
+  ) -> usize {
+    circuit_params
       .iter()
       .map(|circuit| commitment_key_size(&circuit.r1cs_shape, ck_hint1))
       .max()
-      .unwrap();
-
-    E1::CE::setup(b"ck", size_primary)
+      .unwrap()
   }
 
   /// Return the [`PublicParams`]' digest.
@@ -518,6 +525,15 @@ struct ResourceBuffer<E: Engine> {
   T: Vec<E::Scalar>,
 }
 
+impl<E: Engine> ResourceBuffer<E> {
+  /// Bytes held by the scratch vectors of this buffer
+  fn scratch_bytes(&self) -> usize {
+    let products = |ABC_Z: &R1CSResult<E>| ABC_Z.AZ.len() + ABC_Z.BZ.len() + ABC_Z.CZ.len();
+    (products(&self.ABC_Z_1) + products(&self.ABC_Z_2) + self.T.capacity())
+      * std::mem::size_of::<E::Scalar>()
+  }
+}
+
 /// A SNARK that proves the correct execution of an non-uniform incremental
 /// computation
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -558,6 +574,195 @@ where E1: CurveCycleEquipped {
   // Proof for the secondary circuit to be accumulated into r_secondary in the next iteration
   l_w_secondary: R1CSWitness<Dual<E1>>,
   l_u_secondary: R1CSInstance<Dual<E1>>,
+
+  /// Spill storage of the low-memory mode, see [`RecursiveSNARK::enable_low_memory`]. It is never
+  /// stored, and serialization fails while a witness is spilled.
+  #[serde(
+    skip_deserializing,
+    skip_serializing_if = "low_memory::has_no_spills",
+    serialize_with = "low_memory::reject_spills"
+  )]
+  low_memory: Option<LowMemory>,
+}
+
+impl<E1> RecursiveSNARK<E1>
+where E1: CurveCycleEquipped
+{
+  /// Serializes this SNARK as [`FastSerde::to_bytes`] does, reading spilled
+  /// witnesses back
+  ///
+  /// # Errors
+  ///
+  /// Returns [`SuperNovaError::SpillError`] if a spilled witness cannot be
+  /// read back.
+  pub fn try_to_bytes(&self) -> Result<Vec<u8>, SuperNovaError> {
+    let mut out = SectionWriter::new(SerdeByteTypes::RecursiveSNARK);
+
+    out.section(1, self.pp_digest.to_repr().as_ref().to_vec());
+    out.section(2, (self.num_augmented_circuits as u64).to_le_bytes());
+    out.section(3, (self.i as u64).to_le_bytes());
+    out.section(4, fast_serde::scalars_to_bytes(&self.z0_primary));
+    out.section(5, fast_serde::scalars_to_bytes(&self.zi_primary));
+    out.section(6, (self.proven_circuit_index as u64).to_le_bytes());
+    out.section(7, self.program_counter.to_repr().as_ref().to_vec());
+
+    let buffer_sizes =
+      [self.buffer_primary.ABC_Z_1.AZ.len(), self.buffer_secondary.ABC_Z_1.AZ.len()]
+        .iter()
+        .flat_map(|size| (*size as u64).to_le_bytes())
+        .collect::<Vec<u8>>();
+    out.section(8, buffer_sizes);
+
+    let r_W_primary = (0..self.num_augmented_circuits)
+      .map(|i| self.primary_witness(i))
+      .collect::<Result<Vec<_>, _>>()?;
+    let flags = r_W_primary.iter().map(|w| u8::from(w.is_some())).collect::<Vec<u8>>();
+    out.section(9, flags);
+    let witnesses = r_W_primary.iter().flatten().map(|w| w.to_bytes()).collect::<Vec<_>>();
+    out.section(10, fast_serde::list_to_bytes(&witnesses));
+    let r_U_primary = self
+      .r_U_primary
+      .iter()
+      .map(|U| U.as_ref().map_or_else(Vec::new, RelaxedR1CSInstance::to_bytes))
+      .collect::<Vec<_>>();
+    out.section(11, fast_serde::list_to_bytes(&r_U_primary));
+
+    out.section(12, fast_serde::scalars_to_bytes(&self.z0_secondary));
+    out.section(13, fast_serde::scalars_to_bytes(&self.zi_secondary));
+    out.section(14, self.r_W_secondary.to_bytes());
+    out.section(15, self.r_U_secondary.to_bytes());
+    out.section(16, self.l_w_secondary.to_bytes());
+    out.section(17, self.l_u_secondary.to_bytes());
+
+    Ok(out.finish())
+  }
+}
+
+impl<E1> FastSerde for RecursiveSNARK<E1>
+where E1: CurveCycleEquipped
+{
+  /// Sections:
+  ///   1: pp_digest (scalar)
+  ///   2: num_augmented_circuits (u64)
+  ///   3: i (u64)
+  ///   4: z0_primary (scalars)
+  ///   5: zi_primary (scalars)
+  ///   6: proven_circuit_index (u64)
+  ///   7: program_counter (scalar)
+  ///   8: buffer sizes (u64, u64)
+  ///   9: r_W_primary presence flags (one u8 per circuit)
+  ///   10: r_W_primary (list of FastSerde, one per present witness)
+  ///   11: r_U_primary (list of FastSerde, empty entries for circuits not executed yet)
+  ///   12: z0_secondary (scalars)
+  ///   13: zi_secondary (scalars)
+  ///   14: r_W_secondary (FastSerde)
+  ///   15: r_U_secondary (FastSerde)
+  ///   16: l_w_secondary (FastSerde)
+  ///   17: l_u_secondary (FastSerde)
+  ///
+  /// The resource buffers only hold scratch values, so only their sizes are
+  /// stored and they are reallocated on deserialization. Witnesses spilled in
+  /// the low-memory mode are read back and stored like resident ones, and the
+  /// SNARK is deserialized in the regular mode.
+  ///
+  /// # Panics
+  ///
+  /// Panics if a spilled witness cannot be read back, use
+  /// [`RecursiveSNARK::try_to_bytes`] to handle this error.
+  fn to_bytes(&self) -> Vec<u8> { self.try_to_bytes().expect("failed to read a spilled witness") }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::RecursiveSNARK)?;
+
+    // Read all sections
+    let pp_digest = fast_serde::scalar_from_bytes(sections.section(1)?)?;
+    let num_augmented_circuits = fast_serde::usize_from_bytes(sections.section(2)?)?;
+    let i = fast_serde::usize_from_bytes(sections.section(3)?)?;
+    let z0_primary = fast_serde::scalars_from_bytes(sections.section(4)?)?;
+    let zi_primary = fast_serde::scalars_from_bytes(sections.section(5)?)?;
+    let proven_circuit_index = fast_serde::usize_from_bytes(sections.section(6)?)?;
+    let program_counter = fast_serde::scalar_from_bytes(sections.section(7)?)?;
+
+    let buffer_sizes = sections.section(8)?;
+    if buffer_sizes.len() != 16 {
+      return Err(SerdeByteError::InvalidSectionSize);
+    }
+    let num_cons_primary = fast_serde::usize_from_bytes(&buffer_sizes[..8])?;
+    let num_cons_secondary = fast_serde::usize_from_bytes(&buffer_sizes[8..])?;
+
+    let flags = sections.section(9)?;
+    if flags.len() != num_augmented_circuits {
+      return Err(SerdeByteError::InvalidSectionSize);
+    }
+    let mut witnesses = fast_serde::list_from_bytes(sections.section(10)?)?.into_iter();
+    let r_W_primary = flags
+      .iter()
+      .map(|flag| match flag {
+        0 => Ok(None),
+        1 => witnesses
+          .next()
+          .ok_or(SerdeByteError::InvalidSectionSize)
+          .and_then(RelaxedR1CSWitness::from_bytes)
+          .map(Some),
+        _ => Err(SerdeByteError::InvalidSectionType),
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    if witnesses.next().is_some() {
+      return Err(SerdeByteError::InvalidSectionSize);
+    }
+    let r_U_primary = fast_serde::list_from_bytes(sections.section(11)?)?
+      .into_iter()
+      .map(|U| (!U.is_empty()).then(|| RelaxedR1CSInstance::from_bytes(U)).transpose())
+      .collect::<Result<Vec<_>, _>>()?;
+    if r_U_primary.len() != num_augmented_circuits {
+      return Err(SerdeByteError::InvalidSectionSize);
+    }
+
+    let z0_secondary = fast_serde::scalars_from_bytes(sections.section(12)?)?;
+    let zi_secondary = fast_serde::scalars_from_bytes(sections.section(13)?)?;
+    let r_W_secondary = RelaxedR1CSWitness::from_bytes(sections.section(14)?)?;
+    let r_U_secondary = RelaxedR1CSInstance::from_bytes(sections.section(15)?)?;
+    let l_w_secondary = R1CSWitness::from_bytes(sections.section(16)?)?;
+    let l_u_secondary = R1CSInstance::from_bytes(sections.section(17)?)?;
+    sections.finish()?;
+
+    let buffer_primary = ResourceBuffer {
+      l_w:     None,
+      l_u:     None,
+      ABC_Z_1: R1CSResult::default(num_cons_primary),
+      ABC_Z_2: R1CSResult::default(num_cons_primary),
+      T:       r1cs::default_T::<E1>(num_cons_primary),
+    };
+
+    let buffer_secondary = ResourceBuffer {
+      l_w:     None,
+      l_u:     None,
+      ABC_Z_1: R1CSResult::default(num_cons_secondary),
+      ABC_Z_2: R1CSResult::default(num_cons_secondary),
+      T:       r1cs::default_T::<Dual<E1>>(num_cons_secondary),
+    };
+
+    Ok(Self {
+      pp_digest,
+      num_augmented_circuits,
+      i,
+      z0_primary,
+      zi_primary,
+      proven_circuit_index,
+      program_counter,
+      buffer_primary,
+      buffer_secondary,
+      r_W_primary,
+      r_U_primary,
+      z0_secondary,
+      zi_secondary,
+      r_W_secondary,
+      r_U_secondary,
+      l_w_secondary,
+      l_u_secondary,
+      low_memory: None,
+    })
+  }
 }
 
 impl<E1> RecursiveSNARK<E1>
@@ -749,6 +954,7 @@ where E1: CurveCycleEquipped
       r_U_secondary,
       l_w_secondary,
       l_u_secondary,
+      low_memory: None,
     })
   }
 
@@ -768,14 +974,33 @@ where E1: CurveCycleEquipped
   pub fn program_counter(&self) -> E1::Scalar { self.program_counter }
 
   /// executing a step of the incremental computation
-  #[allow(clippy::too_many_arguments)]
-  #[tracing::instrument(skip_all, name = "supernova::RecursiveSNARK::prove_step")]
   pub fn prove_step<C1: StepCircuit<E1::Scalar>, C2: StepCircuit<<Dual<E1> as Engine>::Scalar>>(
     &mut self,
     pp: &PublicParams<E1>,
     c_primary: &C1,
     c_secondary: &C2,
   ) -> Result<(), SuperNovaError> {
+    self.prove_step_with_metrics(pp, c_primary, c_secondary, &mut StepMetrics::default())
+  }
+
+  /// executing a step of the incremental computation like
+  /// [`RecursiveSNARK::prove_step`], and recording its metrics into `metrics`
+  ///
+  /// The first step is proven by [`RecursiveSNARK::new`], so only the circuit
+  /// index is recorded for it.
+  #[tracing::instrument(skip_all, name = "supernova::RecursiveSNARK::prove_step")]
+  pub fn prove_step_with_metrics<
+    C1: StepCircuit<E1::Scalar>,
+    C2: StepCircuit<<Dual<E1> as Engine>::Scalar>,
+  >(
+    &mut self,
+    pp: &PublicParams<E1>,
+    c_primary: &C1,
+    c_secondary: &C2,
+    metrics: &mut StepMetrics,
+  ) -> Result<(), SuperNovaError> {
+    *metrics = StepMetrics { circuit_index: c_primary.circuit_index(), ..Default::default() };
+
     // First step was already done in the constructor
     if self.i == 0 {
       self.i = 1;
@@ -792,21 +1017,44 @@ where E1: CurveCycleEquipped
     let circuit_index = c_primary.circuit_index();
     assert_eq!(self.program_counter, E1::Scalar::from(circuit_index as u64));
 
+    // in the low-memory mode, the running witness of this circuit may be spilled
+    self.restore_witness(circuit_index)?;
+    let chunk_len = self.chunk_len();
+    let msm_window = self.msm_window();
+
     // fold the secondary circuit's instance
-    let (nifs_secondary, _) = NIFS::prove_mut(
-      &*pp.ck_secondary,
-      &pp.ro_consts_secondary,
-      &scalar_as_base::<E1>(self.pp_digest),
-      &pp.circuit_shape_secondary.r1cs_shape,
-      &mut self.r_U_secondary,
-      &mut self.r_W_secondary,
-      &self.l_u_secondary,
-      &self.l_w_secondary,
-      &mut self.buffer_secondary.T,
-      &mut self.buffer_secondary.ABC_Z_1,
-      &mut self.buffer_secondary.ABC_Z_2,
-    )
+    let time = Instant::now();
+    let (nifs_secondary, _) = match chunk_len {
+      Some(chunk_len) => NIFS::prove_mut_chunked(
+        &*pp.ck_secondary,
+        &pp.ro_consts_secondary,
+        &scalar_as_base::<E1>(self.pp_digest),
+        &pp.circuit_shape_secondary.r1cs_shape,
+        &mut self.r_U_secondary,
+        &mut self.r_W_secondary,
+        &self.l_u_secondary,
+        &self.l_w_secondary,
+        &mut self.buffer_secondary.ABC_Z_1,
+        &mut self.buffer_secondary.ABC_Z_2,
+        chunk_len,
+      ),
+      None => NIFS::prove_mut(
+        &*pp.ck_secondary,
+        &pp.ro_consts_secondary,
+        &scalar_as_base::<E1>(self.pp_digest),
+        &pp.circuit_shape_secondary.r1cs_shape,
+        &mut self.r_U_secondary,
+        &mut self.r_W_secondary,
+        &self.l_u_secondary,
+        &self.l_w_secondary,
+        &mut self.buffer_secondary.T,
+        &mut self.buffer_secondary.ABC_Z_1,
+        &mut self.buffer_secondary.ABC_Z_2,
+      ),
+    }
     .map_err(SuperNovaError::NovaError)?;
+    metrics.nifs = time.elapsed();
+    metrics.msm_secondary.cross_term = pp.circuit_shape_secondary.r1cs_shape.num_cons;
 
     let mut cs_primary = SatisfyingAssignment::<E1>::with_capacity(
       pp[circuit_index].r1cs_shape.num_io + 1,
@@ -836,15 +1084,32 @@ where E1: CurveCycleEquipped
         self.num_augmented_circuits,
       );
 
-    let (zi_primary_pc_next, zi_primary) =
-      circuit_primary.synthesize(&mut cs_primary).map_err(NovaError::from)?;
+    let time = Instant::now();
+    let (synthesized, timings) =
+      crate::metrics::record(|| circuit_primary.synthesize(&mut cs_primary));
+    let (zi_primary_pc_next, zi_primary) = synthesized.map_err(NovaError::from)?;
+    metrics.witness_generation = timings.witness_generation;
+    metrics.synthesis = time.elapsed().saturating_sub(timings.witness_generation);
     if zi_primary.len() != pp[circuit_index].F_arity {
       return Err(SuperNovaError::NovaError(NovaError::InvalidInitialInputLength));
     }
 
-    let (l_u_primary, l_w_primary) = cs_primary
-      .r1cs_instance_and_witness(&pp[circuit_index].r1cs_shape, &pp.ck_primary)
-      .map_err(SuperNovaError::NovaError)?;
+    let time = Instant::now();
+    let (l_u_primary, l_w_primary) = match msm_window {
+      Some(msm_window) => low_memory::r1cs_instance_and_witness_windowed(
+        cs_primary,
+        &pp[circuit_index].r1cs_shape,
+        &pp.ck_primary,
+        msm_window,
+      ),
+      None => cs_primary.r1cs_instance_and_witness(&pp[circuit_index].r1cs_shape, &pp.ck_primary),
+    }
+    .map_err(SuperNovaError::NovaError)?;
+    metrics.commit = time.elapsed();
+    metrics.msm_primary = MsmSizes {
+      witness:    l_w_primary.W.len(),
+      cross_term: pp[circuit_index].r1cs_shape.num_cons,
+    };
 
     let (r_U_primary, r_W_primary) = if let (Some(Some(r_U_primary)), Some(Some(r_W_primary))) =
       (self.r_U_primary.get_mut(circuit_index), self.r_W_primary.get_mut(circuit_index))
@@ -861,20 +1126,37 @@ where E1: CurveCycleEquipped
       )
     };
 
-    let (nifs_primary, _) = NIFS::prove_mut(
-      &*pp.ck_primary,
-      &pp.ro_consts_primary,
-      &self.pp_digest,
-      &pp[circuit_index].r1cs_shape,
-      r_U_primary,
-      r_W_primary,
-      &l_u_primary,
-      &l_w_primary,
-      &mut self.buffer_primary.T,
-      &mut self.buffer_primary.ABC_Z_1,
-      &mut self.buffer_primary.ABC_Z_2,
-    )
+    let time = Instant::now();
+    let (nifs_primary, _) = match chunk_len {
+      Some(chunk_len) => NIFS::prove_mut_chunked(
+        &*pp.ck_primary,
+        &pp.ro_consts_primary,
+        &self.pp_digest,
+        &pp[circuit_index].r1cs_shape,
+        r_U_primary,
+        r_W_primary,
+        &l_u_primary,
+        &l_w_primary,
+        &mut self.buffer_primary.ABC_Z_1,
+        &mut self.buffer_primary.ABC_Z_2,
+        chunk_len,
+      ),
+      None => NIFS::prove_mut(
+        &*pp.ck_primary,
+        &pp.ro_consts_primary,
+        &self.pp_digest,
+        &pp[circuit_index].r1cs_shape,
+        r_U_primary,
+        r_W_primary,
+        &l_u_primary,
+        &l_w_primary,
+        &mut self.buffer_primary.T,
+        &mut self.buffer_primary.ABC_Z_1,
+        &mut self.buffer_primary.ABC_Z_2,
+      ),
+    }
     .map_err(SuperNovaError::NovaError)?;
+    metrics.nifs += time.elapsed();
 
     let mut cs_secondary = SatisfyingAssignment::<Dual<E1>>::with_capacity(
       pp.circuit_shape_secondary.r1cs_shape.num_io + 1,
@@ -902,14 +1184,27 @@ where E1: CurveCycleEquipped
       pp.ro_consts_circuit_secondary.clone(),
       self.num_augmented_circuits,
     );
+    let time = Instant::now();
     let (_, zi_secondary) =
       circuit_secondary.synthesize(&mut cs_secondary).map_err(NovaError::from)?;
+    metrics.synthesis += time.elapsed();
     if zi_secondary.len() != pp.circuit_shape_secondary.F_arity {
       return Err(SuperNovaError::NovaError(NovaError::InvalidInitialInputLength));
     }
 
-    let (l_u_secondary_next, l_w_secondary_next) = cs_secondary
-      .r1cs_instance_and_witness(&pp.circuit_shape_secondary.r1cs_shape, &pp.ck_secondary)?;
+    let time = Instant::now();
+    let (l_u_secondary_next, l_w_secondary_next) = match msm_window {
+      Some(msm_window) => low_memory::r1cs_instance_and_witness_windowed(
+        cs_secondary,
+        &pp.circuit_shape_secondary.r1cs_shape,
+        &pp.ck_secondary,
+        msm_window,
+      ),
+      None => cs_secondary
+        .r1cs_instance_and_witness(&pp.circuit_shape_secondary.r1cs_shape, &pp.ck_secondary),
+    }?;
+    metrics.commit += time.elapsed();
+    metrics.msm_secondary.witness = l_w_secondary_next.W.len();
 
     // update the running instances and witnesses
     let zi_primary = zi_primary
@@ -939,7 +1234,11 @@ where E1: CurveCycleEquipped
     self.zi_secondary = zi_secondary;
     self.proven_circuit_index = circuit_index;
     self.program_counter = zi_primary_pc_next;
-    Ok(())
+    metrics.buffer_bytes =
+      self.buffer_primary.scratch_bytes() + self.buffer_secondary.scratch_bytes();
+
+    // in the low-memory mode, only the running witness of the next circuit stays in memory
+    self.spill_inactive_witnesses()
   }
 
   /// verify recursive snark
@@ -968,6 +1267,7 @@ where E1: CurveCycleEquipped
     self.r_U_primary.iter().zip_eq(self.r_W_primary.iter()).enumerate().try_for_each(
       |(i, (u, w))| match (u, w) {
         (Some(_), Some(_)) | (None, None) => Ok(()),
+        (Some(_), None) if self.is_spilled(i) => Ok(()),
         _ => {
           debug!("r_primary[{:?}]: mismatched instance/witness pair", i);
           Err(SuperNovaError::NovaError(NovaError::ProofVerifyError))
@@ -1073,14 +1373,30 @@ where E1: CurveCycleEquipped
     // check the satisfiability of all instance/witness pairs
     let (res_r_primary, (res_r_secondary, res_l_secondary)) = rayon::join(
       || {
-        self.r_U_primary.par_iter().zip_eq(self.r_W_primary.par_iter()).enumerate().try_for_each(
-          |(i, (u, w))| {
-            if let (Some(u), Some(w)) = (u, w) {
-              pp[i].r1cs_shape.is_sat_relaxed(&pp.ck_primary, u, w)?
+        let check_primary =
+          |(i, u): (usize, &Option<RelaxedR1CSInstance<E1>>)| -> Result<(), SuperNovaError> {
+            if let Some(u) = u {
+              // spilled witnesses are read back one at a time
+              let spilled;
+              let w = match &self.r_W_primary[i] {
+                Some(w) => w,
+                None => {
+                  spilled = self.primary_witness(i)?.ok_or(NovaError::ProofVerifyError)?;
+                  &spilled
+                },
+              };
+              pp[i].r1cs_shape.is_sat_relaxed(&pp.ck_primary, u, w).map_err(|err| match err {
+                NovaError::UnSatIndex(i) => SuperNovaError::UnSatIndex("r_primary", i),
+                e => SuperNovaError::NovaError(e),
+              })?
             }
             Ok(())
-          },
-        )
+          };
+        if self.low_memory.is_some() {
+          self.r_U_primary.iter().enumerate().try_for_each(check_primary)
+        } else {
+          self.r_U_primary.par_iter().enumerate().try_for_each(check_primary)
+        }
       },
       || {
         rayon::join(
@@ -1102,10 +1418,7 @@ where E1: CurveCycleEquipped
       },
     );
 
-    res_r_primary.map_err(|err| match err {
-      NovaError::UnSatIndex(i) => SuperNovaError::UnSatIndex("r_primary", i),
-      e => SuperNovaError::NovaError(e),
-    })?;
+    res_r_primary?;
     res_r_secondary.map_err(|err| match err {
       NovaError::UnSatIndex(i) => SuperNovaError::UnSatIndex("r_secondary", i),
       e => SuperNovaError::NovaError(e),
@@ -1191,7 +1504,9 @@ fn num_ro_inputs(num_circuits: usize, num_limbs: usize, arity: usize, is_primary
 }
 
 pub mod error;
+pub mod low_memory;
+pub mod metrics;
 pub mod snark;
-mod utils;
+pub(crate) mod utils;
 
 #[cfg(test)] mod test;
@@ -1,11 +1,20 @@
 //! This module defines a final compressing SNARK for supernova proofs
 
+use ff::PrimeField;
+use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
+use web_time::Instant;
 
-use super::{error::SuperNovaError, PublicParams, RecursiveSNARK};
+use super::{
+  error::SuperNovaError,
+  metrics::{CompressionMetrics, SnarkMetrics},
+  PublicParams, RecursiveSNARK,
+};
 use crate::{
+  abi::{self, AbiEncode, Word},
   constants::NUM_HASH_BITS,
   errors::NovaError,
+  fast_serde::{self, FastSerde, SectionReader, SectionWriter, SerdeByteError, SerdeByteTypes},
   r1cs::{R1CSInstance, RelaxedR1CSWitness},
   scalar_as_base,
   traits::{
@@ -26,6 +35,35 @@ where
   pub pk_secondary: S2::ProverKey,
 }
 
+impl<E1, S1, S2> FastSerde for ProverKey<E1, S1, S2>
+where
+  E1: CurveCycleEquipped,
+  S1: BatchedRelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E1>>,
+  S1::ProverKey: FastSerde,
+  S2::ProverKey: FastSerde,
+{
+  /// Sections:
+  ///   1: pk_primary (FastSerde)
+  ///   2: pk_secondary (FastSerde)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::ProverKey);
+    out.section(1, self.pk_primary.to_bytes());
+    out.section(2, self.pk_secondary.to_bytes());
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::ProverKey)?;
+
+    let pk_primary = S1::ProverKey::from_bytes(sections.section(1)?)?;
+    let pk_secondary = S2::ProverKey::from_bytes(sections.section(2)?)?;
+
+    sections.finish()?;
+    Ok(Self { pk_primary, pk_secondary })
+  }
+}
+
 /// A type that holds the verifier key for `CompressedSNARK`
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VerifierKey<E1, S1, S2>
@@ -37,6 +75,71 @@ where
   pub vk_secondary: S2::VerifierKey,
 }
 
+/// Random satisfying instances folded into the final instances of a
+/// `RecursiveSNARK` before compression, along with the folding proofs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct Blinding<E1>
+where E1: CurveCycleEquipped {
+  U_random_primary:   Vec<RelaxedR1CSInstance<E1>>,
+  nifs_primary:       Vec<NIFS<E1>>,
+  U_random_secondary: RelaxedR1CSInstance<Dual<E1>>,
+  nifs_secondary:     NIFS<Dual<E1>>,
+}
+
+impl<E1> FastSerde for Blinding<E1>
+where E1: CurveCycleEquipped
+{
+  /// Sections:
+  ///   1: U_random_primary (list of FastSerde)
+  ///   2: nifs_primary (list of FastSerde)
+  ///   3: U_random_secondary (FastSerde)
+  ///   4: nifs_secondary (FastSerde)
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::Blinding);
+
+    let U_random_primary = self.U_random_primary.iter().map(|U| U.to_bytes()).collect::<Vec<_>>();
+    let nifs_primary = self.nifs_primary.iter().map(|nifs| nifs.to_bytes()).collect::<Vec<_>>();
+    out.section(1, fast_serde::list_to_bytes(&U_random_primary));
+    out.section(2, fast_serde::list_to_bytes(&nifs_primary));
+    out.section(3, self.U_random_secondary.to_bytes());
+    out.section(4, self.nifs_secondary.to_bytes());
+
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::Blinding)?;
+
+    let U_random_primary = fast_serde::list_from_bytes(sections.section(1)?)?
+      .into_iter()
+      .map(RelaxedR1CSInstance::from_bytes)
+      .collect::<Result<Vec<_>, _>>()?;
+    let nifs_primary = fast_serde::list_from_bytes(sections.section(2)?)?
+      .into_iter()
+      .map(NIFS::from_bytes)
+      .collect::<Result<Vec<_>, _>>()?;
+    let U_random_secondary = RelaxedR1CSInstance::from_bytes(sections.section(3)?)?;
+    let nifs_secondary = NIFS::from_bytes(sections.section(4)?)?;
+    sections.finish()?;
+
+    Ok(Self { U_random_primary, nifs_primary, U_random_secondary, nifs_secondary })
+  }
+}
+
+impl<E1> AbiEncode for Blinding<E1>
+where E1: CurveCycleEquipped
+{
+  /// Words: U_random_primary (list), nifs_primary (list), U_random_secondary,
+  /// nifs_secondary
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    abi::encode_list(out, &self.U_random_primary)?;
+    abi::encode_list(out, &self.nifs_primary)?;
+    self.U_random_secondary.abi_encode(out)?;
+    self.nifs_secondary.abi_encode(out)
+  }
+}
+
 /// A SNARK that proves the knowledge of a valid `RecursiveSNARK`
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -58,6 +161,114 @@ where
 
   zn_primary:   Vec<E1::Scalar>,
   zn_secondary: Vec<<Dual<E1> as Engine>::Scalar>,
+
+  blinding: Option<Blinding<E1>>,
+}
+
+impl<E1, S1, S2> FastSerde for CompressedSNARK<E1, S1, S2>
+where
+  E1: CurveCycleEquipped,
+  S1: BatchedRelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E1>>,
+{
+  /// Sections:
+  ///   1: r_U_primary (list of FastSerde)
+  ///   2: r_W_snark_primary (FastSerde)
+  ///   3: r_U_secondary (FastSerde)
+  ///   4: l_u_secondary (FastSerde)
+  ///   5: nifs_secondary (FastSerde)
+  ///   6: f_W_snark_secondary (FastSerde)
+  ///   7: num_steps (u64)
+  ///   8: program_counter (scalar)
+  ///   9: zn_primary (scalars)
+  ///   10: zn_secondary (scalars)
+  ///   11: blinding (FastSerde, only written for blinded proofs)
+  ///
+  /// Section 11 is a required section when present, so a reader that does not
+  /// know it rejects a blinded proof instead of verifying the unblinded
+  /// instances.
+  fn to_bytes(&self) -> Vec<u8> {
+    let mut out = SectionWriter::new(SerdeByteTypes::CompressedSNARK);
+
+    let r_U_primary = self.r_U_primary.iter().map(|U| U.to_bytes()).collect::<Vec<_>>();
+    out.section(1, fast_serde::list_to_bytes(&r_U_primary));
+    out.section(2, self.r_W_snark_primary.to_bytes());
+    out.section(3, self.r_U_secondary.to_bytes());
+    out.section(4, self.l_u_secondary.to_bytes());
+    out.section(5, self.nifs_secondary.to_bytes());
+    out.section(6, self.f_W_snark_secondary.to_bytes());
+    out.section(7, (self.num_steps as u64).to_le_bytes());
+    out.section(8, self.program_counter.to_repr().as_ref().to_vec());
+    out.section(9, fast_serde::scalars_to_bytes(&self.zn_primary));
+    out.section(10, fast_serde::scalars_to_bytes(&self.zn_secondary));
+    if let Some(blinding) = &self.blinding {
+      out.section(11, blinding.to_bytes());
+    }
+
+    out.finish()
+  }
+
+  fn from_bytes(bytes: &[u8]) -> Result<Self, SerdeByteError> {
+    let mut sections = SectionReader::new(bytes, SerdeByteTypes::CompressedSNARK)?;
+
+    // Read all sections
+    let r_U_primary = fast_serde::list_from_bytes(sections.section(1)?)?
+      .into_iter()
+      .map(RelaxedR1CSInstance::from_bytes)
+      .collect::<Result<Vec<_>, _>>()?;
+    let r_W_snark_primary = S1::from_bytes(sections.section(2)?)?;
+    let r_U_secondary = RelaxedR1CSInstance::from_bytes(sections.section(3)?)?;
+    let l_u_secondary = R1CSInstance::from_bytes(sections.section(4)?)?;
+    let nifs_secondary = NIFS::from_bytes(sections.section(5)?)?;
+    let f_W_snark_secondary = S2::from_bytes(sections.section(6)?)?;
+    let num_steps = fast_serde::usize_from_bytes(sections.section(7)?)?;
+    let program_counter = fast_serde::scalar_from_bytes(sections.section(8)?)?;
+    let zn_primary = fast_serde::scalars_from_bytes(sections.section(9)?)?;
+    let zn_secondary = fast_serde::scalars_from_bytes(sections.section(10)?)?;
+    let blinding = sections.optional_section(11).map(Blinding::from_bytes).transpose()?;
+    sections.finish()?;
+
+    Ok(Self {
+      r_U_primary,
+      r_W_snark_primary,
+      r_U_secondary,
+      l_u_secondary,
+      nifs_secondary,
+      f_W_snark_secondary,
+      num_steps,
+      program_counter,
+      zn_primary,
+      zn_secondary,
+      blinding,
+    })
+  }
+}
+
+impl<E1, S1, S2> AbiEncode for CompressedSNARK<E1, S1, S2>
+where
+  E1: CurveCycleEquipped,
+  S1: BatchedRelaxedR1CSSNARKTrait<E1> + AbiEncode,
+  S2: RelaxedR1CSSNARKTrait<Dual<E1>> + AbiEncode,
+{
+  /// Words: r_U_primary (list), r_W_snark_primary, r_U_secondary,
+  /// l_u_secondary, nifs_secondary, f_W_snark_secondary, whether the proof is
+  /// blinded (0 or 1), followed by the blinding if it is
+  ///
+  /// The number of steps, the program counter and the final registers are
+  /// public inputs of the verifier and are not part of the encoding.
+  fn abi_encode(&self, out: &mut Vec<Word>) -> Result<(), NovaError> {
+    abi::encode_list(out, &self.r_U_primary)?;
+    self.r_W_snark_primary.abi_encode(out)?;
+    self.r_U_secondary.abi_encode(out)?;
+    self.l_u_secondary.abi_encode(out)?;
+    self.nifs_secondary.abi_encode(out)?;
+    self.f_W_snark_secondary.abi_encode(out)?;
+    out.push(abi::uint(self.blinding.is_some() as u64));
+    match &self.blinding {
+      Some(blinding) => blinding.abi_encode(out),
+      None => Ok(()),
+    }
+  }
 }
 
 impl<E1, S1, S2> CompressedSNARK<E1, S1, S2>
@@ -100,6 +311,48 @@ where
     pk: &ProverKey<E1, S1, S2>,
     recursive_snark: &RecursiveSNARK<E1>,
   ) -> Result<Self, SuperNovaError> {
+    Self::prove_inner(pp, pk, recursive_snark, false, &mut CompressionMetrics::default())
+  }
+
+  /// Create a new `CompressedSNARK` whose compressed witnesses are blinded.
+  ///
+  /// Before compression, a random satisfying relaxed instance is folded into
+  /// each final instance, so the witnesses handed to `S1` and `S2` are
+  /// distributed independently of the witnesses of the `RecursiveSNARK`.
+  ///
+  /// This only randomizes the folded instances. The commitment schemes are
+  /// not hiding and the Spartan sumcheck is not zero-knowledge, so the
+  /// resulting proof should not be treated as zero-knowledge on its own.
+  pub fn prove_blinded(
+    pp: &PublicParams<E1>,
+    pk: &ProverKey<E1, S1, S2>,
+    recursive_snark: &RecursiveSNARK<E1>,
+  ) -> Result<Self, SuperNovaError> {
+    Self::prove_inner(pp, pk, recursive_snark, true, &mut CompressionMetrics::default())
+  }
+
+  /// Create a new `CompressedSNARK` like [`CompressedSNARK::prove`], or like
+  /// [`CompressedSNARK::prove_blinded`] if `blind` is set, and record the
+  /// time spent in each part of the compression into `metrics`.
+  pub fn prove_with_metrics(
+    pp: &PublicParams<E1>,
+    pk: &ProverKey<E1, S1, S2>,
+    recursive_snark: &RecursiveSNARK<E1>,
+    blind: bool,
+    metrics: &mut CompressionMetrics,
+  ) -> Result<Self, SuperNovaError> {
+    Self::prove_inner(pp, pk, recursive_snark, blind, metrics)
+  }
+
+  fn prove_inner(
+    pp: &PublicParams<E1>,
+    pk: &ProverKey<E1, S1, S2>,
+    recursive_snark: &RecursiveSNARK<E1>,
+    blind: bool,
+    metrics: &mut CompressionMetrics,
+  ) -> Result<Self, SuperNovaError> {
+    let time = Instant::now();
+
     // fold the secondary circuit's instance
     let res_secondary = NIFS::prove(
       &*pp.ck_secondary,
@@ -128,33 +381,93 @@ where
       .collect::<Vec<_>>();
 
     // Prepare the list of primary relaxed R1CS witnesses (a default witness is
-    // provided for uninitialized circuits)
-    let r_W_primary: Vec<RelaxedR1CSWitness<E1>> = recursive_snark
-      .r_W_primary
-      .iter()
-      .enumerate()
-      .map(|(idx, r_W)| {
-        r_W.clone().unwrap_or_else(|| RelaxedR1CSWitness::default(&pp[idx].r1cs_shape))
+    // provided for uninitialized circuits, spilled witnesses are read back)
+    let r_W_primary: Vec<RelaxedR1CSWitness<E1>> = (0..recursive_snark.r_W_primary.len())
+      .map(|idx| {
+        Ok(
+          recursive_snark
+            .primary_witness(idx)?
+            .unwrap_or_else(|| RelaxedR1CSWitness::default(&pp[idx].r1cs_shape)),
+        )
       })
-      .collect::<Vec<_>>();
+      .collect::<Result<Vec<_>, SuperNovaError>>()?;
+
+    // Optionally fold a random satisfying instance into each of the instances
+    // to be compressed
+    let (blinding, b_U_primary, b_W_primary, f_U_secondary, f_W_secondary) = if blind {
+      let digest_primary = pp.digest();
+      let digest_secondary = scalar_as_base::<E1>(pp.digest());
+
+      let mut U_random_primary = Vec::with_capacity(r_U_primary.len());
+      let mut nifs_primary = Vec::with_capacity(r_U_primary.len());
+      let mut b_U_primary = Vec::with_capacity(r_U_primary.len());
+      let mut b_W_primary = Vec::with_capacity(r_U_primary.len());
+      for (idx, (r_U, r_W)) in r_U_primary.iter().zip(&r_W_primary).enumerate() {
+        let S = &pp[idx].r1cs_shape;
+        let (W_random, U_random) = S.random_witness_instance(&pp.ck_primary, &mut OsRng);
+        let (nifs, (U, W)) = NIFS::prove_relaxed(
+          &pp.ck_primary,
+          &pp.ro_consts_primary,
+          &digest_primary,
+          S,
+          r_U,
+          r_W,
+          &U_random,
+          &W_random,
+        )?;
+        U_random_primary.push(U_random);
+        nifs_primary.push(nifs);
+        b_U_primary.push(U);
+        b_W_primary.push(W);
+      }
+
+      let S = &pp.circuit_shape_secondary.r1cs_shape;
+      let (W_random, U_random_secondary) = S.random_witness_instance(&pp.ck_secondary, &mut OsRng);
+      let (nifs_secondary, (U, W)) = NIFS::prove_relaxed(
+        &pp.ck_secondary,
+        &pp.ro_consts_secondary,
+        &digest_secondary,
+        S,
+        &f_U_secondary,
+        &f_W_secondary,
+        &U_random_secondary,
+        &W_random,
+      )?;
+
+      let blinding =
+        Blinding { U_random_primary, nifs_primary, U_random_secondary, nifs_secondary };
+      (Some(blinding), b_U_primary, b_W_primary, U, W)
+    } else {
+      (None, r_U_primary.clone(), r_W_primary, f_U_secondary, f_W_secondary)
+    };
+
+    metrics.folding = time.elapsed();
 
     // Generate a primary SNARK proof for the list of primary circuits
-    let r_W_snark_primary = S1::prove(
-      &pp.ck_primary,
-      &pk.pk_primary,
-      pp.primary_r1cs_shapes(),
-      &r_U_primary,
-      &r_W_primary,
-    )?;
+    let (r_W_snark_primary, primary) = SnarkMetrics::measure(|| {
+      S1::prove(
+        &pp.ck_primary,
+        &pk.pk_primary,
+        pp.primary_r1cs_shapes(),
+        &b_U_primary,
+        &b_W_primary,
+      )
+    });
+    metrics.primary = primary;
+    let r_W_snark_primary = r_W_snark_primary?;
 
     // Generate a secondary SNARK proof for the secondary circuit
-    let f_W_snark_secondary = S2::prove(
-      &pp.ck_secondary,
-      &pk.pk_secondary,
-      &pp.circuit_shape_secondary.r1cs_shape,
-      &f_U_secondary,
-      &f_W_secondary,
-    )?;
+    let (f_W_snark_secondary, secondary) = SnarkMetrics::measure(|| {
+      S2::prove(
+        &pp.ck_secondary,
+        &pk.pk_secondary,
+        &pp.circuit_shape_secondary.r1cs_shape,
+        &f_U_secondary,
+        &f_W_secondary,
+      )
+    });
+    metrics.secondary = secondary;
+    let f_W_snark_secondary = f_W_snark_secondary?;
 
     let compressed_snark = Self {
       r_U_primary,
@@ -170,11 +483,29 @@ where
 
       zn_primary: recursive_snark.zi_primary.clone(),
       zn_secondary: recursive_snark.zi_secondary.clone(),
+
+      blinding,
     };
 
     Ok(compressed_snark)
   }
 
+  /// Number of steps folded into the compressed `RecursiveSNARK`
+  pub fn num_steps(&self) -> usize { self.num_steps }
+
+  /// Program counter after the last folded step
+  pub fn program_counter(&self) -> E1::Scalar { self.program_counter }
+
+  /// Outputs of the primary circuits
+  pub fn zn_primary(&self) -> &Vec<E1::Scalar> { &self.zn_primary }
+
+  /// Outputs of the secondary circuits
+  pub fn zn_secondary(&self) -> &Vec<<Dual<E1> as Engine>::Scalar> { &self.zn_secondary }
+
+  /// Whether the compressed witnesses were blinded, see
+  /// [`CompressedSNARK::prove_blinded`]
+  pub fn is_blinded(&self) -> bool { self.blinding.is_some() }
+
   /// Verify the correctness of the `CompressedSNARK`
   #[allow(clippy::type_complexity)]
   pub fn verify(
@@ -247,9 +578,6 @@ where
       return Err(NovaError::ProofVerifyError.into());
     }
 
-    // Verify the primary SNARK
-    let res_primary = self.r_W_snark_primary.verify(&vk.vk_primary, &self.r_U_primary);
-
     // Fold the secondary circuit's instance
     let f_U_secondary = self.nifs_secondary.verify(
       &pp.ro_consts_secondary,
@@ -258,6 +586,40 @@ where
       &self.l_u_secondary,
     )?;
 
+    // Fold in the random instances if the witnesses were blinded
+    let (b_U_primary, f_U_secondary) = match &self.blinding {
+      Some(blinding) => {
+        if blinding.U_random_primary.len() != self.r_U_primary.len()
+          || blinding.nifs_primary.len() != self.r_U_primary.len()
+        {
+          return Err(NovaError::ProofVerifyError.into());
+        }
+
+        let b_U_primary = self
+          .r_U_primary
+          .iter()
+          .zip(&blinding.U_random_primary)
+          .zip(&blinding.nifs_primary)
+          .map(|((r_U, U_random), nifs)| {
+            nifs.verify_relaxed(&pp.ro_consts_primary, &pp.digest(), r_U, U_random)
+          })
+          .collect::<Result<Vec<_>, _>>()?;
+
+        let f_U_secondary = blinding.nifs_secondary.verify_relaxed(
+          &pp.ro_consts_secondary,
+          &scalar_as_base::<E1>(pp.digest()),
+          &f_U_secondary,
+          &blinding.U_random_secondary,
+        )?;
+
+        (b_U_primary, f_U_secondary)
+      },
+      None => (self.r_U_primary.clone(), f_U_secondary),
+    };
+
+    // Verify the primary SNARK
+    let res_primary = self.r_W_snark_primary.verify(&vk.vk_primary, &b_U_primary);
+
     // Verify the secondary SNARK
     let res_secondary = self.f_W_snark_secondary.verify(&vk.vk_secondary, &f_U_secondary);
 
@@ -271,16 +633,24 @@ where
 
 #[cfg(test)]
 mod test {
-  use std::marker::PhantomData;
+  use std::{marker::PhantomData, time::Duration};
 
   use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
   use ff::Field;
+  use halo2curves::bn256::Bn256;
 
   use super::*;
   use crate::{
-    provider::{ipa_pc, Bn256EngineIPA},
+    provider::{
+      hyperkzg, ipa_pc, non_hiding_zeromorph::ZMPCS, Bn256EngineIPA, Bn256EngineKZGPoseidon,
+      Bn256EngineZM, PallasEngine,
+    },
     spartan::{batched, batched_ppsnark, snark::RelaxedR1CSSNARK},
-    supernova::{circuit::TrivialCircuit, NonUniformCircuit, StepCircuit},
+    supernova::{
+      circuit::TrivialCircuit,
+      metrics::{MsmSizes, StepMetrics},
+      NonUniformCircuit, StepCircuit,
+    },
   };
 
   type EE<E> = ipa_pc::EvaluationEngine<E>;
@@ -547,6 +917,11 @@ mod test {
     let compressed_snark = CompressedSNARK::prove(&pp, &prover_key, &recursive_snark).unwrap();
 
     compressed_snark.verify(&pp, &verifier_key, &z0_primary, &z0_secondary).unwrap();
+
+    // the native encoding must round-trip to a proof that still verifies
+    let compressed_snark =
+      CompressedSNARK::<_, S1, S2>::from_bytes(&compressed_snark.to_bytes()).unwrap();
+    compressed_snark.verify(&pp, &verifier_key, &z0_primary, &z0_secondary).unwrap();
   }
 
   #[test]
@@ -554,6 +929,138 @@ mod test {
     const NUM_STEPS: usize = 6;
     test_compression_with::<Bn256EngineIPA, S1PP<_>, S2<_>, _, _>(NUM_STEPS, TestCircuit::new);
     test_compression_with::<Bn256EngineIPA, S1<_>, S2<_>, _, _>(NUM_STEPS, TestCircuit::new);
+    test_compression_with::<PallasEngine, S1PP<_>, S2<_>, _, _>(NUM_STEPS, TestCircuit::new);
+    test_compression_with::<PallasEngine, S1<_>, S2<_>, _, _>(NUM_STEPS, TestCircuit::new);
+  }
+
+  #[test]
+  fn test_nivc_trivial_with_compression_poseidon_transcript() {
+    // HyperKZG with a Poseidon transcript, as for primary proofs verified in a circuit
+    type E1 = Bn256EngineKZGPoseidon;
+    type S1KZG = batched::BatchedRelaxedR1CSSNARK<E1, hyperkzg::EvaluationEngine<Bn256, E1>>;
+    const NUM_STEPS: usize = 6;
+    test_compression_with::<E1, S1KZG, S2<_>, _, _>(NUM_STEPS, TestCircuit::new);
+  }
+
+  #[test]
+  fn test_nivc_trivial_with_compression_zeromorph() {
+    // Zeromorph openings as a drop-in alternative to HyperKZG on the primary side
+    type E1 = Bn256EngineZM;
+    type S1ZM = batched::BatchedRelaxedR1CSSNARK<E1, ZMPCS<Bn256, E1>>;
+    const NUM_STEPS: usize = 6;
+    test_compression_with::<E1, S1ZM, S2<_>, _, _>(NUM_STEPS, TestCircuit::new);
+  }
+
+  #[test]
+  fn test_fast_serde_roundtrip() {
+    type E1 = Bn256EngineIPA;
+    const NUM_STEPS: usize = 4;
+    let secondary_circuit = TrivialCircuit::default();
+    let test_circuits = TestCircuit::<E1>::new(NUM_STEPS);
+
+    let pp = PublicParams::setup(&test_circuits[0], &*S1::<E1>::ck_floor(), &*S2::ck_floor());
+
+    let z0_primary = vec![<E1 as Engine>::Scalar::from(17u64)];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::ZERO];
+
+    let mut recursive_snark = RecursiveSNARK::new(
+      &pp,
+      &test_circuits[0],
+      &test_circuits[0],
+      &secondary_circuit,
+      &z0_primary,
+      &z0_secondary,
+    )
+    .unwrap();
+
+    // checkpoint the recursive SNARK halfway and resume proving from the checkpoint
+    for circuit in test_circuits.iter().take(NUM_STEPS / 2) {
+      recursive_snark.prove_step(&pp, circuit, &secondary_circuit).unwrap();
+    }
+    let mut recursive_snark = RecursiveSNARK::from_bytes(&recursive_snark.to_bytes()).unwrap();
+    for circuit in test_circuits.iter().skip(NUM_STEPS / 2) {
+      recursive_snark.prove_step(&pp, circuit, &secondary_circuit).unwrap();
+    }
+    recursive_snark.verify(&pp, &z0_primary, &z0_secondary).unwrap();
+
+    let (prover_key, verifier_key) = CompressedSNARK::<_, S1<E1>, S2<_>>::setup(&pp).unwrap();
+    let prover_key = ProverKey::from_bytes(&prover_key.to_bytes()).unwrap();
+
+    let compressed_snark = CompressedSNARK::prove(&pp, &prover_key, &recursive_snark).unwrap();
+    let compressed_snark = CompressedSNARK::from_bytes(&compressed_snark.to_bytes()).unwrap();
+
+    compressed_snark.verify(&pp, &verifier_key, &z0_primary, &z0_secondary).unwrap();
+
+    // the blinding is carried in its own section
+    let compressed_snark =
+      CompressedSNARK::prove_blinded(&pp, &prover_key, &recursive_snark).unwrap();
+    let bytes = compressed_snark.to_bytes();
+    let compressed_snark = CompressedSNARK::from_bytes(&bytes).unwrap();
+    assert!(compressed_snark.is_blinded());
+
+    // a reader that does not know the blinding section must reject the proof
+    let mut sections = SectionReader::new(&bytes, SerdeByteTypes::CompressedSNARK).unwrap();
+    for id in 1..=10 {
+      sections.section(id).unwrap();
+    }
+    assert!(matches!(sections.finish(), Err(SerdeByteError::UnknownSection(11))));
+
+    compressed_snark.verify(&pp, &verifier_key, &z0_primary, &z0_secondary).unwrap();
+  }
+
+  #[test]
+  fn test_prove_with_metrics() {
+    type E1 = Bn256EngineIPA;
+    const NUM_STEPS: usize = 4;
+    let secondary_circuit = TrivialCircuit::default();
+    let test_circuits = TestCircuit::<E1>::new(NUM_STEPS);
+
+    let pp = PublicParams::setup(&test_circuits[0], &*S1::<E1>::ck_floor(), &*S2::ck_floor());
+
+    let z0_primary = vec![<E1 as Engine>::Scalar::from(17u64)];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::ZERO];
+
+    let mut recursive_snark = RecursiveSNARK::new(
+      &pp,
+      &test_circuits[0],
+      &test_circuits[0],
+      &secondary_circuit,
+      &z0_primary,
+      &z0_secondary,
+    )
+    .unwrap();
+
+    let mut step = StepMetrics::default();
+    for (i, circuit) in test_circuits.iter().take(NUM_STEPS).enumerate() {
+      recursive_snark.prove_step_with_metrics(&pp, circuit, &secondary_circuit, &mut step).unwrap();
+      assert_eq!(step.circuit_index, circuit.circuit_index());
+      // the first step was proven by the constructor
+      if i == 0 {
+        assert_eq!(step.msm_primary, MsmSizes::default());
+        continue;
+      }
+      assert!(step.msm_primary.witness > 0 && step.msm_primary.cross_term > 0);
+      assert!(step.msm_secondary.witness > 0 && step.msm_secondary.cross_term > 0);
+      assert!(step.buffer_bytes > 0);
+    }
+    recursive_snark.verify(&pp, &z0_primary, &z0_secondary).unwrap();
+
+    let (prover_key, verifier_key) = CompressedSNARK::<_, S1<E1>, S2<_>>::setup(&pp).unwrap();
+
+    let mut metrics = CompressionMetrics::default();
+    let compressed_snark =
+      CompressedSNARK::prove_with_metrics(&pp, &prover_key, &recursive_snark, false, &mut metrics)
+        .unwrap();
+    compressed_snark.verify(&pp, &verifier_key, &z0_primary, &z0_secondary).unwrap();
+
+    for snark in [metrics.primary, metrics.secondary] {
+      assert!(snark.sumcheck > Duration::ZERO && snark.pcs > Duration::ZERO);
+      assert!(snark.total >= snark.sumcheck + snark.pcs);
+    }
+
+    let json = serde_json::to_string(&metrics).unwrap();
+    let parsed: CompressionMetrics = serde_json::from_str(&json).unwrap();
+    assert!(parsed.primary.total.abs_diff(metrics.primary.total) <= Duration::from_nanos(1));
   }
 
   #[test]
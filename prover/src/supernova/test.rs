@@ -6,11 +6,13 @@ use expect_test::{expect, Expect};
 use ff::{Field, PrimeField};
 use tap::TapOptional;
 
-use super::{utils::get_selector_vec_from_index, *};
+use super::{low_memory::LowMemoryConfig, utils::get_selector_vec_from_index, *};
 use crate::{
   bellpepper::test_shape_cs::TestShapeCS,
   gadgets::{alloc_one, alloc_zero},
-  provider::{poseidon::PoseidonConstantsCircuit, Bn256EngineIPA, GrumpkinEngine},
+  provider::{
+    poseidon::PoseidonConstantsCircuit, Bn256EngineIPA, GrumpkinEngine, PallasEngine, VestaEngine,
+  },
   supernova::circuit::{StepCircuit, TrivialCircuit},
   traits::snark::default_ck_hint,
 };
@@ -402,8 +404,67 @@ where E1: CurveCycleEquipped {
 fn test_trivial_nivc() {
   // Experimenting with selecting the running claims for nifs
   test_trivial_nivc_with::<Bn256EngineIPA>();
+  test_trivial_nivc_with::<PallasEngine>();
+  test_trivial_nivc_with::<VestaEngine>();
 }
 
+fn test_low_memory_nivc_with<E1>()
+where E1: CurveCycleEquipped {
+  let rom = vec![OPCODE_1, OPCODE_1, OPCODE_0, OPCODE_0, OPCODE_1, OPCODE_0];
+  let test_rom = TestROM::<E1>::new(rom);
+
+  let pp = PublicParams::setup(&test_rom, &*default_ck_hint(), &*default_ck_hint());
+
+  let mut z0_primary = vec![<E1 as Engine>::Scalar::ONE];
+  z0_primary.push(<E1 as Engine>::Scalar::ZERO); // rom_index = 0
+  z0_primary.extend(test_rom.rom.iter().map(|opcode| <E1 as Engine>::Scalar::from(*opcode as u64)));
+  let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::ONE];
+
+  let spill_dir = std::env::temp_dir().join(format!("low-memory-{}", std::process::id()));
+  let config = LowMemoryConfig { spill_dir: spill_dir.clone(), chunk_len: 7, msm_window: 5 };
+  let estimate = pp.memory_estimate(Some(&config));
+  assert!(estimate.total() < pp.memory_estimate(None).total());
+
+  let circuit_secondary = test_rom.secondary_circuit();
+  let circuit_primary = test_rom.primary_circuit(test_rom.rom[0]);
+  let mut recursive_snark = RecursiveSNARK::new(
+    &pp,
+    &test_rom,
+    &circuit_primary,
+    &circuit_secondary,
+    &z0_primary,
+    &z0_secondary,
+  )
+  .unwrap();
+  let mut low_memory_snark = recursive_snark.clone();
+  assert_eq!(low_memory_snark.enable_low_memory(&pp, config).unwrap(), estimate);
+
+  for &op_code in test_rom.rom.iter() {
+    let circuit_primary = test_rom.primary_circuit(op_code);
+    recursive_snark.prove_step(&pp, &circuit_primary, &circuit_secondary).unwrap();
+    low_memory_snark.prove_step(&pp, &circuit_primary, &circuit_secondary).unwrap();
+
+    // at most the witness of the next circuit is resident, and the proofs are identical
+    assert!(low_memory_snark.r_W_primary.iter().flatten().count() <= 1);
+    low_memory_snark.verify(&pp, &z0_primary, &z0_secondary).unwrap();
+    assert_eq!(low_memory_snark.try_to_bytes().unwrap(), recursive_snark.to_bytes());
+  }
+
+  // serde cannot read spilled witnesses back, so it refuses to write them as missing
+  assert!((0..test_rom.num_circuits()).any(|i| low_memory_snark.is_spilled(i)));
+  assert!(bincode::serialize(&low_memory_snark).is_err());
+
+  low_memory_snark.disable_low_memory().unwrap();
+  assert!(!low_memory_snark.is_low_memory());
+  assert_eq!(low_memory_snark.r_W_primary, recursive_snark.r_W_primary);
+  assert!(bincode::serialize(&low_memory_snark).is_ok());
+  assert_eq!(std::fs::read_dir(&spill_dir).unwrap().count(), 0);
+  std::fs::remove_dir(&spill_dir).unwrap();
+}
+
+#[test]
+fn test_low_memory_nivc() { test_low_memory_nivc_with::<Bn256EngineIPA>(); }
+
 // In the following we use 1 to refer to the primary, and 2 to refer to the
 // secondary circuit
 fn test_recursive_circuit_with<E1>(
@@ -545,6 +606,21 @@ fn test_supernova_pp_digest() {
   ]);
 }
 
+#[test]
+fn test_aux_params_fast_serde() {
+  let test_rom = TestROM::<Bn256EngineIPA>::new(vec![OPCODE_0, OPCODE_1]);
+  let pp = PublicParams::setup(&test_rom, &*default_ck_hint(), &*default_ck_hint());
+
+  // the random oracle constants are recreated rather than stored
+  let aux_params = pp.aux_params();
+  let bytes = aux_params.to_bytes();
+  assert_eq!(AuxParams::from_bytes(&bytes).unwrap(), aux_params);
+
+  let (circuit_shapes, aux_params) = pp.into_parts();
+  let pp = PublicParams::from_parts(circuit_shapes, AuxParams::from_bytes(&bytes).unwrap());
+  assert_eq!(pp.digest(), aux_params.digest);
+}
+
 // y is a non-deterministic hint representing the cube root of the input at a
 // step.
 #[derive(Clone, Debug)]
@@ -53,6 +53,15 @@ pub trait CommitmentTrait<E: Engine>:
 
   /// Decompresses a compressed commitment into a commitment
   fn decompress(c: &Self::CompressedCommitment) -> Result<Self, NovaError>;
+
+  /// Returns the canonical encoding of a compressed commitment
+  fn compressed_to_bytes(c: &Self::CompressedCommitment) -> Vec<u8>;
+
+  /// Decodes a compressed commitment from its canonical encoding
+  ///
+  /// Only the length of the encoding is checked, the point itself is checked by
+  /// [`CommitmentTrait::decompress`].
+  fn compressed_from_bytes(bytes: &[u8]) -> Result<Self::CompressedCommitment, NovaError>;
 }
 
 /// A trait that helps determine the length of a structure.
@@ -84,4 +93,23 @@ pub trait CommitmentEngineTrait<E: Engine>: Clone + Send + Sync {
 
   /// Commits to the provided vector using the provided generators
   fn commit(ck: &Self::CommitmentKey, v: &[E::Scalar]) -> Self::Commitment;
+
+  /// Commits to the provided vector using the generators starting at `offset`
+  ///
+  /// Commitments are additive, so the commitments to consecutive chunks of a vector at their
+  /// offsets sum up to the commitment to the whole vector.
+  fn commit_at(ck: &Self::CommitmentKey, v: &[E::Scalar], offset: usize) -> Self::Commitment;
+
+  /// Commits to the provided vector with one multi-scalar multiplication per `window` generators
+  ///
+  /// This is the same commitment as [`CommitmentEngineTrait::commit`], but the scratch memory of
+  /// each multi-scalar multiplication is bounded by the window and only one window of the
+  /// commitment key is touched at a time, which matters when the key is memory-mapped.
+  fn commit_windowed(ck: &Self::CommitmentKey, v: &[E::Scalar], window: usize) -> Self::Commitment {
+    let window = window.max(1);
+    v.chunks(window)
+      .enumerate()
+      .map(|(k, chunk)| Self::commit_at(ck, chunk, k * window))
+      .fold(Self::Commitment::default(), |acc, comm| acc + comm)
+  }
 }
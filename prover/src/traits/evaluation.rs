@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
   errors::NovaError,
+  fast_serde::FastSerde,
   traits::{commitment::CommitmentEngineTrait, Engine},
 };
 
@@ -25,7 +26,7 @@ pub trait EvaluationEngineTrait<E: Engine>: Clone + Send + Sync {
     + for<'de> Deserialize<'de>;
 
   /// A type that holds the evaluation argument
-  type EvaluationArgument: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de>;
+  type EvaluationArgument: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + FastSerde;
 
   /// A method to perform any additional setup needed to produce proofs of
   /// evaluations
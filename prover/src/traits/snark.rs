@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
   errors::NovaError,
+  fast_serde::FastSerde,
   r1cs::{R1CSShape, RelaxedR1CSInstance, RelaxedR1CSWitness},
   traits::Engine,
   CommitmentKey,
@@ -32,12 +33,12 @@ pub fn default_ck_hint<E: Engine>() -> Box<dyn for<'a> Fn(&'a R1CSShape<E>) -> u
 
 /// A trait that defines the behavior of a `zkSNARK`
 pub trait RelaxedR1CSSNARKTrait<E: Engine>:
-  Send + Sync + Serialize + for<'de> Deserialize<'de> {
+  Send + Sync + Serialize + for<'de> Deserialize<'de> + FastSerde {
   /// A type that represents the prover's key
   type ProverKey: Send + Sync;
 
   /// A type that represents the verifier's key
-  type VerifierKey: Send + Sync + Serialize;
+  type VerifierKey: Send + Sync + Serialize + for<'de> Deserialize<'de>;
 
   /// This associated function (not a method) provides a hint that offers
   /// a minimum sizing cue for the commitment key used by this SNARK
@@ -79,7 +80,7 @@ pub trait RelaxedR1CSSNARKTrait<E: Engine>:
 /// A trait that defines the behavior of a `zkSNARK` to prove knowledge of
 /// satisfying witness to batches of relaxed R1CS instances.
 pub trait BatchedRelaxedR1CSSNARKTrait<E: Engine>:
-  Send + Sync + Serialize + for<'de> Deserialize<'de> {
+  Send + Sync + Serialize + for<'de> Deserialize<'de> + FastSerde {
   /// A type that represents the prover's key
   type ProverKey: Send + Sync + Serialize + for<'de> Deserialize<'de>;
 